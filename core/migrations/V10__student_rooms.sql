@@ -0,0 +1,2 @@
+ALTER TABLE students ADD COLUMN room VARCHAR(64);
+ALTER TABLE students ADD COLUMN block VARCHAR(64);
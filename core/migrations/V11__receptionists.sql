@@ -0,0 +1,24 @@
+CREATE TABLE receptionists (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR(512) NOT NULL UNIQUE COLLATE NOCASE,
+    active BOOLEAN NOT NULL DEFAULT TRUE
+);
+
+-- Fill the roster with every receptionist name used so far, misspellings can be deactivated afterwards.
+INSERT OR IGNORE INTO receptionists (name)
+SELECT DISTINCT TRIM(receptionist) FROM (
+    SELECT receptionist FROM key_records
+    UNION ALL
+    SELECT receptionist FROM game_records
+    UNION ALL
+    SELECT receptionist FROM parcel_records
+    UNION ALL
+    SELECT receptionist FROM item_records
+) WHERE receptionist IS NOT NULL AND TRIM(receptionist) != '';
+
+CREATE TABLE shifts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    receptionist_id INTEGER NOT NULL REFERENCES receptionists(id),
+    time_start VARCHAR(64) NOT NULL,
+    time_end VARCHAR(64)
+);
@@ -0,0 +1,10 @@
+-- Until now a single receptionist column was overwritten on sign in, so signed in
+-- records only know who signed them in, and records still out only know who signed them out.
+
+ALTER TABLE key_records RENAME COLUMN receptionist TO receptionist_in;
+ALTER TABLE key_records ADD COLUMN receptionist_out VARCHAR(512);
+UPDATE key_records SET receptionist_out = receptionist_in, receptionist_in = NULL WHERE time_in IS NULL;
+
+ALTER TABLE game_records RENAME COLUMN receptionist TO receptionist_in;
+ALTER TABLE game_records ADD COLUMN receptionist_out VARCHAR(512);
+UPDATE game_records SET receptionist_out = receptionist_in, receptionist_in = NULL WHERE time_in IS NULL;
@@ -0,0 +1,76 @@
+-- Corrections never overwrite a record, instead an amendment row is added.
+-- A NULL field means the whole record was voided.
+CREATE TABLE amendments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    record_table VARCHAR(64) NOT NULL,
+    record_id INTEGER NOT NULL,
+    field VARCHAR(64),
+    old_value VARCHAR(512),
+    new_value VARCHAR(512),
+    reason VARCHAR(512) NOT NULL,
+    receptionist VARCHAR(512) NOT NULL,
+    time VARCHAR(64) NOT NULL
+);
+
+CREATE INDEX amendments_record ON amendments (record_table, record_id);
+
+-- Records as they currently stand, with the latest amendment to each field applied.
+CREATE VIEW key_records_amended AS
+SELECT
+    r.id,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'key_records' AND a.record_id = r.id AND a.field = 'key' ORDER BY a.id DESC LIMIT 1), r.`key`) AS `key`,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'key_records' AND a.record_id = r.id AND a.field = 'student_name' ORDER BY a.id DESC LIMIT 1), r.student_name) AS student_name,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'key_records' AND a.record_id = r.id AND a.field = 'student_number' ORDER BY a.id DESC LIMIT 1), r.student_number) AS student_number,
+    r.receptionist_out,
+    r.receptionist_in,
+    r.time_out,
+    r.time_due,
+    r.time_in,
+    r.notes,
+    EXISTS(SELECT 1 FROM amendments a WHERE a.record_table = 'key_records' AND a.record_id = r.id AND a.field IS NULL) AS voided,
+    EXISTS(SELECT 1 FROM amendments a WHERE a.record_table = 'key_records' AND a.record_id = r.id AND a.field IS NOT NULL) AS amended
+FROM key_records r;
+
+CREATE VIEW parcel_records_amended AS
+SELECT
+    r.id,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'parcel_records' AND a.record_id = r.id AND a.field = 'parcel_desc' ORDER BY a.id DESC LIMIT 1), r.parcel_desc) AS parcel_desc,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'parcel_records' AND a.record_id = r.id AND a.field = 'student_name' ORDER BY a.id DESC LIMIT 1), r.student_name) AS student_name,
+    r.receptionist,
+    r.time_in,
+    r.time_out,
+    r.notes,
+    EXISTS(SELECT 1 FROM amendments a WHERE a.record_table = 'parcel_records' AND a.record_id = r.id AND a.field IS NULL) AS voided,
+    EXISTS(SELECT 1 FROM amendments a WHERE a.record_table = 'parcel_records' AND a.record_id = r.id AND a.field IS NOT NULL) AS amended
+FROM parcel_records r;
+
+CREATE VIEW game_records_amended AS
+SELECT
+    r.id,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'game_records' AND a.record_id = r.id AND a.field = 'game' ORDER BY a.id DESC LIMIT 1), r.game) AS game,
+    IFNULL(CAST((SELECT new_value FROM amendments a WHERE a.record_table = 'game_records' AND a.record_id = r.id AND a.field = 'quantity' ORDER BY a.id DESC LIMIT 1) AS INTEGER), r.quantity) AS quantity,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'game_records' AND a.record_id = r.id AND a.field = 'student_name' ORDER BY a.id DESC LIMIT 1), r.student_name) AS student_name,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'game_records' AND a.record_id = r.id AND a.field = 'student_number' ORDER BY a.id DESC LIMIT 1), r.student_number) AS student_number,
+    r.receptionist_out,
+    r.receptionist_in,
+    r.time_out,
+    r.time_due,
+    r.time_in,
+    r.notes,
+    EXISTS(SELECT 1 FROM amendments a WHERE a.record_table = 'game_records' AND a.record_id = r.id AND a.field IS NULL) AS voided,
+    EXISTS(SELECT 1 FROM amendments a WHERE a.record_table = 'game_records' AND a.record_id = r.id AND a.field IS NOT NULL) AS amended
+FROM game_records r;
+
+CREATE VIEW item_records_amended AS
+SELECT
+    r.id,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'item_records' AND a.record_id = r.id AND a.field = 'item' ORDER BY a.id DESC LIMIT 1), r.item) AS item,
+    IFNULL(CAST((SELECT new_value FROM amendments a WHERE a.record_table = 'item_records' AND a.record_id = r.id AND a.field = 'quantity' ORDER BY a.id DESC LIMIT 1) AS INTEGER), r.quantity) AS quantity,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'item_records' AND a.record_id = r.id AND a.field = 'student_name' ORDER BY a.id DESC LIMIT 1), r.student_name) AS student_name,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'item_records' AND a.record_id = r.id AND a.field = 'student_number' ORDER BY a.id DESC LIMIT 1), r.student_number) AS student_number,
+    r.receptionist,
+    r.time_out,
+    r.notes,
+    EXISTS(SELECT 1 FROM amendments a WHERE a.record_table = 'item_records' AND a.record_id = r.id AND a.field IS NULL) AS voided,
+    EXISTS(SELECT 1 FROM amendments a WHERE a.record_table = 'item_records' AND a.record_id = r.id AND a.field IS NOT NULL) AS amended
+FROM item_records r;
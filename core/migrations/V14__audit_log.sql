@@ -0,0 +1,24 @@
+CREATE TABLE audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    time VARCHAR(64) NOT NULL,
+    action VARCHAR(64) NOT NULL,
+    record_table VARCHAR(64) NOT NULL,
+    record_id VARCHAR(512),
+    before TEXT,
+    after TEXT,
+    receptionist VARCHAR(512)
+);
+
+CREATE INDEX audit_log_record ON audit_log (record_table, record_id);
+
+-- The audit log is append-only.
+
+CREATE TRIGGER audit_log_no_update BEFORE UPDATE ON audit_log
+BEGIN
+    SELECT RAISE(ABORT, 'audit log entries cannot be changed');
+END;
+
+CREATE TRIGGER audit_log_no_delete BEFORE DELETE ON audit_log
+BEGIN
+    SELECT RAISE(ABORT, 'audit log entries cannot be deleted');
+END;
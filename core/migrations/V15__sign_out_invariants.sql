@@ -0,0 +1,43 @@
+-- A key may only be signed out once at a time, and no more of a game may be signed out than are in stock.
+-- The messages are matched by the application, see StorageError.
+
+CREATE TRIGGER key_records_one_out BEFORE INSERT ON key_records
+WHEN NEW.time_in IS NULL AND EXISTS (SELECT 1 FROM key_records_amended WHERE `key` = NEW.`key` AND time_in IS NULL AND NOT voided)
+BEGIN
+    SELECT RAISE(ABORT, 'key already signed out');
+END;
+
+CREATE TRIGGER game_records_in_stock BEFORE INSERT ON game_records
+WHEN NEW.time_in IS NULL AND NEW.quantity > (SELECT quantity FROM games WHERE game = NEW.game)
+    - (SELECT IFNULL(SUM(quantity), 0) FROM game_records_amended WHERE game = NEW.game AND time_in IS NULL AND NOT voided)
+BEGIN
+    SELECT RAISE(ABORT, 'insufficient stock');
+END;
+
+-- amending an open record must not break the rules either
+
+CREATE TRIGGER amendments_key_one_out BEFORE INSERT ON amendments
+WHEN NEW.record_table = 'key_records' AND NEW.field = 'key'
+    AND EXISTS (SELECT 1 FROM key_records_amended WHERE id = NEW.record_id AND time_in IS NULL AND NOT voided)
+    AND EXISTS (SELECT 1 FROM key_records_amended WHERE `key` = NEW.new_value AND id != NEW.record_id AND time_in IS NULL AND NOT voided)
+BEGIN
+    SELECT RAISE(ABORT, 'key already signed out');
+END;
+
+CREATE TRIGGER amendments_game_in_stock BEFORE INSERT ON amendments
+WHEN NEW.record_table = 'game_records' AND NEW.field IN ('game', 'quantity')
+    AND EXISTS (
+        SELECT 1 FROM (
+            SELECT
+                r.id,
+                CASE NEW.field WHEN 'game' THEN NEW.new_value ELSE r.game END AS game,
+                CASE NEW.field WHEN 'quantity' THEN CAST(NEW.new_value AS INTEGER) ELSE r.quantity END AS quantity
+            FROM game_records_amended r
+            WHERE r.id = NEW.record_id AND r.time_in IS NULL AND NOT r.voided
+        ) a
+        WHERE a.quantity > (SELECT quantity FROM games WHERE game = a.game)
+            - (SELECT IFNULL(SUM(o.quantity), 0) FROM game_records_amended o WHERE o.game = a.game AND o.id != a.id AND o.time_in IS NULL AND NOT o.voided)
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'insufficient stock');
+END;
@@ -0,0 +1,286 @@
+-- Times were RFC3339 text, which only compares correctly while every offset is the same.
+-- They are now seconds since the Unix epoch. Text which cannot be read as a time is kept as it is,
+-- so that the database check can find it.
+-- SQLite cannot change the type of a column, so the tables are rebuilt. Views and triggers which refer to
+-- them are dropped first, since a table cannot be renamed while a view refers to a missing table.
+
+DROP TRIGGER key_records_one_out;
+DROP TRIGGER game_records_in_stock;
+DROP TRIGGER amendments_key_one_out;
+DROP TRIGGER amendments_game_in_stock;
+
+DROP VIEW key_records_amended;
+DROP VIEW parcel_records_amended;
+DROP VIEW game_records_amended;
+DROP VIEW item_records_amended;
+
+CREATE TABLE key_records_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    `key` VARCHAR(512) NOT NULL,
+    student_name VARCHAR(512) NOT NULL,
+    student_number VARCHAR(9) NOT NULL REFERENCES students(student_number),
+    receptionist_out VARCHAR(512),
+    receptionist_in VARCHAR(512),
+    time_out INTEGER NOT NULL,
+    time_due INTEGER,
+    time_in INTEGER,
+    notes VARCHAR(512) NOT NULL
+);
+
+INSERT INTO key_records_new (id, `key`, student_name, student_number, receptionist_out, receptionist_in, time_out, time_due, time_in, notes)
+SELECT id, `key`, student_name, student_number, receptionist_out, receptionist_in,
+    IFNULL(unixepoch(time_out), time_out), IFNULL(unixepoch(time_due), time_due), IFNULL(unixepoch(time_in), time_in), notes
+FROM key_records;
+
+DROP TABLE key_records;
+ALTER TABLE key_records_new RENAME TO key_records;
+
+CREATE TABLE parcel_records_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    parcel_desc VARCHAR(512) NOT NULL,
+    student_name VARCHAR(512) NOT NULL,
+    receptionist VARCHAR(512) NOT NULL,
+    time_in INTEGER NOT NULL,
+    time_out INTEGER,
+    notes VARCHAR(512) NOT NULL
+);
+
+INSERT INTO parcel_records_new (id, parcel_desc, student_name, receptionist, time_in, time_out, notes)
+SELECT id, parcel_desc, student_name, receptionist,
+    IFNULL(unixepoch(time_in), time_in), IFNULL(unixepoch(time_out), time_out), notes
+FROM parcel_records;
+
+DROP TABLE parcel_records;
+ALTER TABLE parcel_records_new RENAME TO parcel_records;
+
+CREATE TABLE game_records_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    game VARCHAR(512) NOT NULL,
+    quantity INTEGER NOT NULL,
+    student_name VARCHAR(512) NOT NULL,
+    student_number VARCHAR(9) NOT NULL REFERENCES students(student_number),
+    receptionist_out VARCHAR(512),
+    receptionist_in VARCHAR(512),
+    time_out INTEGER NOT NULL,
+    time_due INTEGER,
+    time_in INTEGER,
+    notes VARCHAR(512) NOT NULL
+);
+
+INSERT INTO game_records_new (id, game, quantity, student_name, student_number, receptionist_out, receptionist_in, time_out, time_due, time_in, notes)
+SELECT id, game, quantity, student_name, student_number, receptionist_out, receptionist_in,
+    IFNULL(unixepoch(time_out), time_out), IFNULL(unixepoch(time_due), time_due), IFNULL(unixepoch(time_in), time_in), notes
+FROM game_records;
+
+DROP TABLE game_records;
+ALTER TABLE game_records_new RENAME TO game_records;
+
+CREATE TABLE item_records_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    item VARCHAR(512) NOT NULL,
+    quantity INTEGER NOT NULL,
+    student_name VARCHAR(512) NOT NULL,
+    student_number VARCHAR(9) NOT NULL REFERENCES students(student_number),
+    receptionist VARCHAR(512) NOT NULL,
+    time_out INTEGER NOT NULL,
+    notes VARCHAR(512) NOT NULL
+);
+
+INSERT INTO item_records_new (id, item, quantity, student_name, student_number, receptionist, time_out, notes)
+SELECT id, item, quantity, student_name, student_number, receptionist, IFNULL(unixepoch(time_out), time_out), notes
+FROM item_records;
+
+DROP TABLE item_records;
+ALTER TABLE item_records_new RENAME TO item_records;
+
+CREATE TABLE shifts_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    receptionist_id INTEGER NOT NULL REFERENCES receptionists(id),
+    time_start INTEGER NOT NULL,
+    time_end INTEGER
+);
+
+INSERT INTO shifts_new (id, receptionist_id, time_start, time_end)
+SELECT id, receptionist_id, IFNULL(unixepoch(time_start), time_start), IFNULL(unixepoch(time_end), time_end)
+FROM shifts;
+
+DROP TABLE shifts;
+ALTER TABLE shifts_new RENAME TO shifts;
+
+CREATE TABLE amendments_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    record_table VARCHAR(64) NOT NULL,
+    record_id INTEGER NOT NULL,
+    field VARCHAR(64),
+    old_value VARCHAR(512),
+    new_value VARCHAR(512),
+    reason VARCHAR(512) NOT NULL,
+    receptionist VARCHAR(512) NOT NULL,
+    time INTEGER NOT NULL
+);
+
+INSERT INTO amendments_new (id, record_table, record_id, field, old_value, new_value, reason, receptionist, time)
+SELECT id, record_table, record_id, field, old_value, new_value, reason, receptionist, IFNULL(unixepoch(time), time)
+FROM amendments;
+
+DROP TABLE amendments;
+ALTER TABLE amendments_new RENAME TO amendments;
+
+CREATE INDEX amendments_record ON amendments (record_table, record_id);
+
+-- The audit log stays append-only, it is copied rather than changed.
+
+CREATE TABLE audit_log_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    time INTEGER NOT NULL,
+    action VARCHAR(64) NOT NULL,
+    record_table VARCHAR(64) NOT NULL,
+    record_id VARCHAR(512),
+    before TEXT,
+    after TEXT,
+    receptionist VARCHAR(512)
+);
+
+INSERT INTO audit_log_new (id, time, action, record_table, record_id, before, after, receptionist)
+SELECT id, IFNULL(unixepoch(time), time), action, record_table, record_id, before, after, receptionist
+FROM audit_log;
+
+DROP TABLE audit_log;
+ALTER TABLE audit_log_new RENAME TO audit_log;
+
+CREATE INDEX audit_log_record ON audit_log (record_table, record_id);
+CREATE INDEX audit_log_time ON audit_log (time);
+
+CREATE TRIGGER audit_log_no_update BEFORE UPDATE ON audit_log
+BEGIN
+    SELECT RAISE(ABORT, 'audit log entries cannot be changed');
+END;
+
+CREATE TRIGGER audit_log_no_delete BEFORE DELETE ON audit_log
+BEGIN
+    SELECT RAISE(ABORT, 'audit log entries cannot be deleted');
+END;
+
+-- Indexes for summaries, date filters, and looking up what is signed out.
+
+CREATE INDEX key_records_time_out ON key_records (time_out);
+CREATE INDEX key_records_time_in ON key_records (time_in);
+CREATE INDEX key_records_student_number ON key_records (student_number);
+CREATE INDEX key_records_key ON key_records (`key`);
+
+CREATE INDEX parcel_records_time_in ON parcel_records (time_in);
+CREATE INDEX parcel_records_time_out ON parcel_records (time_out);
+
+CREATE INDEX game_records_time_out ON game_records (time_out);
+CREATE INDEX game_records_time_in ON game_records (time_in);
+CREATE INDEX game_records_student_number ON game_records (student_number);
+CREATE INDEX game_records_game ON game_records (game);
+
+CREATE INDEX item_records_time_out ON item_records (time_out);
+CREATE INDEX item_records_student_number ON item_records (student_number);
+CREATE INDEX item_records_item ON item_records (item);
+
+-- Views and triggers as they were before.
+
+-- Records as they currently stand, with the latest amendment to each field applied.
+CREATE VIEW key_records_amended AS
+SELECT
+    r.id,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'key_records' AND a.record_id = r.id AND a.field = 'key' ORDER BY a.id DESC LIMIT 1), r.`key`) AS `key`,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'key_records' AND a.record_id = r.id AND a.field = 'student_name' ORDER BY a.id DESC LIMIT 1), r.student_name) AS student_name,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'key_records' AND a.record_id = r.id AND a.field = 'student_number' ORDER BY a.id DESC LIMIT 1), r.student_number) AS student_number,
+    r.receptionist_out,
+    r.receptionist_in,
+    r.time_out,
+    r.time_due,
+    r.time_in,
+    r.notes,
+    EXISTS(SELECT 1 FROM amendments a WHERE a.record_table = 'key_records' AND a.record_id = r.id AND a.field IS NULL) AS voided,
+    EXISTS(SELECT 1 FROM amendments a WHERE a.record_table = 'key_records' AND a.record_id = r.id AND a.field IS NOT NULL) AS amended
+FROM key_records r;
+
+CREATE VIEW parcel_records_amended AS
+SELECT
+    r.id,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'parcel_records' AND a.record_id = r.id AND a.field = 'parcel_desc' ORDER BY a.id DESC LIMIT 1), r.parcel_desc) AS parcel_desc,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'parcel_records' AND a.record_id = r.id AND a.field = 'student_name' ORDER BY a.id DESC LIMIT 1), r.student_name) AS student_name,
+    r.receptionist,
+    r.time_in,
+    r.time_out,
+    r.notes,
+    EXISTS(SELECT 1 FROM amendments a WHERE a.record_table = 'parcel_records' AND a.record_id = r.id AND a.field IS NULL) AS voided,
+    EXISTS(SELECT 1 FROM amendments a WHERE a.record_table = 'parcel_records' AND a.record_id = r.id AND a.field IS NOT NULL) AS amended
+FROM parcel_records r;
+
+CREATE VIEW game_records_amended AS
+SELECT
+    r.id,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'game_records' AND a.record_id = r.id AND a.field = 'game' ORDER BY a.id DESC LIMIT 1), r.game) AS game,
+    IFNULL(CAST((SELECT new_value FROM amendments a WHERE a.record_table = 'game_records' AND a.record_id = r.id AND a.field = 'quantity' ORDER BY a.id DESC LIMIT 1) AS INTEGER), r.quantity) AS quantity,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'game_records' AND a.record_id = r.id AND a.field = 'student_name' ORDER BY a.id DESC LIMIT 1), r.student_name) AS student_name,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'game_records' AND a.record_id = r.id AND a.field = 'student_number' ORDER BY a.id DESC LIMIT 1), r.student_number) AS student_number,
+    r.receptionist_out,
+    r.receptionist_in,
+    r.time_out,
+    r.time_due,
+    r.time_in,
+    r.notes,
+    EXISTS(SELECT 1 FROM amendments a WHERE a.record_table = 'game_records' AND a.record_id = r.id AND a.field IS NULL) AS voided,
+    EXISTS(SELECT 1 FROM amendments a WHERE a.record_table = 'game_records' AND a.record_id = r.id AND a.field IS NOT NULL) AS amended
+FROM game_records r;
+
+CREATE VIEW item_records_amended AS
+SELECT
+    r.id,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'item_records' AND a.record_id = r.id AND a.field = 'item' ORDER BY a.id DESC LIMIT 1), r.item) AS item,
+    IFNULL(CAST((SELECT new_value FROM amendments a WHERE a.record_table = 'item_records' AND a.record_id = r.id AND a.field = 'quantity' ORDER BY a.id DESC LIMIT 1) AS INTEGER), r.quantity) AS quantity,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'item_records' AND a.record_id = r.id AND a.field = 'student_name' ORDER BY a.id DESC LIMIT 1), r.student_name) AS student_name,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'item_records' AND a.record_id = r.id AND a.field = 'student_number' ORDER BY a.id DESC LIMIT 1), r.student_number) AS student_number,
+    r.receptionist,
+    r.time_out,
+    r.notes,
+    EXISTS(SELECT 1 FROM amendments a WHERE a.record_table = 'item_records' AND a.record_id = r.id AND a.field IS NULL) AS voided,
+    EXISTS(SELECT 1 FROM amendments a WHERE a.record_table = 'item_records' AND a.record_id = r.id AND a.field IS NOT NULL) AS amended
+FROM item_records r;
+
+
+CREATE TRIGGER key_records_one_out BEFORE INSERT ON key_records
+WHEN NEW.time_in IS NULL AND EXISTS (SELECT 1 FROM key_records_amended WHERE `key` = NEW.`key` AND time_in IS NULL AND NOT voided)
+BEGIN
+    SELECT RAISE(ABORT, 'key already signed out');
+END;
+
+CREATE TRIGGER game_records_in_stock BEFORE INSERT ON game_records
+WHEN NEW.time_in IS NULL AND NEW.quantity > (SELECT quantity FROM games WHERE game = NEW.game)
+    - (SELECT IFNULL(SUM(quantity), 0) FROM game_records_amended WHERE game = NEW.game AND time_in IS NULL AND NOT voided)
+BEGIN
+    SELECT RAISE(ABORT, 'insufficient stock');
+END;
+
+-- amending an open record must not break the rules either
+
+CREATE TRIGGER amendments_key_one_out BEFORE INSERT ON amendments
+WHEN NEW.record_table = 'key_records' AND NEW.field = 'key'
+    AND EXISTS (SELECT 1 FROM key_records_amended WHERE id = NEW.record_id AND time_in IS NULL AND NOT voided)
+    AND EXISTS (SELECT 1 FROM key_records_amended WHERE `key` = NEW.new_value AND id != NEW.record_id AND time_in IS NULL AND NOT voided)
+BEGIN
+    SELECT RAISE(ABORT, 'key already signed out');
+END;
+
+CREATE TRIGGER amendments_game_in_stock BEFORE INSERT ON amendments
+WHEN NEW.record_table = 'game_records' AND NEW.field IN ('game', 'quantity')
+    AND EXISTS (
+        SELECT 1 FROM (
+            SELECT
+                r.id,
+                CASE NEW.field WHEN 'game' THEN NEW.new_value ELSE r.game END AS game,
+                CASE NEW.field WHEN 'quantity' THEN CAST(NEW.new_value AS INTEGER) ELSE r.quantity END AS quantity
+            FROM game_records_amended r
+            WHERE r.id = NEW.record_id AND r.time_in IS NULL AND NOT r.voided
+        ) a
+        WHERE a.quantity > (SELECT quantity FROM games WHERE game = a.game)
+            - (SELECT IFNULL(SUM(o.quantity), 0) FROM game_records_amended o WHERE o.game = a.game AND o.id != a.id AND o.time_in IS NULL AND NOT o.voided)
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'insufficient stock');
+END;
@@ -0,0 +1,28 @@
+-- Students barred from signing out some or all records, e.g. after an incident.
+CREATE TABLE restrictions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    student_number VARCHAR(64) NOT NULL,
+    -- 'key', 'game', or 'item', NULL for every type of record
+    record_type VARCHAR(16),
+    -- the key, game, or item restricted, NULL for every one of the type
+    name VARCHAR(512),
+    reason VARCHAR(512) NOT NULL,
+    -- blocking restrictions refuse sign outs unless overridden, others only warn
+    blocking BOOLEAN NOT NULL DEFAULT TRUE,
+    time_start INTEGER NOT NULL,
+    -- NULL while in force indefinitely, set when lifted
+    time_end INTEGER,
+    imposed_by VARCHAR(512) NOT NULL,
+    time_imposed INTEGER NOT NULL
+);
+
+CREATE INDEX restrictions_student_number ON restrictions (student_number);
+
+-- Sign outs allowed despite a blocking restriction, and why.
+CREATE TABLE restriction_overrides (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    restriction_id INTEGER NOT NULL REFERENCES restrictions(id),
+    justification VARCHAR(512) NOT NULL,
+    receptionist VARCHAR(512) NOT NULL,
+    time INTEGER NOT NULL
+);
@@ -0,0 +1,12 @@
+-- Most records a student may have out at once.
+CREATE TABLE loan_limits (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    -- 'key' or 'game', NULL for every type of record
+    record_type VARCHAR(16),
+    -- the key or game limited, NULL for every one of the type
+    name VARCHAR(512),
+    max_open INTEGER NOT NULL CHECK (max_open >= 0)
+);
+
+-- one limit for each scope, NULLs are distinct in plain unique indexes
+CREATE UNIQUE INDEX loan_limits_scope ON loan_limits (COALESCE(record_type, ''), COALESCE(name, ''));
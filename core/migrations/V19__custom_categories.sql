@@ -0,0 +1,59 @@
+-- Record categories defined at runtime, e.g. sports equipment, each with its own fields.
+CREATE TABLE categories (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR(512) NOT NULL UNIQUE,
+    -- records are signed back in, rather than given away
+    returnable BOOLEAN NOT NULL,
+    -- entries have a quantity in stock, which open records may not exceed
+    stock_limited BOOLEAN NOT NULL,
+    student_number_required BOOLEAN NOT NULL
+);
+
+-- Extra fields filled in when signing out a record of a category, in order of position.
+CREATE TABLE category_fields (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    category_id INTEGER NOT NULL REFERENCES categories(id) ON DELETE CASCADE,
+    name VARCHAR(512) NOT NULL,
+    -- 'text', 'number', or 'yes_no'
+    kind VARCHAR(16) NOT NULL,
+    required BOOLEAN NOT NULL,
+    position INTEGER NOT NULL,
+    UNIQUE (category_id, name)
+);
+
+-- What can be signed out in each category, e.g. "Soccer Ball".
+CREATE TABLE category_entries (
+    category_id INTEGER NOT NULL REFERENCES categories(id) ON DELETE CASCADE,
+    name VARCHAR(512) NOT NULL,
+    -- in stock, NULL unless the category is stock limited
+    quantity INTEGER,
+    PRIMARY KEY (category_id, name)
+);
+
+-- categories with records cannot be deleted, the records keep their category
+CREATE TABLE category_records (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    category_id INTEGER NOT NULL REFERENCES categories(id),
+    entry VARCHAR(512) NOT NULL,
+    quantity INTEGER NOT NULL,
+    student_name VARCHAR(512) NOT NULL,
+    student_number VARCHAR(64),
+    receptionist_out VARCHAR(512),
+    receptionist_in VARCHAR(512),
+    time_out INTEGER NOT NULL,
+    time_in INTEGER,
+    -- values of the category's fields, a JSON object keyed by field name
+    fields TEXT NOT NULL DEFAULT '{}',
+    notes VARCHAR(512) NOT NULL
+);
+
+CREATE INDEX category_records_category ON category_records (category_id, id);
+CREATE INDEX category_records_time_out ON category_records (time_out);
+
+-- records of categories which are not returnable are never signed in, so they use up stock for good
+CREATE TRIGGER category_records_in_stock BEFORE INSERT ON category_records
+WHEN NEW.time_in IS NULL AND NEW.quantity > (SELECT quantity FROM category_entries WHERE category_id = NEW.category_id AND name = NEW.entry)
+    - (SELECT IFNULL(SUM(quantity), 0) FROM category_records WHERE category_id = NEW.category_id AND entry = NEW.entry AND time_in IS NULL)
+BEGIN
+    SELECT RAISE(ABORT, 'insufficient stock');
+END;
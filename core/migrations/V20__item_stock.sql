@@ -0,0 +1,100 @@
+-- Items may be lent out and signed back in like games, or given away as before, and may have a limited stock.
+-- The stock is the number on hand: signing out takes from it, signing in and restocking put back into it.
+-- Items with no stock, including every item added before, are not limited.
+
+ALTER TABLE items ADD COLUMN returnable BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE items ADD COLUMN quantity INTEGER;
+-- the stock is shown as low at or below this
+ALTER TABLE items ADD COLUMN low_stock INTEGER NOT NULL DEFAULT 0;
+
+-- whether the item was returnable when the record was made, so changing the item does not reopen old records
+ALTER TABLE item_records ADD COLUMN returnable BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE item_records ADD COLUMN receptionist_in VARCHAR(512);
+ALTER TABLE item_records ADD COLUMN time_in INTEGER;
+
+CREATE INDEX item_records_time_in ON item_records (time_in);
+
+CREATE TABLE item_restocks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    item VARCHAR(512) NOT NULL,
+    quantity INTEGER NOT NULL CHECK (quantity > 0),
+    receptionist VARCHAR(512),
+    time INTEGER NOT NULL,
+    notes VARCHAR(512) NOT NULL
+);
+
+CREATE INDEX item_restocks_item ON item_restocks (item);
+
+DROP VIEW item_records_amended;
+
+CREATE VIEW item_records_amended AS
+SELECT
+    r.id,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'item_records' AND a.record_id = r.id AND a.field = 'item' ORDER BY a.id DESC LIMIT 1), r.item) AS item,
+    IFNULL(CAST((SELECT new_value FROM amendments a WHERE a.record_table = 'item_records' AND a.record_id = r.id AND a.field = 'quantity' ORDER BY a.id DESC LIMIT 1) AS INTEGER), r.quantity) AS quantity,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'item_records' AND a.record_id = r.id AND a.field = 'student_name' ORDER BY a.id DESC LIMIT 1), r.student_name) AS student_name,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'item_records' AND a.record_id = r.id AND a.field = 'student_number' ORDER BY a.id DESC LIMIT 1), r.student_number) AS student_number,
+    r.returnable,
+    r.receptionist,
+    r.receptionist_in,
+    r.time_out,
+    r.time_in,
+    r.notes,
+    EXISTS(SELECT 1 FROM amendments a WHERE a.record_table = 'item_records' AND a.record_id = r.id AND a.field IS NULL) AS voided,
+    EXISTS(SELECT 1 FROM amendments a WHERE a.record_table = 'item_records' AND a.record_id = r.id AND a.field IS NOT NULL) AS amended
+FROM item_records r;
+
+-- The messages are matched by the application, see StorageError.
+-- Records of returnable items which are already signed in, e.g. merged from a backup, hold no stock.
+
+CREATE TRIGGER item_records_in_stock BEFORE INSERT ON item_records
+WHEN (NOT NEW.returnable OR NEW.time_in IS NULL) AND NEW.quantity > (SELECT quantity FROM items WHERE item = NEW.item)
+BEGIN
+    SELECT RAISE(ABORT, 'insufficient stock');
+END;
+
+CREATE TRIGGER item_records_take_stock AFTER INSERT ON item_records
+WHEN NOT NEW.returnable OR NEW.time_in IS NULL
+BEGIN
+    UPDATE items SET quantity = quantity - NEW.quantity WHERE item = NEW.item AND quantity IS NOT NULL;
+END;
+
+-- signing in puts back the item as amended
+CREATE TRIGGER item_records_return_stock AFTER UPDATE OF time_in ON item_records
+WHEN OLD.time_in IS NULL AND NEW.time_in IS NOT NULL
+    AND EXISTS (SELECT 1 FROM item_records_amended WHERE id = NEW.id AND NOT voided)
+BEGIN
+    UPDATE items SET quantity = quantity + (SELECT quantity FROM item_records_amended WHERE id = NEW.id)
+    WHERE item = (SELECT item FROM item_records_amended WHERE id = NEW.id) AND quantity IS NOT NULL;
+END;
+
+-- Amending or voiding a record which still holds stock puts it back, then an amended record takes it again,
+-- from another item if the item was amended. Signed in records hold no stock.
+CREATE TRIGGER amendments_item_stock BEFORE INSERT ON amendments
+WHEN NEW.record_table = 'item_records' AND (NEW.field IS NULL OR NEW.field IN ('item', 'quantity'))
+    AND EXISTS (SELECT 1 FROM item_records_amended WHERE id = NEW.record_id AND time_in IS NULL AND NOT voided)
+BEGIN
+    UPDATE items SET quantity = quantity + (SELECT quantity FROM item_records_amended WHERE id = NEW.record_id)
+    WHERE item = (SELECT item FROM item_records_amended WHERE id = NEW.record_id) AND quantity IS NOT NULL;
+
+    SELECT RAISE(ABORT, 'insufficient stock')
+    FROM item_records_amended r
+    WHERE NEW.field IS NOT NULL AND r.id = NEW.record_id
+        AND CASE NEW.field WHEN 'quantity' THEN CAST(NEW.new_value AS INTEGER) ELSE r.quantity END
+            > (SELECT quantity FROM items WHERE item = CASE NEW.field WHEN 'item' THEN NEW.new_value ELSE r.item END);
+
+    UPDATE items SET quantity = quantity - (
+        SELECT CASE NEW.field WHEN 'quantity' THEN CAST(NEW.new_value AS INTEGER) ELSE r.quantity END
+        FROM item_records_amended r WHERE r.id = NEW.record_id
+    )
+    WHERE NEW.field IS NOT NULL AND quantity IS NOT NULL AND item = (
+        SELECT CASE NEW.field WHEN 'item' THEN NEW.new_value ELSE r.item END
+        FROM item_records_amended r WHERE r.id = NEW.record_id
+    );
+END;
+
+-- restocking an item with no stock starts counting it
+CREATE TRIGGER item_restocks_add_stock AFTER INSERT ON item_restocks
+BEGIN
+    UPDATE items SET quantity = IFNULL(quantity, 0) + NEW.quantity WHERE item = NEW.item;
+END;
@@ -0,0 +1,52 @@
+-- Records of custom categories can be amended and voided like any other record.
+-- Voided records no longer hold stock.
+
+DROP TRIGGER category_records_in_stock;
+
+CREATE VIEW category_records_amended AS
+SELECT
+    r.id,
+    r.category_id,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'category_records' AND a.record_id = r.id AND a.field = 'entry' ORDER BY a.id DESC LIMIT 1), r.entry) AS entry,
+    IFNULL(CAST((SELECT new_value FROM amendments a WHERE a.record_table = 'category_records' AND a.record_id = r.id AND a.field = 'quantity' ORDER BY a.id DESC LIMIT 1) AS INTEGER), r.quantity) AS quantity,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'category_records' AND a.record_id = r.id AND a.field = 'student_name' ORDER BY a.id DESC LIMIT 1), r.student_name) AS student_name,
+    IFNULL((SELECT new_value FROM amendments a WHERE a.record_table = 'category_records' AND a.record_id = r.id AND a.field = 'student_number' ORDER BY a.id DESC LIMIT 1), r.student_number) AS student_number,
+    r.receptionist_out,
+    r.receptionist_in,
+    r.time_out,
+    r.time_in,
+    r.fields,
+    r.notes,
+    EXISTS(SELECT 1 FROM amendments a WHERE a.record_table = 'category_records' AND a.record_id = r.id AND a.field IS NULL) AS voided,
+    EXISTS(SELECT 1 FROM amendments a WHERE a.record_table = 'category_records' AND a.record_id = r.id AND a.field IS NOT NULL) AS amended
+FROM category_records r;
+
+-- The messages are matched by the application, see StorageError.
+-- Records of categories which are not returnable are never signed in, so they use up stock for good.
+
+CREATE TRIGGER category_records_in_stock BEFORE INSERT ON category_records
+WHEN NEW.time_in IS NULL AND NEW.quantity > (SELECT quantity FROM category_entries WHERE category_id = NEW.category_id AND name = NEW.entry)
+    - (SELECT IFNULL(SUM(quantity), 0) FROM category_records_amended WHERE category_id = NEW.category_id AND entry = NEW.entry AND time_in IS NULL AND NOT voided)
+BEGIN
+    SELECT RAISE(ABORT, 'insufficient stock');
+END;
+
+-- amending an open record must not break the rules either
+CREATE TRIGGER amendments_category_in_stock BEFORE INSERT ON amendments
+WHEN NEW.record_table = 'category_records' AND NEW.field IN ('entry', 'quantity')
+    AND EXISTS (
+        SELECT 1 FROM (
+            SELECT
+                r.id,
+                r.category_id,
+                CASE NEW.field WHEN 'entry' THEN NEW.new_value ELSE r.entry END AS entry,
+                CASE NEW.field WHEN 'quantity' THEN CAST(NEW.new_value AS INTEGER) ELSE r.quantity END AS quantity
+            FROM category_records_amended r
+            WHERE r.id = NEW.record_id AND r.time_in IS NULL AND NOT r.voided
+        ) a
+        WHERE a.quantity > (SELECT quantity FROM category_entries WHERE category_id = a.category_id AND name = a.entry)
+            - (SELECT IFNULL(SUM(o.quantity), 0) FROM category_records_amended o WHERE o.category_id = a.category_id AND o.entry = a.entry AND o.id != a.id AND o.time_in IS NULL AND NOT o.voided)
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'insufficient stock');
+END;
@@ -0,0 +1,2 @@
+-- Only admins may override blocking restrictions, or make other receptionists admins.
+ALTER TABLE receptionists ADD COLUMN admin BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,132 @@
+-- The amended views take each amendable column from a subquery, so filtering a view by one of them reads every record.
+-- Lookups by an amendable column instead pick the records from the table by its index, along with any record
+-- amended to the value, then check the view, since the value may have been amended away since.
+
+CREATE INDEX amendments_value ON amendments (record_table, field, new_value);
+
+-- only records still out are looked up by key, game, or entry, so the indexes need only hold those
+CREATE INDEX key_records_out ON key_records (`key`) WHERE time_in IS NULL;
+CREATE INDEX game_records_out ON game_records (game) WHERE time_in IS NULL;
+CREATE INDEX category_records_out ON category_records (category_id, entry) WHERE time_in IS NULL;
+
+CREATE INDEX category_records_student_number ON category_records (student_number);
+CREATE INDEX parcel_records_student_name ON parcel_records (lower(student_name));
+
+DROP TRIGGER key_records_one_out;
+DROP TRIGGER game_records_in_stock;
+DROP TRIGGER category_records_in_stock;
+DROP TRIGGER amendments_key_one_out;
+DROP TRIGGER amendments_game_in_stock;
+DROP TRIGGER amendments_category_in_stock;
+
+-- The messages are matched by the application, see StorageError.
+
+CREATE TRIGGER key_records_one_out BEFORE INSERT ON key_records
+WHEN NEW.time_in IS NULL AND EXISTS (
+    SELECT 1 FROM key_records_amended
+    WHERE id IN (
+        SELECT id FROM key_records WHERE `key` = NEW.`key` AND time_in IS NULL
+        UNION SELECT record_id FROM amendments WHERE record_table = 'key_records' AND field = 'key' AND new_value = NEW.`key`
+    )
+        AND `key` = NEW.`key` AND time_in IS NULL AND NOT voided
+)
+BEGIN
+    SELECT RAISE(ABORT, 'key already signed out');
+END;
+
+CREATE TRIGGER game_records_in_stock BEFORE INSERT ON game_records
+WHEN NEW.time_in IS NULL AND NEW.quantity > (SELECT quantity FROM games WHERE game = NEW.game)
+    - (
+        SELECT IFNULL(SUM(quantity), 0) FROM game_records_amended
+        WHERE id IN (
+            SELECT id FROM game_records WHERE game = NEW.game AND time_in IS NULL
+            UNION SELECT record_id FROM amendments WHERE record_table = 'game_records' AND field = 'game' AND new_value = NEW.game
+        )
+            AND game = NEW.game AND time_in IS NULL AND NOT voided
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'insufficient stock');
+END;
+
+-- records of categories which are not returnable are never signed in, so they use up stock for good
+CREATE TRIGGER category_records_in_stock BEFORE INSERT ON category_records
+WHEN NEW.time_in IS NULL AND NEW.quantity > (SELECT quantity FROM category_entries WHERE category_id = NEW.category_id AND name = NEW.entry)
+    - (
+        SELECT IFNULL(SUM(quantity), 0) FROM category_records_amended
+        WHERE id IN (
+            SELECT id FROM category_records WHERE category_id = NEW.category_id AND entry = NEW.entry AND time_in IS NULL
+            UNION SELECT record_id FROM amendments WHERE record_table = 'category_records' AND field = 'entry' AND new_value = NEW.entry
+        )
+            AND category_id = NEW.category_id AND entry = NEW.entry AND time_in IS NULL AND NOT voided
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'insufficient stock');
+END;
+
+-- amending an open record must not break the rules either
+
+CREATE TRIGGER amendments_key_one_out BEFORE INSERT ON amendments
+WHEN NEW.record_table = 'key_records' AND NEW.field = 'key'
+    AND EXISTS (SELECT 1 FROM key_records_amended WHERE id = NEW.record_id AND time_in IS NULL AND NOT voided)
+    AND EXISTS (
+        SELECT 1 FROM key_records_amended
+        WHERE id IN (
+            SELECT id FROM key_records WHERE `key` = NEW.new_value AND time_in IS NULL
+            UNION SELECT record_id FROM amendments WHERE record_table = 'key_records' AND field = 'key' AND new_value = NEW.new_value
+        )
+            AND `key` = NEW.new_value AND id != NEW.record_id AND time_in IS NULL AND NOT voided
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'key already signed out');
+END;
+
+CREATE TRIGGER amendments_game_in_stock BEFORE INSERT ON amendments
+WHEN NEW.record_table = 'game_records' AND NEW.field IN ('game', 'quantity')
+    AND EXISTS (
+        SELECT 1 FROM (
+            SELECT
+                r.id,
+                CASE NEW.field WHEN 'game' THEN NEW.new_value ELSE r.game END AS game,
+                CASE NEW.field WHEN 'quantity' THEN CAST(NEW.new_value AS INTEGER) ELSE r.quantity END AS quantity
+            FROM game_records_amended r
+            WHERE r.id = NEW.record_id AND r.time_in IS NULL AND NOT r.voided
+        ) a
+        WHERE a.quantity > (SELECT quantity FROM games WHERE game = a.game)
+            - (
+                SELECT IFNULL(SUM(o.quantity), 0) FROM game_records_amended o
+                WHERE o.id IN (
+                    SELECT id FROM game_records WHERE game = a.game AND time_in IS NULL
+                    UNION SELECT record_id FROM amendments WHERE record_table = 'game_records' AND field = 'game' AND new_value = a.game
+                )
+                    AND o.game = a.game AND o.id != a.id AND o.time_in IS NULL AND NOT o.voided
+            )
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'insufficient stock');
+END;
+
+CREATE TRIGGER amendments_category_in_stock BEFORE INSERT ON amendments
+WHEN NEW.record_table = 'category_records' AND NEW.field IN ('entry', 'quantity')
+    AND EXISTS (
+        SELECT 1 FROM (
+            SELECT
+                r.id,
+                r.category_id,
+                CASE NEW.field WHEN 'entry' THEN NEW.new_value ELSE r.entry END AS entry,
+                CASE NEW.field WHEN 'quantity' THEN CAST(NEW.new_value AS INTEGER) ELSE r.quantity END AS quantity
+            FROM category_records_amended r
+            WHERE r.id = NEW.record_id AND r.time_in IS NULL AND NOT r.voided
+        ) a
+        WHERE a.quantity > (SELECT quantity FROM category_entries WHERE category_id = a.category_id AND name = a.entry)
+            - (
+                SELECT IFNULL(SUM(o.quantity), 0) FROM category_records_amended o
+                WHERE o.id IN (
+                    SELECT id FROM category_records WHERE category_id = a.category_id AND entry = a.entry AND time_in IS NULL
+                    UNION SELECT record_id FROM amendments WHERE record_table = 'category_records' AND field = 'entry' AND new_value = a.entry
+                )
+                    AND o.category_id = a.category_id AND o.entry = a.entry AND o.id != a.id AND o.time_in IS NULL AND NOT o.voided
+            )
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'insufficient stock');
+END;
@@ -0,0 +1,4 @@
+ALTER TABLE keys ADD COLUMN loan_minutes INTEGER;
+ALTER TABLE games ADD COLUMN loan_minutes INTEGER;
+ALTER TABLE key_records ADD COLUMN time_due VARCHAR(64);
+ALTER TABLE game_records ADD COLUMN time_due VARCHAR(64);
@@ -0,0 +1,72 @@
+CREATE TABLE students (
+    student_number VARCHAR(9) PRIMARY KEY,
+    student_name VARCHAR(512) NOT NULL
+);
+
+-- Fill the registry with the most recent name used for each student number.
+INSERT INTO students (student_number, student_name)
+SELECT student_number, student_name FROM (
+    SELECT student_number, student_name, ROW_NUMBER() OVER(PARTITION BY student_number ORDER BY time_out DESC) rn FROM (
+        SELECT UPPER(student_number) AS student_number, student_name, time_out FROM key_records
+        UNION ALL
+        SELECT UPPER(student_number) AS student_number, student_name, time_out FROM item_records
+        UNION ALL
+        SELECT UPPER(student_number) AS student_number, student_name, time_out FROM game_records
+    )
+) WHERE rn = 1;
+
+-- SQLite cannot add a foreign key to an existing table, so the record tables are rebuilt.
+
+CREATE TABLE key_records_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    `key` VARCHAR(512) NOT NULL,
+    student_name VARCHAR(512) NOT NULL,
+    student_number VARCHAR(9) NOT NULL REFERENCES students(student_number),
+    receptionist VARCHAR(512),
+    time_out VARCHAR(64) NOT NULL,
+    time_due VARCHAR(64),
+    time_in VARCHAR(64),
+    notes VARCHAR(512) NOT NULL
+);
+
+INSERT INTO key_records_new (id, `key`, student_name, student_number, receptionist, time_out, time_due, time_in, notes)
+SELECT id, `key`, student_name, UPPER(student_number), receptionist, time_out, time_due, time_in, notes FROM key_records;
+
+DROP TABLE key_records;
+ALTER TABLE key_records_new RENAME TO key_records;
+
+CREATE TABLE game_records_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    game VARCHAR(512) NOT NULL,
+    quantity INTEGER NOT NULL,
+    student_name VARCHAR(512) NOT NULL,
+    student_number VARCHAR(9) NOT NULL REFERENCES students(student_number),
+    receptionist VARCHAR(512),
+    time_out VARCHAR(64) NOT NULL,
+    time_due VARCHAR(64),
+    time_in VARCHAR(64),
+    notes VARCHAR(512) NOT NULL
+);
+
+INSERT INTO game_records_new (id, game, quantity, student_name, student_number, receptionist, time_out, time_due, time_in, notes)
+SELECT id, game, quantity, student_name, UPPER(student_number), receptionist, time_out, time_due, time_in, notes FROM game_records;
+
+DROP TABLE game_records;
+ALTER TABLE game_records_new RENAME TO game_records;
+
+CREATE TABLE item_records_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    item VARCHAR(512) NOT NULL,
+    quantity INTEGER NOT NULL,
+    student_name VARCHAR(512) NOT NULL,
+    student_number VARCHAR(9) NOT NULL REFERENCES students(student_number),
+    receptionist VARCHAR(512) NOT NULL,
+    time_out VARCHAR(64) NOT NULL,
+    notes VARCHAR(512) NOT NULL
+);
+
+INSERT INTO item_records_new (id, item, quantity, student_name, student_number, receptionist, time_out, notes)
+SELECT id, item, quantity, student_name, UPPER(student_number), receptionist, time_out, notes FROM item_records;
+
+DROP TABLE item_records;
+ALTER TABLE item_records_new RENAME TO item_records;
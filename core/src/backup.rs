@@ -0,0 +1,257 @@
+use std::{path::{Path, PathBuf}, collections::HashSet, time::Duration};
+
+use thiserror::Error;
+
+/// Prefix of automatic backup file names, so that other files in the backup directory are never rotated.
+const BACKUP_PREFIX: &str = "auto_";
+const BACKUP_EXTENSION: &str = "sqlite";
+/// Extension of a backup which is still being written.
+const PARTIAL_EXTENSION: &str = "partial";
+const BACKUP_NAME_FORMAT: &str = "%Y-%m-%d_%H-%M-%S";
+/// Pages copied at a time, so that progress can be reported and writers are not held up for long.
+const BACKUP_STEP_PAGES: i32 = 256;
+/// Wait before retrying a step while another connection is writing.
+const BACKUP_BUSY_PAUSE: Duration = Duration::from_millis(100);
+
+#[derive(Error, Debug)]
+pub enum BackupError {
+    #[error("Database operation failed. {0}")]
+    DbError(#[from] rusqlite::Error),
+    #[error("Failed to write backup. {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Backup failed integrity check. {0}")]
+    IntegrityError(String),
+}
+
+/// How many of the most recent automatic backups to keep. The newest backup in each
+/// hour, day, and month is kept for that many hours, days, and months respectively.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retention {
+    pub hourly: usize,
+    pub daily: usize,
+    pub monthly: usize,
+}
+
+/// Save a backup of the database to the directory, then delete old backups outside of the retention policy.
+/// Returns the path of the new backup.
+pub fn backup(connection: &rusqlite::Connection, directory: &Path, retention: Retention) -> Result<PathBuf, BackupError> {
+    backup_with_progress(connection, directory, retention, |_| {})
+}
+
+/// As `backup`, calling `progress` with the fraction copied so far.
+pub fn backup_with_progress(connection: &rusqlite::Connection, directory: &Path, retention: Retention, progress: impl FnMut(f32)) -> Result<PathBuf, BackupError> {
+    std::fs::create_dir_all(directory)?;
+
+    let time = chrono::Local::now().naive_local();
+
+    let path = directory.join(format!("{BACKUP_PREFIX}{}.{BACKUP_EXTENSION}", time.format(BACKUP_NAME_FORMAT)));
+    let partial_path = path.with_extension(PARTIAL_EXTENSION);
+
+    // write to a temporary file first, so a power failure part way through never leaves a broken backup behind
+    copy(connection, &partial_path, progress)?;
+
+    if let Err(err) = verify(&partial_path) {
+        std::fs::remove_file(&partial_path)?;
+        return Err(err);
+    }
+
+    std::fs::rename(&partial_path, &path)?;
+
+    log::info!("saved automatic backup to {path:?}");
+
+    rotate(directory, retention)?;
+
+    Ok(path)
+}
+
+/// Copy the database to a file a few pages at a time, calling `progress` with the fraction copied so far.
+pub fn copy(connection: &rusqlite::Connection, path: &Path, mut progress: impl FnMut(f32)) -> Result<(), rusqlite::Error> {
+    let mut destination = rusqlite::Connection::open(path)?;
+
+    let backup = rusqlite::backup::Backup::new(connection, &mut destination)?;
+
+    loop {
+        match backup.step(BACKUP_STEP_PAGES)? {
+            rusqlite::backup::StepResult::Done => break,
+            rusqlite::backup::StepResult::More => {},
+            _ => std::thread::sleep(BACKUP_BUSY_PAUSE),
+        }
+
+        let copied = backup.progress();
+
+        progress(1.0 - copied.remaining as f32 / copied.pagecount.max(1) as f32);
+    }
+
+    progress(1.0);
+
+    Ok(())
+}
+
+/// Check that a backup can be opened and is not corrupt.
+pub fn verify(path: &Path) -> Result<(), BackupError> {
+    let connection = rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+    let check: String = connection.query_row("PRAGMA quick_check", (), |row| row.get(0))?;
+
+    if check != "ok" {
+        return Err(BackupError::IntegrityError(check));
+    }
+
+    Ok(())
+}
+
+/// Time of the most recent automatic backup in the directory, if any.
+pub fn last_backup(directory: &Path) -> Option<chrono::NaiveDateTime> {
+    list_backups(directory).ok()?.first().map(|(time, _)| *time)
+}
+
+/// Delete automatic backups which are outside of the retention policy. The newest backup is always kept.
+fn rotate(directory: &Path, retention: Retention) -> Result<(), BackupError> {
+    let backups = list_backups(directory)?;
+
+    let mut keep = HashSet::new();
+
+    if let Some((_, newest)) = backups.first() {
+        keep.insert(newest.clone());
+    }
+
+    for (count, period_format) in [(retention.hourly, "%Y-%m-%d %H"), (retention.daily, "%Y-%m-%d"), (retention.monthly, "%Y-%m")] {
+        let mut periods = HashSet::new();
+
+        // backups are newest first, so the first seen in each period is the newest
+        for (time, path) in &backups {
+            let period = time.format(period_format).to_string();
+
+            if !periods.contains(&period) {
+                if periods.len() >= count {
+                    break;
+                }
+
+                periods.insert(period);
+                keep.insert(path.clone());
+            }
+        }
+    }
+
+    for (_, path) in backups.iter().filter(|(_, path)| !keep.contains(path)) {
+        std::fs::remove_file(path)?;
+
+        log::info!("deleted old automatic backup {path:?}");
+    }
+
+    Ok(())
+}
+
+/// All automatic backups in the directory, newest first.
+fn list_backups(directory: &Path) -> Result<Vec<(chrono::NaiveDateTime, PathBuf)>, std::io::Error> {
+    let mut backups = vec![];
+
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+
+        if path.extension().map(|e| e != BACKUP_EXTENSION).unwrap_or(true) {
+            continue;
+        }
+
+        let time = path.file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.strip_prefix(BACKUP_PREFIX))
+            .and_then(|stem| chrono::NaiveDateTime::parse_from_str(stem, BACKUP_NAME_FORMAT).ok());
+
+        if let Some(time) = time {
+            backups.push((time, path));
+        }
+    }
+
+    backups.sort_by_key(|(time, _)| std::cmp::Reverse(*time));
+
+    Ok(backups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{APP_NAME, open_database};
+
+    fn test_directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("{APP_NAME}_{name}_{}", std::process::id()));
+
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+
+        directory
+    }
+
+    fn names(directory: &Path) -> Vec<String> {
+        let mut names = std::fs::read_dir(directory).unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn rotate_keeps_newest_in_each_period() {
+        let directory = test_directory("rotate_test");
+
+        for name in [
+            "auto_2026-01-15_12-00-00.sqlite",
+            "auto_2026-02-28_12-00-00.sqlite",
+            "auto_2026-03-01_12-00-00.sqlite",
+            "auto_2026-03-02_08-00-00.sqlite",
+            "auto_2026-03-02_09-00-00.sqlite",
+            "auto_2026-03-02_10-10-00.sqlite",
+            "auto_2026-03-02_10-30-00.sqlite",
+            // saved by hand, never rotated
+            "manual.sqlite",
+            "auto_notes.txt",
+        ] {
+            std::fs::write(directory.join(name), "").unwrap();
+        }
+
+        rotate(&directory, Retention { hourly: 2, daily: 2, monthly: 2 }).unwrap();
+
+        assert_eq!(names(&directory), [
+            "auto_2026-02-28_12-00-00.sqlite",
+            "auto_2026-03-01_12-00-00.sqlite",
+            "auto_2026-03-02_09-00-00.sqlite",
+            "auto_2026-03-02_10-30-00.sqlite",
+            "auto_notes.txt",
+            "manual.sqlite",
+        ]);
+
+        // the newest backup is kept even if nothing else is
+        rotate(&directory, Retention { hourly: 0, daily: 0, monthly: 0 }).unwrap();
+
+        assert_eq!(names(&directory), ["auto_2026-03-02_10-30-00.sqlite", "auto_notes.txt", "manual.sqlite"]);
+        assert_eq!(last_backup(&directory), chrono::NaiveDateTime::parse_from_str("2026-03-02_10-30-00", BACKUP_NAME_FORMAT).ok());
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn backup_is_verified_and_complete() {
+        let directory = test_directory("backup_test");
+
+        let connection = open_database(Path::new(":memory:")).unwrap();
+        connection.execute("INSERT INTO students (student_number, student_name) VALUES ('ABCDEF001', 'Alex')", ()).unwrap();
+
+        let mut fractions = vec![];
+        let path = backup_with_progress(&connection, &directory, Retention { hourly: 1, daily: 1, monthly: 1 }, |fraction| fractions.push(fraction)).unwrap();
+
+        assert_eq!(fractions.last(), Some(&1.0));
+        assert!(path.starts_with(&directory));
+        verify(&path).unwrap();
+
+        // no partial file is left behind
+        assert_eq!(names(&directory), [path.file_name().unwrap().to_str().unwrap()]);
+
+        let copy = rusqlite::Connection::open(&path).unwrap();
+        let name: String = copy.query_row("SELECT student_name FROM students", (), |row| row.get(0)).unwrap();
+        assert_eq!(name, "Alex");
+        drop(copy);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
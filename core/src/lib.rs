@@ -0,0 +1,101 @@
+use std::path::Path;
+
+use records::StorageError;
+
+pub mod embedded {
+    use refinery::embed_migrations;
+    embed_migrations!("migrations");
+}
+
+pub mod records;
+pub mod backup;
+pub mod service;
+pub mod worker;
+pub mod report;
+
+pub const APP_NAME: &str = "Blackcurrant";
+
+pub const NAME_MAX_LENGTH: usize = 512;
+pub const NOTES_MAX_LENGTH: usize = 512;
+pub const STUDENT_NUMBER_LENGTH: usize = 9;
+pub const ROOM_MAX_LENGTH: usize = 64;
+pub const STAFF_NUMBER_LENGTH: usize = 8;
+pub const MAX_QUANTITY: i64 = 99;
+pub const DATE_TIME_FORMAT: &str = "%d/%m/%Y %H:%M";
+pub const PAGE_SIZE: i64 = 100;
+
+/// Open a database file, creating it if needed, and bring it up to date.
+pub fn open_database(path: &Path) -> Result<rusqlite::Connection, StorageError> {
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory).map_err(StorageError::FileError)?;
+    }
+
+    let mut connection = rusqlite::Connection::open(path)?;
+
+    log::info!("connected to sqlite database {path:?}");
+
+    // wait for other programs using the database (e.g. the CLI taking a backup), rather than failing at once,
+    // which includes while migrating
+    connection.busy_timeout(std::time::Duration::from_secs(5))?;
+    connection.pragma_update(None, "foreign_keys", true)?;
+
+    embedded::migrations::runner().run(&mut connection)?;
+
+    log::info!("migrations complete");
+
+    Ok(connection)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrations_keep_existing_records() {
+        let mut connection = rusqlite::Connection::open_in_memory().unwrap();
+
+        embedded::migrations::runner().set_target(refinery::Target::Version(7)).run(&mut connection).unwrap();
+
+        // as written before students, receptionist rosters, and epoch times
+        connection.execute_batch("
+            INSERT INTO keys (`key`) VALUES ('Room 1');
+            INSERT INTO games (game, quantity) VALUES ('Chess', 2);
+            INSERT INTO items (item) VALUES ('Stapler');
+            INSERT INTO key_records (`key`, student_name, student_number, receptionist, time_out, time_in, notes) VALUES
+                ('Room 1', 'Al', 'abcdef001', 'Sam', '2023-10-01T09:00:00+01:00', '2023-10-01T10:00:00+01:00', ''),
+                ('Room 1', 'Alex', 'abcdef001', 'Sam ', '2023-10-01T12:00:00+01:00', NULL, ''),
+                ('Room 1', 'Alex', 'ABCDEF001', 'Jo', 'yesterday', 'today', '');
+            INSERT INTO game_records (game, quantity, student_name, student_number, receptionist, time_out, time_in, notes) VALUES
+                ('Chess', 1, 'Alex', 'ABCDEF001', 'Jo', '2023-10-01T12:00:00Z', NULL, '');
+            INSERT INTO item_records (item, quantity, student_name, student_number, receptionist, time_out, notes) VALUES
+                ('Stapler', 1, 'Blake', 'ABCDEF002', 'Jo', '2023-10-01T12:00:00Z', '');
+            INSERT INTO parcel_records (parcel_desc, student_name, receptionist, time_in, time_out, notes) VALUES
+                ('Box', 'Alex', 'Jo', '2023-10-01T12:00:00Z', NULL, '');
+        ").unwrap();
+
+        embedded::migrations::runner().run(&mut connection).unwrap();
+
+        let query = |sql: &str| -> String { connection.query_row(sql, (), |row| row.get(0)).unwrap() };
+
+        // students are registered under their latest name, receptionists once each
+        assert_eq!(query("SELECT group_concat(student_number || ' ' || student_name, ', ') FROM students"), "ABCDEF001 Alex, ABCDEF002 Blake");
+        assert_eq!(query("SELECT group_concat(name, ', ') FROM (SELECT name FROM receptionists ORDER BY name)"), "Jo, Sam");
+
+        // records still out were signed out by the last receptionist, others were signed in by them
+        assert_eq!(query("SELECT IFNULL(receptionist_out, '') || '/' || IFNULL(receptionist_in, '') FROM key_records WHERE id = 1"), "/Sam");
+        assert_eq!(query("SELECT IFNULL(receptionist_out, '') || '/' || IFNULL(receptionist_in, '') FROM key_records WHERE id = 2"), "Sam /");
+
+        // times become epoch seconds, whatever their offset, and unreadable times are kept for the database check
+        assert_eq!(query("SELECT group_concat(typeof(time_out) || ' ' || time_out, ', ') FROM key_records"), "integer 1696147200, integer 1696158000, text yesterday");
+        assert_eq!(query("SELECT typeof(time_in) || ' ' || time_in FROM key_records WHERE id = 3"), "text today");
+        assert_eq!(query("SELECT time_out || ' ' || IFNULL(time_in, 'out') FROM game_records_amended"), "1696161600 out");
+        assert_eq!(query("SELECT typeof(time_in) || ' ' || time_in FROM parcel_records_amended"), "integer 1696161600");
+
+        // items from before stock was counted are given away, and not limited
+        assert_eq!(query("SELECT IIF(quantity IS NULL AND NOT returnable, 'unlimited', 'limited') FROM items"), "unlimited");
+
+        // and the sign out rules hold for records from before them
+        let result = connection.execute("INSERT INTO key_records (`key`, student_name, student_number, time_out, notes) VALUES ('Room 1', 'Blake', 'ABCDEF002', 0, '')", ());
+        assert!(matches!(StorageError::from(result.unwrap_err()), StorageError::KeyAlreadyOut));
+    }
+}
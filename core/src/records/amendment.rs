@@ -0,0 +1,82 @@
+use crate::worker::WorkerHandle;
+
+use super::{AmendmentRecord, StorageError, audit, parse_time};
+
+/// Add an amendment to a record on the worker, see `amend_on`.
+pub(super) fn amend(worker: &WorkerHandle, table: &'static str, amendable: &'static [&'static str], id: i64, change: Option<(&str, &str)>, reason: &str, receptionist: &str) -> Result<(), StorageError> {
+    let change = change.map(|(field, value)| (field.to_owned(), value.to_owned()));
+    let (reason, receptionist) = (reason.to_owned(), receptionist.to_owned());
+
+    worker.call(move |connection| {
+        let change = change.as_ref().map(|(field, value)| (field.as_str(), value.as_str()));
+
+        amend_on(connection, table, amendable, id, change, &reason, &receptionist)
+    })
+}
+
+/// Add an amendment to a record, the record itself is never changed.
+/// `change` is the field and its new value, if it is `None`, the record is voided.
+pub(super) fn amend_on(connection: &mut rusqlite::Connection, table: &str, amendable: &[&str], id: i64, change: Option<(&str, &str)>, reason: &str, receptionist: &str) -> Result<(), StorageError> {
+    let (field, new_value) = (change.map(|(field, _)| field), change.map(|(_, value)| value));
+
+    let tx = connection.transaction()?;
+
+    let old_value = match field {
+        Some(field) => {
+            if !amendable.contains(&field) {
+                return Err(rusqlite::Error::InvalidColumnName(field.into()).into());
+            }
+
+            // old value is the record as currently amended
+            tx.query_row(&format!("SELECT CAST({field} AS TEXT) AS v FROM {table}_amended WHERE id = ?"), (id,), |row| row.get::<_, Option<String>>("v"))?
+        },
+        None => None,
+    };
+
+    // records reference registered students, so register the corrected student number
+    if field == Some("student_number") {
+        tx.execute(
+            &format!("INSERT OR IGNORE INTO students (student_number, student_name) SELECT ?, student_name FROM {table}_amended WHERE id = ?"),
+            (new_value, id)
+        )?;
+    }
+
+    let before = audit::snapshot(&tx, &format!("{table}_amended"), "id", &id)?;
+
+    tx.execute(
+        "INSERT INTO amendments (id, record_table, record_id, field, old_value, new_value, reason, receptionist, time) VALUES (NULL, ?, ?, ?, ?, ?, ?, ?, ?)",
+        (table, id, field, old_value, new_value, reason, receptionist, chrono::Utc::now().timestamp())
+    )?;
+
+    let after = audit::snapshot(&tx, &format!("{table}_amended"), "id", &id)?;
+
+    audit::log_entry(&tx, if field.is_some() { "amend" } else { "void" }, table, &id, before, after)?;
+
+    tx.commit()?;
+
+    log::info!("amended {table} record {id}: {field:?}");
+
+    Ok(())
+}
+
+/// All amendments to a record, oldest first.
+pub(super) fn fetch_amendments(worker: &WorkerHandle, table: &'static str, id: i64) -> Result<Vec<AmendmentRecord>, StorageError> {
+    worker.call(move |connection| {
+        let mut stmt = connection.prepare("SELECT * FROM amendments WHERE record_table = ? AND record_id = ? ORDER BY id")?;
+
+        let records = stmt.query_map((table, id), |row| {
+            let time = parse_time(row, "time")?;
+
+            Ok(AmendmentRecord {
+                field: row.get("field")?,
+                old_value: row.get("old_value")?,
+                new_value: row.get("new_value")?,
+                reason: row.get("reason")?,
+                receptionist: row.get("receptionist")?,
+                time,
+            })
+        })?.collect::<Result<_, _>>()?;
+
+        Ok(records)
+    })
+}
@@ -0,0 +1,258 @@
+use std::fmt::Display;
+
+use rusqlite::{types::{Value, ValueRef}, OptionalExtension};
+
+use crate::worker::WorkerHandle;
+
+use super::{CorruptRow, FetchedPage, PendingPage, request_page, poll_page, fetch_counted_page, parse_time, Page, PagePosition, AuditRecord, StorageError, local_midnight};
+
+/// Name of the receptionist on the current shift, if anyone is on duty.
+const ON_DUTY_RECEPTIONIST: &str = "SELECT r.name FROM shifts s JOIN receptionists r ON r.id = s.receptionist_id WHERE s.time_end IS NULL ORDER BY s.id DESC LIMIT 1";
+
+/// Serialise a row to a JSON object, or `None` if it does not exist.
+pub(super) fn snapshot(connection: &rusqlite::Connection, table: &str, key_column: &str, id: &(impl rusqlite::ToSql + ?Sized)) -> Result<Option<String>, rusqlite::Error> {
+    let mut stmt = connection.prepare(&format!("SELECT * FROM {table} WHERE `{key_column}` = ?"))?;
+
+    let columns = stmt.column_names().into_iter().map(String::from).collect::<Vec<_>>();
+
+    stmt.query_row((id,), |row| {
+        let mut object = serde_json::Map::new();
+
+        for (i, column) in columns.iter().enumerate() {
+            let value = match row.get_ref(i)? {
+                ValueRef::Null => serde_json::Value::Null,
+                ValueRef::Integer(value) => value.into(),
+                ValueRef::Real(value) => value.into(),
+                ValueRef::Text(value) => String::from_utf8_lossy(value).into(),
+                ValueRef::Blob(_) => serde_json::Value::Null,
+            };
+
+            object.insert(column.clone(), value);
+        }
+
+        Ok(serde_json::Value::Object(object).to_string())
+    }).optional()
+}
+
+/// Append an entry to the audit log, attributed to the receptionist on duty.
+pub(super) fn log_entry(connection: &rusqlite::Connection, action: &str, table: &str, id: &impl Display, before: Option<String>, after: Option<String>) -> Result<(), rusqlite::Error> {
+    connection.execute(
+        &format!("INSERT INTO audit_log (id, time, action, record_table, record_id, before, after, receptionist) VALUES (NULL, ?, ?, ?, ?, ?, ?, ({ON_DUTY_RECEPTIONIST}))"),
+        (chrono::Utc::now().timestamp(), action, table, id.to_string(), before, after)
+    )?;
+
+    Ok(())
+}
+
+/// Run a mutation of a single row on the worker, logging the row before and after the change, see `audited_on`.
+pub(super) fn audited<K: rusqlite::ToSql + Display + Send + 'static>(worker: &WorkerHandle, action: &'static str, table: &'static str, key_column: &'static str, id: Option<K>, mutate: impl FnOnce(&rusqlite::Transaction) -> Result<K, rusqlite::Error> + Send + 'static) -> Result<K, StorageError> {
+    worker.call(move |connection| audited_on(connection, action, table, key_column, id, mutate))
+}
+
+/// Run a mutation of a single row in a transaction, logging the row before and after the change.
+/// `id` is the row being changed, or `None` for inserts, `mutate` returns the id of the changed row.
+pub(super) fn audited_on<K: rusqlite::ToSql + Display>(connection: &mut rusqlite::Connection, action: &str, table: &str, key_column: &str, id: Option<K>, mutate: impl FnOnce(&rusqlite::Transaction) -> Result<K, rusqlite::Error>) -> Result<K, StorageError> {
+    let tx = connection.transaction()?;
+
+    let before = match &id {
+        Some(id) => snapshot(&tx, table, key_column, id)?,
+        None => None,
+    };
+
+    let id = mutate(&tx)?;
+
+    let after = snapshot(&tx, table, key_column, &id)?;
+
+    log_entry(&tx, action, table, &id, before, after)?;
+
+    tx.commit()?;
+
+    Ok(id)
+}
+
+/// Criteria used to narrow down the audit log.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AuditFilter {
+    pub action: String,
+    pub table: String,
+    pub record_id: String,
+    pub receptionist: String,
+    /// Inclusive local date.
+    pub from: Option<chrono::NaiveDate>,
+    /// Inclusive local date.
+    pub to: Option<chrono::NaiveDate>,
+}
+
+impl AuditFilter {
+    pub fn is_empty(&self) -> bool {
+        *self == AuditFilter::default()
+    }
+
+    fn clause(&self) -> (String, Vec<Value>) {
+        let mut conditions = vec![];
+        let mut params = vec![];
+
+        for (column, text) in [("action", &self.action), ("record_table", &self.table), ("record_id", &self.record_id), ("receptionist", &self.receptionist)] {
+            let text = text.trim();
+
+            if !text.is_empty() {
+                conditions.push(format!("instr(lower(IFNULL({column}, '')), lower(?)) > 0"));
+                params.push(Value::Text(text.into()));
+            }
+        }
+
+        if let Some(from) = self.from {
+            conditions.push("? <= time".into());
+            params.push(Value::Integer(local_midnight(from).timestamp()));
+        }
+
+        if let Some(to) = self.to {
+            conditions.push("time < ?".into());
+            params.push(Value::Integer(local_midnight(to + chrono::Days::new(1)).timestamp()));
+        }
+
+        if conditions.is_empty() {
+            ("1".into(), params)
+        } else {
+            (conditions.join(" AND "), params)
+        }
+    }
+}
+
+pub struct AuditStorage {
+    worker: WorkerHandle,
+    background: bool,
+    records: Vec<AuditRecord>,
+    page: Page,
+    filter: AuditFilter,
+    count: i64,
+    position: PagePosition,
+    stale: bool,
+    corrupt: Vec<CorruptRow>,
+    pending: Option<PendingPage<AuditRecord>>,
+}
+
+impl AuditStorage {
+    /// Pages are fetched in the background if `background` is set, otherwise waited for.
+    pub fn new(worker: WorkerHandle, background: bool) -> Result<AuditStorage, StorageError> {
+        let mut storage = AuditStorage {
+            worker,
+            background,
+            records: vec![],
+            page: Page::LastPage,
+            filter: AuditFilter::default(),
+            count: 0,
+            position: PagePosition::default(),
+            stale: true,
+            corrupt: vec![],
+            pending: None,
+        };
+
+        storage.refresh()?;
+
+        Ok(storage)
+    }
+
+    fn show(&mut self, fetched: FetchedPage<AuditRecord>) {
+        self.count = fetched.count;
+        self.records = fetched.records;
+        self.corrupt = fetched.corrupt;
+        self.position = fetched.position;
+        self.stale = false;
+    }
+
+    /// Fetch the current page, counting the records again only if they may have changed since the page shown.
+    fn request(&mut self) -> Result<(), StorageError> {
+        let (clause, params) = self.filter.clause();
+        let page = self.page;
+        let shown = (!self.stale).then_some((self.count, self.position));
+
+        let fetch = move |connection: &rusqlite::Connection| {
+            fetch_counted_page(connection, "audit_log", (&clause, &params), page, shown, Self::parse_row, |record| record.id)
+        };
+
+        if let Some(fetched) = request_page(&self.worker, self.background, &mut self.pending, fetch)? {
+            self.show(fetched);
+        }
+
+        log::debug!("refreshed audit log");
+
+        Ok(())
+    }
+
+    pub fn set_page(&mut self, page: Page) -> Result<(), StorageError> {
+        if page != self.page {
+            self.page = page;
+            self.request()
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn count(&self) -> i64 {
+        self.count
+    }
+
+    pub fn position(&self) -> PagePosition {
+        self.position
+    }
+
+    /// Entries skipped by the last refresh because they could not be read.
+    pub fn corrupt(&self) -> &[CorruptRow] {
+        &self.corrupt
+    }
+
+    pub fn set_filter(&mut self, filter: &AuditFilter) -> Result<(), StorageError> {
+        if *filter != self.filter {
+            self.filter = filter.clone();
+            self.page = Page::LastPage;
+            self.refresh()
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn refresh(&mut self) -> Result<(), StorageError> {
+        self.stale = true;
+        self.request()
+    }
+
+    /// Show the page fetched in the background, if it has arrived. Returns whether it had.
+    pub fn poll(&mut self) -> Result<bool, StorageError> {
+        match poll_page(&mut self.pending)? {
+            Some(fetched) => {
+                self.show(fetched);
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+
+    pub fn get_all(&self) -> &[AuditRecord] {
+        self.records.as_slice()
+    }
+
+    /// Record that the database was restored from a backup.
+    pub fn log_restore(&mut self, path: &str) -> Result<(), StorageError> {
+        let path = path.to_owned();
+
+        self.worker.call(move |connection| Ok(log_entry(connection, "restore", "database", &path, None, None)?))?;
+
+        self.refresh()
+    }
+
+    fn parse_row(row: &rusqlite::Row) -> Result<AuditRecord, rusqlite::Error> {
+        let time = parse_time(row, "time")?;
+
+        Ok(AuditRecord {
+            id: row.get("id")?,
+            time,
+            action: row.get("action")?,
+            record_table: row.get("record_table")?,
+            record_id: row.get("record_id")?,
+            before: row.get("before")?,
+            after: row.get("after")?,
+            receptionist: row.get("receptionist")?,
+        })
+    }
+}
@@ -0,0 +1,408 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use rusqlite::{OptionalExtension, types::Value};
+
+use crate::{worker::WorkerHandle, PAGE_SIZE};
+
+use super::{CorruptRow, parse_rows, FetchedPage, PendingPage, request_page, poll_page, fetch_counted_page, parse_time, parse_optional_time, Page, PagePosition, StorageError, PaginatedStorage, RecordFilter, FilterColumns, filter_clause, format_optional_time, set_export_path_extention, InsertableStorage, ReceptionistSignableStorage, NotedStorage, Category, CategoryRecord, NewCategoryRecord, NewOverrideRecord, Overrides, log_overrides, upsert_student, AmendableStorage, AmendmentRecord, amendment, audit};
+
+const FILTER_COLUMNS: FilterColumns = FilterColumns {
+    student: &["student_name", "IFNULL(student_number, '')"],
+    item: "entry",
+    receptionist: &["receptionist_out", "receptionist_in"],
+    notes: "notes",
+    time: "time_out",
+    open: Some("time_in"),
+    due: None,
+    voided: Some("voided"),
+};
+
+/// Columns which can be corrected with an amendment.
+const AMENDABLE_COLUMNS: &[&str] = &["entry", "quantity", "student_name", "student_number"];
+
+/// Records of one custom category at a time, the category shown in its panel.
+pub struct CategoryRecordStorage {
+    worker: WorkerHandle,
+    background: bool,
+    category_id: Option<i64>,
+    records: Vec<CategoryRecord>,
+    page: Page,
+    filter: RecordFilter,
+    count: i64,
+    position: PagePosition,
+    stale: bool,
+    corrupt: Vec<CorruptRow>,
+    pending: Option<PendingPage<CategoryRecord>>,
+}
+
+impl CategoryRecordStorage {
+    /// Pages are fetched in the background if `background` is set, otherwise waited for.
+    pub fn new(worker: WorkerHandle, background: bool) -> Result<CategoryRecordStorage, StorageError> {
+        let mut storage = CategoryRecordStorage {
+            worker,
+            background,
+            category_id: None,
+            records: vec![],
+            page: Page::LastPage,
+            filter: RecordFilter::default(),
+            count: 0,
+            position: PagePosition::default(),
+            stale: true,
+            corrupt: vec![],
+            pending: None,
+        };
+
+        storage.refresh()?;
+
+        Ok(storage)
+    }
+
+    pub fn category_id(&self) -> Option<i64> {
+        self.category_id
+    }
+
+    /// Show the records of another category, from its last page.
+    pub fn set_category(&mut self, category_id: i64) -> Result<(), StorageError> {
+        if self.category_id != Some(category_id) {
+            self.category_id = Some(category_id);
+            self.page = Page::LastPage;
+            self.filter = RecordFilter::default();
+            self.refresh()
+        } else {
+            Ok(())
+        }
+    }
+
+    fn show(&mut self, fetched: FetchedPage<CategoryRecord>) {
+        self.count = fetched.count;
+        self.records = fetched.records;
+        self.corrupt = fetched.corrupt;
+        self.position = fetched.position;
+        self.stale = false;
+    }
+
+    /// Fetch the current page, counting the records again only if they may have changed since the page shown.
+    fn request(&mut self) -> Result<(), StorageError> {
+        let Some(category_id) = self.category_id else {
+            return Ok(());
+        };
+
+        let (clause, params) = filter_clause(&self.filter, &FILTER_COLUMNS);
+        let clause = format!("category_id = ? AND ({clause})");
+        let params = [Value::Integer(category_id)].into_iter().chain(params).collect::<Vec<_>>();
+        let page = self.page;
+        let shown = (!self.stale).then_some((self.count, self.position));
+
+        let fetch = move |connection: &rusqlite::Connection| {
+            fetch_counted_page(connection, "category_records_amended", (&clause, &params), page, shown, Self::parse_row, |record| record.id)
+        };
+
+        if let Some(fetched) = request_page(&self.worker, self.background, &mut self.pending, fetch)? {
+            self.show(fetched);
+        }
+
+        log::debug!("refreshed category records");
+
+        Ok(())
+    }
+
+    /// Fetch a record by id, whether or not it is on the current page.
+    pub fn fetch(&self, id: i64) -> Result<Option<CategoryRecord>, StorageError> {
+        self.worker.call(move |connection| {
+            let record = connection.prepare("SELECT * FROM category_records_amended WHERE id = ?")?
+                .query_row((id,), Self::parse_row)
+                .optional()?;
+
+            Ok(record)
+        })
+    }
+
+    /// Quantity of an entry which is out, or given away if the category is not returnable.
+    pub fn get_signed_out(&self, category_id: i64, entry: &str) -> Result<i64, StorageError> {
+        let entry = entry.to_owned();
+
+        self.worker.call(move |connection| {
+            let num_signed_out = connection.prepare("
+                SELECT IFNULL(SUM(quantity), 0) AS s FROM category_records_amended WHERE id IN (
+                    SELECT id FROM category_records WHERE category_id = ?1 AND entry = ?2 AND time_in IS NULL
+                    UNION SELECT record_id FROM amendments WHERE record_table = 'category_records' AND field = 'entry' AND new_value = ?2
+                ) AND category_id = ?1 AND entry = ?2 AND time_in IS NULL AND NOT voided
+            ")?
+                .query_row((category_id, entry), |row| row.get::<_, i64>("s"))?;
+
+            Ok(num_signed_out)
+        })
+    }
+
+    /// Write every record of the category which is not voided to a CSV file, with a column for each of its fields.
+    /// Read on the connection given, i.e. the worker's task connection, as other exports are.
+    pub fn export_csv(connection: &rusqlite::Connection, category: &Category, mut path: PathBuf, mut progress: impl FnMut(f32)) -> Result<(), StorageError> {
+        path = set_export_path_extention(path);
+
+        let mut stmt = connection.prepare("SELECT * FROM category_records_amended WHERE category_id = ? AND NOT voided ORDER BY id")?;
+
+        let records = parse_rows("category_records", stmt.query((category.id,))?, Self::parse_row)?.0;
+
+        let mut writer = csv::Writer::from_path(path)?;
+
+        let headers = ["Time Out", "Time In", "Entry", "Quantity", "Student Name", "Student Number", "Signed Out By", "Signed In By"].into_iter()
+            .chain(category.fields.iter().map(|field| field.name.as_str()))
+            .chain(["Notes"]);
+
+        writer.write_record(headers)?;
+
+        for (i, record) in records.iter().enumerate() {
+            let (time_out, time_in, quantity) = (record.time_out.to_rfc3339(), format_optional_time(record.time_in), record.quantity.to_string());
+
+            let values = [
+                time_out.as_str(),
+                &time_in,
+                &record.entry,
+                &quantity,
+                &record.student_name,
+                record.student_number.as_deref().unwrap_or_default(),
+                record.receptionist_out.as_deref().unwrap_or_default(),
+                record.receptionist_in.as_deref().unwrap_or_default(),
+            ].into_iter()
+                .chain(category.fields.iter().map(|field| record.field(&field.name)))
+                .chain([record.notes.as_str()]);
+
+            writer.write_record(values)?;
+
+            if i % PAGE_SIZE as usize == 0 {
+                progress(i as f32 / records.len() as f32);
+            }
+        }
+
+        progress(1.0);
+
+        writer.flush()?;
+
+        log::debug!("exported records of category {}", category.name);
+
+        Ok(())
+    }
+
+    /// Quantity of each entry of the category signed out with time_out in [start, end).
+    pub fn summary(&self, category_id: i64, start: chrono::DateTime<chrono::Utc>, end: chrono::DateTime<chrono::Utc>) -> Result<Vec<(String, i64)>, StorageError> {
+        self.worker.call(move |connection| {
+            let mut stmt = connection.prepare("SELECT entry, SUM(quantity) AS c FROM category_records_amended WHERE category_id = ? AND ? <= time_out AND time_out < ? AND NOT voided GROUP BY entry ORDER BY entry")?;
+
+            let records = stmt.query_map((category_id, start.timestamp(), end.timestamp()), |row| Ok((row.get("entry")?, row.get("c")?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(records)
+        })
+    }
+
+    /// Times records of the category were signed out in [start, end), with their quantities.
+    pub fn sign_out_times(&self, category_id: i64, start: chrono::DateTime<chrono::Utc>, end: chrono::DateTime<chrono::Utc>) -> Result<Vec<(chrono::DateTime<chrono::Utc>, i64)>, StorageError> {
+        self.worker.call(move |connection| {
+            let mut stmt = connection.prepare("SELECT time_out, quantity FROM category_records_amended WHERE category_id = ? AND ? <= time_out AND time_out < ? AND NOT voided ORDER BY time_out")?;
+
+            let times = stmt.query_map((category_id, start.timestamp(), end.timestamp()), |row| Ok((parse_time(row, "time_out")?, row.get("quantity")?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(times)
+        })
+    }
+
+    /// Fails with `StorageError::InsufficientStock` if not enough of a stock limited entry are left, checked by
+    /// the database within the insert. Records of categories which are not returnable are never signed in.
+    /// Restrictions overridden to sign out the record are logged in the same transaction.
+    pub fn insert_with_overrides(&mut self, record: NewCategoryRecord, overrides: Option<&NewOverrideRecord>) -> Result<(), StorageError> {
+        let (category_id, entry, quantity, student_name, student_number) = (record.category_id, record.entry.to_owned(), record.quantity, record.student_name.to_owned(), record.student_number.to_owned());
+        let (receptionist, values, notes) = (record.receptionist.to_owned(), record.fields.to_vec(), record.notes.to_owned());
+        let overrides = overrides.map(Overrides::from);
+
+        audit::audited(&self.worker, "insert", "category_records", "id", None, move |tx| {
+            let field_names = tx.prepare("SELECT name FROM category_fields WHERE category_id = ? ORDER BY position")?
+                .query_map((category_id,), |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let fields = field_names.into_iter()
+                .zip(values)
+                .filter(|(_, value)| !value.is_empty())
+                .collect::<BTreeMap<_, _>>();
+
+            let fields = serde_json::to_string(&fields)
+                .map_err(|err| rusqlite::Error::ToSqlConversionFailure(Box::new(err)))?;
+
+            let student_number = match student_number.as_str() {
+                "" => None,
+                student_number => {
+                    upsert_student(tx, student_number, &student_name)?;
+                    Some(student_number.to_uppercase())
+                },
+            };
+
+            tx.execute(
+                "INSERT INTO category_records (id, category_id, entry, quantity, student_name, student_number, receptionist_out, time_out, time_in, fields, notes) VALUES (NULL, ?, ?, ?, ?, ?, ?, ?, NULL, ?, ?)",
+                (category_id, entry, quantity, student_name, student_number, receptionist, chrono::Utc::now().timestamp(), fields, notes)
+            )?;
+
+            let id = tx.last_insert_rowid();
+
+            if let Some(overrides) = overrides {
+                log_overrides(tx, &overrides)?;
+            }
+
+            Ok(id)
+        })?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+}
+
+impl PaginatedStorage<CategoryRecord, i64> for CategoryRecordStorage {
+    fn page(&self) -> Page {
+        self.page
+    }
+
+    fn set_page(&mut self, page: Page) -> Result<(), StorageError> {
+        if page != self.page {
+            self.page = page;
+            self.request()
+        } else {
+            Ok(())
+        }
+    }
+
+    fn count(&self) -> i64 {
+        self.count
+    }
+
+    fn position(&self) -> PagePosition {
+        self.position
+    }
+
+    fn filter(&self) -> &RecordFilter {
+        &self.filter
+    }
+
+    fn set_filter(&mut self, filter: &RecordFilter) -> Result<(), StorageError> {
+        if *filter != self.filter {
+            self.filter = filter.clone();
+            self.page = Page::LastPage;
+            self.refresh()
+        } else {
+            Ok(())
+        }
+    }
+
+    fn refresh(&mut self) -> Result<(), StorageError> {
+        self.stale = true;
+        self.request()
+    }
+
+    fn poll(&mut self) -> Result<bool, StorageError> {
+        match poll_page(&mut self.pending)? {
+            Some(fetched) => {
+                self.show(fetched);
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+
+    fn get_all(&self) -> &[CategoryRecord] {
+        self.records.as_slice()
+    }
+
+    fn corrupt(&self) -> &[CorruptRow] {
+        &self.corrupt
+    }
+
+    fn parse_row(row: &rusqlite::Row) -> Result<CategoryRecord, rusqlite::Error> {
+        let fields: String = row.get("fields")?;
+
+        // values are kept as text, whatever the kind of field
+        let fields = serde_json::from_str::<BTreeMap<String, String>>(&fields)
+            .map_err(|err| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(err)))?;
+
+        Ok(CategoryRecord {
+            id: row.get("id")?,
+            category_id: row.get("category_id")?,
+            entry: row.get("entry")?,
+            quantity: row.get("quantity")?,
+            student_name: row.get("student_name")?,
+            student_number: row.get("student_number")?,
+            receptionist_out: row.get("receptionist_out")?,
+            receptionist_in: row.get("receptionist_in")?,
+            time_out: parse_time(row, "time_out")?,
+            time_in: parse_optional_time(row, "time_in")?,
+            fields,
+            notes: row.get("notes")?,
+            voided: row.get("voided")?,
+            amended: row.get("amended")?,
+        })
+    }
+}
+
+impl InsertableStorage<NewCategoryRecord<'_>, i64> for CategoryRecordStorage {
+    fn insert(&mut self, record: NewCategoryRecord) -> Result<(), StorageError> {
+        self.insert_with_overrides(record, None)
+    }
+}
+
+impl ReceptionistSignableStorage<CategoryRecord, i64> for CategoryRecordStorage {
+    fn signin(&mut self, id: i64, receptionist: &str) -> Result<(), StorageError> {
+        let receptionist = receptionist.to_owned();
+
+        audit::audited(&self.worker, "sign in", "category_records", "id", Some(id), move |tx| {
+            tx.execute(
+                "UPDATE category_records SET receptionist_in = ?, time_in = ? WHERE id = ?",
+                (receptionist, chrono::Utc::now().timestamp(), id)
+            )?;
+
+            Ok(id)
+        })?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+}
+
+impl NotedStorage<CategoryRecord, i64> for CategoryRecordStorage {
+    fn update_notes(&mut self, id: i64, notes: &str) -> Result<(), StorageError> {
+        let notes = notes.to_owned();
+
+        audit::audited(&self.worker, "update notes", "category_records", "id", Some(id), move |tx| {
+            tx.execute(
+                "UPDATE category_records SET notes = ? WHERE id = ?",
+                (notes, id)
+            )?;
+
+            Ok(id)
+        })?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+}
+
+impl AmendableStorage<CategoryRecord, i64> for CategoryRecordStorage {
+    fn amend(&mut self, id: i64, field: &str, new_value: &str, reason: &str, receptionist: &str) -> Result<(), StorageError> {
+        amendment::amend(&self.worker, "category_records", AMENDABLE_COLUMNS, id, Some((field, new_value)), reason, receptionist)?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+
+    fn void(&mut self, id: i64, reason: &str, receptionist: &str) -> Result<(), StorageError> {
+        amendment::amend(&self.worker, "category_records", AMENDABLE_COLUMNS, id, None, reason, receptionist)?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+
+    fn amendments(&self, id: i64) -> Result<Vec<AmendmentRecord>, StorageError> {
+        amendment::fetch_amendments(&self.worker, "category_records", id)
+    }
+}
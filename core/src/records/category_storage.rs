@@ -0,0 +1,213 @@
+use crate::worker::WorkerHandle;
+
+use super::{Category, CategoryField, CategoryEntry, NewCategory, FieldKind, StorageError, Storage, InsertableStorage, DeletableStorage, audit};
+
+/// Custom categories, with their fields and entries.
+pub struct CategoryStorage {
+    worker: WorkerHandle,
+    categories: Vec<Category>,
+}
+
+impl CategoryStorage {
+    pub fn new(worker: WorkerHandle) -> Result<CategoryStorage, StorageError> {
+        let mut storage = CategoryStorage {
+            worker,
+            categories: vec![],
+        };
+
+        storage.refresh()?;
+
+        Ok(storage)
+    }
+
+    pub fn get_by_name(&self, name: &str) -> Option<&Category> {
+        self.categories.iter().find(|category| category.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Add something which can be signed out in the category, `quantity` being the number in stock if it is stock limited.
+    pub fn insert_entry(&mut self, category_id: i64, name: &str, quantity: Option<i64>) -> Result<(), StorageError> {
+        let name = name.to_owned();
+
+        audit::audited(&self.worker, "insert", "category_entries", "rowid", None, move |tx| {
+            tx.execute(
+                "INSERT INTO category_entries (category_id, name, quantity) VALUES (?, ?, ?)",
+                (category_id, name, quantity)
+            )?;
+
+            Ok(tx.last_insert_rowid())
+        })?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+
+    pub fn update_entry_quantity(&mut self, category_id: i64, name: &str, quantity: i64) -> Result<(), StorageError> {
+        let rowid = self.entry_rowid(category_id, name)?;
+
+        audit::audited(&self.worker, "update quantity", "category_entries", "rowid", Some(rowid), move |tx| {
+            tx.execute(
+                "UPDATE category_entries SET quantity = ? WHERE rowid = ?",
+                (quantity, rowid)
+            )?;
+
+            Ok(rowid)
+        })?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+
+    /// Old records keep the name of a deleted entry.
+    pub fn delete_entry(&mut self, category_id: i64, name: &str) -> Result<(), StorageError> {
+        let rowid = self.entry_rowid(category_id, name)?;
+
+        audit::audited(&self.worker, "delete", "category_entries", "rowid", Some(rowid), move |tx| {
+            tx.execute(
+                "DELETE FROM category_entries WHERE rowid = ?",
+                (rowid,)
+            )?;
+
+            Ok(rowid)
+        })?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+
+    /// Entries have no id of their own, the audit log refers to them by rowid.
+    fn entry_rowid(&self, category_id: i64, name: &str) -> Result<i64, StorageError> {
+        let name = name.to_owned();
+
+        self.worker.call(move |connection| {
+            let rowid = connection.query_row("SELECT rowid FROM category_entries WHERE category_id = ? AND name = ?", (category_id, name), |row| row.get(0))?;
+
+            Ok(rowid)
+        })
+    }
+
+    /// Read a category on the connection given, e.g. by the worker exporting its records.
+    pub fn fetch(connection: &rusqlite::Connection, id: i64) -> Result<Option<Category>, StorageError> {
+        let category = Self::fetch_all(connection)?.into_iter().find(|category| category.id == id);
+
+        Ok(category)
+    }
+
+    fn fetch_all(connection: &rusqlite::Connection) -> Result<Vec<Category>, StorageError> {
+        let mut categories: Vec<Category> = connection.prepare("SELECT * FROM categories ORDER BY name")?
+            .query_map((), Self::parse_row)?
+            .collect::<Result<_, _>>()?;
+
+        let fields = connection.prepare("SELECT * FROM category_fields ORDER BY position")?
+            .query_map((), Self::parse_field)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let entries = connection.prepare("SELECT * FROM category_entries ORDER BY name")?
+            .query_map((), Self::parse_entry)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for category in &mut categories {
+            category.fields = fields.iter().filter(|(id, _)| *id == category.id).map(|(_, field)| field.clone()).collect();
+            category.entries = entries.iter().filter(|(id, _)| *id == category.id).map(|(_, entry)| entry.clone()).collect();
+        }
+
+        Ok(categories)
+    }
+
+    fn parse_field(row: &rusqlite::Row) -> Result<(i64, CategoryField), rusqlite::Error> {
+        let kind: String = row.get("kind")?;
+
+        Ok((row.get("category_id")?, CategoryField {
+            name: row.get("name")?,
+            kind: FieldKind::parse(&kind).unwrap_or_default(),
+            required: row.get("required")?,
+        }))
+    }
+
+    fn parse_entry(row: &rusqlite::Row) -> Result<(i64, CategoryEntry), rusqlite::Error> {
+        Ok((row.get("category_id")?, CategoryEntry {
+            name: row.get("name")?,
+            quantity: row.get("quantity")?,
+        }))
+    }
+}
+
+impl Storage<Category, i64> for CategoryStorage {
+    fn refresh(&mut self) -> Result<(), StorageError> {
+        self.categories = self.worker.call(|connection| Self::fetch_all(connection))?;
+
+        log::debug!("refreshed categories");
+
+        Ok(())
+    }
+
+    fn get_all(&self) -> &[Category] {
+        self.categories.as_slice()
+    }
+
+    fn get(&self, id: i64) -> Option<&Category> {
+        self.categories.iter().find(|category| category.id == id)
+    }
+
+    /// Fields and entries are filled in by `refresh`.
+    fn parse_row(row: &rusqlite::Row) -> Result<Category, rusqlite::Error> {
+        Ok(Category {
+            id: row.get("id")?,
+            name: row.get("name")?,
+            returnable: row.get("returnable")?,
+            stock_limited: row.get("stock_limited")?,
+            student_number_required: row.get("student_number_required")?,
+            fields: vec![],
+            entries: vec![],
+        })
+    }
+}
+
+impl InsertableStorage<NewCategory<'_>, i64> for CategoryStorage {
+    fn insert(&mut self, category: NewCategory) -> Result<(), StorageError> {
+        let (name, returnable, stock_limited, student_number_required) = (category.name.to_owned(), category.returnable, category.stock_limited, category.student_number_required);
+        let fields = category.fields.to_vec();
+
+        audit::audited(&self.worker, "insert", "categories", "id", None, move |tx| {
+            tx.execute(
+                "INSERT INTO categories (id, name, returnable, stock_limited, student_number_required) VALUES (NULL, ?, ?, ?, ?)",
+                (name, returnable, stock_limited, student_number_required)
+            )?;
+
+            let id = tx.last_insert_rowid();
+
+            for (position, field) in fields.iter().enumerate() {
+                tx.execute(
+                    "INSERT INTO category_fields (id, category_id, name, kind, required, position) VALUES (NULL, ?, ?, ?, ?, ?)",
+                    (id, &field.name, field.kind.as_str(), field.required, position)
+                )?;
+            }
+
+            Ok(id)
+        })?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+}
+
+impl DeletableStorage<Category, i64> for CategoryStorage {
+    /// Fails if the category has records, its fields and entries are deleted with it.
+    fn delete(&mut self, id: i64) -> Result<(), StorageError> {
+        audit::audited(&self.worker, "delete", "categories", "id", Some(id), move |tx| {
+            tx.execute(
+                "DELETE FROM categories WHERE id = ?",
+                (id,)
+            )?;
+
+            Ok(id)
+        })?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+use rusqlite::types::Value;
+
+/// Criteria used to narrow down the records shown in a panel.
+/// Empty text fields and unset dates are ignored.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RecordFilter {
+    /// Matches student name or student number.
+    pub student: String,
+    /// Matches the key, game, item, or parcel description.
+    pub item: String,
+    pub receptionist: String,
+    pub notes: String,
+    /// Inclusive local date.
+    pub from: Option<chrono::NaiveDate>,
+    /// Inclusive local date.
+    pub to: Option<chrono::NaiveDate>,
+    pub still_out: bool,
+    pub overdue: bool,
+}
+
+impl RecordFilter {
+    pub fn is_empty(&self) -> bool {
+        *self == RecordFilter::default()
+    }
+}
+
+/// Describes which columns of a record table each filter criterion applies to.
+pub(super) struct FilterColumns {
+    pub student: &'static [&'static str],
+    pub item: &'static str,
+    pub receptionist: &'static [&'static str],
+    pub notes: &'static str,
+    /// Column checked against the date range.
+    pub time: &'static str,
+    /// Column which is null while the record is still out, if any.
+    pub open: Option<&'static str>,
+    /// Column holding the time the record is due back, if any.
+    pub due: Option<&'static str>,
+    /// Column which is true once the record is voided, if any. Voided records are never still out.
+    pub voided: Option<&'static str>,
+}
+
+/// Convert a local date to the UTC time of its midnight.
+pub fn local_midnight(date: chrono::NaiveDate) -> chrono::DateTime<chrono::Utc> {
+    let midnight = date.and_time(chrono::NaiveTime::MIN);
+
+    midnight
+        // convert to 00:00 in local time zone
+        .and_local_timezone(chrono::Local).single()
+        // convert to UTC, since db in UTC
+        .map(|date| date.with_timezone(&chrono::Utc))
+        // if datetime does not exist (due to when countries change their timezone), default to UTC
+        .unwrap_or(midnight.and_utc())
+}
+
+/// Build an SQL condition (without the `WHERE` keyword) and its parameters from a filter.
+pub(super) fn filter_clause(filter: &RecordFilter, columns: &FilterColumns) -> (String, Vec<Value>) {
+    let mut conditions = vec![];
+    let mut params = vec![];
+
+    fn contains(column: &str) -> String {
+        format!("instr(lower({column}), lower(?)) > 0")
+    }
+
+    let student = filter.student.trim();
+    if !student.is_empty() {
+        let any = columns.student.iter().map(|c| contains(c)).collect::<Vec<_>>().join(" OR ");
+        conditions.push(format!("({any})"));
+        params.extend(columns.student.iter().map(|_| Value::Text(student.into())));
+    }
+
+    let item = filter.item.trim();
+    if !item.is_empty() {
+        conditions.push(contains(columns.item));
+        params.push(Value::Text(item.into()));
+    }
+
+    let receptionist = filter.receptionist.trim();
+    if !receptionist.is_empty() {
+        let any = columns.receptionist.iter().map(|c| contains(&format!("IFNULL({c}, '')"))).collect::<Vec<_>>().join(" OR ");
+        conditions.push(format!("({any})"));
+        params.extend(columns.receptionist.iter().map(|_| Value::Text(receptionist.into())));
+    }
+
+    let notes = filter.notes.trim();
+    if !notes.is_empty() {
+        conditions.push(contains(columns.notes));
+        params.push(Value::Text(notes.into()));
+    }
+
+    if let Some(from) = filter.from {
+        conditions.push(format!("? <= {}", columns.time));
+        params.push(Value::Integer(local_midnight(from).timestamp()));
+    }
+
+    if let Some(to) = filter.to {
+        conditions.push(format!("{} < ?", columns.time));
+        params.push(Value::Integer(local_midnight(to + chrono::Days::new(1)).timestamp()));
+    }
+
+    let not_voided = columns.voided.map(|voided| format!(" AND NOT {voided}")).unwrap_or_default();
+
+    if let (true, Some(open)) = (filter.still_out, columns.open) {
+        conditions.push(format!("{open} IS NULL{not_voided}"));
+    }
+
+    if let (true, Some(open), Some(due)) = (filter.overdue, columns.open, columns.due) {
+        conditions.push(format!("{open} IS NULL AND {due} < ?{not_voided}"));
+        params.push(Value::Integer(chrono::Utc::now().timestamp()));
+    }
+
+    if conditions.is_empty() {
+        ("1".into(), params)
+    } else {
+        (conditions.join(" AND "), params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLUMNS: FilterColumns = FilterColumns {
+        student: &["student_name", "student_number"],
+        item: "key",
+        receptionist: &["receptionist_out", "receptionist_in"],
+        notes: "notes",
+        time: "time_out",
+        open: Some("time_in"),
+        due: Some("time_due"),
+        voided: Some("voided"),
+    };
+
+    #[test]
+    fn empty_filter_matches_everything() {
+        let (clause, params) = filter_clause(&RecordFilter::default(), &COLUMNS);
+
+        assert_eq!(clause, "1");
+        assert!(params.is_empty());
+    }
+
+    #[test]
+    fn text_criteria_are_trimmed_and_matched_in_any_column() {
+        let filter = RecordFilter { student: " abc ".into(), item: "K1".into(), notes: "   ".into(), ..Default::default() };
+        let (clause, params) = filter_clause(&filter, &COLUMNS);
+
+        assert_eq!(clause, "(instr(lower(student_name), lower(?)) > 0 OR instr(lower(student_number), lower(?)) > 0) AND instr(lower(key), lower(?)) > 0");
+        assert_eq!(params, vec![Value::Text("abc".into()), Value::Text("abc".into()), Value::Text("K1".into())]);
+    }
+
+    #[test]
+    fn date_range_includes_the_whole_of_the_last_day() {
+        let day = chrono::NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let filter = RecordFilter { from: Some(day), to: Some(day), ..Default::default() };
+        let (clause, params) = filter_clause(&filter, &COLUMNS);
+
+        assert_eq!(clause, "? <= time_out AND time_out < ?");
+        assert_eq!(params, vec![
+            Value::Integer(local_midnight(day).timestamp()),
+            Value::Integer(local_midnight(day.succ_opt().unwrap()).timestamp()),
+        ]);
+    }
+
+    #[test]
+    fn voided_records_are_never_still_out_or_overdue() {
+        let filter = RecordFilter { still_out: true, overdue: true, ..Default::default() };
+        let (clause, params) = filter_clause(&filter, &COLUMNS);
+
+        assert_eq!(clause, "time_in IS NULL AND NOT voided AND time_in IS NULL AND time_due < ? AND NOT voided");
+        assert_eq!(params.len(), 1);
+    }
+
+    #[test]
+    fn open_and_due_criteria_need_their_columns() {
+        let columns = FilterColumns { open: None, due: None, voided: None, ..COLUMNS };
+        let filter = RecordFilter { still_out: true, overdue: true, ..Default::default() };
+
+        assert_eq!(filter_clause(&filter, &columns), ("1".into(), vec![]));
+
+        let columns = FilterColumns { due: None, voided: None, ..COLUMNS };
+        assert_eq!(filter_clause(&filter, &columns).0, "time_in IS NULL");
+    }
+}
@@ -0,0 +1,426 @@
+use std::path::PathBuf;
+
+use rusqlite::OptionalExtension;
+
+use crate::worker::WorkerHandle;
+
+use super::{CorruptRow, parse_rows, FetchedPage, PendingPage, request_page, poll_page, fetch_counted_page, parse_time, parse_optional_time, Page, PagePosition, StorageError, PaginatedStorage, RecordFilter, FilterColumns, filter_clause, format_optional_time, InsertableStorage, ReceptionistSignableStorage, NotedStorage, ExportableStorage, GameRecord, NewGameRecord, NewOverrideRecord, Overrides, log_overrides, Summary, ReceptionistSummary, SignOutTimes, upsert_student, AmendableStorage, AmendmentRecord, amendment, audit};
+
+const FILTER_COLUMNS: FilterColumns = FilterColumns {
+    student: &["student_name", "student_number"],
+    item: "game",
+    receptionist: &["receptionist_out", "receptionist_in"],
+    notes: "notes",
+    time: "time_out",
+    open: Some("time_in"),
+    due: Some("time_due"),
+    voided: Some("voided"),
+};
+
+/// Columns which can be corrected with an amendment.
+const AMENDABLE_COLUMNS: &[&str] = &["game", "quantity", "student_name", "student_number"];
+
+pub struct GameStorage {
+    worker: WorkerHandle,
+    background: bool,
+    records: Vec<GameRecord>,
+    page: Page,
+    filter: RecordFilter,
+    count: i64,
+    position: PagePosition,
+    stale: bool,
+    corrupt: Vec<CorruptRow>,
+    pending: Option<PendingPage<GameRecord>>,
+}
+
+impl GameStorage {
+    /// Pages are fetched in the background if `background` is set, otherwise waited for.
+    pub fn new(worker: WorkerHandle, background: bool) -> Result<GameStorage, StorageError> {
+        let mut storage = GameStorage {
+            worker,
+            background,
+            records: vec![],
+            page: Page::LastPage,
+            filter: RecordFilter::default(),
+            count: 0,
+            position: PagePosition::default(),
+            stale: true,
+            corrupt: vec![],
+            pending: None,
+        };
+
+        storage.refresh()?;
+
+        Ok(storage)
+    }
+
+    fn show(&mut self, fetched: FetchedPage<GameRecord>) {
+        self.count = fetched.count;
+        self.records = fetched.records;
+        self.corrupt = fetched.corrupt;
+        self.position = fetched.position;
+        self.stale = false;
+    }
+
+    /// Fetch the current page, counting the records again only if they may have changed since the page shown.
+    fn request(&mut self) -> Result<(), StorageError> {
+        let (clause, params) = filter_clause(&self.filter, &FILTER_COLUMNS);
+        let page = self.page;
+        let shown = (!self.stale).then_some((self.count, self.position));
+
+        let fetch = move |connection: &rusqlite::Connection| {
+            fetch_counted_page(connection, "game_records_amended", (&clause, &params), page, shown, Self::parse_row, |record| record.id)
+        };
+
+        if let Some(fetched) = request_page(&self.worker, self.background, &mut self.pending, fetch)? {
+            self.show(fetched);
+        }
+
+        log::debug!("refreshed game records");
+
+        Ok(())
+    }
+
+    /// Fetch a record by id, whether or not it is on the current page.
+    pub fn fetch(&self, id: i64) -> Result<Option<GameRecord>, StorageError> {
+        self.worker.call(move |connection| {
+            let record = connection.prepare("SELECT * FROM game_records_amended WHERE id = ?")?
+                .query_row((id,), Self::parse_row)
+                .optional()?;
+
+            Ok(record)
+        })
+    }
+    
+    pub fn get_signed_out(&mut self, item_type: &str) -> Result<i64, StorageError> {
+        let item_type = item_type.to_owned();
+
+        self.worker.call(move |connection| {
+            let num_signed_out = connection.prepare("
+                SELECT IFNULL(SUM(quantity), 0) AS s FROM game_records_amended WHERE id IN (
+                    SELECT id FROM game_records WHERE game = ?1 AND time_in IS NULL
+                    UNION SELECT record_id FROM amendments WHERE record_table = 'game_records' AND field = 'game' AND new_value = ?1
+                ) AND game = ?1 AND time_in IS NULL AND NOT voided
+            ")?
+                .query_row((item_type,), |row| row.get::<_, i64>("s"))?;
+
+            Ok(num_signed_out)
+        })
+    }
+
+    /// All records which are still signed out, oldest first.
+    pub fn fetch_outstanding(&self) -> Result<Vec<GameRecord>, StorageError> {
+        self.worker.call(|connection| {
+            let mut stmt = connection.prepare("SELECT * FROM game_records_amended WHERE time_in IS NULL AND NOT voided ORDER BY time_out")?;
+            
+            let records = parse_rows("game_records", stmt.query(())?, Self::parse_row)?.0;
+
+            Ok(records)
+        })
+    }
+
+    pub fn fetch_overdue(&self) -> Result<Vec<GameRecord>, StorageError> {
+        self.worker.call(|connection| {
+            let mut stmt = connection.prepare("SELECT * FROM game_records_amended WHERE time_in IS NULL AND time_due < ? AND NOT voided ORDER BY time_due")?;
+            
+            let records = parse_rows("game_records", stmt.query((chrono::Utc::now().timestamp(),))?, Self::parse_row)?.0;
+
+            Ok(records)
+        })
+    }
+
+    /// Counted on the connection given, e.g. on the worker, since records become overdue without any change to be refreshed on.
+    pub fn count_overdue(connection: &rusqlite::Connection) -> Result<i64, StorageError> {
+        let count = connection.prepare("SELECT COUNT(*) AS c FROM game_records_amended WHERE time_in IS NULL AND time_due < ? AND NOT voided")?
+            .query_row((chrono::Utc::now().timestamp(),), |row| row.get("c"))?;
+
+        Ok(count)
+    }
+
+    /// Fails with `StorageError::InsufficientStock` if not enough of the game are left, checked by the
+    /// database within the insert so that two receptionists cannot sign out the last copy at once.
+    /// Restrictions overridden to sign out the record are logged in the same transaction.
+    pub fn insert_with_overrides(&mut self, record: NewGameRecord, overrides: Option<&NewOverrideRecord>) -> Result<(), StorageError> {
+        let (game, quantity, student_name, student_number, receptionist, notes) = (record.game.to_owned(), record.quantity, record.student_name.to_owned(), record.student_number.to_uppercase(), record.receptionist.to_owned(), record.notes.to_owned());
+        let overrides = overrides.map(Overrides::from);
+
+        audit::audited(&self.worker, "insert", "game_records", "id", None, move |tx| {
+            let time_out = chrono::Utc::now();
+            
+            let loan_minutes = tx.prepare("SELECT loan_minutes FROM games WHERE game = ?")?
+                .query_row((&game,), |row| row.get::<_, Option<i64>>("loan_minutes"))
+                .optional()?.flatten();
+
+            let time_due = loan_minutes.map(|minutes| time_out + chrono::Duration::minutes(minutes));
+
+            upsert_student(tx, &student_number, &student_name)?;
+
+            tx.execute(
+                "INSERT INTO game_records (id, game, quantity, student_name, student_number, receptionist_out, time_out, time_due, time_in, notes) VALUES (NULL, ?, ?, ?, ?, ?, ?, ?, NULL, ?)",
+                (game, quantity, student_name, student_number, receptionist, time_out.timestamp(), time_due.map(|t| t.timestamp()), notes)
+            )?;
+
+            let id = tx.last_insert_rowid();
+
+            if let Some(overrides) = overrides {
+                log_overrides(tx, &overrides)?;
+            }
+
+            Ok(id)
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+}
+
+impl PaginatedStorage<GameRecord, i64> for GameStorage {
+    fn page(&self) -> Page {
+        self.page
+    }
+    
+    fn set_page(&mut self, page: Page) -> Result<(), StorageError> {
+        if page != self.page {
+            self.page = page;
+            self.request()
+        } else {
+            Ok(())
+        }
+    }
+
+    fn count(&self) -> i64 {
+        self.count
+    }
+
+    fn position(&self) -> PagePosition {
+        self.position
+    }
+
+    fn filter(&self) -> &RecordFilter {
+        &self.filter
+    }
+
+    fn set_filter(&mut self, filter: &RecordFilter) -> Result<(), StorageError> {
+        if *filter != self.filter {
+            self.filter = filter.clone();
+            self.page = Page::LastPage;
+            self.refresh()
+        } else {
+            Ok(())
+        }
+    }
+    
+    fn refresh(&mut self) -> Result<(), StorageError> {
+        self.stale = true;
+        self.request()
+    }
+
+    fn poll(&mut self) -> Result<bool, StorageError> {
+        match poll_page(&mut self.pending)? {
+            Some(fetched) => {
+                self.show(fetched);
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+
+    fn get_all(&self) -> &[GameRecord] {
+        self.records.as_slice()
+    }
+
+    fn corrupt(&self) -> &[CorruptRow] {
+        &self.corrupt
+    }
+    
+    fn parse_row(row: &rusqlite::Row) -> Result<GameRecord, rusqlite::Error> {
+        let time_out = parse_time(row, "time_out")?;
+        
+        let time_due = parse_optional_time(row, "time_due")?;
+        
+        let time_in = parse_optional_time(row, "time_in")?;
+        
+        Ok(GameRecord {
+            id: row.get("id")?,
+            game: row.get("game")?,
+            quantity: row.get("quantity")?,
+            student_name: row.get("student_name")?,
+            student_number: row.get("student_number")?,
+            receptionist_out: row.get("receptionist_out")?,
+            receptionist_in: row.get("receptionist_in")?,
+            time_out,
+            time_due,
+            time_in,
+            notes: row.get("notes")?,
+            voided: row.get("voided")?,
+            amended: row.get("amended")?,
+        })
+    }
+}
+
+impl InsertableStorage<NewGameRecord<'_>, i64> for GameStorage {
+    fn insert(&mut self, record: NewGameRecord) -> Result<(), StorageError> {
+        self.insert_with_overrides(record, None)
+    }
+}
+
+impl ReceptionistSignableStorage<GameRecord, i64> for GameStorage {
+    fn signin(&mut self, id: i64, receptionist: &str) -> Result<(), StorageError> {
+        let receptionist = receptionist.to_owned();
+
+        audit::audited(&self.worker, "sign in", "game_records", "id", Some(id), move |tx| {
+            tx.execute(
+                "UPDATE game_records SET receptionist_in = ?, time_in = ? WHERE id = ?",
+                (receptionist, chrono::Utc::now().timestamp(), id)
+            )?;
+
+            Ok(id)
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+}
+
+impl NotedStorage<GameRecord, i64> for GameStorage {
+    fn update_notes(&mut self, id: i64, notes: &str) -> Result<(), StorageError> {
+        let notes = notes.to_owned();
+
+        audit::audited(&self.worker, "update notes", "game_records", "id", Some(id), move |tx| {
+            tx.execute(
+                "UPDATE game_records SET notes = ? WHERE id = ?",
+                (notes, id)
+            )?;
+
+            Ok(id)
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+}
+
+impl ExportableStorage<GameRecord> for GameStorage {
+    fn fetch_all(connection: &rusqlite::Connection) -> Result<Vec<GameRecord>, StorageError> {
+        let mut stmt = connection.prepare("SELECT * FROM game_records_amended WHERE NOT voided")?;
+        
+        let records = parse_rows("game_records", stmt.query(())?, Self::parse_row)?.0;
+        
+        log::debug!("fetched all game records");
+
+        Ok(records)
+    }
+
+    fn csv_headers() -> &'static [&'static str] {
+        &[
+            "Time Out",
+            "Due",
+            "Time In",
+            "Game",
+            "Quantity",
+            "Student Name",
+            "Student Number",
+            "Signed Out By",
+            "Signed In By",
+            "Notes",
+        ]
+    }
+
+    fn write_record<W: std::io::Write>(writer: &mut csv::Writer<W>, record: &GameRecord) -> Result<(), csv::Error> {
+        writer.write_record([
+            record.time_out.to_rfc3339().as_str(),
+            &format_optional_time(record.time_due),
+            &format_optional_time(record.time_in),
+            &record.game,
+            &record.quantity.to_string(),
+            &record.student_name,
+            &record.student_number,
+            record.receptionist_out.as_deref().unwrap_or_default(),
+            record.receptionist_in.as_deref().unwrap_or_default(),
+            &record.notes,
+        ])
+    }
+
+    fn export_csv(connection: &rusqlite::Connection, path: PathBuf, progress: impl FnMut(f32)) -> Result<(), StorageError> {
+        super::export_csv::<GameRecord, Self>(connection, path, progress)
+    }
+}
+
+impl Summary for GameStorage {
+    /// find all records with time_out in [start, end)
+    fn summary(&self, start: chrono::DateTime<chrono::Utc>, end: chrono::DateTime<chrono::Utc>) -> Result<Vec<(String, i64)>, StorageError> {
+        fn parse_row(row: &rusqlite::Row) -> Result<(String, i64), rusqlite::Error> {
+            Ok((row.get("game")?, row.get("c")?))
+        }
+
+        let records = self.worker.call(move |conn| {
+            let mut stmt = conn.prepare("SELECT game, SUM(quantity) AS c FROM game_records_amended WHERE ? <= time_out AND time_out < ? AND NOT voided GROUP BY game ORDER BY game")?;
+
+            let start = start.timestamp();
+            let end = end.timestamp();
+
+            let records = stmt.query_map((start, end), parse_row)?;
+            Ok(records.collect::<Result<Vec<_>, _>>()?)
+        })?;
+
+        Ok(records)
+    }
+}
+
+impl SignOutTimes for GameStorage {
+    fn sign_out_times(&self, start: chrono::DateTime<chrono::Utc>, end: chrono::DateTime<chrono::Utc>) -> Result<Vec<(chrono::DateTime<chrono::Utc>, i64)>, StorageError> {
+        super::sign_out_times(&self.worker, "game_records_amended", "1", start, end)
+    }
+}
+
+impl ReceptionistSummary for GameStorage {
+    /// count records signed out with time_out in [start, end), and signed in with time_in in [start, end)
+    fn receptionist_summary(&self, start: chrono::DateTime<chrono::Utc>, end: chrono::DateTime<chrono::Utc>) -> Result<Vec<(String, i64, i64)>, StorageError> {
+        fn parse_row(row: &rusqlite::Row) -> Result<(String, i64, i64), rusqlite::Error> {
+            Ok((row.get("receptionist")?, row.get("signed_out")?, row.get("signed_in")?))
+        }
+
+        let records = self.worker.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT receptionist, SUM(signed_out) AS signed_out, SUM(signed_in) AS signed_in FROM (
+                    SELECT receptionist_out AS receptionist, 1 AS signed_out, 0 AS signed_in FROM game_records_amended WHERE receptionist_out IS NOT NULL AND ?1 <= time_out AND time_out < ?2 AND NOT voided
+                    UNION ALL
+                    SELECT receptionist_in AS receptionist, 0 AS signed_out, 1 AS signed_in FROM game_records_amended WHERE receptionist_in IS NOT NULL AND ?1 <= time_in AND time_in < ?2 AND NOT voided
+                ) GROUP BY receptionist ORDER BY receptionist"
+            )?;
+
+            let start = start.timestamp();
+            let end = end.timestamp();
+
+            let records = stmt.query_map((start, end), parse_row)?;
+            Ok(records.collect::<Result<Vec<_>, _>>()?)
+        })?;
+
+        Ok(records)
+    }
+}
+
+impl AmendableStorage<GameRecord, i64> for GameStorage {
+    fn amend(&mut self, id: i64, field: &str, new_value: &str, reason: &str, receptionist: &str) -> Result<(), StorageError> {
+        amendment::amend(&self.worker, "game_records", AMENDABLE_COLUMNS, id, Some((field, new_value)), reason, receptionist)?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+
+    fn void(&mut self, id: i64, reason: &str, receptionist: &str) -> Result<(), StorageError> {
+        amendment::amend(&self.worker, "game_records", AMENDABLE_COLUMNS, id, None, reason, receptionist)?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+
+    fn amendments(&self, id: i64) -> Result<Vec<AmendmentRecord>, StorageError> {
+        amendment::fetch_amendments(&self.worker, "game_records", id)
+    }
+}
@@ -0,0 +1,127 @@
+use crate::worker::WorkerHandle;
+
+use super::{GameTypeRecord, StorageError, Storage, InsertableStorage, DeletableStorage, NewGameTypeRecord, audit};
+
+pub struct GameTypeStorage {
+    worker: WorkerHandle,
+    records: Vec<GameTypeRecord>,
+}
+
+impl GameTypeStorage {
+    pub fn new(worker: WorkerHandle) -> Result<GameTypeStorage, StorageError> {
+        let mut storage = GameTypeStorage {
+            worker,
+            records: vec![],
+        };
+
+        storage.refresh()?;
+
+        Ok(storage)
+    }
+
+    pub fn update_quantity(&mut self, game: &str, quantity: i64) -> Result<(), StorageError> {
+        let game = game.to_owned();
+
+        audit::audited(&self.worker, "update quantity", "games", "game", Some(game.clone()), move |tx| {
+            tx.execute(
+                "UPDATE games SET quantity = ? WHERE game = ?",
+                (quantity, &game)
+            )?;
+
+            Ok(game)
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+
+    pub fn update_loan_period(&mut self, game: &str, loan_minutes: Option<i64>) -> Result<(), StorageError> {
+        let game = game.to_owned();
+
+        audit::audited(&self.worker, "update loan period", "games", "game", Some(game.clone()), move |tx| {
+            tx.execute(
+                "UPDATE games SET loan_minutes = ? WHERE game = ?",
+                (loan_minutes, &game)
+            )?;
+
+            Ok(game)
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+}
+
+impl Storage<GameTypeRecord, &str> for GameTypeStorage {
+    fn refresh(&mut self) -> Result<(), StorageError> {
+        self.records = self.worker.call(|connection| {
+            let mut stmt = connection.prepare("SELECT * FROM games ORDER BY game")?;
+        
+            let records = stmt
+                .query_map((), Self::parse_row)?
+                .collect::<Result<_, _>>()?;
+
+            Ok(records)
+        })?;
+
+        log::debug!("refreshed game types");
+        
+        Ok(())
+    }
+
+    fn get_all(&self) -> &[GameTypeRecord] {
+        self.records.as_slice()
+    }
+
+    fn get(&self, id: &str) -> Option<&GameTypeRecord> {
+        self.records.iter().find(|r| r.game == id)
+    }
+
+    fn parse_row(row: &rusqlite::Row) -> Result<GameTypeRecord, rusqlite::Error> {
+        Ok(GameTypeRecord {
+            game: row.get("game")?,
+            quantity: row.get("quantity")?,
+            loan_minutes: row.get("loan_minutes")?,
+        })
+    }
+}
+
+impl InsertableStorage<NewGameTypeRecord<'_>, &str> for GameTypeStorage {
+    fn insert(&mut self, record: NewGameTypeRecord) -> Result<(), StorageError> {
+        let (game, quantity, loan_minutes) = (record.game.to_owned(), record.quantity, record.loan_minutes);
+
+        audit::audited(&self.worker, "insert", "games", "game", None, move |tx| {
+            tx.execute(
+                "INSERT INTO games (game, quantity, loan_minutes) VALUES (?, ?, ?)",
+                (&game, quantity, loan_minutes)
+            )?;
+
+            Ok(game)
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+}
+
+impl DeletableStorage<GameTypeRecord, &str> for GameTypeStorage {
+    fn delete(&mut self, id: &str) -> Result<(), StorageError> {
+        let id = id.to_owned();
+
+        audit::audited(&self.worker, "delete", "games", "game", Some(id.clone()), move |tx| {
+            tx.execute(
+                "DELETE FROM games WHERE game = ?",
+                (&id,)
+            )?;
+
+            Ok(id)
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+}
@@ -0,0 +1,575 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use rusqlite::types::{Value, ValueRef};
+
+use crate::service;
+
+use super::{StorageError, amendment, audit};
+
+/// Time columns of each record table, and whether the column may be empty.
+const TIME_COLUMNS: &[(&str, &[(&str, bool)])] = &[
+    ("key_records", &[("time_out", false), ("time_due", true), ("time_in", true)]),
+    ("parcel_records", &[("time_in", false), ("time_out", true)]),
+    ("game_records", &[("time_out", false), ("time_due", true), ("time_in", true)]),
+    ("item_records", &[("time_out", false), ("time_in", true)]),
+    ("category_records", &[("time_out", false), ("time_in", true)]),
+];
+
+/// Record tables which are loans, with the column of the loaned thing and its catalogue.
+struct LoanTable {
+    table: &'static str,
+    column: &'static str,
+    catalogue: &'static str,
+    quantity: bool,
+    /// Whether the record is signed back in, rather than given away.
+    returnable: &'static str,
+    /// Condition for a record to still be out, records which are never returned always are.
+    still_out: &'static str,
+}
+
+const LOAN_TABLES: &[LoanTable] = &[
+    LoanTable { table: "key_records", column: "key", catalogue: "keys", quantity: false, returnable: "1", still_out: "time_in IS NULL" },
+    LoanTable { table: "game_records", column: "game", catalogue: "games", quantity: true, returnable: "1", still_out: "time_in IS NULL" },
+    LoanTable { table: "item_records", column: "item", catalogue: "items", quantity: true, returnable: "returnable", still_out: "(NOT returnable OR time_in IS NULL)" },
+];
+
+/// Something wrong with the database found by `check_database`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// Reported by SQLite itself, e.g. a damaged page or a missing student.
+    Corrupt(String),
+    InvalidTime { column: &'static str, value: String },
+    MissingTime(&'static str),
+    /// Signed in (or collected) before it was signed out (or received).
+    OutOfOrder,
+    InvalidStudentNumber(String),
+    InvalidQuantity(i64),
+    NotInCatalogue(String),
+    /// Another loan of the same key started while this one was still open.
+    KeyOutTwice { newer_id: i64 },
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Problem::Corrupt(message) => f.write_str(message),
+            Problem::InvalidTime { column, value } => write!(f, "Unreadable {column} {value:?}."),
+            Problem::MissingTime(column) => write!(f, "Missing {column}."),
+            Problem::OutOfOrder => f.write_str("Signed in before it was signed out."),
+            Problem::InvalidStudentNumber(student_number) => write!(f, "Invalid student number {student_number:?}."),
+            Problem::InvalidQuantity(quantity) => write!(f, "Quantity of {quantity}."),
+            Problem::NotInCatalogue(name) => write!(f, "{name:?} is signed out but not in the catalogue."),
+            Problem::KeyOutTwice { newer_id } => write!(f, "Still signed out, but the key was signed out again by record {newer_id}."),
+        }
+    }
+}
+
+/// A change which corrects a problem. Records are corrected with amendments where possible, and
+/// every fix is written to the audit log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fix {
+    /// Rewrite a time saved as text as seconds since the unix epoch.
+    NormaliseTime { column: &'static str, time: chrono::DateTime<chrono::Utc> },
+    ClearTime(&'static str),
+    Void,
+    AmendStudentNumber(String),
+    /// Sign the record in at the given time, e.g. when the key was next signed out.
+    SignIn(chrono::DateTime<chrono::Utc>),
+    /// Games are stocked with `quantity`, items are returnable if any of their open records are.
+    AddToCatalogue { quantity: i64, returnable: bool },
+    /// Add the entry to its category, stock limited categories are stocked with `quantity`.
+    AddToCategory { category_id: i64, quantity: Option<i64> },
+}
+
+impl Display for Fix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Fix::NormaliseTime { time, .. } => write!(f, "Set to {}", time.with_timezone(&chrono::Local).format(crate::DATE_TIME_FORMAT)),
+            Fix::ClearTime(column) => write!(f, "Clear {column}"),
+            Fix::Void => f.write_str("Void"),
+            Fix::AmendStudentNumber(student_number) => write!(f, "Amend to {student_number}"),
+            Fix::SignIn(time) => write!(f, "Sign in at {}", time.with_timezone(&chrono::Local).format(crate::DATE_TIME_FORMAT)),
+            Fix::AddToCatalogue { .. } => f.write_str("Add to catalogue"),
+            Fix::AddToCategory { .. } => f.write_str("Add to category"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// Table the problem is in, empty for the database as a whole.
+    pub table: &'static str,
+    /// Record the problem is in, if it is in a record table.
+    pub id: Option<i64>,
+    pub problem: Problem,
+    pub fix: Option<Fix>,
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.table, &self.id) {
+            ("", _) => write!(f, "{}", self.problem),
+            (table, Some(id)) => write!(f, "{table} {id}: {}", self.problem),
+            (table, None) => write!(f, "{table}: {}", self.problem),
+        }
+    }
+}
+
+/// Run SQLite's own integrity checks, then validate every record. Run on the worker, since it reads every table.
+pub fn check_database(connection: &rusqlite::Connection) -> Result<Vec<Finding>, StorageError> {
+    let mut findings = vec![];
+
+    let mut stmt = connection.prepare("PRAGMA integrity_check")?;
+
+    for message in stmt.query_map((), |row| row.get::<_, String>(0))? {
+        let message = message?;
+
+        if message != "ok" {
+            findings.push(Finding { table: "", id: None, problem: Problem::Corrupt(message), fix: None });
+        }
+    }
+
+    let mut stmt = connection.prepare("PRAGMA foreign_key_check")?;
+
+    for row in stmt.query_map((), |row| Ok((row.get::<_, String>("table")?, row.get::<_, Option<i64>>("rowid")?, row.get::<_, String>("parent")?)))? {
+        let (table, rowid, parent) = row?;
+
+        findings.push(Finding {
+            table: "",
+            id: None,
+            problem: Problem::Corrupt(format!("{table} {} refers to a missing entry in {parent}.", rowid.map(|id| id.to_string()).unwrap_or_default())),
+            fix: None,
+        });
+    }
+
+    check_times(connection, &mut findings)?;
+    check_loans(connection, &mut findings)?;
+    check_category_records(connection, &mut findings)?;
+    check_open_keys(connection, &mut findings)?;
+
+    log::info!("checked database, {} problems found", findings.len());
+
+    Ok(findings)
+}
+
+fn check_times(connection: &rusqlite::Connection, findings: &mut Vec<Finding>) -> Result<(), rusqlite::Error> {
+    for (table, columns) in TIME_COLUMNS {
+        let names = columns.iter().map(|(column, _)| *column).collect::<Vec<_>>().join(", ");
+
+        let mut stmt = connection.prepare(&format!("SELECT id, {names} FROM {table} ORDER BY id"))?;
+        let mut rows = stmt.query(())?;
+
+        while let Some(row) = rows.next()? {
+            let id: i64 = row.get("id")?;
+
+            // times are checked on the raw row, the order only if both can be read
+            let mut times = BTreeMap::new();
+
+            for &(column, optional) in columns.iter() {
+                let (problem, fix) = match row.get_ref(column)? {
+                    ValueRef::Null if optional => continue,
+                    ValueRef::Null => (Problem::MissingTime(column), None),
+                    ValueRef::Integer(seconds) => {
+                        if let Some(time) = chrono::DateTime::from_timestamp(seconds, 0) {
+                            times.insert(column, time);
+                            continue;
+                        }
+
+                        (Problem::InvalidTime { column, value: seconds.to_string() }, invalid_time_fix(column, None))
+                    },
+                    // text the migration to integer times could not convert
+                    value => {
+                        let value = match value {
+                            ValueRef::Text(text) => String::from_utf8_lossy(text).into_owned(),
+                            ValueRef::Real(real) => real.to_string(),
+                            _ => "binary data".into(),
+                        };
+
+                        let fix = invalid_time_fix(column, reinterpret_time(&value));
+
+                        (Problem::InvalidTime { column, value }, fix)
+                    },
+                };
+
+                findings.push(Finding { table, id: Some(id), problem, fix });
+            }
+
+            let order = match *table {
+                "parcel_records" => ("time_in", "time_out"),
+                _ => ("time_out", "time_in"),
+            };
+
+            if let (Some(start), Some(end)) = (times.get(order.0), times.get(order.1)) {
+                if end < start && !is_voided(connection, table, id)? {
+                    findings.push(Finding { table, id: Some(id), problem: Problem::OutOfOrder, fix: Some(Fix::Void) });
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn invalid_time_fix(column: &'static str, time: Option<chrono::DateTime<chrono::Utc>>) -> Option<Fix> {
+    match time {
+        Some(time) => Some(Fix::NormaliseTime { column, time }),
+        // only the due time can be dropped without losing the record
+        None if column == "time_due" => Some(Fix::ClearTime(column)),
+        None => None,
+    }
+}
+
+/// Times saved as text, as RFC3339 by older versions or e.g. by SQLite's `datetime()`, assumed to be UTC.
+fn reinterpret_time(value: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    let value = value.trim();
+
+    let zoned = chrono::DateTime::parse_from_rfc3339(value)
+        .or_else(|_| chrono::DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f%:z"));
+
+    if let Ok(time) = zoned {
+        return Some(time.with_timezone(&chrono::Utc));
+    }
+
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"].iter()
+        .find_map(|format| chrono::NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|time| time.and_utc())
+}
+
+fn is_voided(connection: &rusqlite::Connection, table: &str, id: i64) -> Result<bool, rusqlite::Error> {
+    connection.query_row(&format!("SELECT voided FROM {table}_amended WHERE id = ?"), (id,), |row| row.get("voided"))
+}
+
+fn check_loans(connection: &rusqlite::Connection, findings: &mut Vec<Finding>) -> Result<(), rusqlite::Error> {
+    for loan in LOAN_TABLES {
+        let LoanTable { table, column, catalogue, .. } = *loan;
+
+        let quantity = if loan.quantity { "quantity" } else { "1" };
+
+        let mut stmt = connection.prepare(&format!("SELECT id, `{column}` AS name, student_number, {quantity} AS quantity FROM {table}_amended WHERE NOT voided ORDER BY id"))?;
+        let mut rows = stmt.query(())?;
+
+        while let Some(row) = rows.next()? {
+            let id: i64 = row.get("id")?;
+
+            check_student_number(table, id, row.get("student_number")?, findings);
+            check_quantity(table, id, row.get("quantity")?, findings);
+        }
+
+        // catalogue names are free text on records, so removed or renamed entries are only found here
+        let (returnable, still_out) = (loan.returnable, loan.still_out);
+
+        let mut stmt = connection.prepare(&format!(
+            "SELECT `{column}` AS name, MAX({quantity}) AS quantity, MAX({returnable}) AS returnable FROM {table}_amended r
+            WHERE NOT voided AND {still_out} AND NOT EXISTS(SELECT 1 FROM {catalogue} c WHERE c.`{column}` = r.`{column}`)
+            GROUP BY `{column}` ORDER BY `{column}`"
+        ))?;
+
+        for row in stmt.query_map((), |row| Ok((row.get::<_, String>("name")?, row.get::<_, i64>("quantity")?, row.get::<_, bool>("returnable")?)))? {
+            let (name, quantity, returnable) = row?;
+
+            findings.push(Finding {
+                table: catalogue,
+                id: None,
+                problem: Problem::NotInCatalogue(name),
+                fix: Some(Fix::AddToCatalogue { quantity: quantity.max(1), returnable }),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Category records only need a student number if their category does, and their entries are names within their category.
+fn check_category_records(connection: &rusqlite::Connection, findings: &mut Vec<Finding>) -> Result<(), rusqlite::Error> {
+    let table = "category_records";
+
+    let mut stmt = connection.prepare("SELECT id, student_number, quantity FROM category_records_amended WHERE NOT voided ORDER BY id")?;
+    let mut rows = stmt.query(())?;
+
+    while let Some(row) = rows.next()? {
+        let id: i64 = row.get("id")?;
+
+        if let Some(student_number) = row.get::<_, Option<String>>("student_number")?.filter(|student_number| !student_number.is_empty()) {
+            check_student_number(table, id, student_number, findings);
+        }
+
+        check_quantity(table, id, row.get("quantity")?, findings);
+    }
+
+    let mut stmt = connection.prepare(
+        "SELECT r.category_id, r.entry, MAX(r.quantity) AS quantity, c.stock_limited FROM category_records_amended r
+        JOIN categories c ON c.id = r.category_id
+        WHERE NOT r.voided AND (NOT c.returnable OR r.time_in IS NULL)
+            AND NOT EXISTS(SELECT 1 FROM category_entries e WHERE e.category_id = r.category_id AND e.name = r.entry)
+        GROUP BY r.category_id, r.entry ORDER BY r.category_id, r.entry"
+    )?;
+
+    let missing = stmt.query_map((), |row| Ok((row.get::<_, i64>("category_id")?, row.get::<_, String>("entry")?, row.get::<_, i64>("quantity")?, row.get::<_, bool>("stock_limited")?)))?;
+
+    for row in missing {
+        let (category_id, entry, quantity, stock_limited) = row?;
+
+        findings.push(Finding {
+            table: "category_entries",
+            id: None,
+            problem: Problem::NotInCatalogue(entry),
+            fix: Some(Fix::AddToCategory { category_id, quantity: stock_limited.then_some(quantity.max(1)) }),
+        });
+    }
+
+    Ok(())
+}
+
+fn check_student_number(table: &'static str, id: i64, student_number: String, findings: &mut Vec<Finding>) {
+    if service::check_student_number(&student_number).is_err() {
+        let normalised = student_number.trim().to_uppercase();
+
+        let fix = service::check_student_number(&normalised).ok()
+            .map(|_| Fix::AmendStudentNumber(normalised));
+
+        findings.push(Finding { table, id: Some(id), problem: Problem::InvalidStudentNumber(student_number), fix });
+    }
+}
+
+fn check_quantity(table: &'static str, id: i64, quantity: i64, findings: &mut Vec<Finding>) {
+    if quantity < 1 {
+        findings.push(Finding { table, id: Some(id), problem: Problem::InvalidQuantity(quantity), fix: Some(Fix::Void) });
+    }
+}
+
+/// Only checks keys, the database refuses a second open loan since V15 but older records may have one.
+fn check_open_keys(connection: &rusqlite::Connection, findings: &mut Vec<Finding>) -> Result<(), rusqlite::Error> {
+    let mut stmt = connection.prepare("SELECT id, `key`, time_out FROM key_records_amended WHERE time_in IS NULL AND NOT voided ORDER BY `key`, time_out, id")?;
+
+    let open = stmt.query_map((), |row| Ok((row.get::<_, i64>("id")?, row.get::<_, String>("key")?, row.get::<_, Option<i64>>("time_out").ok().flatten())))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for pair in open.windows(2) {
+        let ((id, key, _), (newer_id, newer_key, newer_time_out)) = (&pair[0], &pair[1]);
+
+        if key != newer_key {
+            continue;
+        }
+
+        // unreadable times are reported by check_times
+        let fix = newer_time_out.and_then(|seconds| chrono::DateTime::from_timestamp(seconds, 0))
+            .map(Fix::SignIn);
+
+        findings.push(Finding { table: "key_records", id: Some(*id), problem: Problem::KeyOutTwice { newer_id: *newer_id }, fix });
+    }
+
+    Ok(())
+}
+
+/// Apply the fix of a finding, attributed to the receptionist on duty. Run on the worker.
+pub fn repair(connection: &mut rusqlite::Connection, finding: &Finding, receptionist: &str) -> Result<(), StorageError> {
+    let Some(fix) = &finding.fix else {
+        return Ok(());
+    };
+
+    let table = finding.table;
+    let reason = format!("Database check: {}", finding.problem);
+
+    // every fix other than adding to a catalogue is to a record
+    let id = match (finding.id, fix) {
+        (Some(id), _) => id,
+        (None, Fix::AddToCatalogue { .. } | Fix::AddToCategory { .. }) => 0,
+        (None, _) => return Err(rusqlite::Error::QueryReturnedNoRows.into()),
+    };
+
+    match fix {
+        Fix::NormaliseTime { column, time } => {
+            audit::audited_on(connection, "repair", table, "id", Some(id), |tx| {
+                tx.execute(&format!("UPDATE {table} SET {column} = ? WHERE id = ?"), (time.timestamp(), id))?;
+                Ok(id)
+            })?;
+        },
+        Fix::ClearTime(column) => {
+            audit::audited_on(connection, "repair", table, "id", Some(id), |tx| {
+                tx.execute(&format!("UPDATE {table} SET {column} = NULL WHERE id = ?"), (id,))?;
+                Ok(id)
+            })?;
+        },
+        Fix::SignIn(time) => {
+            audit::audited_on(connection, "repair", table, "id", Some(id), |tx| {
+                tx.execute(
+                    &format!("UPDATE {table} SET time_in = ?, receptionist_in = ? WHERE id = ? AND time_in IS NULL"),
+                    (time.timestamp(), receptionist, id)
+                )?;
+                Ok(id)
+            })?;
+        },
+        Fix::Void => {
+            amendment::amend_on(connection, table, &[], id, None, &reason, receptionist)?;
+        },
+        Fix::AmendStudentNumber(student_number) => {
+            amendment::amend_on(connection, table, &["student_number"], id, Some(("student_number", student_number)), &reason, receptionist)?;
+        },
+        Fix::AddToCatalogue { quantity, returnable } => {
+            let Problem::NotInCatalogue(name) = &finding.problem else {
+                return Ok(());
+            };
+
+            // unused parameters cannot be bound, so each catalogue is given only its own columns
+            let (column, insert, params): (_, _, Vec<Value>) = match table {
+                "keys" => ("key", "INSERT INTO keys (`key`) VALUES (?)", vec![name.clone().into()]),
+                "games" => ("game", "INSERT INTO games (game, quantity) VALUES (?, ?)", vec![name.clone().into(), (*quantity).into()]),
+                // stock is not counted until the item is restocked
+                "items" => ("item", "INSERT INTO items (item, returnable, quantity) VALUES (?, ?, NULL)", vec![name.clone().into(), (*returnable).into()]),
+                _ => return Err(rusqlite::Error::InvalidParameterName(format!("{table} is not a catalogue")).into()),
+            };
+
+            audit::audited_on(connection, "repair", table, column, None, |tx| {
+                tx.execute(insert, rusqlite::params_from_iter(params))?;
+
+                Ok(name.clone())
+            })?;
+        },
+        Fix::AddToCategory { category_id, quantity } => {
+            let Problem::NotInCatalogue(name) = &finding.problem else {
+                return Ok(());
+            };
+
+            audit::audited_on(connection, "repair", "category_entries", "rowid", None, |tx| {
+                tx.execute(
+                    "INSERT INTO category_entries (category_id, name, quantity) VALUES (?, ?, ?)",
+                    (category_id, name, quantity)
+                )?;
+
+                Ok(tx.last_insert_rowid())
+            })?;
+        },
+    }
+
+    log::info!("repaired {finding}");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::open_database;
+
+    const RECEPTIONIST: &str = "Reception";
+
+    fn database() -> rusqlite::Connection {
+        let connection = open_database(Path::new(":memory:")).unwrap();
+
+        connection.execute("INSERT INTO students (student_number, student_name) VALUES ('ABCDEF001', 'Alex')", ()).unwrap();
+
+        connection
+    }
+
+    fn findings_in(connection: &rusqlite::Connection, table: &str) -> Vec<Finding> {
+        check_database(connection).unwrap().into_iter()
+            .filter(|finding| finding.table == table)
+            .collect()
+    }
+
+    #[test]
+    fn clean_database_has_no_findings() {
+        assert_eq!(check_database(&database()).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn text_times_are_normalised() {
+        let mut connection = database();
+
+        connection.execute(
+            "INSERT INTO key_records (`key`, student_name, student_number, time_out, time_due, time_in, notes) VALUES ('K1', 'Alex', 'ABCDEF001', '2023-05-01 10:00:00', 'soon', NULL, '')",
+            ()
+        ).unwrap();
+        connection.execute("INSERT INTO keys (`key`) VALUES ('K1')", ()).unwrap();
+
+        let findings = findings_in(&connection, "key_records");
+        let time = chrono::DateTime::parse_from_rfc3339("2023-05-01T10:00:00Z").unwrap().with_timezone(&chrono::Utc);
+
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].fix, Some(Fix::NormaliseTime { column: "time_out", time }));
+        assert_eq!(findings[1].fix, Some(Fix::ClearTime("time_due")));
+
+        for finding in &findings {
+            repair(&mut connection, finding, RECEPTIONIST).unwrap();
+        }
+
+        assert_eq!(check_database(&connection).unwrap(), vec![]);
+
+        let time_out: i64 = connection.query_row("SELECT time_out FROM key_records", (), |row| row.get(0)).unwrap();
+        assert_eq!(time_out, time.timestamp());
+    }
+
+    #[test]
+    fn missing_item_is_added_as_returnable() {
+        let mut connection = database();
+
+        connection.execute(
+            "INSERT INTO item_records (item, quantity, student_name, student_number, receptionist, returnable, time_out, notes) VALUES ('Umbrella', 2, 'Alex', 'ABCDEF001', 'Reception', TRUE, 0, '')",
+            ()
+        ).unwrap();
+
+        let findings = findings_in(&connection, "items");
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].problem, Problem::NotInCatalogue("Umbrella".into()));
+        assert_eq!(findings[0].fix, Some(Fix::AddToCatalogue { quantity: 2, returnable: true }));
+
+        repair(&mut connection, &findings[0], RECEPTIONIST).unwrap();
+
+        let (returnable, quantity): (bool, Option<i64>) = connection
+            .query_row("SELECT returnable, quantity FROM items WHERE item = 'Umbrella'", (), |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+
+        assert!(returnable);
+        assert_eq!(quantity, None);
+        assert_eq!(check_database(&connection).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn unknown_catalogue_is_refused() {
+        let finding = Finding {
+            table: "rooms",
+            id: None,
+            problem: Problem::NotInCatalogue("Hall".into()),
+            fix: Some(Fix::AddToCatalogue { quantity: 1, returnable: false }),
+        };
+
+        assert!(repair(&mut database(), &finding, RECEPTIONIST).is_err());
+    }
+
+    #[test]
+    fn category_records_are_checked() {
+        let mut connection = database();
+
+        connection.execute("INSERT INTO categories (name, returnable, stock_limited, student_number_required) VALUES ('Vacuums', TRUE, TRUE, FALSE)", ()).unwrap();
+        connection.execute(
+            "INSERT INTO category_records (category_id, entry, quantity, student_name, student_number, time_out, time_in, notes) VALUES
+                (1, 'Upright', 2, 'Alex', NULL, 0, NULL, ''),
+                (1, 'Upright', 0, 'Alex', ' ABCDEF001 ', 0, 'later', '')",
+            ()
+        ).unwrap();
+
+        let findings = findings_in(&connection, "category_records");
+
+        assert_eq!(findings.len(), 3);
+        assert_eq!(findings[0].problem, Problem::InvalidTime { column: "time_in", value: "later".into() });
+        assert_eq!(findings[1].fix, Some(Fix::AmendStudentNumber("ABCDEF001".into())));
+        assert_eq!(findings[2].fix, Some(Fix::Void));
+
+        let findings = findings_in(&connection, "category_entries");
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].fix, Some(Fix::AddToCategory { category_id: 1, quantity: Some(2) }));
+
+        repair(&mut connection, &findings[0], RECEPTIONIST).unwrap();
+
+        let quantity: Option<i64> = connection
+            .query_row("SELECT quantity FROM category_entries WHERE category_id = 1 AND name = 'Upright'", (), |row| row.get(0))
+            .unwrap();
+
+        assert_eq!(quantity, Some(2));
+        assert_eq!(findings_in(&connection, "category_entries"), vec![]);
+    }
+}
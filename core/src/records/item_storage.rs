@@ -0,0 +1,343 @@
+use std::path::PathBuf;
+
+use rusqlite::OptionalExtension;
+
+use crate::worker::WorkerHandle;
+
+use super::{CorruptRow, parse_rows, FetchedPage, PendingPage, request_page, poll_page, fetch_counted_page, parse_time, parse_optional_time, format_optional_time, Page, PagePosition, StorageError, PaginatedStorage, RecordFilter, FilterColumns, filter_clause, InsertableStorage, ReceptionistSignableStorage, NotedStorage, ExportableStorage, ItemRecord, NewItemRecord, NewOverrideRecord, Overrides, log_overrides, Summary, SignOutTimes, upsert_student, AmendableStorage, AmendmentRecord, amendment, audit};
+
+const FILTER_COLUMNS: FilterColumns = FilterColumns {
+    student: &["student_name", "student_number"],
+    item: "item",
+    receptionist: &["receptionist", "receptionist_in"],
+    notes: "notes",
+    time: "time_out",
+    // items which are given away are never out
+    open: Some("IIF(returnable, time_in, time_out)"),
+    due: None,
+    voided: Some("voided"),
+};
+
+/// Columns which can be corrected with an amendment.
+const AMENDABLE_COLUMNS: &[&str] = &["item", "quantity", "student_name", "student_number"];
+
+pub struct ItemStorage {
+    worker: WorkerHandle,
+    background: bool,
+    records: Vec<ItemRecord>,
+    page: Page,
+    filter: RecordFilter,
+    count: i64,
+    position: PagePosition,
+    stale: bool,
+    corrupt: Vec<CorruptRow>,
+    pending: Option<PendingPage<ItemRecord>>,
+}
+
+impl ItemStorage {
+    /// Pages are fetched in the background if `background` is set, otherwise waited for.
+    pub fn new(worker: WorkerHandle, background: bool) -> Result<ItemStorage, StorageError> {
+        let mut storage = ItemStorage {
+            worker,
+            background,
+            records: vec![],
+            page: Page::LastPage,
+            filter: RecordFilter::default(),
+            count: 0,
+            position: PagePosition::default(),
+            stale: true,
+            corrupt: vec![],
+            pending: None,
+        };
+
+        storage.refresh()?;
+
+        Ok(storage)
+    }
+
+    fn show(&mut self, fetched: FetchedPage<ItemRecord>) {
+        self.count = fetched.count;
+        self.records = fetched.records;
+        self.corrupt = fetched.corrupt;
+        self.position = fetched.position;
+        self.stale = false;
+    }
+
+    /// Fetch the current page, counting the records again only if they may have changed since the page shown.
+    fn request(&mut self) -> Result<(), StorageError> {
+        let (clause, params) = filter_clause(&self.filter, &FILTER_COLUMNS);
+        let page = self.page;
+        let shown = (!self.stale).then_some((self.count, self.position));
+
+        let fetch = move |connection: &rusqlite::Connection| {
+            fetch_counted_page(connection, "item_records_amended", (&clause, &params), page, shown, Self::parse_row, |record| record.id)
+        };
+
+        if let Some(fetched) = request_page(&self.worker, self.background, &mut self.pending, fetch)? {
+            self.show(fetched);
+        }
+
+        log::debug!("refreshed item records");
+
+        Ok(())
+    }
+
+    /// Fetch a record by id, whether or not it is on the current page.
+    pub fn fetch(&self, id: i64) -> Result<Option<ItemRecord>, StorageError> {
+        self.worker.call(move |connection| {
+            let record = connection.prepare("SELECT * FROM item_records_amended WHERE id = ?")?
+                .query_row((id,), Self::parse_row)
+                .optional()?;
+
+            Ok(record)
+        })
+    }
+
+    /// Fails with `StorageError::InsufficientStock` if the item's stock is counted and too few are left,
+    /// checked by the database within the insert, which also takes them from the stock.
+    /// Restrictions overridden to sign out the record are logged in the same transaction.
+    pub fn insert_with_overrides(&mut self, record: NewItemRecord, overrides: Option<&NewOverrideRecord>) -> Result<(), StorageError> {
+        let (item, quantity, student_name, student_number, receptionist, notes) = (record.item.to_owned(), record.quantity, record.student_name.to_owned(), record.student_number.to_uppercase(), record.receptionist.to_owned(), record.notes.to_owned());
+        let overrides = overrides.map(Overrides::from);
+
+        audit::audited(&self.worker, "insert", "item_records", "id", None, move |tx| {
+            upsert_student(tx, &student_number, &student_name)?;
+
+            tx.execute(
+                "INSERT INTO item_records (id, item, quantity, student_name, student_number, returnable, receptionist, time_out, notes)
+                    VALUES (NULL, ?1, ?2, ?3, ?4, IFNULL((SELECT returnable FROM items WHERE item = ?1), FALSE), ?5, ?6, ?7)",
+                (item, quantity, student_name, student_number, receptionist, chrono::Utc::now().timestamp(), notes)
+            )?;
+
+            let id = tx.last_insert_rowid();
+
+            if let Some(overrides) = overrides {
+                log_overrides(tx, &overrides)?;
+            }
+
+            Ok(id)
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+}
+
+impl PaginatedStorage<ItemRecord, i64> for ItemStorage {
+    fn page(&self) -> Page {
+        self.page
+    }
+    
+    fn set_page(&mut self, page: Page) -> Result<(), StorageError> {
+        if page != self.page {
+            self.page = page;
+            self.request()
+        } else {
+            Ok(())
+        }
+    }
+
+    fn count(&self) -> i64 {
+        self.count
+    }
+
+    fn position(&self) -> PagePosition {
+        self.position
+    }
+
+    fn filter(&self) -> &RecordFilter {
+        &self.filter
+    }
+
+    fn set_filter(&mut self, filter: &RecordFilter) -> Result<(), StorageError> {
+        if *filter != self.filter {
+            self.filter = filter.clone();
+            self.page = Page::LastPage;
+            self.refresh()
+        } else {
+            Ok(())
+        }
+    }
+    
+    fn refresh(&mut self) -> Result<(), StorageError> {
+        self.stale = true;
+        self.request()
+    }
+
+    fn poll(&mut self) -> Result<bool, StorageError> {
+        match poll_page(&mut self.pending)? {
+            Some(fetched) => {
+                self.show(fetched);
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+
+    fn get_all(&self) -> &[ItemRecord] {
+        self.records.as_slice()
+    }
+
+    fn corrupt(&self) -> &[CorruptRow] {
+        &self.corrupt
+    }
+
+    fn parse_row(row: &rusqlite::Row) -> Result<ItemRecord, rusqlite::Error> {
+        let time_out = parse_time(row, "time_out")?;
+        let time_in = parse_optional_time(row, "time_in")?;
+        
+        Ok(ItemRecord {
+            id: row.get("id")?,
+            item: row.get("item")?,
+            quantity: row.get("quantity")?,
+            student_name: row.get("student_name")?,
+            student_number: row.get("student_number")?,
+            returnable: row.get("returnable")?,
+            receptionist: row.get("receptionist")?,
+            receptionist_in: row.get("receptionist_in")?,
+            time_out,
+            time_in,
+            notes: row.get("notes")?,
+            voided: row.get("voided")?,
+            amended: row.get("amended")?,
+        })
+    }
+}
+
+impl InsertableStorage<NewItemRecord<'_>, i64> for ItemStorage {
+    fn insert(&mut self, record: NewItemRecord) -> Result<(), StorageError> {
+        self.insert_with_overrides(record, None)
+    }
+}
+
+impl ReceptionistSignableStorage<ItemRecord, i64> for ItemStorage {
+    fn signin(&mut self, id: i64, receptionist: &str) -> Result<(), StorageError> {
+        let receptionist = receptionist.to_owned();
+
+        audit::audited(&self.worker, "sign in", "item_records", "id", Some(id), move |tx| {
+            tx.execute(
+                "UPDATE item_records SET receptionist_in = ?, time_in = ? WHERE id = ?",
+                (receptionist, chrono::Utc::now().timestamp(), id)
+            )?;
+
+            Ok(id)
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+}
+
+impl NotedStorage<ItemRecord, i64> for ItemStorage {
+    fn update_notes(&mut self, id: i64, notes: &str) -> Result<(), StorageError> {
+        let notes = notes.to_owned();
+
+        audit::audited(&self.worker, "update notes", "item_records", "id", Some(id), move |tx| {
+            tx.execute(
+                "UPDATE item_records SET notes = ? WHERE id = ?",
+                (notes, id)
+            )?;
+
+            Ok(id)
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+}
+
+impl ExportableStorage<ItemRecord> for ItemStorage {
+    fn fetch_all(connection: &rusqlite::Connection) -> Result<Vec<ItemRecord>, StorageError> {
+        let mut stmt = connection.prepare("SELECT * FROM item_records_amended WHERE NOT voided")?;
+        
+        let records = parse_rows("item_records", stmt.query(())?, Self::parse_row)?.0;
+        
+        log::debug!("fetched all item records");
+
+        Ok(records)
+    }
+
+    fn csv_headers() -> &'static [&'static str] {
+        &[
+            "Time Out",
+            "Item",
+            "Quantity",
+            "Student Name",
+            "Student Number",
+            "Receptionist",
+            "Time In",
+            "Signed In By",
+            "Notes",
+        ]
+    }
+
+    fn write_record<W: std::io::Write>(writer: &mut csv::Writer<W>, record: &ItemRecord) -> Result<(), csv::Error> {
+        writer.write_record([
+            record.time_out.to_rfc3339().as_str(),
+            &record.item,
+            &record.quantity.to_string(),
+            &record.student_name,
+            &record.student_number,
+            &record.receptionist,
+            &format_optional_time(record.time_in),
+            record.receptionist_in.as_deref().unwrap_or_default(),
+            &record.notes,
+        ])
+    }
+
+    fn export_csv(connection: &rusqlite::Connection, path: PathBuf, progress: impl FnMut(f32)) -> Result<(), StorageError> {
+        super::export_csv::<ItemRecord, Self>(connection, path, progress)
+    }
+}
+
+impl Summary for ItemStorage {
+    /// find all records with time_out in [start, end)
+    fn summary(&self, start: chrono::DateTime<chrono::Utc>, end: chrono::DateTime<chrono::Utc>) -> Result<Vec<(String, i64)>, StorageError> {
+        fn parse_row(row: &rusqlite::Row) -> Result<(String, i64), rusqlite::Error> {
+            Ok((row.get("item")?, row.get("c")?))
+        }
+
+        let records = self.worker.call(move |conn| {
+            let mut stmt = conn.prepare("SELECT item, SUM(quantity) AS c FROM item_records_amended WHERE ? <= time_out AND time_out < ? AND NOT voided GROUP BY item ORDER BY item")?;
+
+            let start = start.timestamp();
+            let end = end.timestamp();
+
+            let records = stmt.query_map((start, end), parse_row)?;
+            Ok(records.collect::<Result<Vec<_>, _>>()?)
+        })?;
+
+        Ok(records)
+    }
+}
+
+impl SignOutTimes for ItemStorage {
+    fn sign_out_times(&self, start: chrono::DateTime<chrono::Utc>, end: chrono::DateTime<chrono::Utc>) -> Result<Vec<(chrono::DateTime<chrono::Utc>, i64)>, StorageError> {
+        super::sign_out_times(&self.worker, "item_records_amended", "quantity", start, end)
+    }
+}
+
+impl AmendableStorage<ItemRecord, i64> for ItemStorage {
+    fn amend(&mut self, id: i64, field: &str, new_value: &str, reason: &str, receptionist: &str) -> Result<(), StorageError> {
+        amendment::amend(&self.worker, "item_records", AMENDABLE_COLUMNS, id, Some((field, new_value)), reason, receptionist)?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+
+    fn void(&mut self, id: i64, reason: &str, receptionist: &str) -> Result<(), StorageError> {
+        amendment::amend(&self.worker, "item_records", AMENDABLE_COLUMNS, id, None, reason, receptionist)?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+
+    fn amendments(&self, id: i64) -> Result<Vec<AmendmentRecord>, StorageError> {
+        amendment::fetch_amendments(&self.worker, "item_records", id)
+    }
+}
@@ -0,0 +1,175 @@
+use crate::worker::WorkerHandle;
+
+use super::{ItemTypeRecord, NewItemTypeRecord, ItemRestockRecord, StorageError, Storage, DeletableStorage, InsertableStorage, parse_time, audit};
+
+pub struct ItemTypeStorage {
+    worker: WorkerHandle,
+    records: Vec<ItemTypeRecord>,
+}
+
+impl ItemTypeStorage {
+    pub fn new(worker: WorkerHandle) -> Result<ItemTypeStorage, StorageError> {
+        let mut storage = ItemTypeStorage {
+            worker,
+            records: vec![],
+        };
+
+        storage.refresh()?;
+
+        Ok(storage)
+    }
+
+    /// Items at or below their low stock level.
+    pub fn low_stock(&self) -> impl Iterator<Item = &ItemTypeRecord> {
+        self.records.iter().filter(|r| r.is_low_stock())
+    }
+
+    pub fn update_returnable(&mut self, item: &str, returnable: bool) -> Result<(), StorageError> {
+        let item = item.to_owned();
+
+        audit::audited(&self.worker, "update returnable", "items", "item", Some(item.clone()), move |tx| {
+            tx.execute(
+                "UPDATE items SET returnable = ? WHERE item = ?",
+                (returnable, &item)
+            )?;
+
+            Ok(item)
+        })?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+
+    pub fn update_low_stock(&mut self, item: &str, low_stock: i64) -> Result<(), StorageError> {
+        let item = item.to_owned();
+
+        audit::audited(&self.worker, "update low stock", "items", "item", Some(item.clone()), move |tx| {
+            tx.execute(
+                "UPDATE items SET low_stock = ? WHERE item = ?",
+                (low_stock, &item)
+            )?;
+
+            Ok(item)
+        })?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+
+    /// Add to the stock of an item, which starts counting it if it was not already.
+    pub fn restock(&mut self, item: &str, quantity: i64, receptionist: &str, notes: &str) -> Result<(), StorageError> {
+        let (item, receptionist, notes) = (item.to_owned(), receptionist.to_owned(), notes.to_owned());
+
+        audit::audited(&self.worker, "insert", "item_restocks", "id", None, move |tx| {
+            tx.execute(
+                "INSERT INTO item_restocks (id, item, quantity, receptionist, time, notes) VALUES (NULL, ?, ?, ?, ?, ?)",
+                (item, quantity, receptionist, chrono::Utc::now().timestamp(), notes)
+            )?;
+
+            Ok(tx.last_insert_rowid())
+        })?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+
+    /// Restocks of an item, newest first.
+    pub fn restocks(&self, item: &str) -> Result<Vec<ItemRestockRecord>, StorageError> {
+        fn parse_row(row: &rusqlite::Row) -> Result<ItemRestockRecord, rusqlite::Error> {
+            Ok(ItemRestockRecord {
+                id: row.get("id")?,
+                item: row.get("item")?,
+                quantity: row.get("quantity")?,
+                receptionist: row.get("receptionist")?,
+                time: parse_time(row, "time")?,
+                notes: row.get("notes")?,
+            })
+        }
+
+        let item = item.to_owned();
+
+        self.worker.call(move |connection| {
+            let records = connection.prepare("SELECT * FROM item_restocks WHERE item = ? ORDER BY time DESC, id DESC")?
+                .query_map((item,), parse_row)?
+                .collect::<Result<_, _>>()?;
+
+            Ok(records)
+        })
+    }
+}
+
+impl Storage<ItemTypeRecord, &str> for ItemTypeStorage {
+    fn refresh(&mut self) -> Result<(), StorageError> {
+        self.records = self.worker.call(|connection| {
+            let mut stmt = connection.prepare("SELECT * FROM items ORDER BY item")?;
+
+            let records = stmt
+                .query_map((), Self::parse_row)?
+                .collect::<Result<_, _>>()?;
+
+            Ok(records)
+        })?;
+
+        log::debug!("refreshed item types");
+
+        Ok(())
+    }
+
+    fn get_all(&self) -> &[ItemTypeRecord] {
+        self.records.as_slice()
+    }
+
+    fn get(&self, id: &str) -> Option<&ItemTypeRecord> {
+        self.records.iter().find(|r| r.item == id)
+    }
+
+    fn parse_row(row: &rusqlite::Row) -> Result<ItemTypeRecord, rusqlite::Error> {
+        Ok(ItemTypeRecord {
+            item: row.get("item")?,
+            returnable: row.get("returnable")?,
+            quantity: row.get("quantity")?,
+            low_stock: row.get("low_stock")?,
+        })
+    }
+}
+
+impl InsertableStorage<NewItemTypeRecord<'_>, &str> for ItemTypeStorage {
+    fn insert(&mut self, record: NewItemTypeRecord) -> Result<(), StorageError> {
+        let (item, returnable, quantity, low_stock) = (record.item.to_owned(), record.returnable, record.quantity, record.low_stock);
+
+        audit::audited(&self.worker, "insert", "items", "item", None, move |tx| {
+            tx.execute(
+                "INSERT INTO items (item, returnable, quantity, low_stock) VALUES (?, ?, ?, ?)",
+                (&item, returnable, quantity, low_stock)
+            )?;
+
+            Ok(item)
+        })?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+}
+
+impl DeletableStorage<ItemTypeRecord, &str> for ItemTypeStorage {
+    fn delete(&mut self, item: &str) -> Result<(), StorageError> {
+        let item = item.to_owned();
+
+        audit::audited(&self.worker, "delete", "items", "item", Some(item.clone()), move |tx| {
+            tx.execute(
+                "DELETE FROM items WHERE item = ?",
+                (&item,)
+            )?;
+
+            Ok(item)
+        })?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+}
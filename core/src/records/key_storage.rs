@@ -0,0 +1,429 @@
+use std::path::PathBuf;
+
+use rusqlite::OptionalExtension;
+
+use crate::worker::WorkerHandle;
+
+use super::{CorruptRow, parse_rows, FetchedPage, PendingPage, request_page, poll_page, fetch_counted_page, parse_time, parse_optional_time, Page, PagePosition, StorageError, PaginatedStorage, RecordFilter, FilterColumns, filter_clause, format_optional_time, InsertableStorage, ReceptionistSignableStorage, NotedStorage, ExportableStorage, KeyRecord, NewKeyRecord, NewOverrideRecord, Overrides, log_overrides, Summary, ReceptionistSummary, SignOutTimes, upsert_student, AmendableStorage, AmendmentRecord, amendment, audit};
+
+const FILTER_COLUMNS: FilterColumns = FilterColumns {
+    student: &["student_name", "student_number"],
+    item: "key",
+    receptionist: &["receptionist_out", "receptionist_in"],
+    notes: "notes",
+    time: "time_out",
+    open: Some("time_in"),
+    due: Some("time_due"),
+    voided: Some("voided"),
+};
+
+/// Columns which can be corrected with an amendment.
+const AMENDABLE_COLUMNS: &[&str] = &["key", "student_name", "student_number"];
+
+pub struct KeyStorage {
+    worker: WorkerHandle,
+    background: bool,
+    records: Vec<KeyRecord>,
+    page: Page,
+    filter: RecordFilter,
+    count: i64,
+    position: PagePosition,
+    stale: bool,
+    corrupt: Vec<CorruptRow>,
+    pending: Option<PendingPage<KeyRecord>>,
+}
+
+impl KeyStorage {
+    /// Pages are fetched in the background if `background` is set, otherwise waited for.
+    pub fn new(worker: WorkerHandle, background: bool) -> Result<KeyStorage, StorageError> {
+        let mut storage = KeyStorage {
+            worker,
+            background,
+            records: vec![],
+            page: Page::LastPage,
+            filter: RecordFilter::default(),
+            count: 0,
+            position: PagePosition::default(),
+            stale: true,
+            corrupt: vec![],
+            pending: None,
+        };
+
+        storage.refresh()?;
+
+        Ok(storage)
+    }
+
+    fn show(&mut self, fetched: FetchedPage<KeyRecord>) {
+        self.count = fetched.count;
+        self.records = fetched.records;
+        self.corrupt = fetched.corrupt;
+        self.position = fetched.position;
+        self.stale = false;
+    }
+
+    /// Fetch the current page, counting the records again only if they may have changed since the page shown.
+    fn request(&mut self) -> Result<(), StorageError> {
+        let (clause, params) = filter_clause(&self.filter, &FILTER_COLUMNS);
+        let page = self.page;
+        let shown = (!self.stale).then_some((self.count, self.position));
+
+        let fetch = move |connection: &rusqlite::Connection| {
+            fetch_counted_page(connection, "key_records_amended", (&clause, &params), page, shown, Self::parse_row, |record| record.id)
+        };
+
+        if let Some(fetched) = request_page(&self.worker, self.background, &mut self.pending, fetch)? {
+            self.show(fetched);
+        }
+
+        log::debug!("refreshed key records");
+
+        Ok(())
+    }
+
+    /// Fetch a record by id, whether or not it is on the current page.
+    pub fn fetch(&self, id: i64) -> Result<Option<KeyRecord>, StorageError> {
+        self.worker.call(move |connection| {
+            let record = connection.prepare("SELECT * FROM key_records_amended WHERE id = ?")?
+                .query_row((id,), Self::parse_row)
+                .optional()?;
+
+            Ok(record)
+        })
+    }
+    
+    pub fn get_signed_out(&mut self, item_type: &str) -> Result<Option<KeyRecord>, StorageError> {
+        let item_type = item_type.to_owned();
+
+        self.worker.call(move |connection| {
+            let mut stmt = connection.prepare("
+                SELECT * FROM key_records_amended WHERE id IN (
+                    SELECT id FROM key_records WHERE `key` = ?1 AND time_in IS NULL
+                    UNION SELECT record_id FROM amendments WHERE record_table = 'key_records' AND field = 'key' AND new_value = ?1
+                ) AND `key` = ?1 AND time_in IS NULL AND NOT voided LIMIT 1
+            ")?;
+            
+            let record = stmt.query_row((item_type,), Self::parse_row)
+                .optional()?;
+
+            Ok(record)
+        })
+    }
+
+    /// All records which are still signed out, oldest first.
+    pub fn fetch_outstanding(&self) -> Result<Vec<KeyRecord>, StorageError> {
+        self.worker.call(|connection| {
+            let mut stmt = connection.prepare("SELECT * FROM key_records_amended WHERE time_in IS NULL AND NOT voided ORDER BY time_out")?;
+            
+            let records = parse_rows("key_records", stmt.query(())?, Self::parse_row)?.0;
+
+            Ok(records)
+        })
+    }
+
+    pub fn fetch_overdue(&self) -> Result<Vec<KeyRecord>, StorageError> {
+        self.worker.call(|connection| {
+            let mut stmt = connection.prepare("SELECT * FROM key_records_amended WHERE time_in IS NULL AND time_due < ? AND NOT voided ORDER BY time_due")?;
+            
+            let records = parse_rows("key_records", stmt.query((chrono::Utc::now().timestamp(),))?, Self::parse_row)?.0;
+
+            Ok(records)
+        })
+    }
+
+    /// Counted on the connection given, e.g. on the worker, since records become overdue without any change to be refreshed on.
+    pub fn count_overdue(connection: &rusqlite::Connection) -> Result<i64, StorageError> {
+        let count = connection.prepare("SELECT COUNT(*) AS c FROM key_records_amended WHERE time_in IS NULL AND time_due < ? AND NOT voided")?
+            .query_row((chrono::Utc::now().timestamp(),), |row| row.get("c"))?;
+
+        Ok(count)
+    }
+
+    /// Fails with `StorageError::KeyAlreadyOut` if the key is already signed out, checked by the database
+    /// within the insert so that two receptionists cannot sign out the same key at once.
+    /// Restrictions overridden to sign out the record are logged in the same transaction.
+    pub fn insert_with_overrides(&mut self, record: NewKeyRecord, overrides: Option<&NewOverrideRecord>) -> Result<(), StorageError> {
+        let (key, student_name, student_number, receptionist, notes) = (record.key.to_owned(), record.student_name.to_owned(), record.student_number.to_uppercase(), record.receptionist.to_owned(), record.notes.to_owned());
+        let overrides = overrides.map(Overrides::from);
+
+        audit::audited(&self.worker, "insert", "key_records", "id", None, move |tx| {
+            let time_out = chrono::Utc::now();
+            
+            let loan_minutes = tx.prepare("SELECT loan_minutes FROM keys WHERE key = ?")?
+                .query_row((&key,), |row| row.get::<_, Option<i64>>("loan_minutes"))
+                .optional()?.flatten();
+
+            let time_due = loan_minutes.map(|minutes| time_out + chrono::Duration::minutes(minutes));
+
+            upsert_student(tx, &student_number, &student_name)?;
+
+            tx.execute(
+                "INSERT INTO key_records (id, key, student_name, student_number, receptionist_out, time_out, time_due, time_in, notes) VALUES (NULL, ?, ?, ?, ?, ?, ?, NULL, ?)",
+                (key, student_name, student_number, receptionist, time_out.timestamp(), time_due.map(|t| t.timestamp()), notes)
+            )?;
+
+            let id = tx.last_insert_rowid();
+
+            if let Some(overrides) = overrides {
+                log_overrides(tx, &overrides)?;
+            }
+
+            Ok(id)
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+}
+
+impl PaginatedStorage<KeyRecord, i64> for KeyStorage {
+    fn page(&self) -> Page {
+        self.page
+    }
+    
+    fn set_page(&mut self, page: Page) -> Result<(), StorageError> {
+        if page != self.page {
+            self.page = page;
+            self.request()
+        } else {
+            Ok(())
+        }
+    }
+
+    fn count(&self) -> i64 {
+        self.count
+    }
+
+    fn position(&self) -> PagePosition {
+        self.position
+    }
+
+    fn filter(&self) -> &RecordFilter {
+        &self.filter
+    }
+
+    fn set_filter(&mut self, filter: &RecordFilter) -> Result<(), StorageError> {
+        if *filter != self.filter {
+            self.filter = filter.clone();
+            self.page = Page::LastPage;
+            self.refresh()
+        } else {
+            Ok(())
+        }
+    }
+    
+    fn refresh(&mut self) -> Result<(), StorageError> {
+        self.stale = true;
+        self.request()
+    }
+
+    fn poll(&mut self) -> Result<bool, StorageError> {
+        match poll_page(&mut self.pending)? {
+            Some(fetched) => {
+                self.show(fetched);
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+
+    fn get_all(&self) -> &[KeyRecord] {
+        self.records.as_slice()
+    }
+
+    fn corrupt(&self) -> &[CorruptRow] {
+        &self.corrupt
+    }
+
+    fn parse_row(row: &rusqlite::Row) -> Result<KeyRecord, rusqlite::Error> {
+        let time_out = parse_time(row, "time_out")?;
+        
+        let time_due = parse_optional_time(row, "time_due")?;
+        
+        let time_in = parse_optional_time(row, "time_in")?;
+        
+        Ok(KeyRecord {
+            id: row.get("id")?,
+            key: row.get("key")?,
+            student_name: row.get("student_name")?,
+            student_number: row.get("student_number")?,
+            receptionist_out: row.get("receptionist_out")?,
+            receptionist_in: row.get("receptionist_in")?,
+            time_out,
+            time_due,
+            time_in,
+            notes: row.get("notes")?,
+            voided: row.get("voided")?,
+            amended: row.get("amended")?,
+        })
+    }
+}
+
+impl InsertableStorage<NewKeyRecord<'_>, i64> for KeyStorage {
+    fn insert(&mut self, record: NewKeyRecord) -> Result<(), StorageError> {
+        self.insert_with_overrides(record, None)
+    }
+}
+
+impl ReceptionistSignableStorage<KeyRecord, i64> for KeyStorage {
+    fn signin(&mut self, id: i64, receptionist: &str) -> Result<(), StorageError> {
+        let receptionist = receptionist.to_owned();
+
+        let receptionist = receptionist.to_owned();
+
+        audit::audited(&self.worker, "sign in", "key_records", "id", Some(id), move |tx| {
+            tx.execute(
+                "UPDATE key_records SET receptionist_in = ?, time_in = ? WHERE id = ?",
+                (receptionist, chrono::Utc::now().timestamp(), id)
+            )?;
+
+            Ok(id)
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+}
+
+impl NotedStorage<KeyRecord, i64> for KeyStorage {
+    fn update_notes(&mut self, id: i64, notes: &str) -> Result<(), StorageError> {
+        let notes = notes.to_owned();
+
+        let notes = notes.to_owned();
+
+        audit::audited(&self.worker, "update notes", "key_records", "id", Some(id), move |tx| {
+            tx.execute(
+                "UPDATE key_records SET notes = ? WHERE id = ?",
+                (notes, id)
+            )?;
+
+            Ok(id)
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+}
+
+impl ExportableStorage<KeyRecord> for KeyStorage {
+    fn fetch_all(connection: &rusqlite::Connection) -> Result<Vec<KeyRecord>, StorageError> {
+        let mut stmt = connection.prepare("SELECT * FROM key_records_amended WHERE NOT voided")?;
+        
+        let records = parse_rows("key_records", stmt.query(())?, Self::parse_row)?.0;
+        
+        log::debug!("fetched all key records");
+
+        Ok(records)
+    }
+
+    fn csv_headers() -> &'static [&'static str] {
+        &[
+            "Time Out",
+            "Due",
+            "Time In",
+            "Key",
+            "Student Name",
+            "Student Number",
+            "Signed Out By",
+            "Signed In By",
+            "Notes",
+        ]
+    }
+
+    fn write_record<W: std::io::Write>(writer: &mut csv::Writer<W>, record: &KeyRecord) -> Result<(), csv::Error> {
+        writer.write_record([
+            record.time_out.to_rfc3339().as_str(),
+            &format_optional_time(record.time_due),
+            &format_optional_time(record.time_in),
+            &record.key,
+            &record.student_name,
+            &record.student_number,
+            record.receptionist_out.as_ref().unwrap_or(&String::new()),
+            record.receptionist_in.as_ref().unwrap_or(&String::new()),
+            &record.notes,
+        ])
+    }
+    
+    fn export_csv(connection: &rusqlite::Connection, path: PathBuf, progress: impl FnMut(f32)) -> Result<(), StorageError> {
+        super::export_csv::<KeyRecord, Self>(connection, path, progress)
+    }
+}
+
+impl Summary for KeyStorage {
+    /// find all records with time_out in [start, end)
+    fn summary(&self, start: chrono::DateTime<chrono::Utc>, end: chrono::DateTime<chrono::Utc>) -> Result<Vec<(String, i64)>, StorageError> {
+        fn parse_row(row: &rusqlite::Row) -> Result<(String, i64), rusqlite::Error> {
+            Ok((row.get("key")?, row.get("c")?))
+        }
+
+        let records = self.worker.call(move |conn| {
+            let mut stmt = conn.prepare("SELECT `key`, COUNT(*) AS c FROM key_records_amended WHERE ? <= time_out AND time_out < ? AND NOT voided GROUP BY key ORDER BY key")?;
+
+            let start = start.timestamp();
+            let end = end.timestamp();
+
+            let records = stmt.query_map((start, end), parse_row)?;
+            Ok(records.collect::<Result<Vec<_>, _>>()?)
+        })?;
+
+        Ok(records)
+    }
+}
+
+impl SignOutTimes for KeyStorage {
+    fn sign_out_times(&self, start: chrono::DateTime<chrono::Utc>, end: chrono::DateTime<chrono::Utc>) -> Result<Vec<(chrono::DateTime<chrono::Utc>, i64)>, StorageError> {
+        super::sign_out_times(&self.worker, "key_records_amended", "1", start, end)
+    }
+}
+
+impl ReceptionistSummary for KeyStorage {
+    /// count records signed out with time_out in [start, end), and signed in with time_in in [start, end)
+    fn receptionist_summary(&self, start: chrono::DateTime<chrono::Utc>, end: chrono::DateTime<chrono::Utc>) -> Result<Vec<(String, i64, i64)>, StorageError> {
+        fn parse_row(row: &rusqlite::Row) -> Result<(String, i64, i64), rusqlite::Error> {
+            Ok((row.get("receptionist")?, row.get("signed_out")?, row.get("signed_in")?))
+        }
+
+        let records = self.worker.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT receptionist, SUM(signed_out) AS signed_out, SUM(signed_in) AS signed_in FROM (
+                    SELECT receptionist_out AS receptionist, 1 AS signed_out, 0 AS signed_in FROM key_records_amended WHERE receptionist_out IS NOT NULL AND ?1 <= time_out AND time_out < ?2 AND NOT voided
+                    UNION ALL
+                    SELECT receptionist_in AS receptionist, 0 AS signed_out, 1 AS signed_in FROM key_records_amended WHERE receptionist_in IS NOT NULL AND ?1 <= time_in AND time_in < ?2 AND NOT voided
+                ) GROUP BY receptionist ORDER BY receptionist"
+            )?;
+
+            let start = start.timestamp();
+            let end = end.timestamp();
+
+            let records = stmt.query_map((start, end), parse_row)?;
+            Ok(records.collect::<Result<Vec<_>, _>>()?)
+        })?;
+
+        Ok(records)
+    }
+}
+
+impl AmendableStorage<KeyRecord, i64> for KeyStorage {
+    fn amend(&mut self, id: i64, field: &str, new_value: &str, reason: &str, receptionist: &str) -> Result<(), StorageError> {
+        amendment::amend(&self.worker, "key_records", AMENDABLE_COLUMNS, id, Some((field, new_value)), reason, receptionist)?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+
+    fn void(&mut self, id: i64, reason: &str, receptionist: &str) -> Result<(), StorageError> {
+        amendment::amend(&self.worker, "key_records", AMENDABLE_COLUMNS, id, None, reason, receptionist)?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+
+    fn amendments(&self, id: i64) -> Result<Vec<AmendmentRecord>, StorageError> {
+        amendment::fetch_amendments(&self.worker, "key_records", id)
+    }
+}
@@ -0,0 +1,109 @@
+use crate::worker::WorkerHandle;
+
+use super::{KeyTypeRecord, NewKeyTypeRecord, StorageError, Storage, InsertableStorage, DeletableStorage, audit};
+
+pub struct KeyTypeStorage {
+    worker: WorkerHandle,
+    records: Vec<KeyTypeRecord>,
+}
+
+impl KeyTypeStorage {
+    pub fn new(worker: WorkerHandle) -> Result<KeyTypeStorage, StorageError> {
+        let mut storage = KeyTypeStorage {
+            worker,
+            records: vec![],
+        };
+
+        storage.refresh()?;
+
+        Ok(storage)
+    }
+
+    pub fn update_loan_period(&mut self, key: &str, loan_minutes: Option<i64>) -> Result<(), StorageError> {
+        let key = key.to_owned();
+
+        audit::audited(&self.worker, "update loan period", "keys", "key", Some(key.clone()), move |tx| {
+            tx.execute(
+                "UPDATE keys SET loan_minutes = ? WHERE key = ?",
+                (loan_minutes, &key)
+            )?;
+
+            Ok(key)
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+}
+
+impl Storage<KeyTypeRecord, &str> for KeyTypeStorage {
+    fn refresh(&mut self) -> Result<(), StorageError> {
+        self.records = self.worker.call(|connection| {
+            let mut stmt = connection.prepare("SELECT * FROM keys ORDER BY key")?;
+        
+            let records = stmt
+                .query_map((), Self::parse_row)?
+                .collect::<Result<_, _>>()?;
+
+            Ok(records)
+        })?;
+
+        log::debug!("refreshed key types");
+        
+        Ok(())
+    }
+
+    fn get_all(&self) -> &[KeyTypeRecord] {
+        self.records.as_slice()
+    }
+
+    fn get(&self, id: &str) -> Option<&KeyTypeRecord> {
+        self.records.iter().find(|r| r.key == id)
+    }
+
+    fn parse_row(row: &rusqlite::Row) -> Result<KeyTypeRecord, rusqlite::Error> {
+        Ok(KeyTypeRecord {
+            key: row.get("key")?,
+            loan_minutes: row.get("loan_minutes")?,
+        })
+    }
+}
+
+impl InsertableStorage<NewKeyTypeRecord<'_>, &str> for KeyTypeStorage {
+    fn insert(&mut self, record: NewKeyTypeRecord) -> Result<(), StorageError> {
+        let (key, loan_minutes) = (record.key.to_owned(), record.loan_minutes);
+
+        audit::audited(&self.worker, "insert", "keys", "key", None, move |tx| {
+            tx.execute(
+                "INSERT INTO keys (key, loan_minutes) VALUES (?, ?)",
+                (&key, loan_minutes)
+            )?;
+
+            Ok(key)
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+}
+
+impl DeletableStorage<KeyTypeRecord, &str> for KeyTypeStorage {
+    fn delete(&mut self, key: &str) -> Result<(), StorageError> {
+        let key = key.to_owned();
+
+        audit::audited(&self.worker, "delete", "keys", "key", Some(key.clone()), move |tx| {
+            tx.execute(
+                "DELETE FROM keys WHERE key = ?",
+                (&key,)
+            )?;
+
+            Ok(key)
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+}
@@ -0,0 +1,117 @@
+use crate::worker::WorkerHandle;
+
+use super::{LoanLimit, RecordType, StorageError, Storage, DeletableStorage, audit, restriction_storage::{record_type_text, parse_record_type}};
+
+pub struct LoanLimitStorage {
+    worker: WorkerHandle,
+    limits: Vec<LoanLimit>,
+}
+
+impl LoanLimitStorage {
+    pub fn new(worker: WorkerHandle) -> Result<LoanLimitStorage, StorageError> {
+        let mut storage = LoanLimitStorage {
+            worker,
+            limits: vec![],
+        };
+
+        storage.refresh()?;
+
+        Ok(storage)
+    }
+
+    /// Limits which a record of `record_type` named `name` counts towards.
+    pub fn applicable(&self, record_type: RecordType, name: &str) -> Vec<&LoanLimit> {
+        self.limits.iter()
+            .filter(|limit| limit.applies_to(record_type, name))
+            .collect()
+    }
+
+    /// Set the limit of a scope, replacing any limit it already has.
+    pub fn set(&mut self, record_type: Option<RecordType>, name: Option<&str>, max_open: i64) -> Result<(), StorageError> {
+        let existing = self.limits.iter()
+            .find(|limit| limit.record_type == record_type && limit.name.as_deref() == name)
+            .map(|limit| limit.id);
+
+        match existing {
+            Some(id) => audit::audited(&self.worker, "update", "loan_limits", "id", Some(id), move |tx| {
+                tx.execute(
+                    "UPDATE loan_limits SET max_open = ? WHERE id = ?",
+                    (max_open, id)
+                )?;
+
+                Ok(id)
+            })?,
+            None => {
+                let name = name.map(str::to_owned);
+
+                audit::audited(&self.worker, "insert", "loan_limits", "id", None, move |tx| {
+                    tx.execute(
+                        "INSERT INTO loan_limits (id, record_type, name, max_open) VALUES (NULL, ?, ?, ?)",
+                        (record_type.map(record_type_text), name, max_open)
+                    )?;
+
+                    Ok(tx.last_insert_rowid())
+                })?
+            },
+        };
+
+        self.refresh()?;
+
+        Ok(())
+    }
+}
+
+impl Storage<LoanLimit, i64> for LoanLimitStorage {
+    fn refresh(&mut self) -> Result<(), StorageError> {
+        self.limits = self.worker.call(|connection| {
+            // broadest limits first
+            let mut stmt = connection.prepare("SELECT * FROM loan_limits ORDER BY record_type IS NOT NULL, record_type, name IS NOT NULL, name")?;
+
+            let limits = stmt
+                .query_map((), Self::parse_row)?
+                .collect::<Result<_, _>>()?;
+
+            Ok(limits)
+        })?;
+
+        log::debug!("refreshed loan limits");
+
+        Ok(())
+    }
+
+    fn get_all(&self) -> &[LoanLimit] {
+        self.limits.as_slice()
+    }
+
+    fn get(&self, id: i64) -> Option<&LoanLimit> {
+        self.limits.iter().find(|limit| limit.id == id)
+    }
+
+    fn parse_row(row: &rusqlite::Row) -> Result<LoanLimit, rusqlite::Error> {
+        let record_type: Option<String> = row.get("record_type")?;
+
+        Ok(LoanLimit {
+            id: row.get("id")?,
+            record_type: record_type.as_deref().and_then(parse_record_type),
+            name: row.get("name")?,
+            max_open: row.get("max_open")?,
+        })
+    }
+}
+
+impl DeletableStorage<LoanLimit, i64> for LoanLimitStorage {
+    fn delete(&mut self, id: i64) -> Result<(), StorageError> {
+        audit::audited(&self.worker, "delete", "loan_limits", "id", Some(id), move |tx| {
+            tx.execute(
+                "DELETE FROM loan_limits WHERE id = ?",
+                (id,)
+            )?;
+
+            Ok(id)
+        })?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+}
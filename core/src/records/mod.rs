@@ -0,0 +1,628 @@
+use std::{fmt::Display, path::PathBuf, sync::mpsc::{Receiver, TryRecvError}};
+
+use strum::EnumIter;
+
+pub mod models;
+pub mod key_storage;
+pub mod parcel_storage;
+pub mod game_storage;
+pub mod item_storage;
+pub mod key_type_storage;
+pub mod game_type_storage;
+pub mod item_type_storage;
+pub mod student_storage;
+pub mod receptionist_storage;
+pub mod restriction_storage;
+pub mod loan_limit_storage;
+pub mod category_storage;
+pub mod category_record_storage;
+pub mod filter;
+pub mod amendment;
+pub mod audit;
+pub mod roster;
+pub mod restore;
+pub mod integrity;
+pub mod period;
+
+pub use models::*;
+pub use key_storage::*;
+pub use parcel_storage::*;
+pub use game_storage::*;
+pub use item_storage::*;
+pub use key_type_storage::*;
+pub use game_type_storage::*;
+pub use item_type_storage::*;
+pub use student_storage::*;
+pub use receptionist_storage::*;
+pub use restriction_storage::*;
+pub use loan_limit_storage::*;
+pub use category_storage::*;
+pub use category_record_storage::*;
+pub use filter::*;
+pub use audit::{AuditStorage, AuditFilter};
+pub use roster::*;
+pub use restore::*;
+pub use integrity::{Finding, Problem, Fix, check_database, repair};
+pub use period::*;
+use rusqlite::types::Value;
+use thiserror::Error;
+
+use crate::{PAGE_SIZE, worker::WorkerHandle};
+
+fn set_export_path_extention(mut path: PathBuf) -> PathBuf {
+    if let Some(extension) = path.extension() {
+        if extension.to_string_lossy().parse::<u64>().is_ok() {
+            // If the extention is a number, then the user has not 
+            // specified an extension and it's detecting the 
+            // fractional part of the seconds as the extension. 
+            // In which case, we cannot use the regular set extension 
+            // function.
+            let mut p = path.into_os_string();
+            p.push(".csv");
+            path = p.into();
+        }
+    }
+    
+    path.set_extension("csv");
+
+    path
+}
+
+/// Read a time column, failing on an unparsable time instead of panicking so that the row can be skipped.
+fn parse_time(row: &rusqlite::Row, column: &str) -> Result<chrono::DateTime<chrono::Utc>, rusqlite::Error> {
+    parse_optional_time(row, column)?
+        .ok_or_else(|| rusqlite::Error::InvalidColumnType(row.as_ref().column_index(column).unwrap_or_default(), column.into(), rusqlite::types::Type::Null))
+}
+
+fn parse_optional_time(row: &rusqlite::Row, column: &str) -> Result<Option<chrono::DateTime<chrono::Utc>>, rusqlite::Error> {
+    // times are stored as seconds since the unix epoch, text left over from older versions fails here
+    let time: Option<i64> = row.get(column)?;
+
+    time.map(|time| {
+        chrono::DateTime::from_timestamp(time, 0)
+            .ok_or_else(|| rusqlite::Error::IntegralValueOutOfRange(row.as_ref().column_index(column).unwrap_or_default(), time))
+    }).transpose()
+}
+
+/// A row which could not be read, and was left out of the records shown.
+#[derive(Debug, Clone)]
+pub struct CorruptRow {
+    pub table: &'static str,
+    pub id: Option<i64>,
+    pub error: String,
+}
+
+impl Display for CorruptRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.id {
+            Some(id) => write!(f, "{} {id}: {}", self.table, self.error),
+            None => write!(f, "{}: {}", self.table, self.error),
+        }
+    }
+}
+
+/// Parse each row, skipping rows whose values cannot be read so that one corrupt row does not
+/// hide the rest. Skipped rows are logged and returned alongside the records.
+fn parse_rows<T>(table: &'static str, mut rows: rusqlite::Rows, parse: impl Fn(&rusqlite::Row) -> Result<T, rusqlite::Error>) -> Result<(Vec<T>, Vec<CorruptRow>), rusqlite::Error> {
+    let mut records = vec![];
+    let mut corrupt = vec![];
+
+    while let Some(row) = rows.next()? {
+        match parse(row) {
+            Ok(record) => records.push(record),
+            Err(err @ (rusqlite::Error::FromSqlConversionFailure(..) | rusqlite::Error::InvalidColumnType(..) | rusqlite::Error::IntegralValueOutOfRange(..))) => {
+                let row = CorruptRow {
+                    table,
+                    id: row.get("id").ok(),
+                    error: err.to_string(),
+                };
+
+                log::error!("skipped corrupt row {row}");
+
+                corrupt.push(row);
+            },
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok((records, corrupt))
+}
+
+fn format_optional_time(time: Option<chrono::DateTime<chrono::Utc>>) -> String {
+    match time {
+        Some(time) => time.to_rfc3339(),
+        None => "".into(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, EnumIter)]
+pub enum RecordType {
+    #[default]
+    Key,
+    Parcel,
+    Game,
+    Item,
+    /// Records of a runtime-defined category, which have their own panels, exports, and summaries,
+    /// so it is left out when iterating over the built in types.
+    #[strum(disabled)]
+    Category,
+}
+
+impl RecordType {
+    /// Name of several records of the type, e.g. "Games".
+    pub fn plural(&self) -> &'static str {
+        match self {
+            RecordType::Key => "Keys",
+            RecordType::Parcel => "Parcels",
+            RecordType::Game => "Games",
+            RecordType::Item => "Items",
+            RecordType::Category => "Categories",
+        }
+    }
+}
+
+impl Display for RecordType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordType::Key => f.write_str("Key"),
+            RecordType::Parcel => f.write_str("Parcel"),
+            RecordType::Game => f.write_str("Game"),
+            RecordType::Item => f.write_str("Item"),
+            RecordType::Category => f.write_str("Category"),
+        }
+    }
+}
+
+/// Messages raised by the database triggers which enforce sign out rules.
+const KEY_ALREADY_OUT_MESSAGE: &str = "key already signed out";
+const INSUFFICIENT_STOCK_MESSAGE: &str = "insufficient stock";
+
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("Database operation failed. {0}")]
+    DbError(rusqlite::Error),
+    #[error("Key is already signed out.")]
+    KeyAlreadyOut,
+    #[error("Not enough in stock.")]
+    InsufficientStock,
+    #[error("Failed to export database. {0}")]
+    ExportCsvError(#[from] csv::Error),
+    #[error("Failed to export database. {0}")]
+    ExportIoError(#[from] std::io::Error),
+    #[error("Failed to export report. {0}")]
+    ExportPdfError(#[from] printpdf::Error),
+    #[error("Failed to import file. {0}")]
+    ImportCsvError(csv::Error),
+    #[error("Failed to import spreadsheet. {0}")]
+    ImportSpreadsheetError(#[from] calamine::Error),
+    #[error("Failed to access database file. {0}")]
+    FileError(std::io::Error),
+    #[error("Failed to update database to the current version. {0}")]
+    /// Boxed, since it is many times the size of the other errors.
+    MigrationError(Box<refinery::Error>),
+    #[error("Database worker stopped.")]
+    WorkerStopped,
+}
+
+impl StorageError {
+    /// Whether a change was refused for breaking a sign out rule, rather than the database failing.
+    pub fn is_conflict(&self) -> bool {
+        matches!(self, StorageError::KeyAlreadyOut | StorageError::InsufficientStock)
+    }
+}
+
+impl From<refinery::Error> for StorageError {
+    fn from(err: refinery::Error) -> Self {
+        StorageError::MigrationError(Box::new(err))
+    }
+}
+
+impl From<rusqlite::Error> for StorageError {
+    fn from(err: rusqlite::Error) -> Self {
+        if let rusqlite::Error::SqliteFailure(_, Some(message)) = &err {
+            match message.as_str() {
+                KEY_ALREADY_OUT_MESSAGE => return StorageError::KeyAlreadyOut,
+                INSUFFICIENT_STOCK_MESSAGE => return StorageError::InsufficientStock,
+                _ => {},
+            }
+        }
+
+        StorageError::DbError(err)
+    }
+}
+
+/// Pages are found by record id rather than by offset, so that later pages are as quick to fetch as
+/// the first and records inserted while paging do not shift the page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Page {
+    FirstPage,
+    /// The page of records following the record with this id.
+    After(i64),
+    /// The page of records preceding the record with this id.
+    Before(i64),
+    #[default]
+    LastPage,
+}
+
+/// Where the current page lies among the records matching the filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PagePosition {
+    /// Number of records before the page.
+    pub before: i64,
+    /// Number of records after the page.
+    pub after: i64,
+    first_id: Option<i64>,
+    last_id: Option<i64>,
+}
+
+impl PagePosition {
+    /// Zero based page number, pages are counted from the first record so the last page may overlap the one before it.
+    pub fn number(&self) -> i64 {
+        (self.before + PAGE_SIZE - 1) / PAGE_SIZE
+    }
+
+    pub fn previous(&self) -> Page {
+        match self.first_id {
+            Some(id) => Page::Before(id),
+            None => Page::FirstPage,
+        }
+    }
+
+    pub fn next(&self) -> Page {
+        match self.last_id {
+            Some(id) => Page::After(id),
+            None => Page::LastPage,
+        }
+    }
+}
+
+/// Fetch a page of `source` matching the filter clause, `count` being the number of matching records.
+/// A page which runs past the first or last record is filled from that end instead. The page next to
+/// the `shown` position, if unchanged since, is placed from it rather than by counting the records before it.
+fn fetch_page<T>(connection: &rusqlite::Connection, source: &'static str, (clause, params): (&str, &[Value]), (count, shown): (i64, Option<PagePosition>), page: Page, parse: impl Fn(&rusqlite::Row) -> Result<T, rusqlite::Error>, id: impl Fn(&T) -> i64) -> Result<(Vec<T>, Vec<CorruptRow>, PagePosition), rusqlite::Error> {
+    // corrupt rows are reported against the table rather than its amended view
+    let table = source.strip_suffix("_amended").unwrap_or(source);
+
+    let query = |page: Page| -> Result<(Vec<T>, Vec<CorruptRow>), rusqlite::Error> {
+        let (seek, order, from) = match page {
+            Page::FirstPage => ("", "ASC", None),
+            Page::After(id) => ("AND id > ?", "ASC", Some(id)),
+            Page::Before(id) => ("AND id < ?", "DESC", Some(id)),
+            Page::LastPage => ("", "DESC", None),
+        };
+
+        let mut stmt = connection.prepare(&format!("SELECT * FROM {source} WHERE ({clause}) {seek} ORDER BY id {order} LIMIT ?"))?;
+
+        let params = params.iter().cloned().chain(from.map(Value::Integer)).chain([Value::Integer(PAGE_SIZE)]);
+
+        let (mut records, mut corrupt) = parse_rows(table, stmt.query(rusqlite::params_from_iter(params))?, &parse)?;
+
+        if order == "DESC" {
+            records.reverse();
+            corrupt.reverse();
+        }
+
+        Ok((records, corrupt))
+    };
+
+    let (mut records, mut corrupt) = query(page)?;
+
+    let mut page = page;
+
+    if ((records.len() + corrupt.len()) as i64) < PAGE_SIZE {
+        let end = match page {
+            Page::After(_) => Some(Page::LastPage),
+            Page::Before(_) => Some(Page::FirstPage),
+            _ => None,
+        };
+
+        if let Some(end) = end {
+            (records, corrupt) = query(end)?;
+            page = end;
+        }
+    }
+
+    let ids = records.iter().map(&id).chain(corrupt.iter().filter_map(|row| row.id));
+    let first_id = ids.clone().min();
+    let last_id = ids.max();
+
+    let rows = (records.len() + corrupt.len()) as i64;
+
+    let before = match (page, first_id, shown) {
+        (Page::FirstPage, _, _) | (_, None, _) => 0,
+        (Page::LastPage, _, _) => count - rows,
+        (Page::After(id), _, Some(shown)) if shown.last_id == Some(id) => count - shown.after,
+        (Page::Before(id), _, Some(shown)) if shown.first_id == Some(id) => shown.before - rows,
+        (_, Some(first_id), _) => {
+            let params = params.iter().cloned().chain([Value::Integer(first_id)]);
+
+            connection.prepare(&format!("SELECT COUNT(*) AS c FROM {source} WHERE ({clause}) AND id < ?"))?
+                .query_row(rusqlite::params_from_iter(params), |row| row.get("c"))?
+        },
+    };
+
+    let position = PagePosition {
+        before,
+        after: (count - before - rows).max(0),
+        first_id,
+        last_id,
+    };
+
+    Ok((records, corrupt, position))
+}
+
+/// A page of records as fetched by a refresh, with the number of records matching the filter.
+struct FetchedPage<T> {
+    count: i64,
+    records: Vec<T>,
+    corrupt: Vec<CorruptRow>,
+    position: PagePosition,
+}
+
+/// A refresh still running on the database worker.
+type PendingPage<T> = Receiver<Result<FetchedPage<T>, rusqlite::Error>>;
+
+/// `shown` is the count and position of the page shown, if no records have changed since it was fetched,
+/// in which case the records are not counted again.
+fn fetch_counted_page<T>(connection: &rusqlite::Connection, source: &'static str, (clause, params): (&str, &[Value]), page: Page, shown: Option<(i64, PagePosition)>, parse: impl Fn(&rusqlite::Row) -> Result<T, rusqlite::Error>, id: impl Fn(&T) -> i64) -> Result<FetchedPage<T>, rusqlite::Error> {
+    let count = match shown {
+        Some((count, _)) => count,
+        None => connection.prepare(&format!("SELECT COUNT(*) AS c FROM {source} WHERE {clause}"))?
+            .query_row(rusqlite::params_from_iter(params), |row| row.get("c"))?,
+    };
+
+    let (records, corrupt, position) = fetch_page(connection, source, (clause, params), (count, shown.map(|(_, position)| position)), page, parse, id)?;
+
+    Ok(FetchedPage { count, records, corrupt, position })
+}
+
+/// Run `fetch` on the worker. In the `background` the page is left pending to be collected by `poll_page`,
+/// otherwise it is waited for.
+fn request_page<T: Send + 'static>(worker: &WorkerHandle, background: bool, pending: &mut Option<PendingPage<T>>, fetch: impl FnOnce(&rusqlite::Connection) -> Result<FetchedPage<T>, rusqlite::Error> + Send + 'static) -> Result<Option<FetchedPage<T>>, StorageError> {
+    // replacing an older request drops its result when it arrives
+    let receiver = worker.query(move |connection| fetch(connection));
+
+    if background {
+        *pending = Some(receiver);
+        Ok(None)
+    } else {
+        *pending = None;
+        Ok(Some(receiver.recv().map_err(|_| StorageError::WorkerStopped)??))
+    }
+}
+
+/// The page requested by `request_page`, once it has arrived.
+fn poll_page<T>(pending: &mut Option<PendingPage<T>>) -> Result<Option<FetchedPage<T>>, StorageError> {
+    let Some(receiver) = pending else {
+        return Ok(None);
+    };
+
+    match receiver.try_recv() {
+        Ok(fetched) => {
+            *pending = None;
+            Ok(Some(fetched?))
+        },
+        Err(TryRecvError::Empty) => Ok(None),
+        Err(TryRecvError::Disconnected) => {
+            *pending = None;
+            Err(StorageError::WorkerStopped)
+        },
+    }
+}
+
+pub trait Storage<T, I: Copy> {
+    fn refresh(&mut self) -> Result<(), StorageError>;
+    fn get_all(&self) -> &[T];
+    fn get(&self, id: I) -> Option<&T>;
+    fn parse_row(row: &rusqlite::Row) -> Result<T, rusqlite::Error>;
+}
+
+pub trait PaginatedStorage<T, I: Copy> {
+    fn page(&self) -> Page;
+    fn set_page(&mut self, page: Page) -> Result<(), StorageError>;
+    fn count(&self) -> i64;
+    fn position(&self) -> PagePosition;
+    fn filter(&self) -> &RecordFilter;
+    fn set_filter(&mut self, filter: &RecordFilter) -> Result<(), StorageError>;
+    /// Fetch the page again, in the background if the storage has a worker.
+    fn refresh(&mut self) -> Result<(), StorageError>;
+    /// Show the page fetched in the background, if it has arrived. Returns whether it had.
+    fn poll(&mut self) -> Result<bool, StorageError>;
+    fn get_all(&self) -> &[T];
+    /// Rows skipped by the last refresh because they could not be read.
+    fn corrupt(&self) -> &[CorruptRow];
+    fn parse_row(row: &rusqlite::Row) -> Result<T, rusqlite::Error>;
+}
+
+pub trait InsertableStorage<T, I: Copy> {
+    fn insert(&mut self, record_info: T) -> Result<(), StorageError>;
+}
+
+pub trait DeletableStorage<T, I: Copy> {
+    fn delete(&mut self, id: I) -> Result<(), StorageError>;
+}
+
+pub trait SignableStorage<T, I: Copy> {
+    fn signin(&mut self, id: I) -> Result<(), StorageError>;
+}
+
+pub trait ReceptionistSignableStorage<T, I: Copy> {
+    fn signin(&mut self, id: I, receptionist: &str) -> Result<(), StorageError>;
+}
+
+pub trait NotedStorage<T, I: Copy> {
+    fn update_notes(&mut self, id: I, note: &str) -> Result<(), StorageError>;
+}
+
+/// Records which can be corrected or voided, without changing the original record.
+pub trait AmendableStorage<T, I: Copy> {
+    fn amend(&mut self, id: I, field: &str, new_value: &str, reason: &str, receptionist: &str) -> Result<(), StorageError>;
+    fn void(&mut self, id: I, reason: &str, receptionist: &str) -> Result<(), StorageError>;
+    fn amendments(&self, id: I) -> Result<Vec<AmendmentRecord>, StorageError>;
+}
+
+/// Exports are read on the connection given, i.e. the worker's task connection, so that they hold up no other reads.
+pub trait ExportableStorage<T> {
+    fn fetch_all(connection: &rusqlite::Connection) -> Result<Vec<T>, StorageError>;
+    fn csv_headers() -> &'static [&'static str];
+    fn write_record<W: std::io::Write>(writer: &mut csv::Writer<W>, record: &T) -> Result<(), csv::Error>;
+    /// Write every record to a CSV file, calling `progress` with the fraction written so far.
+    fn export_csv(connection: &rusqlite::Connection, path: PathBuf, progress: impl FnMut(f32)) -> Result<(), StorageError>;
+}
+
+pub trait Summary {
+    fn summary(&self, start: chrono::DateTime<chrono::Utc>, end: chrono::DateTime<chrono::Utc>) -> Result<Vec<(String, i64)>, StorageError>;
+}
+
+/// Number of records each receptionist signed out and signed in.
+pub trait ReceptionistSummary {
+    fn receptionist_summary(&self, start: chrono::DateTime<chrono::Utc>, end: chrono::DateTime<chrono::Utc>) -> Result<Vec<(String, i64, i64)>, StorageError>;
+}
+
+pub trait CountWithin {
+    fn count_within(&self, start: chrono::DateTime<chrono::Utc>, end: chrono::DateTime<chrono::Utc>) -> Result<i64, StorageError>;
+}
+
+/// Times records were signed out, each with the number of records it counts for, e.g. the quantity of items.
+pub trait SignOutTimes {
+    fn sign_out_times(&self, start: chrono::DateTime<chrono::Utc>, end: chrono::DateTime<chrono::Utc>) -> Result<Vec<(chrono::DateTime<chrono::Utc>, i64)>, StorageError>;
+}
+
+/// Find the sign out times of records in `source` with time_out in [start, end), counting each as `count`.
+fn sign_out_times(worker: &WorkerHandle, source: &'static str, count: &'static str, start: chrono::DateTime<chrono::Utc>, end: chrono::DateTime<chrono::Utc>) -> Result<Vec<(chrono::DateTime<chrono::Utc>, i64)>, StorageError> {
+    worker.call(move |connection| {
+        let mut stmt = connection.prepare(&format!("SELECT time_out, {count} AS c FROM {source} WHERE ? <= time_out AND time_out < ? AND NOT voided ORDER BY time_out"))?;
+
+        let times = stmt.query_map((start.timestamp(), end.timestamp()), |row| Ok((parse_time(row, "time_out")?, row.get("c")?)))?;
+        let times = times.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(times)
+    })
+}
+
+fn export_csv<T, S: ExportableStorage<T>>(connection: &rusqlite::Connection, mut path: PathBuf, mut progress: impl FnMut(f32)) -> Result<(), StorageError> {
+    path = set_export_path_extention(path);
+
+    let mut writer = csv::Writer::from_path(path)?;
+
+    writer.write_record(S::csv_headers())?;
+    
+    let records = S::fetch_all(connection)?;
+
+    for (i, record) in records.iter().enumerate() {
+        S::write_record(&mut writer, record)?;
+
+        if i % PAGE_SIZE as usize == 0 {
+            progress(i as f32 / records.len() as f32);
+        }
+    }
+
+    progress(1.0);
+    
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::worker::DbWorker;
+
+    /// Key records signed out and back in, with ids from 1 to `count`.
+    fn key_records(count: i64) -> WorkerHandle {
+        let worker = DbWorker::spawn(Path::new(":memory:"), || {}).unwrap().handle();
+
+        worker.call(move |connection| {
+            connection.execute("INSERT INTO students (student_number, student_name) VALUES ('ABCDEF001', 'Alex')", ())?;
+            connection.execute(
+                "WITH RECURSIVE n(i) AS (SELECT 1 WHERE ?1 > 0 UNION ALL SELECT i + 1 FROM n WHERE i < ?1)
+                INSERT INTO key_records (`key`, student_name, student_number, time_out, time_in, notes)
+                SELECT 'Room ' || i, 'Alex', 'ABCDEF001', i, i + 1, '' FROM n",
+                (count,),
+            )?;
+
+            Ok(())
+        }).unwrap();
+
+        worker
+    }
+
+    /// Ids of the first and last records on the page, and the number of records before and after it.
+    fn shown(storage: &KeyStorage) -> (i64, i64, i64, i64) {
+        let records = PaginatedStorage::get_all(storage);
+        let position = storage.position();
+
+        (records[0].id, records[records.len() - 1].id, position.before, position.after)
+    }
+
+    #[test]
+    fn keys_are_found_as_amended() {
+        let worker = key_records(0);
+        let mut storage = KeyStorage::new(worker.clone(), false).unwrap();
+
+        worker.call(|connection| Ok(connection.execute_batch("
+            INSERT INTO key_records (`key`, student_name, student_number, time_out, notes) VALUES ('Room 1', 'Alex', 'ABCDEF001', 0, '');
+            INSERT INTO amendments (record_table, record_id, field, old_value, new_value, reason, receptionist, time)
+                VALUES ('key_records', 1, 'key', 'Room 1', 'Room 2', 'Wrong key', 'Sam', 0);
+        ")?)).unwrap();
+
+        assert!(storage.get_signed_out("Room 1").unwrap().is_none());
+        assert_eq!(storage.get_signed_out("Room 2").unwrap().map(|record| record.id), Some(1));
+
+        let sign_out = |key: &'static str| worker.call(move |connection| Ok(connection.execute(
+            "INSERT INTO key_records (`key`, student_name, student_number, time_out, notes) VALUES (?, 'Alex', 'ABCDEF001', 0, '')",
+            (key,),
+        )?));
+
+        assert!(sign_out("Room 1").is_ok());
+        assert!(matches!(sign_out("Room 2"), Err(StorageError::KeyAlreadyOut)));
+    }
+
+    #[test]
+    fn pages_are_placed_among_the_records() {
+        let mut storage = KeyStorage::new(key_records(250), false).unwrap();
+
+        assert_eq!(storage.count(), 250);
+        assert_eq!(shown(&storage), (151, 250, 150, 0));
+
+        storage.set_page(storage.position().previous()).unwrap();
+        assert_eq!(shown(&storage), (51, 150, 50, 100));
+
+        // running past the first record fills the page from it
+        storage.set_page(storage.position().previous()).unwrap();
+        assert_eq!(shown(&storage), (1, 100, 0, 150));
+        assert_eq!(storage.page(), Page::Before(51));
+
+        storage.set_page(storage.position().next()).unwrap();
+        assert_eq!(shown(&storage), (101, 200, 100, 50));
+        assert_eq!(storage.position().number(), 1);
+
+        storage.set_page(storage.position().next()).unwrap();
+        assert_eq!(shown(&storage), (151, 250, 150, 0));
+    }
+
+    #[test]
+    fn pages_are_placed_by_counting_after_a_refresh() {
+        let worker = key_records(250);
+        let mut storage = KeyStorage::new(worker.clone(), false).unwrap();
+
+        storage.set_page(Page::After(120)).unwrap();
+        assert_eq!(shown(&storage), (121, 220, 120, 30));
+
+        worker.call(|connection| Ok(connection.execute("DELETE FROM key_records WHERE id <= 20", ())?)).unwrap();
+
+        // paging keeps the count until told the records have changed
+        storage.set_page(storage.position().previous()).unwrap();
+        assert_eq!(storage.count(), 250);
+
+        storage.refresh().unwrap();
+        assert_eq!(storage.count(), 230);
+        assert_eq!(shown(&storage), (21, 120, 0, 130));
+
+        storage.set_filter(&RecordFilter { item: "Room 2".to_owned(), ..Default::default() }).unwrap();
+        assert_eq!(storage.count(), 60);
+    }
+}
@@ -0,0 +1,520 @@
+#[derive(Debug, Clone)]
+pub struct KeyRecord {
+    pub id: i64,
+    pub key: String,
+    pub student_name: String,
+    pub student_number: String,
+    pub receptionist_out: Option<String>,
+    pub receptionist_in: Option<String>,
+    pub time_out: chrono::DateTime<chrono::Utc>,
+    pub time_due: Option<chrono::DateTime<chrono::Utc>>,
+    pub time_in: Option<chrono::DateTime<chrono::Utc>>,
+    pub notes: String,
+    pub voided: bool,
+    pub amended: bool,
+}
+
+impl KeyRecord {
+    pub fn is_overdue(&self) -> bool {
+        !self.voided && is_overdue(self.time_due, self.time_in)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewKeyRecord<'a> {
+    pub key: &'a str,
+    pub student_name: &'a str,
+    pub student_number: &'a str,
+    pub receptionist: &'a str,
+    pub notes: &'a str,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParcelRecord {
+    pub id: i64,
+    pub parcel_desc: String,
+    pub student_name: String,
+    pub receptionist: String,
+    pub time_in: chrono::DateTime<chrono::Utc>,
+    pub time_out: Option<chrono::DateTime<chrono::Utc>>,
+    pub notes: String,
+    pub voided: bool,
+    pub amended: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewParcelRecord<'a> {
+    pub parcel_desc: &'a str,
+    pub student_name: &'a str,
+    pub receptionist: &'a str,
+    pub notes: &'a str,
+}
+
+#[derive(Debug, Clone)]
+pub struct GameRecord {
+    pub id: i64,
+    pub game: String,
+    pub quantity: i64,
+    pub student_name: String,
+    pub student_number: String,
+    pub receptionist_out: Option<String>,
+    pub receptionist_in: Option<String>,
+    pub time_out: chrono::DateTime<chrono::Utc>,
+    pub time_due: Option<chrono::DateTime<chrono::Utc>>,
+    pub time_in: Option<chrono::DateTime<chrono::Utc>>,
+    pub notes: String,
+    pub voided: bool,
+    pub amended: bool,
+}
+
+impl GameRecord {
+    pub fn is_overdue(&self) -> bool {
+        !self.voided && is_overdue(self.time_due, self.time_in)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewGameRecord<'a> {
+    pub game: &'a str,
+    pub quantity: i64,
+    pub student_name: &'a str,
+    pub student_number: &'a str,
+    pub receptionist: &'a str,
+    pub notes: &'a str,
+}
+
+#[derive(Debug, Clone)]
+pub struct ItemRecord {
+    pub id: i64,
+    pub item: String,
+    pub quantity: i64,
+    pub student_name: String,
+    pub student_number: String,
+    /// Whether the item was returnable when signed out, otherwise the record is never signed in.
+    pub returnable: bool,
+    pub receptionist: String,
+    pub receptionist_in: Option<String>,
+    pub time_out: chrono::DateTime<chrono::Utc>,
+    pub time_in: Option<chrono::DateTime<chrono::Utc>>,
+    pub notes: String,
+    pub voided: bool,
+    pub amended: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewItemRecord<'a> {
+    pub item: &'a str,
+    pub quantity: i64,
+    pub student_name: &'a str,
+    pub student_number: &'a str,
+    pub receptionist: &'a str,
+    pub notes: &'a str,
+}
+
+#[derive(Debug, Clone)]
+pub struct ItemTypeRecord {
+    pub item: String,
+    pub returnable: bool,
+    /// Number on hand, or `None` if the stock is not counted.
+    pub quantity: Option<i64>,
+    /// The stock is low at or below this.
+    pub low_stock: i64,
+}
+
+impl ItemTypeRecord {
+    pub fn is_low_stock(&self) -> bool {
+        self.quantity.is_some_and(|quantity| quantity <= self.low_stock)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewItemTypeRecord<'a> {
+    pub item: &'a str,
+    pub returnable: bool,
+    pub quantity: Option<i64>,
+    pub low_stock: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ItemRestockRecord {
+    pub id: i64,
+    pub item: String,
+    pub quantity: i64,
+    pub receptionist: Option<String>,
+    pub time: chrono::DateTime<chrono::Utc>,
+    pub notes: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyTypeRecord {
+    pub key: String,
+    pub loan_minutes: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewKeyTypeRecord<'a> {
+    pub key: &'a str,
+    pub loan_minutes: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GameTypeRecord {
+    pub game: String,
+    pub quantity: i64,
+    pub loan_minutes: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewGameTypeRecord<'a> {
+    pub game: &'a str,
+    pub quantity: i64,
+    pub loan_minutes: Option<i64>,
+}
+
+/// A record is overdue if it has not been signed in by its due time.
+fn is_overdue(time_due: Option<chrono::DateTime<chrono::Utc>>, time_in: Option<chrono::DateTime<chrono::Utc>>) -> bool {
+    match (time_due, time_in) {
+        (Some(time_due), None) => time_due < chrono::Utc::now(),
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StudentRecord {
+    pub student_number: String,
+    pub student_name: String,
+    pub room: Option<String>,
+    pub block: Option<String>,
+}
+
+/// A record of any type involving a student, for their history.
+#[derive(Debug, Clone)]
+pub struct HistoryRecord {
+    pub record_type: super::RecordType,
+    pub id: i64,
+    /// The key, game, item, or parcel description, or the category of a category record.
+    pub name: String,
+    pub quantity: i64,
+    /// When it was signed out, or when a parcel arrived.
+    pub time_out: chrono::DateTime<chrono::Utc>,
+    pub time_due: Option<chrono::DateTime<chrono::Utc>>,
+    /// When it was signed back in, or when a parcel was collected.
+    pub time_in: Option<chrono::DateTime<chrono::Utc>>,
+    /// Whether it is signed back in, only items which are given away are not.
+    pub returnable: bool,
+    pub voided: bool,
+}
+
+impl HistoryRecord {
+    /// Whether the student still has it, or a parcel is waiting for them.
+    pub fn is_outstanding(&self) -> bool {
+        !self.voided && self.returnable && self.time_in.is_none()
+    }
+
+    pub fn is_overdue(&self) -> bool {
+        !self.voided && is_overdue(self.time_due, self.time_in)
+    }
+}
+
+/// Everything a student has signed out or received, newest first.
+#[derive(Debug, Clone)]
+pub struct StudentHistory {
+    pub student_number: String,
+    pub student_name: String,
+    pub records: Vec<HistoryRecord>,
+    /// Records skipped because they could not be read.
+    pub corrupt: Vec<super::CorruptRow>,
+}
+
+impl StudentHistory {
+    pub fn outstanding(&self) -> impl Iterator<Item = &HistoryRecord> {
+        self.records.iter().filter(|record| record.is_outstanding())
+    }
+
+    /// Number of records of each type, voided records aside.
+    pub fn count(&self, record_type: super::RecordType) -> i64 {
+        self.records.iter()
+            .filter(|record| record.record_type == record_type && !record.voided)
+            .map(|record| record.quantity)
+            .sum()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewStudentRecord<'a> {
+    pub student_number: &'a str,
+    pub student_name: &'a str,
+    pub room: Option<&'a str>,
+    pub block: Option<&'a str>,
+}
+
+/// A student barred from signing out some or all records.
+#[derive(Debug, Clone)]
+pub struct RestrictionRecord {
+    pub id: i64,
+    pub student_number: String,
+    /// Every type of record if `None`.
+    pub record_type: Option<super::RecordType>,
+    /// The key, game, or item restricted, or every one of the type if `None`.
+    pub name: Option<String>,
+    pub reason: String,
+    /// Blocking restrictions refuse sign outs unless overridden, others only warn.
+    pub blocking: bool,
+    pub time_start: chrono::DateTime<chrono::Utc>,
+    /// In force indefinitely if `None`.
+    pub time_end: Option<chrono::DateTime<chrono::Utc>>,
+    pub imposed_by: String,
+    pub time_imposed: chrono::DateTime<chrono::Utc>,
+}
+
+impl RestrictionRecord {
+    pub fn is_active(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.time_start <= now && self.time_end.is_none_or(|time_end| now < time_end)
+    }
+
+    /// Whether the restriction covers signing out `name` of `record_type` to the student.
+    pub fn applies_to(&self, student_number: &str, record_type: super::RecordType, name: &str) -> bool {
+        self.student_number.eq_ignore_ascii_case(student_number.trim())
+            && self.record_type.is_none_or(|t| t == record_type)
+            && self.name.as_ref().is_none_or(|n| n.eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// What the restriction covers, e.g. "All games".
+    pub fn scope(&self) -> String {
+        describe_scope(self.record_type, self.name.as_deref())
+    }
+}
+
+/// Records covered by a restriction or limit, e.g. "All games", or "Game Chess".
+pub fn describe_scope(record_type: Option<super::RecordType>, name: Option<&str>) -> String {
+    match (record_type, name) {
+        (None, _) => "Everything".into(),
+        (Some(record_type), None) => format!("All {}", record_type.plural().to_lowercase()),
+        (Some(record_type), Some(name)) => format!("{record_type} {name}"),
+    }
+}
+
+impl std::fmt::Display for RestrictionRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.scope(), self.reason)?;
+
+        if let Some(time_end) = self.time_end {
+            write!(f, " (until {})", time_end.with_timezone(&chrono::Local).format(crate::DATE_TIME_FORMAT))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewRestrictionRecord<'a> {
+    pub student_number: &'a str,
+    pub record_type: Option<super::RecordType>,
+    pub name: Option<&'a str>,
+    pub reason: &'a str,
+    pub blocking: bool,
+    pub time_start: chrono::DateTime<chrono::Utc>,
+    pub time_end: Option<chrono::DateTime<chrono::Utc>>,
+    pub imposed_by: &'a str,
+}
+
+/// Most records a student may have out at once, of every type, a type, or a single key, game, item, or category.
+#[derive(Debug, Clone)]
+pub struct LoanLimit {
+    pub id: i64,
+    /// Every type of record if `None`.
+    pub record_type: Option<super::RecordType>,
+    /// The key, game, item, or category limited, or every one of the type if `None`.
+    pub name: Option<String>,
+    pub max_open: i64,
+}
+
+impl LoanLimit {
+    /// Whether a record of `record_type` named `name` counts towards the limit.
+    pub fn applies_to(&self, record_type: super::RecordType, name: &str) -> bool {
+        self.record_type.is_none_or(|t| t == record_type)
+            && self.name.as_ref().is_none_or(|n| n.eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn scope(&self) -> String {
+        describe_scope(self.record_type, self.name.as_deref())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReceptionistRecord {
+    pub id: i64,
+    pub name: String,
+    pub active: bool,
+    /// May override blocking restrictions, and make other receptionists admins.
+    pub admin: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewReceptionistRecord<'a> {
+    pub name: &'a str,
+}
+
+/// Blocking restrictions a record was signed out despite, logged along with the record.
+#[derive(Debug, Clone)]
+pub struct NewOverrideRecord<'a> {
+    pub restrictions: &'a [i64],
+    pub justification: &'a str,
+    pub receptionist: &'a str,
+}
+
+/// The receptionist currently on duty, every record created or signed in is attributed to them.
+#[derive(Debug, Clone)]
+pub struct Shift {
+    pub id: i64,
+    pub receptionist: ReceptionistRecord,
+    pub time_start: chrono::DateTime<chrono::Utc>,
+}
+
+/// A correction to a single field of a record, or the voiding of the whole record if `field` is `None`.
+#[derive(Debug, Clone)]
+pub struct AmendmentRecord {
+    pub field: Option<String>,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub reason: String,
+    pub receptionist: String,
+    pub time: chrono::DateTime<chrono::Utc>,
+}
+
+/// An entry in the append-only log of changes to the database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditRecord {
+    pub id: i64,
+    pub time: chrono::DateTime<chrono::Utc>,
+    pub action: String,
+    pub record_table: String,
+    pub record_id: String,
+    /// JSON object of the row before the change, if it existed.
+    pub before: Option<String>,
+    /// JSON object of the row after the change, if it still exists.
+    pub after: Option<String>,
+    /// Receptionist on duty at the time, if any.
+    pub receptionist: Option<String>,
+}
+
+/// Kind of value a field of a custom category holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, strum::EnumIter)]
+pub enum FieldKind {
+    #[default]
+    Text,
+    Number,
+    YesNo,
+}
+
+impl FieldKind {
+    /// How the kind is stored in the category_fields table.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FieldKind::Text => "text",
+            FieldKind::Number => "number",
+            FieldKind::YesNo => "yes_no",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<FieldKind> {
+        match text {
+            "text" => Some(FieldKind::Text),
+            "number" => Some(FieldKind::Number),
+            "yes_no" => Some(FieldKind::YesNo),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for FieldKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldKind::Text => f.write_str("Text"),
+            FieldKind::Number => f.write_str("Number"),
+            FieldKind::YesNo => f.write_str("Yes/No"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CategoryField {
+    pub name: String,
+    pub kind: FieldKind,
+    pub required: bool,
+}
+
+/// Something which can be signed out in a custom category.
+#[derive(Debug, Clone)]
+pub struct CategoryEntry {
+    pub name: String,
+    /// Number in stock, if the category is stock limited.
+    pub quantity: Option<i64>,
+}
+
+/// A type of record defined at runtime, e.g. sports equipment.
+#[derive(Debug, Clone)]
+pub struct Category {
+    pub id: i64,
+    pub name: String,
+    /// Records are signed back in, rather than given away.
+    pub returnable: bool,
+    pub stock_limited: bool,
+    pub student_number_required: bool,
+    pub fields: Vec<CategoryField>,
+    pub entries: Vec<CategoryEntry>,
+}
+
+impl Category {
+    pub fn entry(&self, name: &str) -> Option<&CategoryEntry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewCategory<'a> {
+    pub name: &'a str,
+    pub returnable: bool,
+    pub stock_limited: bool,
+    pub student_number_required: bool,
+    pub fields: &'a [CategoryField],
+}
+
+#[derive(Debug, Clone)]
+pub struct CategoryRecord {
+    pub id: i64,
+    pub category_id: i64,
+    pub entry: String,
+    pub quantity: i64,
+    pub student_name: String,
+    pub student_number: Option<String>,
+    pub receptionist_out: Option<String>,
+    pub receptionist_in: Option<String>,
+    pub time_out: chrono::DateTime<chrono::Utc>,
+    pub time_in: Option<chrono::DateTime<chrono::Utc>>,
+    /// Values of the category's fields by name, fields added since the record was made are missing.
+    pub fields: std::collections::BTreeMap<String, String>,
+    pub notes: String,
+    pub voided: bool,
+    pub amended: bool,
+}
+
+impl CategoryRecord {
+    pub fn field(&self, name: &str) -> &str {
+        self.fields.get(name).map(String::as_str).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewCategoryRecord<'a> {
+    pub category_id: i64,
+    pub entry: &'a str,
+    pub quantity: i64,
+    pub student_name: &'a str,
+    /// Empty if not given.
+    pub student_number: &'a str,
+    pub receptionist: &'a str,
+    /// Value of each field of the category, in order.
+    pub fields: &'a [String],
+    pub notes: &'a str,
+}
@@ -0,0 +1,309 @@
+use std::path::PathBuf;
+
+use rusqlite::OptionalExtension;
+
+use crate::worker::WorkerHandle;
+
+use super::{CorruptRow, parse_rows, FetchedPage, PendingPage, request_page, poll_page, fetch_counted_page, parse_time, parse_optional_time, Page, PagePosition, StorageError, PaginatedStorage, RecordFilter, FilterColumns, filter_clause, format_optional_time, InsertableStorage, SignableStorage, NotedStorage, ExportableStorage, ParcelRecord, NewParcelRecord, CountWithin, SignOutTimes, AmendableStorage, AmendmentRecord, amendment, audit};
+
+const FILTER_COLUMNS: FilterColumns = FilterColumns {
+    student: &["student_name"],
+    item: "parcel_desc",
+    receptionist: &["receptionist"],
+    notes: "notes",
+    time: "time_in",
+    open: Some("time_out"),
+    due: None,
+    voided: Some("voided"),
+};
+
+/// Columns which can be corrected with an amendment.
+const AMENDABLE_COLUMNS: &[&str] = &["parcel_desc", "student_name"];
+
+pub struct ParcelStorage {
+    worker: WorkerHandle,
+    background: bool,
+    records: Vec<ParcelRecord>,
+    page: Page,
+    filter: RecordFilter,
+    count: i64,
+    position: PagePosition,
+    stale: bool,
+    corrupt: Vec<CorruptRow>,
+    pending: Option<PendingPage<ParcelRecord>>,
+}
+
+impl ParcelStorage {
+    /// Pages are fetched in the background if `background` is set, otherwise waited for.
+    pub fn new(worker: WorkerHandle, background: bool) -> Result<ParcelStorage, StorageError> {
+        let mut storage = ParcelStorage {
+            worker,
+            background,
+            records: vec![],
+            page: Page::LastPage,
+            filter: RecordFilter::default(),
+            count: 0,
+            position: PagePosition::default(),
+            stale: true,
+            corrupt: vec![],
+            pending: None,
+        };
+
+        storage.refresh()?;
+
+        Ok(storage)
+    }
+
+    fn show(&mut self, fetched: FetchedPage<ParcelRecord>) {
+        self.count = fetched.count;
+        self.records = fetched.records;
+        self.corrupt = fetched.corrupt;
+        self.position = fetched.position;
+        self.stale = false;
+    }
+
+    /// Fetch the current page, counting the records again only if they may have changed since the page shown.
+    fn request(&mut self) -> Result<(), StorageError> {
+        let (clause, params) = filter_clause(&self.filter, &FILTER_COLUMNS);
+        let page = self.page;
+        let shown = (!self.stale).then_some((self.count, self.position));
+
+        let fetch = move |connection: &rusqlite::Connection| {
+            fetch_counted_page(connection, "parcel_records_amended", (&clause, &params), page, shown, Self::parse_row, |record| record.id)
+        };
+
+        if let Some(fetched) = request_page(&self.worker, self.background, &mut self.pending, fetch)? {
+            self.show(fetched);
+        }
+
+        log::debug!("refreshed parcel records");
+
+        Ok(())
+    }
+
+    /// Fetch a record by id, whether or not it is on the current page.
+    pub fn fetch(&self, id: i64) -> Result<Option<ParcelRecord>, StorageError> {
+        self.worker.call(move |connection| {
+            let record = connection.prepare("SELECT * FROM parcel_records_amended WHERE id = ?")?
+                .query_row((id,), Self::parse_row)
+                .optional()?;
+
+            Ok(record)
+        })
+    }
+}
+
+impl PaginatedStorage<ParcelRecord, i64> for ParcelStorage {
+    fn page(&self) -> Page {
+        self.page
+    }
+    
+    fn set_page(&mut self, page: Page) -> Result<(), StorageError> {
+        if page != self.page {
+            self.page = page;
+            self.request()
+        } else {
+            Ok(())
+        }
+    }
+
+    fn count(&self) -> i64 {
+        self.count
+    }
+
+    fn position(&self) -> PagePosition {
+        self.position
+    }
+
+    fn filter(&self) -> &RecordFilter {
+        &self.filter
+    }
+
+    fn set_filter(&mut self, filter: &RecordFilter) -> Result<(), StorageError> {
+        if *filter != self.filter {
+            self.filter = filter.clone();
+            self.page = Page::LastPage;
+            self.refresh()
+        } else {
+            Ok(())
+        }
+    }
+    
+    fn refresh(&mut self) -> Result<(), StorageError> {
+        self.stale = true;
+        self.request()
+    }
+
+    fn poll(&mut self) -> Result<bool, StorageError> {
+        match poll_page(&mut self.pending)? {
+            Some(fetched) => {
+                self.show(fetched);
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+
+    fn get_all(&self) -> &[ParcelRecord] {
+        self.records.as_slice()
+    }
+
+    fn corrupt(&self) -> &[CorruptRow] {
+        &self.corrupt
+    }
+
+    fn parse_row(row: &rusqlite::Row) -> Result<ParcelRecord, rusqlite::Error> {
+        let time_in = parse_time(row, "time_in")?;
+        
+        let time_out = parse_optional_time(row, "time_out")?;
+        
+        Ok(ParcelRecord {
+            id: row.get("id")?,
+            parcel_desc: row.get("parcel_desc")?,
+            student_name: row.get("student_name")?,
+            receptionist: row.get("receptionist")?,
+            time_in,
+            time_out,
+            notes: row.get("notes")?,
+            voided: row.get("voided")?,
+            amended: row.get("amended")?,
+        })
+    }
+}
+
+impl InsertableStorage<NewParcelRecord<'_>, i64> for ParcelStorage {
+    fn insert(&mut self, record: NewParcelRecord) -> Result<(), StorageError> {
+        let (parcel_desc, student_name, receptionist, notes) = (record.parcel_desc.to_owned(), record.student_name.to_owned(), record.receptionist.to_owned(), record.notes.to_owned());
+
+        audit::audited(&self.worker, "insert", "parcel_records", "id", None, move |tx| {
+            tx.execute(
+                "INSERT INTO parcel_records (id, parcel_desc, student_name, receptionist, time_in, time_out, notes) VALUES (NULL, ?, ?, ?, ?, NULL, ?)",
+                (parcel_desc, student_name, receptionist, chrono::Utc::now().timestamp(), notes)
+            )?;
+
+            Ok(tx.last_insert_rowid())
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+}
+
+impl SignableStorage<ParcelRecord, i64> for ParcelStorage {
+    fn signin(&mut self, id: i64) -> Result<(), StorageError> {
+        audit::audited(&self.worker, "sign in", "parcel_records", "id", Some(id), move |tx| {
+            tx.execute(
+                "UPDATE parcel_records SET time_out = ? WHERE id = ?",
+                (chrono::Utc::now().timestamp(), id)
+            )?;
+
+            Ok(id)
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+}
+
+impl NotedStorage<ParcelRecord, i64> for ParcelStorage {
+    fn update_notes(&mut self, id: i64, notes: &str) -> Result<(), StorageError> {
+        let notes = notes.to_owned();
+
+        audit::audited(&self.worker, "update notes", "parcel_records", "id", Some(id), move |tx| {
+            tx.execute(
+                "UPDATE parcel_records SET notes = ? WHERE id = ?",
+                (notes, id)
+            )?;
+
+            Ok(id)
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+}
+
+impl ExportableStorage<ParcelRecord> for ParcelStorage {
+    fn fetch_all(connection: &rusqlite::Connection) -> Result<Vec<ParcelRecord>, StorageError> {
+        let mut stmt = connection.prepare("SELECT * FROM parcel_records_amended WHERE NOT voided")?;
+        
+        let records = parse_rows("parcel_records", stmt.query(())?, Self::parse_row)?.0;
+        
+        log::debug!("fetched all parcel records");
+
+        Ok(records)
+    }
+
+    fn csv_headers() -> &'static [&'static str] {
+        &[
+            "Time In",
+            "Time Out",
+            "Parcel Description",
+            "Student Name",
+            "Receptionist",
+            "Notes",
+        ]
+    }
+
+    fn write_record<W: std::io::Write>(writer: &mut csv::Writer<W>, record: &ParcelRecord) -> Result<(), csv::Error> {
+        writer.write_record([
+            record.time_in.to_rfc3339().as_str(),
+            &format_optional_time(record.time_out),
+            &record.parcel_desc,
+            &record.student_name,
+            &record.receptionist,
+            &record.notes,
+        ])
+    }
+
+    fn export_csv(connection: &rusqlite::Connection, path: PathBuf, progress: impl FnMut(f32)) -> Result<(), StorageError> {
+        super::export_csv::<ParcelRecord, Self>(connection, path, progress)
+    }
+}
+
+impl CountWithin for ParcelStorage {
+    fn count_within(&self, start: chrono::DateTime<chrono::Utc>, end: chrono::DateTime<chrono::Utc>) -> Result<i64, StorageError> {
+        self.worker.call(move |conn| {
+            let mut stmt = conn.prepare("SELECT COUNT(*) AS c FROM parcel_records_amended WHERE ? <= time_out AND time_out < ? AND NOT voided")?;
+
+            let start = start.timestamp();
+            let end = end.timestamp();
+
+            let records = stmt.query_row((start, end), |row| row.get::<_, i64>("c"))
+                .optional()?.unwrap_or(0);
+
+            Ok(records)
+        })
+    }
+}
+
+impl SignOutTimes for ParcelStorage {
+    fn sign_out_times(&self, start: chrono::DateTime<chrono::Utc>, end: chrono::DateTime<chrono::Utc>) -> Result<Vec<(chrono::DateTime<chrono::Utc>, i64)>, StorageError> {
+        super::sign_out_times(&self.worker, "parcel_records_amended", "1", start, end)
+    }
+}
+
+impl AmendableStorage<ParcelRecord, i64> for ParcelStorage {
+    fn amend(&mut self, id: i64, field: &str, new_value: &str, reason: &str, receptionist: &str) -> Result<(), StorageError> {
+        amendment::amend(&self.worker, "parcel_records", AMENDABLE_COLUMNS, id, Some((field, new_value)), reason, receptionist)?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+
+    fn void(&mut self, id: i64, reason: &str, receptionist: &str) -> Result<(), StorageError> {
+        amendment::amend(&self.worker, "parcel_records", AMENDABLE_COLUMNS, id, None, reason, receptionist)?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+
+    fn amendments(&self, id: i64) -> Result<Vec<AmendmentRecord>, StorageError> {
+        amendment::fetch_amendments(&self.worker, "parcel_records", id)
+    }
+}
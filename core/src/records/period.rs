@@ -0,0 +1,262 @@
+use chrono::{Datelike, Timelike};
+use strum::EnumIter;
+
+use super::local_midnight;
+
+/// Length of a summary period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, EnumIter)]
+pub enum PeriodKind {
+    Day,
+    /// Monday to Sunday.
+    Week,
+    #[default]
+    Month,
+    /// A quarter of the year, i.e. term 1 is January to March.
+    Term,
+    Custom,
+}
+
+impl std::fmt::Display for PeriodKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PeriodKind::Day => f.write_str("Day"),
+            PeriodKind::Week => f.write_str("Week"),
+            PeriodKind::Month => f.write_str("Month"),
+            PeriodKind::Term => f.write_str("Term"),
+            PeriodKind::Custom => f.write_str("Custom"),
+        }
+    }
+}
+
+/// A range of local dates which records are summarised over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Period {
+    pub kind: PeriodKind,
+    /// Inclusive local date.
+    pub from: chrono::NaiveDate,
+    /// Inclusive local date.
+    pub to: chrono::NaiveDate,
+}
+
+impl Default for Period {
+    /// Last month.
+    fn default() -> Self {
+        let today = chrono::Local::now().date_naive();
+
+        Period::containing(PeriodKind::Month, today - chrono::Months::new(1))
+    }
+}
+
+impl Period {
+    /// The period of the given kind which `date` falls in. Custom periods are just the one day.
+    pub fn containing(kind: PeriodKind, date: chrono::NaiveDate) -> Period {
+        let (from, to) = match kind {
+            PeriodKind::Day | PeriodKind::Custom => (date, date),
+            PeriodKind::Week => {
+                let from = date - chrono::Days::new(date.weekday().num_days_from_monday() as u64);
+                (from, from + chrono::Days::new(6))
+            },
+            PeriodKind::Month => {
+                let from = date - chrono::Days::new(date.day0() as u64);
+                (from, from + chrono::Months::new(1) - chrono::Days::new(1))
+            },
+            PeriodKind::Term => {
+                let from = chrono::NaiveDate::from_ymd_opt(date.year(), date.month0() / 3 * 3 + 1, 1)
+                    .expect("first day of a quarter exists");
+                (from, from + chrono::Months::new(3) - chrono::Days::new(1))
+            },
+        };
+
+        Period { kind, from, to }
+    }
+
+    /// A custom period, with the dates swapped if given backwards.
+    pub fn custom(from: chrono::NaiveDate, to: chrono::NaiveDate) -> Period {
+        Period { kind: PeriodKind::Custom, from: from.min(to), to: from.max(to) }
+    }
+
+    /// The same period with its length changed, keeping its start.
+    pub fn with_kind(&self, kind: PeriodKind) -> Period {
+        match kind {
+            PeriodKind::Custom => Period { kind, ..*self },
+            kind => Period::containing(kind, self.from),
+        }
+    }
+
+    /// Number of days in the period.
+    pub fn days(&self) -> u64 {
+        (self.to - self.from).num_days() as u64 + 1
+    }
+
+    /// The period of the same length just before this one.
+    pub fn previous(&self) -> Period {
+        match self.kind {
+            PeriodKind::Custom => Period::custom(self.from - chrono::Days::new(self.days()), self.from - chrono::Days::new(1)),
+            kind => Period::containing(kind, self.from - chrono::Days::new(1)),
+        }
+    }
+
+    /// The period of the same length just after this one.
+    pub fn next(&self) -> Period {
+        match self.kind {
+            PeriodKind::Custom => Period::custom(self.to + chrono::Days::new(1), self.to + chrono::Days::new(self.days())),
+            kind => Period::containing(kind, self.to + chrono::Days::new(1)),
+        }
+    }
+
+    /// Start of the first day, in UTC.
+    pub fn start(&self) -> chrono::DateTime<chrono::Utc> {
+        local_midnight(self.from)
+    }
+
+    /// End of the last day, in UTC, exclusive.
+    pub fn end(&self) -> chrono::DateTime<chrono::Utc> {
+        local_midnight(self.to + chrono::Days::new(1))
+    }
+
+    /// Each day of the period, in order.
+    pub fn dates(&self) -> impl Iterator<Item = chrono::NaiveDate> {
+        self.from.iter_days().take(self.days() as usize)
+    }
+}
+
+impl std::fmt::Display for Period {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            PeriodKind::Day => write!(f, "{}", self.from.format("%a %d %B %Y")),
+            PeriodKind::Week => write!(f, "Week of {}", self.from.format("%d %B %Y")),
+            PeriodKind::Month => write!(f, "{}", self.from.format("%B %Y")),
+            PeriodKind::Term => write!(f, "Term {} {}", self.from.month0() / 3 + 1, self.from.year()),
+            PeriodKind::Custom => write!(f, "{} to {}", self.from.format("%d %b %Y"), self.to.format("%d %b %Y")),
+        }
+    }
+}
+
+/// Number of records signed out over a period, by day, and by hour of each weekday.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Activity {
+    /// Count for each day of the period, in order.
+    pub daily: Vec<(chrono::NaiveDate, i64)>,
+    /// Count for each weekday from Monday, and each hour of the day, in local time.
+    pub hourly: [[i64; 24]; 7],
+}
+
+impl Activity {
+    /// Tally sign out times, each with the number of records it counts for, over the days of `period`.
+    pub fn new(period: &Period, times: &[(chrono::DateTime<chrono::Utc>, i64)]) -> Activity {
+        let mut daily = period.dates().map(|date| (date, 0)).collect::<Vec<_>>();
+        let mut hourly = [[0; 24]; 7];
+
+        for (time, count) in times {
+            let time = chrono::DateTime::<chrono::Local>::from(*time);
+
+            if let Some((_, day)) = daily.get_mut((time.date_naive() - period.from).num_days() as usize) {
+                *day += count;
+            }
+
+            hourly[time.weekday().num_days_from_monday() as usize][time.hour() as usize] += count;
+        }
+
+        Activity { daily, hourly }
+    }
+
+    pub fn total(&self) -> i64 {
+        self.daily.iter().map(|(_, count)| count).sum()
+    }
+
+    /// Add the counts of another activity over the same period.
+    pub fn add(&mut self, other: &Activity) {
+        for ((_, count), (_, other)) in self.daily.iter_mut().zip(&other.daily) {
+            *count += other;
+        }
+
+        for (hours, other) in self.hourly.iter_mut().zip(&other.hourly) {
+            for (count, other) in hours.iter_mut().zip(other) {
+                *count += other;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> chrono::NaiveDate {
+        chrono::NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn containing_spans_the_whole_period() {
+        // a Wednesday
+        let day = date(2026, 3, 18);
+
+        assert_eq!(Period::containing(PeriodKind::Day, day), Period { kind: PeriodKind::Day, from: day, to: day });
+        assert_eq!(Period::containing(PeriodKind::Week, day), Period { kind: PeriodKind::Week, from: date(2026, 3, 16), to: date(2026, 3, 22) });
+        assert_eq!(Period::containing(PeriodKind::Month, day), Period { kind: PeriodKind::Month, from: date(2026, 3, 1), to: date(2026, 3, 31) });
+        assert_eq!(Period::containing(PeriodKind::Term, day), Period { kind: PeriodKind::Term, from: date(2026, 1, 1), to: date(2026, 3, 31) });
+        assert_eq!(Period::containing(PeriodKind::Term, date(2026, 11, 5)).from, date(2026, 10, 1));
+        assert_eq!(Period::containing(PeriodKind::Month, date(2024, 2, 10)).days(), 29);
+    }
+
+    #[test]
+    fn previous_and_next_cross_years() {
+        let january = Period::containing(PeriodKind::Month, date(2026, 1, 20));
+
+        assert_eq!(january.previous(), Period::containing(PeriodKind::Month, date(2025, 12, 1)));
+        assert_eq!(january.previous().next(), january);
+
+        let term = Period::containing(PeriodKind::Term, date(2026, 2, 1));
+
+        assert_eq!(term.previous(), Period { kind: PeriodKind::Term, from: date(2025, 10, 1), to: date(2025, 12, 31) });
+        assert_eq!(term.next().from, date(2026, 4, 1));
+
+        // weeks starting in one year and ending in the next
+        let week = Period::containing(PeriodKind::Week, date(2026, 1, 1));
+
+        assert_eq!((week.from, week.to), (date(2025, 12, 29), date(2026, 1, 4)));
+        assert_eq!(week.next().from, date(2026, 1, 5));
+    }
+
+    #[test]
+    fn custom_periods_keep_their_length() {
+        let period = Period::custom(date(2026, 3, 10), date(2026, 3, 1));
+
+        assert_eq!((period.from, period.to), (date(2026, 3, 1), date(2026, 3, 10)));
+        assert_eq!(period.days(), 10);
+        assert_eq!(period.dates().count(), 10);
+
+        assert_eq!(period.previous(), Period::custom(date(2026, 2, 19), date(2026, 2, 28)));
+        assert_eq!(period.next(), Period::custom(date(2026, 3, 11), date(2026, 3, 20)));
+    }
+
+    #[test]
+    fn with_kind_keeps_the_start() {
+        let period = Period::custom(date(2026, 3, 18), date(2026, 4, 2));
+
+        assert_eq!(period.with_kind(PeriodKind::Month), Period::containing(PeriodKind::Month, date(2026, 3, 1)));
+        assert_eq!(period.with_kind(PeriodKind::Month).with_kind(PeriodKind::Custom), Period { kind: PeriodKind::Custom, from: date(2026, 3, 1), to: date(2026, 3, 31) });
+        assert_eq!(period.end() - period.start(), local_midnight(date(2026, 4, 3)) - local_midnight(date(2026, 3, 18)));
+    }
+
+    #[test]
+    fn activity_is_tallied_by_day_and_hour() {
+        let period = Period::containing(PeriodKind::Week, date(2026, 3, 18));
+        let at = |day: chrono::NaiveDate, hour: i64| local_midnight(day) + chrono::Duration::hours(hour);
+
+        let activity = Activity::new(&period, &[(at(date(2026, 3, 16), 9), 1), (at(date(2026, 3, 16), 9), 2), (at(date(2026, 3, 22), 20), 1)]);
+
+        assert_eq!(activity.daily.len(), 7);
+        assert_eq!(activity.daily[0], (date(2026, 3, 16), 3));
+        assert_eq!(activity.daily[6], (date(2026, 3, 22), 1));
+        assert_eq!(activity.hourly[0][9], 3);
+        assert_eq!(activity.hourly[6][20], 1);
+        assert_eq!(activity.total(), 4);
+
+        let mut sum = activity.clone();
+        sum.add(&activity);
+
+        assert_eq!(sum.total(), 8);
+        assert_eq!(sum.hourly[0][9], 6);
+    }
+}
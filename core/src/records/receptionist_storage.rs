@@ -0,0 +1,168 @@
+use crate::worker::WorkerHandle;
+
+use super::{ReceptionistRecord, NewReceptionistRecord, Shift, StorageError, Storage, InsertableStorage, audit};
+
+pub struct ReceptionistStorage {
+    worker: WorkerHandle,
+    records: Vec<ReceptionistRecord>,
+}
+
+impl ReceptionistStorage {
+    pub fn new(worker: WorkerHandle) -> Result<ReceptionistStorage, StorageError> {
+        let mut storage = ReceptionistStorage {
+            worker,
+            records: vec![],
+        };
+
+        storage.refresh()?;
+
+        Ok(storage)
+    }
+
+    /// Receptionists who can sign on, inactive receptionists are kept for their history.
+    pub fn get_active(&self) -> impl Iterator<Item = &ReceptionistRecord> {
+        self.records.iter().filter(|r| r.active)
+    }
+
+    pub fn set_active(&mut self, id: i64, active: bool) -> Result<(), StorageError> {
+        audit::audited(&self.worker, "set active", "receptionists", "id", Some(id), move |tx| {
+            tx.execute(
+                "UPDATE receptionists SET active = ? WHERE id = ?",
+                (active, id)
+            )?;
+
+            Ok(id)
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+
+    pub fn set_admin(&mut self, id: i64, admin: bool) -> Result<(), StorageError> {
+        audit::audited(&self.worker, "set admin", "receptionists", "id", Some(id), move |tx| {
+            tx.execute(
+                "UPDATE receptionists SET admin = ? WHERE id = ?",
+                (admin, id)
+            )?;
+
+            Ok(id)
+        })?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+
+    /// Whether the receptionist may override blocking restrictions, unknown receptionists may not.
+    pub fn is_admin(&self, name: &str) -> bool {
+        self.records.iter().any(|r| r.admin && r.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Put a receptionist on duty, recording the start of their shift.
+    pub fn start_shift(&mut self, receptionist: &ReceptionistRecord) -> Result<Shift, StorageError> {
+        let time_start = chrono::Utc::now();
+
+        let receptionist_id = receptionist.id;
+
+        let id = self.worker.call(move |connection| {
+            // only one receptionist is on duty at a time, changes are attributed to them
+            connection.execute(
+                "UPDATE shifts SET time_end = ? WHERE time_end IS NULL",
+                (time_start.timestamp(),))?;
+            
+            connection.execute(
+                "INSERT INTO shifts (id, receptionist_id, time_start, time_end) VALUES (NULL, ?, ?, NULL)",
+                (receptionist_id, time_start.timestamp()))?;
+
+            Ok(connection.last_insert_rowid())
+        })?;
+
+        log::info!("started shift {id} for {}", receptionist.name);
+
+        Ok(Shift {
+            id,
+            receptionist: receptionist.clone(),
+            time_start,
+        })
+    }
+
+    /// End shifts left open, e.g. if the program was not closed properly.
+    pub fn end_open_shifts(&mut self) -> Result<(), StorageError> {
+        let ended = self.worker.call(|connection| Ok(connection.execute(
+            "UPDATE shifts SET time_end = ? WHERE time_end IS NULL",
+            (chrono::Utc::now().timestamp(),))?))?;
+
+        if ended > 0 {
+            log::warn!("ended {ended} shifts left open");
+        }
+
+        Ok(())
+    }
+
+    pub fn end_shift(&mut self, shift: &Shift) -> Result<(), StorageError> {
+        let id = shift.id;
+
+        self.worker.call(move |connection| Ok(connection.execute(
+            "UPDATE shifts SET time_end = ? WHERE id = ?",
+            (chrono::Utc::now().timestamp(), id))?))?;
+
+        log::info!("ended shift {} for {}", shift.id, shift.receptionist.name);
+
+        Ok(())
+    }
+}
+
+impl Storage<ReceptionistRecord, i64> for ReceptionistStorage {
+    fn refresh(&mut self) -> Result<(), StorageError> {
+        self.records = self.worker.call(|connection| {
+            let mut stmt = connection.prepare("SELECT * FROM receptionists ORDER BY name")?;
+            
+            let records = stmt
+                .query_map((), Self::parse_row)?
+                .collect::<Result<_, _>>()?;
+
+            Ok(records)
+        })?;
+
+        log::debug!("refreshed receptionists");
+        
+        Ok(())
+    }
+
+    fn get_all(&self) -> &[ReceptionistRecord] {
+        self.records.as_slice()
+    }
+
+    fn get(&self, id: i64) -> Option<&ReceptionistRecord> {
+        self.records.iter().find(|r| r.id == id)
+    }
+
+    fn parse_row(row: &rusqlite::Row) -> Result<ReceptionistRecord, rusqlite::Error> {
+        Ok(ReceptionistRecord {
+            id: row.get("id")?,
+            name: row.get("name")?,
+            active: row.get("active")?,
+            admin: row.get("admin")?,
+        })
+    }
+}
+
+impl InsertableStorage<NewReceptionistRecord<'_>, i64> for ReceptionistStorage {
+    fn insert(&mut self, record: NewReceptionistRecord) -> Result<(), StorageError> {
+        let name = record.name.to_owned();
+
+        audit::audited(&self.worker, "insert", "receptionists", "id", None, move |tx| {
+            tx.execute(
+                "INSERT INTO receptionists (id, name, active) VALUES (NULL, ?, TRUE)",
+                (name,)
+            )?;
+
+            Ok(tx.last_insert_rowid())
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+}
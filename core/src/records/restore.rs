@@ -0,0 +1,511 @@
+use std::path::{Path, PathBuf};
+
+use rusqlite::{types::ValueRef, OptionalExtension};
+
+use crate::{APP_NAME, worker::WorkerHandle};
+
+use super::{StorageError, audit};
+
+/// Describes how rows of a table are matched between the current database and a backup.
+struct MergeTable {
+    table: &'static str,
+    label: &'static str,
+    /// Columns which identify the same row in both databases.
+    key: &'static [&'static str],
+    /// Other columns, compared to find conflicting rows.
+    values: &'static [&'static str],
+    /// Column used to identify rows in the audit log.
+    audit_key: &'static str,
+    /// Records keep their amendments when merged, and get a new id.
+    record: bool,
+    /// Column which references another table by id.
+    reference: Option<Reference>,
+    /// Condition on backup row `b` for rows which are only in the backup, but cannot be merged.
+    /// They are counted as conflicts, and left out of the merge.
+    skip: Option<&'static str>,
+}
+
+/// A column holding the id of a row of another table, compared by the key of that row since ids differ between databases.
+struct Reference {
+    column: &'static str,
+    table: &'static str,
+    key: &'static [&'static str],
+}
+
+const CATEGORY: Option<Reference> = Some(Reference { column: "category_id", table: "categories", key: &["name"] });
+
+/// Key records still out in the backup, whose key has since been signed out again. Merging them would sign the key out twice.
+const OPEN_KEY_CLASH: &str = "EXISTS (
+    SELECT 1 FROM backup.key_records_amended ba WHERE ba.id = b.id AND ba.time_in IS NULL AND NOT ba.voided
+        AND EXISTS (
+            SELECT 1 FROM main.key_records_amended ma WHERE ma.id IN (
+                SELECT id FROM main.key_records WHERE `key` = ba.`key` AND time_in IS NULL
+                UNION SELECT record_id FROM main.amendments WHERE record_table = 'key_records' AND field = 'key' AND new_value = ba.`key`
+            ) AND ma.`key` = ba.`key` AND ma.time_in IS NULL AND NOT ma.voided
+        )
+)";
+
+/// Triggers which enforce sign out rules and keep stock counts. They are dropped while merging, and recreated
+/// before committing, since merged records and restocks were already counted against the stock of the backup, and
+/// conflicting stock is chosen like any other catalogue conflict. Keys signed out twice are left out by [`OPEN_KEY_CLASH`].
+/// Open records of returnable items are the exception, they take their stock in [`RestorePreview::merge`], as they put it back when signed in.
+const MERGE_SUSPENDED_TRIGGERS: &[&str] = &[
+    "key_records_one_out",
+    "game_records_in_stock",
+    "item_records_in_stock",
+    "item_records_take_stock",
+    "item_restocks_add_stock",
+    "category_records_in_stock",
+    "amendments_key_one_out",
+    "amendments_game_in_stock",
+    "amendments_item_stock",
+    "amendments_category_in_stock",
+];
+
+/// Catalogue tables are merged before records, since records reference students and categories.
+const CATALOGUE_TABLES: &[MergeTable] = &[
+    MergeTable { table: "students", label: "Students", key: &["student_number"], values: &["student_name", "room", "block"], audit_key: "student_number", record: false, reference: None, skip: None },
+    MergeTable { table: "keys", label: "Keys", key: &["key"], values: &["loan_minutes"], audit_key: "key", record: false, reference: None, skip: None },
+    MergeTable { table: "games", label: "Games", key: &["game"], values: &["quantity", "loan_minutes"], audit_key: "game", record: false, reference: None, skip: None },
+    MergeTable { table: "items", label: "Items", key: &["item"], values: &["returnable", "quantity", "low_stock"], audit_key: "item", record: false, reference: None, skip: None },
+    MergeTable { table: "receptionists", label: "Receptionists", key: &["name"], values: &[], audit_key: "id", record: false, reference: None, skip: None },
+    MergeTable { table: "categories", label: "Categories", key: &["name"], values: &["returnable", "stock_limited", "student_number_required"], audit_key: "id", record: false, reference: None, skip: None },
+    MergeTable { table: "category_fields", label: "Category Fields", key: &["category_id", "name"], values: &["kind", "required", "position"], audit_key: "id", record: false, reference: CATEGORY, skip: None },
+    MergeTable { table: "category_entries", label: "Category Entries", key: &["category_id", "name"], values: &["quantity"], audit_key: "rowid", record: false, reference: CATEGORY, skip: None },
+    MergeTable { table: "loan_limits", label: "Loan Limits", key: &["record_type", "name"], values: &["max_open"], audit_key: "id", record: false, reference: None, skip: None },
+];
+
+const RECORD_TABLES: &[MergeTable] = &[
+    MergeTable { table: "key_records", label: "Key Records", key: &["key", "student_number", "time_out"], values: &["student_name", "receptionist_out", "receptionist_in", "time_due", "time_in", "notes"], audit_key: "id", record: true, reference: None, skip: Some(OPEN_KEY_CLASH) },
+    MergeTable { table: "parcel_records", label: "Parcel Records", key: &["parcel_desc", "student_name", "time_in"], values: &["receptionist", "time_out", "notes"], audit_key: "id", record: true, reference: None, skip: None },
+    MergeTable { table: "game_records", label: "Game Records", key: &["game", "student_number", "time_out"], values: &["quantity", "student_name", "receptionist_out", "receptionist_in", "time_due", "time_in", "notes"], audit_key: "id", record: true, reference: None, skip: None },
+    MergeTable { table: "item_records", label: "Item Records", key: &["item", "student_number", "time_out"], values: &["quantity", "student_name", "returnable", "receptionist", "receptionist_in", "time_in", "notes"], audit_key: "id", record: true, reference: None, skip: None },
+    MergeTable { table: "category_records", label: "Category Records", key: &["category_id", "entry", "student_name", "time_out"], values: &["quantity", "student_number", "receptionist_out", "receptionist_in", "time_in", "fields", "notes"], audit_key: "id", record: true, reference: CATEGORY, skip: None },
+    MergeTable { table: "restrictions", label: "Restrictions", key: &["student_number", "time_imposed"], values: &["record_type", "name", "reason", "blocking", "time_start", "time_end", "imposed_by"], audit_key: "id", record: false, reference: None, skip: None },
+    MergeTable { table: "restriction_overrides", label: "Restriction Overrides", key: &["restriction_id", "time"], values: &["justification", "receptionist"], audit_key: "id", record: false, reference: Some(Reference { column: "restriction_id", table: "restrictions", key: &["student_number", "time_imposed"] }), skip: None },
+    MergeTable { table: "item_restocks", label: "Item Restocks", key: &["item", "time"], values: &["quantity", "receptionist", "notes"], audit_key: "id", record: false, reference: None, skip: None },
+    // the receptionist on duty is found by their open shift
+    MergeTable { table: "shifts", label: "Shifts", key: &["receptionist_id", "time_start"], values: &["time_end"], audit_key: "id", record: false, reference: Some(Reference { column: "receptionist_id", table: "receptionists", key: &["name"] }), skip: Some("b.time_end IS NULL") },
+];
+
+/// Number of rows of a table found in only one of the databases, or in both with different values.
+#[derive(Debug, Clone)]
+pub struct TableDiff {
+    pub label: &'static str,
+    pub only_backup: i64,
+    pub only_current: i64,
+    pub conflicting: i64,
+}
+
+/// A catalogue entry (student, key, game) which differs between the current database and the backup.
+#[derive(Debug, Clone)]
+pub struct CatalogueConflict {
+    pub label: &'static str,
+    pub id: String,
+    pub current: String,
+    pub backup: String,
+    /// Whether the merge should overwrite the current entry with the backup.
+    pub use_backup: bool,
+    /// Rows of the entry in the current database and the backup.
+    rowids: (i64, i64),
+}
+
+/// A backup opened for comparison with the current database. The backup file itself is never
+/// changed, a copy is migrated to the current schema and attached while comparing or merging.
+#[derive(Debug)]
+pub struct RestorePreview {
+    worker: WorkerHandle,
+    pub path: PathBuf,
+    copy_path: PathBuf,
+    pub tables: Vec<TableDiff>,
+    pub conflicts: Vec<CatalogueConflict>,
+    /// Keys out in the backup which are out again in the current database, their backup records are not merged.
+    pub open_keys: Vec<String>,
+}
+
+impl RestorePreview {
+    pub fn open(worker: WorkerHandle, path: PathBuf) -> Result<RestorePreview, StorageError> {
+        let copy_path = std::env::temp_dir().join(format!("{APP_NAME}_restore_{}.sqlite", std::process::id()));
+
+        std::fs::copy(&path, &copy_path).map_err(StorageError::FileError)?;
+
+        let mut preview = RestorePreview {
+            worker,
+            path,
+            copy_path,
+            tables: vec![],
+            conflicts: vec![],
+            open_keys: vec![],
+        };
+
+        {
+            // backups from older versions are brought up to date, so the schemas match
+            let mut copy = rusqlite::Connection::open(&preview.copy_path)?;
+            crate::embedded::migrations::runner().run(&mut copy)?;
+        }
+
+        preview.compare()?;
+
+        Ok(preview)
+    }
+
+    fn compare(&mut self) -> Result<(), StorageError> {
+        let copy_path = self.copy_path.clone();
+
+        (self.tables, self.conflicts, self.open_keys) = self.worker.call(move |connection| {
+            attach(connection, &copy_path)?;
+
+            let result = (|| {
+                let mut tables = vec![];
+                let mut conflicts = vec![];
+
+                for table in CATALOGUE_TABLES.iter().chain(RECORD_TABLES) {
+                    let mut diff = TableDiff {
+                        label: table.label,
+                        only_backup: connection.query_row(&format!("SELECT COUNT(*) FROM backup.{0} b WHERE {1}", table.table, table.missing()), (), |row| row.get(0))?,
+                        only_current: connection.query_row(&format!("SELECT COUNT(*) FROM main.{0} m WHERE NOT EXISTS (SELECT 1 FROM backup.{0} b WHERE {1})", table.table, table.same_row()), (), |row| row.get(0))?,
+                        conflicting: 0,
+                    };
+
+                    if let Some(skip) = table.skip {
+                        diff.conflicting += connection.query_row(&format!("SELECT COUNT(*) FROM backup.{0} b WHERE NOT EXISTS (SELECT 1 FROM main.{0} m WHERE {1}) AND {2}", table.table, table.same_row(), skip), (), |row| row.get::<_, i64>(0))?;
+                    }
+
+                    if !table.values.is_empty() {
+                        diff.conflicting += connection.query_row(&format!("SELECT COUNT(*) FROM backup.{0} b JOIN main.{0} m ON {1} WHERE NOT ({2})", table.table, table.same_row(), table.same_values()), (), |row| row.get::<_, i64>(0))?;
+
+                        if !table.record {
+                            conflicts.extend(table.conflicts(connection)?);
+                        }
+                    }
+
+                    tables.push(diff);
+                }
+
+                let open_keys = connection.prepare(&format!(
+                    "SELECT DISTINCT a.`key` FROM backup.key_records b JOIN backup.key_records_amended a ON a.id = b.id
+                        WHERE NOT EXISTS (SELECT 1 FROM main.key_records m WHERE {0}) AND {1} ORDER BY a.`key`",
+                    RECORD_TABLES[0].same_row(), OPEN_KEY_CLASH
+                ))?
+                    .query_map((), |row| row.get(0))?
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok::<_, rusqlite::Error>((tables, conflicts, open_keys))
+            })();
+
+            detach(connection)?;
+
+            Ok(result?)
+        })?;
+
+
+        Ok(())
+    }
+
+    /// Copy rows which are only in the backup into the current database, along with the amendments
+    /// of any merged records. Conflicting catalogue entries are overwritten if chosen, conflicting
+    /// records are left as they are, as are records of keys which are out again and shifts left open.
+    /// Stock is not taken again for merged records, except by those of returnable items which are still
+    /// out. Returns the number of rows merged.
+    pub fn merge(&self) -> Result<usize, StorageError> {
+        let (path, copy_path, conflicts) = (self.path.clone(), self.copy_path.clone(), self.conflicts.clone());
+
+        let merged = self.worker.call(move |connection| {
+            attach(connection, &copy_path)?;
+
+            let result = (|| {
+                let tx = connection.transaction()?;
+
+                let mut suspended = vec![];
+
+                for name in MERGE_SUSPENDED_TRIGGERS {
+                    let sql: Option<String> = tx.query_row("SELECT sql FROM main.sqlite_master WHERE type = 'trigger' AND name = ?", (name,), |row| row.get(0)).optional()?;
+
+                    if let Some(sql) = sql {
+                        tx.execute(&format!("DROP TRIGGER main.{name}"), ())?;
+                        suspended.push(sql);
+                    }
+                }
+
+                let mut merged = 0;
+
+                for table in CATALOGUE_TABLES.iter().chain(RECORD_TABLES) {
+                    let rowids = table.merge_missing(&tx)?;
+
+                    // they put their stock back when signed in, so take it before any stock chosen from the backup
+                    if table.table == "item_records" {
+                        for rowid in &rowids {
+                            tx.execute(
+                                "UPDATE main.items SET quantity = quantity - (SELECT quantity FROM main.item_records_amended WHERE id = ?1)
+                                    WHERE quantity IS NOT NULL AND item = (SELECT item FROM main.item_records_amended WHERE id = ?1 AND returnable AND time_in IS NULL AND NOT voided)",
+                                (rowid,)
+                            )?;
+                        }
+                    }
+
+                    merged += rowids.len();
+                }
+
+                for conflict in conflicts.iter().filter(|c| c.use_backup) {
+                    let Some(table) = CATALOGUE_TABLES.iter().find(|t| t.label == conflict.label) else {
+                        continue;
+                    };
+
+                    let (rowid, backup_rowid) = conflict.rowids;
+
+                    let id: String = tx.query_row(&format!("SELECT CAST(`{1}` AS TEXT) FROM main.{0} WHERE rowid = ?", table.table, table.audit_key), (rowid,), |row| row.get(0))?;
+
+                    let before = audit::snapshot(&tx, table.table, table.audit_key, &id)?;
+
+                    tx.execute(
+                        &format!("UPDATE main.{0} SET ({1}) = (SELECT {1} FROM backup.{0} WHERE rowid = ?2) WHERE rowid = ?1", table.table, table.columns(table.values)),
+                        (rowid, backup_rowid)
+                    )?;
+
+                    let after = audit::snapshot(&tx, table.table, table.audit_key, &id)?;
+
+                    audit::log_entry(&tx, "merge", table.table, &id, before, after)?;
+
+                    merged += 1;
+                }
+
+                audit::log_entry(&tx, "merge", "database", &path.display(), None, None)?;
+
+                for sql in suspended {
+                    tx.execute_batch(&sql)?;
+                }
+
+                tx.commit()?;
+
+                Ok::<_, rusqlite::Error>(merged)
+            })();
+
+            detach(connection)?;
+
+            Ok(result?)
+        })?;
+
+
+        log::info!("merged {merged} rows from backup {:?}", self.path);
+
+        Ok(merged)
+    }
+}
+
+impl Drop for RestorePreview {
+    fn drop(&mut self) {
+        if let Err(err) = std::fs::remove_file(&self.copy_path) {
+            log::warn!("failed to remove copy of backup {:?}: {err}", self.copy_path);
+        }
+    }
+}
+
+impl MergeTable {
+    /// Condition matching row `m` of the current database with row `b` of the backup.
+    fn same_row(&self) -> String {
+        // IS, since e.g. a loan limit for every type of record has no type
+        self.key.iter().map(|c| format!("{} IS {}", self.column("m", c), self.column("b", c))).collect::<Vec<_>>().join(" AND ")
+    }
+
+    /// Value of a column of row `m` or `b` which is comparable between the databases, references by the key of the row referenced.
+    fn column(&self, alias: &str, column: &str) -> String {
+        match &self.reference {
+            Some(reference) if reference.column == column => {
+                let key = reference.key.iter().map(|c| format!("`{c}`")).collect::<Vec<_>>().join(" || ' ' || ");
+
+                format!("(SELECT {key} FROM {}.{} WHERE id = {alias}.`{column}`)", if alias == "m" { "main" } else { "backup" }, reference.table)
+            },
+            _ => format!("{alias}.`{column}`"),
+        }
+    }
+
+    /// Value of a column of backup row `b` to insert into the current database, references by the current id of the row referenced.
+    fn backup_value(&self, column: &str) -> String {
+        match &self.reference {
+            Some(reference) if reference.column == column => {
+                let same = reference.key.iter().map(|c| format!("r.`{c}` = br.`{c}`")).collect::<Vec<_>>().join(" AND ");
+
+                format!("(SELECT r.id FROM main.{0} r JOIN backup.{0} br ON {same} WHERE br.id = b.`{column}`)", reference.table)
+            },
+            _ => format!("b.`{column}`"),
+        }
+    }
+
+    /// Condition on backup row `b` for rows which are only in the backup, and can be merged.
+    fn missing(&self) -> String {
+        let missing = format!("NOT EXISTS (SELECT 1 FROM main.{0} m WHERE {1})", self.table, self.same_row());
+
+        match self.skip {
+            Some(skip) => format!("{missing} AND NOT {skip}"),
+            None => missing,
+        }
+    }
+
+    fn same_values(&self) -> String {
+        self.values.iter().map(|c| format!("m.`{c}` IS b.`{c}`")).collect::<Vec<_>>().join(" AND ")
+    }
+
+    fn columns(&self, columns: &[&str]) -> String {
+        columns.iter().map(|c| format!("`{c}`")).collect::<Vec<_>>().join(", ")
+    }
+
+    fn conflicts(&self, connection: &rusqlite::Connection) -> Result<Vec<CatalogueConflict>, rusqlite::Error> {
+        let values = |alias: &str| self.values.iter().map(|c| format!("{alias}.`{c}`")).collect::<Vec<_>>().join(", ");
+
+        // e.g. "Umbrellas: Red" for an entry of a category, or "key: all" for a limit on every key
+        let id = self.key.iter().map(|c| format!("IFNULL({}, 'all')", self.column("m", c))).collect::<Vec<_>>().join(" || ': ' || ");
+
+        let mut stmt = connection.prepare(&format!(
+            "SELECT CAST({1} AS TEXT) AS id, m.rowid, b.rowid, {2}, {3} FROM backup.{0} b JOIN main.{0} m ON {4} WHERE NOT ({5}) ORDER BY id",
+            self.table, id, values("m"), values("b"), self.same_row(), self.same_values()
+        ))?;
+
+        let count = self.values.len();
+
+        let conflicts = stmt.query_map((), |row| {
+            let describe = |offset: usize| (0..count)
+                .map(|i| row.get_ref(offset + i).map(|v| format!("{}: {}", self.values[i], value_text(v))))
+                .collect::<Result<Vec<_>, _>>()
+                .map(|v| v.join(", "));
+
+            Ok(CatalogueConflict {
+                label: self.label,
+                id: row.get(0)?,
+                current: describe(3)?,
+                backup: describe(3 + count)?,
+                use_backup: false,
+                rowids: (row.get(1)?, row.get(2)?),
+            })
+        })?.collect::<Result<_, _>>()?;
+
+        Ok(conflicts)
+    }
+
+    /// Insert rows which are only in the backup, returns the rowids of the rows inserted.
+    fn merge_missing(&self, tx: &rusqlite::Transaction) -> Result<Vec<i64>, rusqlite::Error> {
+        let all_columns = self.key.iter().chain(self.values).copied().collect::<Vec<_>>();
+        let columns = self.columns(&all_columns);
+        let backup_values = all_columns.iter().map(|c| self.backup_value(c)).collect::<Vec<_>>().join(", ");
+
+        let missing = tx.prepare(&format!("SELECT b.rowid FROM backup.{0} b WHERE {1} ORDER BY b.rowid", self.table, self.missing()))?
+            .query_map((), |row| row.get::<_, i64>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut merged = vec![];
+
+        for rowid in &missing {
+            tx.execute(&format!("INSERT INTO main.{0} ({1}) SELECT {2} FROM backup.{0} b WHERE b.rowid = ?", self.table, columns, backup_values), (rowid,))?;
+
+            let new_rowid = tx.last_insert_rowid();
+            merged.push(new_rowid);
+
+            if self.record {
+                // amendments follow the record to its new id
+                tx.execute(
+                    "INSERT INTO main.amendments (record_table, record_id, field, old_value, new_value, reason, receptionist, time)
+                        SELECT record_table, ?, field, old_value, new_value, reason, receptionist, time FROM backup.amendments WHERE record_table = ? AND record_id = ? ORDER BY id",
+                    (new_rowid, self.table, rowid)
+                )?;
+            }
+
+            let id: String = tx.query_row(&format!("SELECT CAST(`{1}` AS TEXT) FROM main.{0} WHERE rowid = ?", self.table, self.audit_key), (new_rowid,), |row| row.get(0))?;
+
+            let after = audit::snapshot(tx, self.table, self.audit_key, &id)?;
+
+            audit::log_entry(tx, "merge", self.table, &id, None, after)?;
+        }
+
+        Ok(merged)
+    }
+}
+
+fn attach(connection: &rusqlite::Connection, path: &Path) -> Result<(), rusqlite::Error> {
+    connection.execute("ATTACH DATABASE ? AS backup", (path.to_string_lossy(),))?;
+
+    Ok(())
+}
+
+fn detach(connection: &rusqlite::Connection) -> Result<(), rusqlite::Error> {
+    connection.execute("DETACH DATABASE backup", ())?;
+
+    Ok(())
+}
+
+fn value_text(value: ValueRef) -> String {
+    match value {
+        ValueRef::Null => "none".into(),
+        ValueRef::Integer(value) => value.to_string(),
+        ValueRef::Real(value) => value.to_string(),
+        ValueRef::Text(value) => String::from_utf8_lossy(value).into(),
+        ValueRef::Blob(_) => "blob".into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{open_database, worker::DbWorker};
+
+    fn count(worker: &WorkerHandle, sql: &'static str) -> i64 {
+        worker.call(|connection| Ok(connection.query_row(sql, (), |row| row.get(0))?)).unwrap()
+    }
+
+    fn diff<'a>(preview: &'a RestorePreview, label: &str) -> &'a TableDiff {
+        preview.tables.iter().find(|table| table.label == label).unwrap()
+    }
+
+    #[test]
+    fn merge() {
+        let backup_path = std::env::temp_dir().join(format!("{APP_NAME}_restore_test_{}.sqlite", std::process::id()));
+
+        open_database(&backup_path).unwrap().execute_batch("
+            INSERT INTO students (student_number, student_name) VALUES ('ABCDEF001', 'Alex');
+            INSERT INTO receptionists (name) VALUES ('Reception');
+            INSERT INTO shifts (receptionist_id, time_start, time_end) VALUES (1, 100, 200), (1, 300, NULL);
+            INSERT INTO items (item, returnable, quantity) VALUES ('Umbrella', TRUE, 5);
+            INSERT INTO item_records (item, quantity, student_name, student_number, receptionist, returnable, time_out, notes) VALUES ('Umbrella', 2, 'Alex', 'ABCDEF001', 'Reception', TRUE, 100, '');
+            INSERT INTO item_restocks (item, quantity, receptionist, time, notes) VALUES ('Umbrella', 1, 'Reception', 150, '');
+            INSERT INTO loan_limits (record_type, name, max_open) VALUES ('game', NULL, 2);
+            INSERT INTO restrictions (student_number, reason, time_start, imposed_by, time_imposed) VALUES ('ABCDEF001', 'Late', 0, 'Reception', 50);
+            INSERT INTO restriction_overrides (restriction_id, justification, receptionist, time) VALUES (1, 'Exam', 'Reception', 100);
+        ").unwrap();
+
+        let worker = DbWorker::spawn(Path::new(":memory:"), || {}).unwrap();
+        let connection = worker.handle();
+
+        connection.call(|connection| Ok(connection.execute_batch("
+            INSERT INTO restrictions (student_number, reason, time_start, imposed_by, time_imposed) VALUES ('ABCDEF002', 'Noise', 0, 'Reception', 10);
+            INSERT INTO items (item, returnable, quantity) VALUES ('Umbrella', TRUE, 5);
+        ")?)).unwrap();
+
+        let preview = RestorePreview::open(connection.clone(), backup_path.clone()).unwrap();
+
+        // the shift still open in the backup is left out
+        assert_eq!(diff(&preview, "Shifts").only_backup, 1);
+        assert_eq!(diff(&preview, "Shifts").conflicting, 1);
+        assert_eq!(diff(&preview, "Loan Limits").only_backup, 1);
+        assert_eq!(preview.conflicts.len(), 1);
+        assert!(!preview.conflicts[0].use_backup);
+
+        preview.merge().unwrap();
+        drop(preview);
+
+        assert_eq!(count(&connection, "SELECT COUNT(*) FROM shifts WHERE time_end IS NULL"), 0);
+        assert_eq!(count(&connection, "SELECT COUNT(*) FROM item_restocks"), 1);
+        assert_eq!(count(&connection, "SELECT restriction_id FROM restriction_overrides"), count(&connection, "SELECT id FROM restrictions WHERE student_number = 'ABCDEF001'"));
+
+        // the open record takes its stock, and puts it back when signed in, merged restocks add none
+        assert_eq!(count(&connection, "SELECT quantity FROM items"), 3);
+        connection.call(|connection| Ok(connection.execute("UPDATE item_records SET time_in = 200", ())?)).unwrap();
+        assert_eq!(count(&connection, "SELECT quantity FROM items"), 5);
+
+        // rows with empty key columns are matched, so merging again adds nothing
+        let preview = RestorePreview::open(connection.clone(), backup_path.clone()).unwrap();
+
+        assert!(preview.tables.iter().all(|table| table.only_backup == 0), "{:?}", preview.tables);
+        assert_eq!(preview.merge().unwrap(), 0);
+        drop(preview);
+
+        std::fs::remove_file(&backup_path).unwrap();
+    }
+}
@@ -0,0 +1,180 @@
+use crate::worker::WorkerHandle;
+
+use super::{RestrictionRecord, NewRestrictionRecord, NewOverrideRecord, RecordType, StorageError, Storage, InsertableStorage, parse_time, parse_optional_time, audit};
+
+/// How a type of record is stored in the restrictions and loan limits tables.
+pub(super) fn record_type_text(record_type: RecordType) -> &'static str {
+    match record_type {
+        RecordType::Key => "key",
+        RecordType::Parcel => "parcel",
+        RecordType::Game => "game",
+        RecordType::Item => "item",
+        RecordType::Category => "category",
+    }
+}
+
+pub(super) fn parse_record_type(text: &str) -> Option<RecordType> {
+    match text {
+        "key" => Some(RecordType::Key),
+        "parcel" => Some(RecordType::Parcel),
+        "game" => Some(RecordType::Game),
+        "item" => Some(RecordType::Item),
+        "category" => Some(RecordType::Category),
+        _ => None,
+    }
+}
+
+/// Every restriction, lifted and expired ones included, newest first.
+pub struct RestrictionStorage {
+    worker: WorkerHandle,
+    records: Vec<RestrictionRecord>,
+}
+
+impl RestrictionStorage {
+    pub fn new(worker: WorkerHandle) -> Result<RestrictionStorage, StorageError> {
+        let mut storage = RestrictionStorage {
+            worker,
+            records: vec![],
+        };
+
+        storage.refresh()?;
+
+        Ok(storage)
+    }
+
+    /// Restrictions in force now against signing out `name` of `record_type` to the student.
+    pub fn applicable(&self, student_number: &str, record_type: RecordType, name: &str) -> Vec<&RestrictionRecord> {
+        let now = chrono::Utc::now();
+
+        self.records.iter()
+            .filter(|r| r.is_active(now) && r.applies_to(student_number, record_type, name))
+            .collect()
+    }
+
+    /// End a restriction now, it is kept for its history.
+    pub fn lift(&mut self, id: i64) -> Result<(), StorageError> {
+        audit::audited(&self.worker, "lift", "restrictions", "id", Some(id), move |tx| {
+            tx.execute(
+                "UPDATE restrictions SET time_end = ? WHERE id = ?",
+                (chrono::Utc::now().timestamp(), id)
+            )?;
+
+            Ok(id)
+        })?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+}
+
+/// Copy of the overrides of a sign out, sent to the worker along with the record.
+pub(super) struct Overrides {
+    restrictions: Vec<i64>,
+    justification: String,
+    receptionist: String,
+}
+
+impl From<&NewOverrideRecord<'_>> for Overrides {
+    fn from(overrides: &NewOverrideRecord) -> Self {
+        Overrides {
+            restrictions: overrides.restrictions.to_vec(),
+            justification: overrides.justification.to_owned(),
+            receptionist: overrides.receptionist.to_owned(),
+        }
+    }
+}
+
+/// Record why a sign out was allowed despite blocking restrictions, within the transaction inserting the record.
+pub(super) fn log_overrides(tx: &rusqlite::Transaction, overrides: &Overrides) -> Result<(), rusqlite::Error> {
+    let Overrides { restrictions, justification, receptionist } = overrides;
+
+    for restriction in restrictions {
+        tx.execute(
+            "INSERT INTO restriction_overrides (id, restriction_id, justification, receptionist, time) VALUES (NULL, ?, ?, ?, ?)",
+            (restriction, justification, receptionist, chrono::Utc::now().timestamp())
+        )?;
+
+        let id = tx.last_insert_rowid();
+        let after = audit::snapshot(tx, "restriction_overrides", "id", &id)?;
+
+        audit::log_entry(tx, "override", "restriction_overrides", &id, None, after)?;
+    }
+
+    log::warn!("{receptionist} overrode restrictions {restrictions:?}: {justification}");
+
+    Ok(())
+}
+
+impl Storage<RestrictionRecord, i64> for RestrictionStorage {
+    fn refresh(&mut self) -> Result<(), StorageError> {
+        self.records = self.worker.call(|connection| {
+            let mut stmt = connection.prepare("SELECT * FROM restrictions ORDER BY id DESC")?;
+
+            let records = stmt
+                .query_map((), Self::parse_row)?
+                .collect::<Result<_, _>>()?;
+
+            Ok(records)
+        })?;
+
+        log::debug!("refreshed restrictions");
+
+        Ok(())
+    }
+
+    fn get_all(&self) -> &[RestrictionRecord] {
+        self.records.as_slice()
+    }
+
+    fn get(&self, id: i64) -> Option<&RestrictionRecord> {
+        self.records.iter().find(|r| r.id == id)
+    }
+
+    fn parse_row(row: &rusqlite::Row) -> Result<RestrictionRecord, rusqlite::Error> {
+        let record_type: Option<String> = row.get("record_type")?;
+
+        Ok(RestrictionRecord {
+            id: row.get("id")?,
+            student_number: row.get("student_number")?,
+            record_type: record_type.as_deref().and_then(parse_record_type),
+            name: row.get("name")?,
+            reason: row.get("reason")?,
+            blocking: row.get("blocking")?,
+            time_start: parse_time(row, "time_start")?,
+            time_end: parse_optional_time(row, "time_end")?,
+            imposed_by: row.get("imposed_by")?,
+            time_imposed: parse_time(row, "time_imposed")?,
+        })
+    }
+}
+
+impl InsertableStorage<NewRestrictionRecord<'_>, i64> for RestrictionStorage {
+    fn insert(&mut self, record: NewRestrictionRecord) -> Result<(), StorageError> {
+        let (student_number, name, reason, imposed_by) = (record.student_number.to_uppercase(), record.name.map(str::to_owned), record.reason.to_owned(), record.imposed_by.to_owned());
+        let (record_type, blocking, time_start, time_end) = (record.record_type, record.blocking, record.time_start, record.time_end);
+
+        audit::audited(&self.worker, "insert", "restrictions", "id", None, move |tx| {
+            tx.execute(
+                "INSERT INTO restrictions (id, student_number, record_type, name, reason, blocking, time_start, time_end, imposed_by, time_imposed) VALUES (NULL, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                (
+                    student_number,
+                    record_type.map(record_type_text),
+                    name,
+                    reason,
+                    blocking,
+                    time_start.timestamp(),
+                    time_end.map(|time| time.timestamp()),
+                    imposed_by,
+                    chrono::Utc::now().timestamp(),
+                )
+            )?;
+
+            Ok(tx.last_insert_rowid())
+        })?;
+
+        self.refresh()?;
+
+        Ok(())
+    }
+}
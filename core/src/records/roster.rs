@@ -0,0 +1,68 @@
+use std::path::Path;
+
+use calamine::Reader;
+
+use super::StorageError;
+
+/// The raw cells of a residence roster spreadsheet, before columns are mapped.
+#[derive(Debug, Clone, Default)]
+pub struct Roster {
+    pub rows: Vec<Vec<String>>,
+}
+
+/// A single student read from a roster.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RosterEntry {
+    pub student_number: String,
+    pub student_name: String,
+    pub room: Option<String>,
+    pub block: Option<String>,
+}
+
+impl Roster {
+    /// Read the first sheet of an XLSX/XLS/ODS workbook, or a CSV file.
+    pub fn read(path: &Path) -> Result<Roster, StorageError> {
+        let extension = path.extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        let rows: Vec<Vec<String>> = match extension.as_str() {
+            "xlsx" | "xlsm" | "xlsb" | "xls" | "ods" => {
+                let mut workbook = calamine::open_workbook_auto(path)?;
+
+                match workbook.worksheet_range_at(0) {
+                    Some(range) => range?.rows()
+                        .map(|row| row.iter().map(|cell| cell.to_string().trim().to_string()).collect())
+                        .collect(),
+                    None => vec![],
+                }
+            },
+            _ => {
+                let mut reader = csv::ReaderBuilder::new()
+                    .has_headers(false)
+                    .flexible(true)
+                    .from_path(path)
+                    .map_err(StorageError::ImportCsvError)?;
+
+                let mut rows = vec![];
+
+                for record in reader.records() {
+                    let record = record.map_err(StorageError::ImportCsvError)?;
+                    rows.push(record.iter().map(|cell| cell.trim().to_string()).collect());
+                }
+
+                rows
+            },
+        };
+
+        log::debug!("read roster with {} rows", rows.len());
+
+        Ok(Roster {
+            rows,
+        })
+    }
+
+    pub fn column_count(&self) -> usize {
+        self.rows.iter().map(|row| row.len()).max().unwrap_or(0)
+    }
+}
@@ -0,0 +1,286 @@
+use crate::worker::WorkerHandle;
+
+use rusqlite::OptionalExtension;
+
+use super::{StudentRecord, NewStudentRecord, RosterEntry, StorageError, Storage, InsertableStorage, DeletableStorage, HistoryRecord, StudentHistory, RecordType, parse_rows, parse_time, parse_optional_time, audit};
+
+pub struct StudentStorage {
+    worker: WorkerHandle,
+    records: Vec<StudentRecord>,
+}
+
+impl StudentStorage {
+    pub fn new(worker: WorkerHandle) -> Result<StudentStorage, StorageError> {
+        let mut storage = StudentStorage {
+            worker,
+            records: vec![],
+        };
+
+        storage.refresh()?;
+
+        Ok(storage)
+    }
+
+    pub fn update(&mut self, record: NewStudentRecord) -> Result<(), StorageError> {
+        let (student_number, student_name) = (record.student_number.to_uppercase(), record.student_name.to_owned());
+        let (room, block) = (record.room.map(str::to_owned), record.block.map(str::to_owned));
+
+        audit::audited(&self.worker, "update", "students", "student_number", Some(student_number.clone()), move |tx| {
+            tx.execute(
+                "UPDATE students SET student_name = ?, room = ?, block = ? WHERE student_number = ?",
+                (student_name, room, block, &student_number)
+            )?;
+
+            Ok(student_number)
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+
+    /// Add or update all students from a roster in a single transaction.
+    pub fn import(&mut self, entries: &[RosterEntry]) -> Result<(), StorageError> {
+        let count = entries.len();
+        let entries = entries.to_vec();
+
+        self.worker.call(move |connection| {
+            let tx = connection.transaction()?;
+
+            {
+                let mut stmt = tx.prepare(
+                    "INSERT INTO students (student_number, student_name, room, block) VALUES (?, ?, ?, ?)
+                        ON CONFLICT (student_number) DO UPDATE SET student_name = excluded.student_name, room = excluded.room, block = excluded.block"
+                )?;
+
+                for entry in &entries {
+                    let student_number = entry.student_number.to_uppercase();
+
+                    let before = audit::snapshot(&tx, "students", "student_number", &student_number)?;
+
+                    stmt.execute((&student_number, &entry.student_name, &entry.room, &entry.block))?;
+
+                    let after = audit::snapshot(&tx, "students", "student_number", &student_number)?;
+
+                    audit::log_entry(&tx, "import", "students", &student_number, before, after)?;
+                }
+            }
+
+            tx.commit()?;
+
+            Ok(())
+        })?;
+
+        log::info!("imported {count} students");
+
+        self.refresh()?;
+
+        Ok(())
+    }
+
+    /// Find all students whose number or name contains the search text.
+    pub fn search(&self, text: &str) -> impl Iterator<Item = &StudentRecord> {
+        let text = text.trim().to_uppercase();
+
+        self.records.iter()
+            .filter(move |s| s.student_number.contains(&text) || s.student_name.to_uppercase().contains(&text))
+    }
+}
+
+/// Condition on a record table's amended view, `alias` being the view's alias if any, for the records of the student
+/// numbered `?1`. They are picked from the table and from amendments by their indexes, which the view's amended student
+/// number cannot use, then checked against the view since the number may since have been amended away.
+fn of_student(table: &str, alias: &str) -> String {
+    format!("{alias}id IN (
+        SELECT id FROM {table} WHERE student_number = ?1
+        UNION SELECT record_id FROM amendments WHERE record_table = '{table}' AND field = 'student_number' AND new_value = ?1
+    ) AND {alias}student_number = ?1")
+}
+
+/// Records of every type involving a student, parcels are matched by the student's name since they have no number.
+fn history_query() -> String {
+    format!("
+        SELECT 'key' AS record_type, id, `key` AS name, 1 AS quantity, time_out, time_due, time_in, 1 AS returnable, voided FROM key_records_amended WHERE {}
+        UNION ALL
+        SELECT 'game', id, game, quantity, time_out, time_due, time_in, 1, voided FROM game_records_amended WHERE {}
+        UNION ALL
+        SELECT 'item', id, item, quantity, time_out, NULL, time_in, returnable, voided FROM item_records_amended WHERE {}
+        UNION ALL
+        SELECT 'parcel', id, parcel_desc, 1, time_in, NULL, time_out, 1, voided FROM parcel_records_amended WHERE ?2 != '' AND id IN (
+            SELECT id FROM parcel_records WHERE lower(student_name) = lower(?2)
+            UNION SELECT record_id FROM amendments WHERE record_table = 'parcel_records' AND field = 'student_name' AND lower(new_value) = lower(?2)
+        ) AND lower(student_name) = lower(?2)
+        ORDER BY time_out DESC, id DESC
+    ", of_student("key_records", ""), of_student("game_records", ""), of_student("item_records", ""))
+}
+
+impl StudentStorage {
+    /// Everything a student has signed out or received, named as in the registry, or as on their latest record if unregistered.
+    pub fn history(&self, student_number: &str) -> Result<StudentHistory, StorageError> {
+        let student_number = student_number.trim().to_uppercase();
+        let registered_name = self.get(&student_number).map(|student| student.student_name.clone());
+
+        self.worker.call(move |connection| {
+            let student_name = match registered_name {
+                Some(student_name) => student_name,
+                None => connection.query_row(
+                    &format!("SELECT student_name FROM (
+                        SELECT student_name, time_out FROM key_records_amended WHERE {}
+                        UNION ALL
+                        SELECT student_name, time_out FROM game_records_amended WHERE {}
+                        UNION ALL
+                        SELECT student_name, time_out FROM item_records_amended WHERE {}
+                    ) ORDER BY time_out DESC LIMIT 1", of_student("key_records", ""), of_student("game_records", ""), of_student("item_records", "")),
+                    (&student_number,),
+                    |row| row.get(0),
+                ).optional()?.unwrap_or_default(),
+            };
+
+            let mut stmt = connection.prepare(&history_query())?;
+
+            let rows = stmt.query((&student_number, student_name.trim()))?;
+            let (records, corrupt) = parse_rows("student history", rows, Self::parse_history_row)?;
+
+            Ok(StudentHistory { student_number, student_name, records, corrupt })
+        })
+    }
+
+    /// Keys, games, returnable items, and records of returnable categories the student has signed out and not yet returned.
+    /// Category records are named by their category, rather than their entry.
+    pub fn open_loans(&self, student_number: &str) -> Result<Vec<HistoryRecord>, StorageError> {
+        let student_number = student_number.trim().to_uppercase();
+
+        self.worker.call(move |connection| {
+            let mut stmt = connection.prepare(&format!("
+                SELECT * FROM (
+                    SELECT 'key' AS record_type, id, `key` AS name, 1 AS quantity, time_out, time_due, time_in, 1 AS returnable, voided FROM key_records_amended WHERE {}
+                    UNION ALL
+                    SELECT 'game', id, game, quantity, time_out, time_due, time_in, 1, voided FROM game_records_amended WHERE {}
+                    UNION ALL
+                    SELECT 'item', id, item, quantity, time_out, NULL, time_in, returnable, voided FROM item_records_amended WHERE {}
+                    UNION ALL
+                    SELECT 'category', r.id, c.name, r.quantity, r.time_out, NULL, r.time_in, c.returnable, r.voided FROM category_records_amended r
+                        JOIN categories c ON c.id = r.category_id WHERE {}
+                ) WHERE returnable AND time_in IS NULL AND NOT voided
+                ORDER BY time_out
+            ", of_student("key_records", ""), of_student("game_records", ""), of_student("item_records", ""), of_student("category_records", "r.")))?;
+
+            let rows = stmt.query((&student_number,))?;
+            let (records, _) = parse_rows("open loans", rows, Self::parse_history_row)?;
+
+            Ok(records)
+        })
+    }
+
+    fn parse_history_row(row: &rusqlite::Row) -> Result<HistoryRecord, rusqlite::Error> {
+        let record_type = match row.get_ref("record_type")?.as_str()? {
+            "key" => RecordType::Key,
+            "game" => RecordType::Game,
+            "item" => RecordType::Item,
+            "category" => RecordType::Category,
+            _ => RecordType::Parcel,
+        };
+
+        Ok(HistoryRecord {
+            record_type,
+            id: row.get("id")?,
+            name: row.get("name")?,
+            quantity: row.get("quantity")?,
+            time_out: parse_time(row, "time_out")?,
+            time_due: parse_optional_time(row, "time_due")?,
+            time_in: parse_optional_time(row, "time_in")?,
+            returnable: row.get("returnable")?,
+            voided: row.get("voided")?,
+        })
+    }
+}
+
+/// Add a student to the registry, or update their name if they are already registered.
+/// Used when signing out to a student, so that records always reference a registered student.
+pub(super) fn upsert_student(connection: &rusqlite::Connection, student_number: &str, student_name: &str) -> Result<(), rusqlite::Error> {
+    connection.execute(
+        "INSERT INTO students (student_number, student_name) VALUES (?, ?)
+            ON CONFLICT (student_number) DO UPDATE SET student_name = excluded.student_name",
+        (student_number.to_uppercase(), student_name)
+    )?;
+
+    Ok(())
+}
+
+impl Storage<StudentRecord, &str> for StudentStorage {
+    fn refresh(&mut self) -> Result<(), StorageError> {
+        self.records = self.worker.call(|connection| {
+            let mut stmt = connection.prepare("SELECT * FROM students ORDER BY student_number")?;
+        
+            let records = stmt
+                .query_map((), Self::parse_row)?
+                .collect::<Result<_, _>>()?;
+
+            Ok(records)
+        })?;
+
+        log::debug!("refreshed students");
+        
+        Ok(())
+    }
+
+    fn get_all(&self) -> &[StudentRecord] {
+        self.records.as_slice()
+    }
+
+    fn get(&self, id: &str) -> Option<&StudentRecord> {
+        let id = id.to_uppercase();
+        
+        self.records.binary_search_by(|r| r.student_number.cmp(&id)).ok()
+            .map(|i| &self.records[i])
+    }
+
+    fn parse_row(row: &rusqlite::Row) -> Result<StudentRecord, rusqlite::Error> {
+        Ok(StudentRecord {
+            student_number: row.get("student_number")?,
+            student_name: row.get("student_name")?,
+            room: row.get("room")?,
+            block: row.get("block")?,
+        })
+    }
+}
+
+impl InsertableStorage<NewStudentRecord<'_>, &str> for StudentStorage {
+    fn insert(&mut self, record: NewStudentRecord) -> Result<(), StorageError> {
+        let (student_number, student_name) = (record.student_number.to_uppercase(), record.student_name.to_owned());
+        let (room, block) = (record.room.map(str::to_owned), record.block.map(str::to_owned));
+
+        audit::audited(&self.worker, "insert", "students", "student_number", None, move |tx| {
+            tx.execute(
+                "INSERT INTO students (student_number, student_name, room, block) VALUES (?, ?, ?, ?)",
+                (&student_number, student_name, room, block)
+            )?;
+
+            Ok(student_number)
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+}
+
+impl DeletableStorage<StudentRecord, &str> for StudentStorage {
+    /// Fails if the student is referenced by any records.
+    fn delete(&mut self, student_number: &str) -> Result<(), StorageError> {
+        let student_number = student_number.to_owned();
+
+        audit::audited(&self.worker, "delete", "students", "student_number", Some(student_number.clone()), move |tx| {
+            tx.execute(
+                "DELETE FROM students WHERE student_number = ?",
+                (&student_number,)
+            )?;
+
+            Ok(student_number)
+        })?;
+
+        self.refresh()?;
+        
+        Ok(())
+    }
+}
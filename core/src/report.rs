@@ -0,0 +1,542 @@
+use std::{fs::File, io::BufWriter, path::Path};
+
+use printpdf::{Mm, PdfDocument, PdfDocumentReference, PdfLayerReference, IndirectFontRef, BuiltinFont, Rect, Line, Point, Color, Rgb, path::PaintMode};
+
+use crate::{APP_NAME, DATE_TIME_FORMAT, records::{Activity, Period, RecordType, StorageError}};
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+const LINE_HEIGHT: f32 = 5.0;
+const CHART_HEIGHT: f32 = 30.0;
+const HEATMAP_CELL_SIZE: f32 = 6.5;
+/// Left edge of the count columns.
+const COUNT_X: f32 = 130.0;
+const PREVIOUS_X: f32 = 160.0;
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Counts of records signed out over one period.
+#[derive(Debug, Clone, Default)]
+pub struct Totals {
+    pub key_summary: Vec<(String, i64)>,
+    pub parcel_count: i64,
+    pub game_summary: Vec<(String, i64)>,
+    pub item_summary: Vec<(String, i64)>,
+    /// Name of each custom category, with the count of each of its entries.
+    pub category_summaries: Vec<(String, Vec<(String, i64)>)>,
+    /// Records signed out by day and hour, for each type of record.
+    pub activity: Vec<(RecordType, Activity)>,
+    /// Records signed out by day and hour, for each custom category.
+    pub category_activity: Vec<(String, Activity)>,
+}
+
+impl Totals {
+    /// Activity of one type of record, or of every type and category if `None`.
+    pub fn activity(&self, record_type: Option<RecordType>) -> Option<Activity> {
+        let categories = self.category_activity.iter()
+            .filter(|_| record_type.is_none())
+            .map(|(_, activity)| activity);
+
+        let mut activities = self.activity.iter()
+            .filter(|(t, _)| record_type.is_none() || record_type == Some(*t))
+            .map(|(_, activity)| activity)
+            .chain(categories);
+
+        let mut total = activities.next()?.clone();
+        activities.for_each(|activity| total.add(activity));
+
+        Some(total)
+    }
+
+    /// Names and counts of each summarised type of record and category, parcels have no names.
+    pub fn sections(&self) -> Vec<(&str, &[(String, i64)])> {
+        let mut sections: Vec<(&str, &[(String, i64)])> = vec![
+            ("Keys", &self.key_summary),
+            ("Games", &self.game_summary),
+            ("Items", &self.item_summary),
+        ];
+
+        sections.extend(self.category_summaries.iter().map(|(name, summary)| (name.as_str(), summary.as_slice())));
+
+        sections
+    }
+
+    /// Counts of the section titled `title`, empty if there is none, e.g. a category added since.
+    pub fn section(&self, title: &str) -> &[(String, i64)] {
+        self.sections().into_iter()
+            .find(|(t, _)| *t == title)
+            .map(|(_, rows)| rows)
+            .unwrap_or_default()
+    }
+
+    /// Daily and hourly activity titled by type of record or category.
+    pub fn activities(&self) -> Vec<(String, &Activity)> {
+        self.activity.iter()
+            .map(|(record_type, activity)| (format!("{record_type}s"), activity))
+            .chain(self.category_activity.iter().map(|(name, activity)| (name.clone(), activity)))
+            .collect()
+    }
+}
+
+/// Pair up the counts of each name in two periods, with names from either.
+pub fn compare(current: &[(String, i64)], previous: &[(String, i64)]) -> Vec<(String, i64, i64)> {
+    let mut counts = std::collections::BTreeMap::new();
+
+    for (name, count) in current {
+        counts.entry(name.clone()).or_insert((0, 0)).0 += count;
+    }
+
+    for (name, count) in previous {
+        counts.entry(name.clone()).or_insert((0, 0)).1 += count;
+    }
+
+    counts.into_iter()
+        .map(|(name, (current, previous))| (name, current, previous))
+        .collect()
+}
+
+/// Summary of a period, written out for the house committee.
+#[derive(Debug, Clone)]
+pub struct Report<'a> {
+    pub facility_name: &'a str,
+    pub period: Period,
+    pub totals: &'a Totals,
+    /// Totals of the previous period, if they are compared.
+    pub previous: Option<&'a Totals>,
+    /// Keys and games signed out and signed in by each receptionist.
+    pub receptionists: &'a [(String, i64, i64)],
+    pub generated: chrono::DateTime<chrono::Local>,
+}
+
+impl Report<'_> {
+    fn title(&self) -> String {
+        match self.facility_name.trim() {
+            "" => format!("{APP_NAME} Summary"),
+            name => format!("{name} Summary"),
+        }
+    }
+
+    /// Counts of each name in a section, with the previous period's count if compared.
+    fn rows(&self, current: &[(String, i64)], previous: impl Fn(&Totals) -> &[(String, i64)]) -> Vec<(String, i64, Option<i64>)> {
+        match self.previous {
+            Some(totals) => compare(current, previous(totals)).into_iter()
+                .map(|(name, count, previous)| (name, count, Some(previous)))
+                .collect(),
+            None => current.iter().map(|(name, count)| (name.clone(), *count, None)).collect(),
+        }
+    }
+
+    /// Write the report as rows of section, name, count, and the previous period's count.
+    pub fn write_csv(&self, path: &Path) -> Result<(), StorageError> {
+        let mut writer = csv::WriterBuilder::new().flexible(true).from_path(path)?;
+
+        let optional = |count: Option<i64>| count.map(|count| count.to_string()).unwrap_or_default();
+
+        writer.write_record(["Facility", self.facility_name])?;
+        writer.write_record(["Period", &self.period.to_string(), &self.period.from.to_string(), &self.period.to.to_string()])?;
+
+        if self.previous.is_some() {
+            let previous = self.period.previous();
+            writer.write_record(["Previous Period", &previous.to_string(), &previous.from.to_string(), &previous.to.to_string()])?;
+        }
+
+        writer.write_record(["Generated", &self.generated.to_rfc3339()])?;
+        writer.write_record(["Section", "Name", "Count", "Previous"])?;
+
+        let sections = self.totals.sections();
+
+        for (title, current) in &sections {
+            let rows = self.rows(current, |totals| totals.section(title));
+
+            let total = rows.iter().map(|(_, count, _)| count).sum::<i64>();
+            let previous = self.previous.map(|_| rows.iter().filter_map(|(_, _, previous)| *previous).sum::<i64>());
+
+            writer.write_record([*title, "Total", &total.to_string(), &optional(previous)])?;
+
+            for (name, count, previous) in &rows {
+                writer.write_record([*title, name, &count.to_string(), &optional(*previous)])?;
+            }
+        }
+
+        writer.write_record(["Parcels", "Total", &self.totals.parcel_count.to_string(), &optional(self.previous.map(|totals| totals.parcel_count))])?;
+
+        for (receptionist, signed_out, signed_in) in self.receptionists {
+            writer.write_record(["Signed Out By", receptionist, &signed_out.to_string(), ""])?;
+            writer.write_record(["Signed In By", receptionist, &signed_in.to_string(), ""])?;
+        }
+
+        let previous_activities = self.previous.map(Totals::activities).unwrap_or_default();
+
+        for (title, activity) in self.totals.activities() {
+            let previous = previous_activities.iter().find(|(t, _)| *t == title).map(|(_, activity)| *activity);
+
+            for (day, (date, count)) in activity.daily.iter().enumerate() {
+                let previous = previous.and_then(|previous| previous.daily.get(day)).map(|(_, count)| *count);
+                writer.write_record([&format!("{title} by Day"), &date.to_string(), &count.to_string(), &optional(previous)])?;
+            }
+        }
+
+        if let Some(activity) = self.totals.activity(None) {
+            for (weekday, hours) in activity.hourly.iter().enumerate() {
+                for (hour, count) in hours.iter().enumerate() {
+                    writer.write_record(["Records by Hour", &format!("{} {hour:02}:00", WEEKDAYS[weekday]), &count.to_string(), ""])?;
+                }
+            }
+        }
+
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Write the report as an A4 document with the totals, a chart of each day, and a heatmap of the week.
+    pub fn write_pdf(&self, path: &Path) -> Result<(), StorageError> {
+        let title = self.title();
+
+        let mut pdf = PdfWriter::new(&title)?;
+
+        pdf.text(&title, 18.0, true);
+        pdf.text(&self.period.to_string(), 12.0, false);
+
+        if self.previous.is_some() {
+            pdf.text(&format!("Compared with {}", self.period.previous()), 10.0, false);
+        }
+
+        pdf.text(&format!("Generated {}", self.generated.format(DATE_TIME_FORMAT)), 9.0, false);
+        pdf.space(LINE_HEIGHT);
+
+        if self.previous.is_some() {
+            pdf.row(&["", "Count", "Previous"], true);
+        }
+
+        let sections = self.totals.sections();
+
+        for (title, current) in &sections {
+            let rows = self.rows(current, |totals| totals.section(title));
+
+            let total = rows.iter().map(|(_, count, _)| count).sum::<i64>();
+            let previous = self.previous.map(|_| rows.iter().filter_map(|(_, _, previous)| *previous).sum::<i64>());
+
+            pdf.row(&[title, &total.to_string(), &previous.map(|count| count.to_string()).unwrap_or_default()], true);
+
+            for (name, count, previous) in &rows {
+                pdf.row(&[name, &count.to_string(), &previous.map(|count| count.to_string()).unwrap_or_default()], false);
+            }
+        }
+
+        let previous_parcels = self.previous.map(|totals| totals.parcel_count.to_string()).unwrap_or_default();
+        pdf.row(&["Parcels", &self.totals.parcel_count.to_string(), &previous_parcels], true);
+
+        if !self.receptionists.is_empty() {
+            pdf.space(LINE_HEIGHT);
+            pdf.row(&["Receptionist", "Signed Out", "Signed In"], true);
+
+            for (receptionist, signed_out, signed_in) in self.receptionists {
+                pdf.row(&[receptionist, &signed_out.to_string(), &signed_in.to_string()], false);
+            }
+        }
+
+        let previous_activities = self.previous.map(Totals::activities).unwrap_or_default();
+
+        for (title, activity) in self.totals.activities() {
+            let previous = previous_activities.iter().find(|(t, _)| *t == title).map(|(_, activity)| *activity);
+
+            pdf.space(LINE_HEIGHT);
+            pdf.keep(LINE_HEIGHT * 2.0 + CHART_HEIGHT);
+            pdf.text(&format!("{title} Signed Out per Day"), 11.0, true);
+            pdf.chart(&activity.daily, previous.map(|previous| previous.daily.as_slice()));
+        }
+
+        if let Some(activity) = self.totals.activity(None) {
+            pdf.space(LINE_HEIGHT);
+            pdf.keep(LINE_HEIGHT * 2.0 + HEATMAP_CELL_SIZE * 8.0);
+            pdf.text("Records Signed Out by Hour", 11.0, true);
+            pdf.heatmap(&activity.hourly);
+        }
+
+        pdf.save(path)
+    }
+}
+
+/// Lays out a document from the top of the page down, starting a new page when one is full.
+struct PdfWriter {
+    document: PdfDocumentReference,
+    layer: PdfLayerReference,
+    font: IndirectFontRef,
+    bold: IndirectFontRef,
+    /// Distance of the next line from the bottom of the page, in mm.
+    y: f32,
+}
+
+impl PdfWriter {
+    fn new(title: &str) -> Result<PdfWriter, StorageError> {
+        let (document, page, layer) = PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Report");
+
+        let font = document.add_builtin_font(BuiltinFont::Helvetica)?;
+        let bold = document.add_builtin_font(BuiltinFont::HelveticaBold)?;
+        let layer = document.get_page(page).get_layer(layer);
+
+        Ok(PdfWriter { document, layer, font, bold, y: PAGE_HEIGHT - MARGIN })
+    }
+
+    /// Start a new page unless there is `height` left on this one.
+    fn keep(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            let (page, layer) = self.document.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Report");
+            self.layer = self.document.get_page(page).get_layer(layer);
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+    }
+
+    fn space(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    fn text(&mut self, text: &str, size: f32, bold: bool) {
+        // points to mm, with some room between lines
+        let height = size * 0.3528 * 1.4;
+
+        self.keep(height);
+        self.y -= height;
+
+        let font = if bold { &self.bold } else { &self.font };
+        self.layer.set_fill_color(grey(0.0));
+        self.layer.use_text(text, size, Mm(MARGIN), Mm(self.y), font);
+    }
+
+    /// A line of the totals table, with the name and up to two counts.
+    fn row(&mut self, cells: &[&str], bold: bool) {
+        self.keep(LINE_HEIGHT);
+        self.y -= LINE_HEIGHT;
+
+        let font = if bold { &self.bold } else { &self.font };
+        self.layer.set_fill_color(grey(0.0));
+
+        for (cell, x) in cells.iter().zip([MARGIN, COUNT_X, PREVIOUS_X]) {
+            self.layer.use_text(*cell, 10.0, Mm(x), Mm(self.y), font);
+        }
+    }
+
+    /// Bar chart of a count for each day, with the previous period outlined behind, day for day.
+    fn chart(&mut self, daily: &[(chrono::NaiveDate, i64)], previous: Option<&[(chrono::NaiveDate, i64)]>) {
+        self.keep(CHART_HEIGHT + LINE_HEIGHT);
+
+        let left = MARGIN + 10.0;
+        let width = PAGE_WIDTH - MARGIN - left;
+        let bottom = self.y - CHART_HEIGHT;
+
+        let max = daily.iter()
+            .chain(previous.unwrap_or_default())
+            .map(|(_, count)| *count)
+            .max()
+            .unwrap_or_default()
+            .max(1);
+
+        self.layer.set_fill_color(grey(0.0));
+        self.layer.use_text(max.to_string(), 7.0, Mm(MARGIN), Mm(self.y - 2.5), &self.font);
+        self.layer.use_text("0", 7.0, Mm(MARGIN), Mm(bottom), &self.font);
+
+        self.layer.set_outline_color(grey(0.6));
+        self.layer.set_outline_thickness(0.5);
+        self.layer.add_line(line(&[(left, bottom), (left + width, bottom)]));
+
+        if daily.is_empty() {
+            self.y = bottom - LINE_HEIGHT;
+            return;
+        }
+
+        let slot = width / daily.len() as f32;
+        let bar = slot * 0.8;
+        let height = |count: i64| CHART_HEIGHT * count as f32 / max as f32;
+
+        if let Some(previous) = previous {
+            for (i, (_, count)) in previous.iter().take(daily.len()).enumerate() {
+                if *count > 0 {
+                    let x = left + slot * i as f32 + (slot - bar) / 2.0;
+                    self.layer.add_rect(Rect::new(Mm(x), Mm(bottom), Mm(x + bar), Mm(bottom + height(*count))).with_mode(PaintMode::Stroke));
+                }
+            }
+        }
+
+        self.layer.set_fill_color(Color::Rgb(Rgb::new(0.2, 0.4, 0.7, None)));
+
+        for (i, (_, count)) in daily.iter().enumerate() {
+            if *count > 0 {
+                let x = left + slot * i as f32 + slot / 2.0 - bar / 4.0;
+                self.layer.add_rect(Rect::new(Mm(x), Mm(bottom), Mm(x + bar / 2.0), Mm(bottom + height(*count))));
+            }
+        }
+
+        self.layer.set_fill_color(grey(0.0));
+
+        let (first, _) = daily[0];
+        let (last, _) = daily[daily.len() - 1];
+        self.layer.use_text(first.format("%d %b").to_string(), 7.0, Mm(left), Mm(bottom - 3.0), &self.font);
+        self.layer.use_text(last.format("%d %b").to_string(), 7.0, Mm(left + width - 8.0), Mm(bottom - 3.0), &self.font);
+
+        self.y = bottom - LINE_HEIGHT;
+    }
+
+    /// Grid of counts for each hour of each weekday, darker the busier.
+    fn heatmap(&mut self, hourly: &[[i64; 24]; 7]) {
+        self.keep(HEATMAP_CELL_SIZE * 8.0);
+
+        let left = MARGIN + 10.0;
+        let top = self.y - LINE_HEIGHT;
+
+        let max = hourly.iter().flatten().copied().max().unwrap_or_default().max(1);
+
+        self.layer.set_fill_color(grey(0.0));
+
+        for hour in (0..24).step_by(3) {
+            self.layer.use_text(format!("{hour:02}"), 7.0, Mm(left + HEATMAP_CELL_SIZE * hour as f32), Mm(top + 1.0), &self.font);
+        }
+
+        for (weekday, hours) in hourly.iter().enumerate() {
+            let y = top - HEATMAP_CELL_SIZE * (weekday + 1) as f32;
+
+            self.layer.set_fill_color(grey(0.0));
+            self.layer.use_text(WEEKDAYS[weekday], 7.0, Mm(MARGIN), Mm(y + 2.0), &self.font);
+
+            for (hour, count) in hours.iter().enumerate() {
+                let x = left + HEATMAP_CELL_SIZE * hour as f32;
+                let shade = 1.0 - 0.85 * *count as f32 / max as f32;
+
+                self.layer.set_fill_color(Color::Rgb(Rgb::new(shade, shade, 1.0 - 0.3 * (1.0 - shade), None)));
+                self.layer.add_rect(Rect::new(Mm(x + 0.3), Mm(y + 0.3), Mm(x + HEATMAP_CELL_SIZE - 0.3), Mm(y + HEATMAP_CELL_SIZE - 0.3)));
+            }
+        }
+
+        self.y = top - HEATMAP_CELL_SIZE * 7.0 - LINE_HEIGHT;
+    }
+
+    fn save(self, path: &Path) -> Result<(), StorageError> {
+        let file = File::create(path)?;
+
+        self.document.save(&mut BufWriter::new(file))?;
+
+        Ok(())
+    }
+}
+
+fn grey(level: f32) -> Color {
+    Color::Rgb(Rgb::new(level, level, level, None))
+}
+
+fn line(points: &[(f32, f32)]) -> Line {
+    Line {
+        points: points.iter().map(|(x, y)| (Point::new(Mm(*x), Mm(*y)), false)).collect(),
+        is_closed: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn totals(period: &Period, keys: &[(&str, i64)], parcels: i64) -> Totals {
+        let time = crate::records::local_midnight(period.from) + chrono::Duration::hours(9);
+        let signed_out = keys.iter().map(|(_, count)| count).sum();
+
+        Totals {
+            key_summary: keys.iter().map(|(name, count)| (name.to_string(), *count)).collect(),
+            parcel_count: parcels,
+            category_summaries: vec![("Laundry".into(), vec![("Iron".into(), 1)])],
+            activity: vec![(RecordType::Key, Activity::new(period, &[(time, signed_out)]))],
+            category_activity: vec![("Laundry".into(), Activity::new(period, &[(time, 1)]))],
+            ..Default::default()
+        }
+    }
+
+    fn report_path(extension: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("{APP_NAME}_report_test_{}.{extension}", std::process::id()))
+    }
+
+    #[test]
+    fn compare_includes_names_from_either_period() {
+        let current = [("A1".to_string(), 2), ("B2".to_string(), 1)];
+        let previous = [("A1".to_string(), 3), ("C3".to_string(), 4)];
+
+        assert_eq!(compare(&current, &previous), [("A1".to_string(), 2, 3), ("B2".to_string(), 1, 0), ("C3".to_string(), 0, 4)]);
+    }
+
+    #[test]
+    fn activity_adds_categories_only_to_every_type() {
+        let period = Period::custom(chrono::NaiveDate::from_ymd_opt(2026, 3, 1).unwrap(), chrono::NaiveDate::from_ymd_opt(2026, 3, 7).unwrap());
+        let totals = totals(&period, &[("A1", 2)], 0);
+
+        assert_eq!(totals.activity(Some(RecordType::Key)).unwrap().total(), 2);
+        assert_eq!(totals.activity(None).unwrap().total(), 3);
+        assert!(totals.activity(Some(RecordType::Game)).is_none());
+        assert_eq!(totals.section("Laundry"), [("Iron".to_string(), 1)]);
+        assert!(totals.section("Removed").is_empty());
+    }
+
+    #[test]
+    fn csv_report_compares_with_the_previous_period() {
+        let period = Period::custom(chrono::NaiveDate::from_ymd_opt(2026, 3, 1).unwrap(), chrono::NaiveDate::from_ymd_opt(2026, 3, 7).unwrap());
+        let current = totals(&period, &[("A1", 2), ("B2", 1)], 5);
+        let previous = totals(&period.previous(), &[("A1", 4)], 3);
+        let receptionists = [("Reception".to_string(), 3, 2)];
+
+        let report = Report {
+            facility_name: "Residence",
+            period,
+            totals: &current,
+            previous: Some(&previous),
+            receptionists: &receptionists,
+            generated: chrono::Local::now(),
+        };
+
+        let path = report_path("csv");
+        report.write_csv(&path).unwrap();
+
+        let rows = csv::ReaderBuilder::new().has_headers(false).flexible(true).from_path(&path).unwrap()
+            .records()
+            .map(|record| record.unwrap().iter().map(str::to_owned).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        std::fs::remove_file(&path).unwrap();
+
+        let find = |section: &str, name: &str| rows.iter()
+            .find(|row| row[0] == section && row[1] == name)
+            .unwrap_or_else(|| panic!("no row {section} {name} in {rows:?}"))[2..]
+            .to_vec();
+
+        assert_eq!(rows[0], ["Facility", "Residence"]);
+        assert_eq!(rows[2][2..], ["2026-02-22", "2026-02-28"]);
+        assert_eq!(find("Keys", "Total"), ["3", "4"]);
+        assert_eq!(find("Keys", "B2"), ["1", "0"]);
+        assert_eq!(find("Laundry", "Iron"), ["1", "1"]);
+        assert_eq!(find("Parcels", "Total"), ["5", "3"]);
+        assert_eq!(find("Signed In By", "Reception"), ["2", ""]);
+        assert_eq!(find("Keys by Day", "2026-03-01"), ["3", "4"]);
+        assert_eq!(find("Records by Hour", &format!("{} 09:00", WEEKDAYS[6])), ["4", ""]);
+        assert_eq!(rows.iter().filter(|row| row[0] == "Records by Hour").count(), 7 * 24);
+    }
+
+    #[test]
+    fn pdf_report_is_written() {
+        let period = Period::containing(crate::records::PeriodKind::Month, chrono::NaiveDate::from_ymd_opt(2026, 3, 1).unwrap());
+        let current = totals(&period, &[("A1", 2)], 1);
+
+        let report = Report {
+            facility_name: "",
+            period,
+            totals: &current,
+            previous: None,
+            receptionists: &[],
+            generated: chrono::Local::now(),
+        };
+
+        assert_eq!(report.title(), format!("{APP_NAME} Summary"));
+
+        let path = report_path("pdf");
+        report.write_pdf(&path).unwrap();
+
+        let contents = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(contents.starts_with(b"%PDF"));
+    }
+}
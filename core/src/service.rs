@@ -0,0 +1,988 @@
+//! Rules for signing records in and out, shared by every front-end. Each operation checks its
+//! input against the catalogue and the open records before writing through the storages.
+
+use std::fmt::Display;
+
+use thiserror::Error;
+
+use crate::{records::{StorageError, Storage, InsertableStorage, SignableStorage, ReceptionistSignableStorage, AmendableStorage, KeyTypeStorage, KeyStorage, NewKeyRecord, GameTypeStorage, GameStorage, NewGameRecord, ItemTypeStorage, ItemStorage, NewItemRecord, ParcelStorage, NewParcelRecord, StudentStorage, ReceptionistStorage, RestrictionStorage, RestrictionRecord, NewOverrideRecord, LoanLimitStorage, LoanLimit, HistoryRecord, RecordType, CategoryStorage, CategoryRecordStorage, NewCategoryRecord, FieldKind}, NAME_MAX_LENGTH, NOTES_MAX_LENGTH, STUDENT_NUMBER_LENGTH, STAFF_NUMBER_LENGTH, MAX_QUANTITY, DATE_TIME_FORMAT};
+
+/// Part of an entry which a validation error applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    /// The key, game, item, or parcel description.
+    Item,
+    Quantity,
+    StudentName,
+    StudentNumber,
+    Notes,
+    /// Why a restriction was overridden.
+    Justification,
+    /// Why a record was amended or voided.
+    Reason,
+    /// A field of a custom category, by position.
+    Custom(usize),
+}
+
+impl Display for Field {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Field::Item => f.write_str("Item"),
+            Field::Quantity => f.write_str("Quantity"),
+            Field::StudentName => f.write_str("Student Name"),
+            Field::StudentNumber => f.write_str("Student Number"),
+            Field::Notes => f.write_str("Notes"),
+            Field::Justification => f.write_str("Justification"),
+            Field::Reason => f.write_str("Reason"),
+            Field::Custom(i) => write!(f, "Field {}", i + 1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ValidationError {
+    #[error("Required")]
+    Required,
+    #[error("Too long. (> {0} characters)")]
+    TooLong(usize),
+    #[error("Invalid student number.")]
+    InvalidStudentNumber,
+    #[error("Invalid staff number.")]
+    InvalidStaffNumber,
+    #[error("Not in the catalogue.")]
+    NotInCatalogue,
+    #[error("Must be a number from 1 to {MAX_QUANTITY}.")]
+    InvalidQuantity,
+    #[error("Must be a number.")]
+    NotANumber,
+    #[error("Key already signed out on {}.", .0.with_timezone(&chrono::Local).format(DATE_TIME_FORMAT))]
+    KeyAlreadyOut(chrono::DateTime<chrono::Utc>),
+    #[error("Not enough in stock. ({available} available)")]
+    InsufficientStock { available: i64 },
+}
+
+#[derive(Debug, Error)]
+pub enum ServiceError {
+    #[error("{}", describe_invalid(.0))]
+    Invalid(Vec<(Field, ValidationError)>),
+    #[error("Record does not exist.")]
+    NotFound,
+    #[error("Record has been voided.")]
+    Voided,
+    #[error("Record has already been signed in.")]
+    AlreadySignedIn,
+    #[error("Record is not returnable.")]
+    NotReturnable,
+    /// The student is barred from signing this out, unless the restrictions are overridden.
+    #[error("{}", describe_restrictions(.0))]
+    Restricted(Vec<RestrictionRecord>),
+    #[error("Only an admin receptionist can override restrictions.")]
+    OverrideNotAllowed,
+    /// The student already has as many records out as a limit allows.
+    #[error("{}", describe_limit(.limit, .open))]
+    LimitReached { limit: LoanLimit, open: Vec<HistoryRecord> },
+    #[error(transparent)]
+    StorageError(#[from] StorageError),
+}
+
+impl ServiceError {
+    /// Whether the database failed, rather than the entry being refused.
+    pub fn is_failure(&self) -> bool {
+        match self {
+            ServiceError::StorageError(err) => !err.is_conflict(),
+            _ => false,
+        }
+    }
+
+    /// Message to show beside a field of the entry, if it is invalid.
+    pub fn field_error(&self, field: Field) -> Option<String> {
+        match self {
+            ServiceError::Invalid(errors) => errors.iter()
+                .find(|(f, _)| *f == field)
+                .map(|(_, error)| error.to_string()),
+            // the database refused the record, e.g. another receptionist signed out the same key first
+            ServiceError::StorageError(err @ StorageError::KeyAlreadyOut) if field == Field::Item => Some(err.to_string()),
+            ServiceError::StorageError(err @ StorageError::InsufficientStock) if field == Field::Quantity => Some(err.to_string()),
+            ServiceError::LimitReached { .. } if field == Field::StudentNumber => Some(self.to_string()),
+            ServiceError::OverrideNotAllowed if field == Field::Justification => Some(self.to_string()),
+            _ => None,
+        }
+    }
+}
+
+fn describe_invalid(errors: &[(Field, ValidationError)]) -> String {
+    errors.iter()
+        .map(|(field, error)| format!("{field}: {error}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn describe_restrictions(restrictions: &[RestrictionRecord]) -> String {
+    restrictions.iter()
+        .map(|restriction| format!("Restricted. {restriction}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn describe_limit(limit: &LoanLimit, open: &[HistoryRecord]) -> String {
+    let open = open.iter()
+        .map(|record| match record.quantity {
+            1 => format!("{} {}", record.record_type, record.name),
+            quantity => format!("{quantity} × {} {}", record.record_type, record.name),
+        })
+        .collect::<Vec<_>>();
+
+    match open.len() {
+        0 => format!("Limit of {} for {} reached.", limit.max_open, limit.scope().to_lowercase()),
+        _ => format!("Limit of {} for {} reached. Currently out: {}.", limit.max_open, limit.scope().to_lowercase(), open.join(", ")),
+    }
+}
+
+pub fn check_required(value: &str) -> Result<(), ValidationError> {
+    if value.is_empty() {
+        Err(ValidationError::Required)
+    } else {
+        Ok(())
+    }
+}
+
+pub fn check_length(value: &str, max: usize) -> Result<(), ValidationError> {
+    if value.len() > max {
+        Err(ValidationError::TooLong(max))
+    } else {
+        Ok(())
+    }
+}
+
+/// Accepts student numbers (e.g. ABCDEF001) and staff numbers (eight digits).
+pub fn check_student_number(student_number: &str) -> Result<(), ValidationError> {
+    match student_number.parse::<i64>() {
+        // Maybe Staff Number
+        Ok(_) => {
+            if student_number.len() != STAFF_NUMBER_LENGTH {
+                return Err(ValidationError::InvalidStaffNumber);
+            }
+        },
+        // Maybe Student Number
+        Err(_) => {
+            if student_number.len() != STUDENT_NUMBER_LENGTH ||
+                    !student_number.chars().enumerate().all(|(i, c)| i < 6 && c.is_ascii_alphabetic() || i >= 6 && c.is_ascii_digit()) {
+                return Err(ValidationError::InvalidStudentNumber);
+            }
+        },
+    }
+
+    Ok(())
+}
+
+/// Collects the validation errors of an entry, so that all of them can be shown at once.
+#[derive(Debug, Default)]
+struct Checks(Vec<(Field, ValidationError)>);
+
+impl Checks {
+    fn check(&mut self, field: Field, result: Result<(), ValidationError>) {
+        if let Err(error) = result {
+            self.0.push((field, error));
+        }
+    }
+
+    fn required_text(&mut self, field: Field, value: &str, max: usize) {
+        self.check(field, check_required(value).and_then(|_| check_length(value, max)));
+    }
+
+    fn student(&mut self, student_name: &str, student_number: &str) {
+        self.required_text(Field::StudentName, student_name, NAME_MAX_LENGTH);
+        self.check(Field::StudentNumber, check_required(student_number).and_then(|_| check_student_number(student_number)));
+    }
+
+    fn notes(&mut self, notes: &str) {
+        self.check(Field::Notes, check_length(notes, NOTES_MAX_LENGTH));
+    }
+
+    fn is_valid(&self, field: Field) -> bool {
+        !self.0.iter().any(|(f, _)| *f == field)
+    }
+
+    fn finish(self) -> Result<(), ServiceError> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(ServiceError::Invalid(self.0))
+        }
+    }
+}
+
+/// The student registry, the restrictions and loan limits which every record signed out to a student is checked against,
+/// and the receptionists, only admins among whom may override restrictions.
+pub struct SignOut<'a> {
+    pub students: &'a mut StudentStorage,
+    pub restrictions: &'a RestrictionStorage,
+    pub limits: &'a LoanLimitStorage,
+    pub receptionists: &'a ReceptionistStorage,
+}
+
+/// Refuse to sign out `quantity` of `name` to the student if it would take them over a limit on open loans.
+fn check_loan_limits(sign_out: &SignOut, student_number: &str, record_type: RecordType, name: &str, quantity: i64) -> Result<(), ServiceError> {
+    let applicable = sign_out.limits.applicable(record_type, name);
+
+    if applicable.is_empty() {
+        return Ok(());
+    }
+
+    let open = sign_out.students.open_loans(student_number)?;
+
+    for limit in applicable {
+        let out: i64 = open.iter()
+            .filter(|record| limit.applies_to(record.record_type, &record.name))
+            .map(|record| record.quantity)
+            .sum();
+
+        if out + quantity > limit.max_open {
+            return Err(ServiceError::LimitReached { limit: limit.clone(), open });
+        }
+    }
+
+    Ok(())
+}
+
+/// Refuse to sign out `name` to the student while a blocking restriction is in force, unless an admin overrides
+/// it with a justification. Returns the restrictions overridden, to be logged along with the record.
+fn check_restrictions(sign_out: &SignOut, student_number: &str, record_type: RecordType, name: &str, receptionist: &str, justification: Option<&str>) -> Result<Vec<i64>, ServiceError> {
+    let blocking = sign_out.restrictions.applicable(student_number, record_type, name).into_iter()
+        .filter(|restriction| restriction.blocking)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    if blocking.is_empty() {
+        return Ok(vec![]);
+    }
+
+    match justification.map(str::trim) {
+        Some(justification) if !justification.is_empty() => {
+            let mut checks = Checks::default();
+            checks.check(Field::Justification, check_length(justification, NOTES_MAX_LENGTH));
+            checks.finish()?;
+
+            if !sign_out.receptionists.is_admin(receptionist) {
+                return Err(ServiceError::OverrideNotAllowed);
+            }
+
+            Ok(blocking.iter().map(|restriction| restriction.id).collect())
+        },
+        _ => Err(ServiceError::Restricted(blocking)),
+    }
+}
+
+/// Blocking restrictions are overridden if a `justification` is given by an admin, which is logged against them.
+pub fn sign_out_key(key_types: &KeyTypeStorage, key_records: &mut KeyStorage, sign_out: &mut SignOut, record: NewKeyRecord, justification: Option<&str>) -> Result<(), ServiceError> {
+    let record = NewKeyRecord {
+        key: record.key.trim(),
+        student_name: record.student_name.trim(),
+        student_number: record.student_number.trim(),
+        receptionist: record.receptionist,
+        notes: record.notes.trim(),
+    };
+
+    let mut checks = Checks::default();
+
+    checks.required_text(Field::Item, record.key, NAME_MAX_LENGTH);
+
+    if checks.is_valid(Field::Item) {
+        if key_types.get(record.key).is_none() {
+            checks.check(Field::Item, Err(ValidationError::NotInCatalogue));
+        } else if let Some(signed_out) = key_records.get_signed_out(record.key)? {
+            checks.check(Field::Item, Err(ValidationError::KeyAlreadyOut(signed_out.time_out)));
+        }
+    }
+
+    checks.student(record.student_name, record.student_number);
+    checks.notes(record.notes);
+    checks.finish()?;
+
+    check_loan_limits(sign_out, record.student_number, RecordType::Key, record.key, 1)?;
+
+    let overridden = check_restrictions(sign_out, record.student_number, RecordType::Key, record.key, record.receptionist, justification)?;
+
+    let justification = format!("{}: {}", record.student_number, justification.unwrap_or_default().trim());
+    let overrides = NewOverrideRecord { restrictions: &overridden, justification: &justification, receptionist: record.receptionist };
+
+    key_records.insert_with_overrides(record, (!overridden.is_empty()).then_some(&overrides))?;
+    sign_out.students.refresh()?;
+
+    log::info!("added key record");
+
+    Ok(())
+}
+
+pub fn sign_in_key(key_records: &mut KeyStorage, id: i64, receptionist: &str) -> Result<(), ServiceError> {
+    let record = key_records.fetch(id)?.ok_or(ServiceError::NotFound)?;
+
+    check_open(record.voided, record.time_in)?;
+
+    key_records.signin(id, receptionist)?;
+
+    log::info!("signed in key record");
+
+    Ok(())
+}
+
+/// Blocking restrictions are overridden if a `justification` is given by an admin, which is logged against them.
+pub fn sign_out_game(game_types: &GameTypeStorage, game_records: &mut GameStorage, sign_out: &mut SignOut, record: NewGameRecord, justification: Option<&str>) -> Result<(), ServiceError> {
+    let record = NewGameRecord {
+        game: record.game.trim(),
+        quantity: record.quantity,
+        student_name: record.student_name.trim(),
+        student_number: record.student_number.trim(),
+        receptionist: record.receptionist,
+        notes: record.notes.trim(),
+    };
+
+    let mut checks = Checks::default();
+
+    checks.required_text(Field::Item, record.game, NAME_MAX_LENGTH);
+
+    if record.quantity < 1 || record.quantity > MAX_QUANTITY {
+        checks.check(Field::Quantity, Err(ValidationError::InvalidQuantity));
+    }
+
+    if checks.is_valid(Field::Item) {
+        match game_types.get(record.game) {
+            Some(game) => {
+                // Count quantity of all games already out
+                let available = game.quantity - game_records.get_signed_out(record.game)?;
+
+                if checks.is_valid(Field::Quantity) && record.quantity > available {
+                    checks.check(Field::Quantity, Err(ValidationError::InsufficientStock { available: available.max(0) }));
+                }
+            },
+            None => checks.check(Field::Item, Err(ValidationError::NotInCatalogue)),
+        }
+    }
+
+    checks.student(record.student_name, record.student_number);
+    checks.notes(record.notes);
+    checks.finish()?;
+
+    check_loan_limits(sign_out, record.student_number, RecordType::Game, record.game, record.quantity)?;
+
+    let overridden = check_restrictions(sign_out, record.student_number, RecordType::Game, record.game, record.receptionist, justification)?;
+
+    let justification = format!("{}: {}", record.student_number, justification.unwrap_or_default().trim());
+    let overrides = NewOverrideRecord { restrictions: &overridden, justification: &justification, receptionist: record.receptionist };
+
+    game_records.insert_with_overrides(record, (!overridden.is_empty()).then_some(&overrides))?;
+    sign_out.students.refresh()?;
+
+    log::info!("added game record");
+
+    Ok(())
+}
+
+pub fn sign_in_game(game_records: &mut GameStorage, id: i64, receptionist: &str) -> Result<(), ServiceError> {
+    let record = game_records.fetch(id)?.ok_or(ServiceError::NotFound)?;
+
+    check_open(record.voided, record.time_in)?;
+
+    game_records.signin(id, receptionist)?;
+
+    log::info!("signed in game record");
+
+    Ok(())
+}
+
+/// Blocking restrictions are overridden if a `justification` is given by an admin, which is logged against them.
+/// Loan limits only cover returnable items, items which are given away are never out.
+pub fn sign_out_item(item_types: &mut ItemTypeStorage, item_records: &mut ItemStorage, sign_out: &mut SignOut, record: NewItemRecord, justification: Option<&str>) -> Result<(), ServiceError> {
+    let record = NewItemRecord {
+        item: record.item.trim(),
+        quantity: record.quantity,
+        student_name: record.student_name.trim(),
+        student_number: record.student_number.trim(),
+        receptionist: record.receptionist,
+        notes: record.notes.trim(),
+    };
+
+    let mut checks = Checks::default();
+
+    checks.required_text(Field::Item, record.item, NAME_MAX_LENGTH);
+
+    let item = checks.is_valid(Field::Item).then(|| item_types.get(record.item)).flatten();
+
+    if checks.is_valid(Field::Item) && item.is_none() {
+        checks.check(Field::Item, Err(ValidationError::NotInCatalogue));
+    }
+
+    if record.quantity < 1 || record.quantity > MAX_QUANTITY {
+        checks.check(Field::Quantity, Err(ValidationError::InvalidQuantity));
+    }
+
+    if let Some(available) = item.and_then(|item| item.quantity) {
+        if checks.is_valid(Field::Quantity) && record.quantity > available {
+            checks.check(Field::Quantity, Err(ValidationError::InsufficientStock { available: available.max(0) }));
+        }
+    }
+
+    checks.student(record.student_name, record.student_number);
+    checks.notes(record.notes);
+    checks.finish()?;
+
+    if item.is_some_and(|item| item.returnable) {
+        check_loan_limits(sign_out, record.student_number, RecordType::Item, record.item, record.quantity)?;
+    }
+
+    let overridden = check_restrictions(sign_out, record.student_number, RecordType::Item, record.item, record.receptionist, justification)?;
+
+    let justification = format!("{}: {}", record.student_number, justification.unwrap_or_default().trim());
+    let overrides = NewOverrideRecord { restrictions: &overridden, justification: &justification, receptionist: record.receptionist };
+
+    item_records.insert_with_overrides(record, (!overridden.is_empty()).then_some(&overrides))?;
+    item_types.refresh()?;
+    sign_out.students.refresh()?;
+
+    log::info!("added item record");
+
+    Ok(())
+}
+
+/// Only records of items which were returnable when signed out can be signed in, which puts them back in stock.
+pub fn sign_in_item(item_types: &mut ItemTypeStorage, item_records: &mut ItemStorage, id: i64, receptionist: &str) -> Result<(), ServiceError> {
+    let record = item_records.fetch(id)?.ok_or(ServiceError::NotFound)?;
+
+    if !record.returnable {
+        return Err(ServiceError::NotReturnable);
+    }
+
+    check_open(record.voided, record.time_in)?;
+
+    item_records.signin(id, receptionist)?;
+    item_types.refresh()?;
+
+    log::info!("signed in item record");
+
+    Ok(())
+}
+
+pub fn receive_parcel(parcel_records: &mut ParcelStorage, record: NewParcelRecord) -> Result<(), ServiceError> {
+    let record = NewParcelRecord {
+        parcel_desc: record.parcel_desc.trim(),
+        student_name: record.student_name.trim(),
+        receptionist: record.receptionist,
+        notes: record.notes.trim(),
+    };
+
+    let mut checks = Checks::default();
+
+    checks.required_text(Field::Item, record.parcel_desc, NAME_MAX_LENGTH);
+    checks.required_text(Field::StudentName, record.student_name, NAME_MAX_LENGTH);
+    checks.notes(record.notes);
+    checks.finish()?;
+
+    parcel_records.insert(record)?;
+
+    log::info!("added parcel record");
+
+    Ok(())
+}
+
+/// Mark a parcel as collected by its recipient.
+pub fn collect_parcel(parcel_records: &mut ParcelStorage, id: i64) -> Result<(), ServiceError> {
+    let record = parcel_records.fetch(id)?.ok_or(ServiceError::NotFound)?;
+
+    check_open(record.voided, record.time_out)?;
+
+    parcel_records.signin(id)?;
+
+    log::info!("signed in parcel record");
+
+    Ok(())
+}
+
+/// Records can only be signed in once, and not after being voided.
+fn check_open(voided: bool, closed: Option<chrono::DateTime<chrono::Utc>>) -> Result<(), ServiceError> {
+    if voided {
+        Err(ServiceError::Voided)
+    } else if closed.is_some() {
+        Err(ServiceError::AlreadySignedIn)
+    } else {
+        Ok(())
+    }
+}
+
+/// Fields the category requires are checked, along with the student number if the category requires it.
+/// Restrictions and loan limits cover records signed out to a student number, by category rather than entry,
+/// and loan limits only those of returnable categories. Blocking restrictions are overridden as for keys.
+pub fn sign_out_category(categories: &CategoryStorage, category_records: &mut CategoryRecordStorage, sign_out: &mut SignOut, record: NewCategoryRecord, justification: Option<&str>) -> Result<(), ServiceError> {
+    let category = categories.get(record.category_id).ok_or(ServiceError::NotFound)?;
+
+    let fields = record.fields.iter().map(|value| value.trim().to_string()).collect::<Vec<_>>();
+
+    let record = NewCategoryRecord {
+        category_id: record.category_id,
+        entry: record.entry.trim(),
+        quantity: record.quantity,
+        student_name: record.student_name.trim(),
+        student_number: record.student_number.trim(),
+        receptionist: record.receptionist,
+        fields: &fields,
+        notes: record.notes.trim(),
+    };
+
+    let mut checks = Checks::default();
+
+    checks.required_text(Field::Item, record.entry, NAME_MAX_LENGTH);
+
+    if record.quantity < 1 || record.quantity > MAX_QUANTITY {
+        checks.check(Field::Quantity, Err(ValidationError::InvalidQuantity));
+    }
+
+    if checks.is_valid(Field::Item) {
+        match category.entry(record.entry) {
+            Some(entry) => {
+                if let (Some(quantity), true) = (entry.quantity, checks.is_valid(Field::Quantity)) {
+                    let available = quantity - category_records.get_signed_out(category.id, record.entry)?;
+
+                    if record.quantity > available {
+                        checks.check(Field::Quantity, Err(ValidationError::InsufficientStock { available: available.max(0) }));
+                    }
+                }
+            },
+            None => checks.check(Field::Item, Err(ValidationError::NotInCatalogue)),
+        }
+    }
+
+    if category.student_number_required || !record.student_number.is_empty() {
+        checks.student(record.student_name, record.student_number);
+    } else {
+        checks.required_text(Field::StudentName, record.student_name, NAME_MAX_LENGTH);
+    }
+
+    for (i, field) in category.fields.iter().enumerate() {
+        let value = fields.get(i).map(String::as_str).unwrap_or_default();
+
+        if field.required {
+            checks.check(Field::Custom(i), check_required(value));
+        }
+
+        checks.check(Field::Custom(i), check_length(value, NOTES_MAX_LENGTH));
+
+        if field.kind == FieldKind::Number && !value.is_empty() && value.parse::<f64>().is_err() {
+            checks.check(Field::Custom(i), Err(ValidationError::NotANumber));
+        }
+    }
+
+    checks.notes(record.notes);
+    checks.finish()?;
+
+    if category.returnable && !record.student_number.is_empty() {
+        check_loan_limits(sign_out, record.student_number, RecordType::Category, &category.name, record.quantity)?;
+    }
+
+    let overridden = match record.student_number {
+        "" => vec![],
+        student_number => check_restrictions(sign_out, student_number, RecordType::Category, &category.name, record.receptionist, justification)?,
+    };
+
+    let justification = format!("{}: {}", record.student_number, justification.unwrap_or_default().trim());
+    let overrides = NewOverrideRecord { restrictions: &overridden, justification: &justification, receptionist: record.receptionist };
+
+    category_records.insert_with_overrides(record, (!overridden.is_empty()).then_some(&overrides))?;
+    sign_out.students.refresh()?;
+
+    log::info!("added {} record", category.name);
+
+    Ok(())
+}
+
+/// Only records of returnable categories can be signed in.
+pub fn sign_in_category(categories: &CategoryStorage, category_records: &mut CategoryRecordStorage, id: i64, receptionist: &str) -> Result<(), ServiceError> {
+    let record = category_records.fetch(id)?.ok_or(ServiceError::NotFound)?;
+
+    if !categories.get(record.category_id).is_some_and(|category| category.returnable) {
+        return Err(ServiceError::NotReturnable);
+    }
+
+    check_open(record.voided, record.time_in)?;
+
+    category_records.signin(id, receptionist)?;
+
+    log::info!("signed in category record");
+
+    Ok(())
+}
+
+/// Field of an entry which holds an amendable column, for showing errors beside the new value.
+pub fn amended_field(column: &str) -> Field {
+    match column {
+        "quantity" => Field::Quantity,
+        "student_name" => Field::StudentName,
+        "student_number" => Field::StudentNumber,
+        _ => Field::Item,
+    }
+}
+
+/// The new value is checked as if the record were being signed out with it, and must be one of `options` if
+/// given, e.g. the keys in the catalogue. Student numbers are stored in upper case.
+pub fn amend<T, I: Copy>(records: &mut impl AmendableStorage<T, I>, id: I, column: &str, new_value: &str, options: Option<&[String]>, reason: &str, receptionist: &str) -> Result<(), ServiceError> {
+    let (new_value, reason) = (new_value.trim(), reason.trim());
+
+    let mut checks = Checks::default();
+    let field = amended_field(column);
+
+    let new_value = match field {
+        Field::Quantity => match new_value.parse::<i64>() {
+            Ok(quantity) if (1..=MAX_QUANTITY).contains(&quantity) => quantity.to_string(),
+            _ => {
+                checks.check(field, Err(ValidationError::InvalidQuantity));
+                new_value.to_string()
+            },
+        },
+        Field::StudentNumber => {
+            let student_number = new_value.to_uppercase();
+            checks.check(field, check_required(&student_number).and_then(|_| check_student_number(&student_number)));
+            student_number
+        },
+        _ => {
+            checks.required_text(field, new_value, NAME_MAX_LENGTH);
+            new_value.to_string()
+        },
+    };
+
+    if let Some(options) = options {
+        if checks.is_valid(field) && !options.contains(&new_value) {
+            checks.check(field, Err(ValidationError::NotInCatalogue));
+        }
+    }
+
+    checks.required_text(Field::Reason, reason, NOTES_MAX_LENGTH);
+    checks.finish()?;
+
+    records.amend(id, column, &new_value, reason, receptionist)?;
+
+    log::info!("amended {column} of record");
+
+    Ok(())
+}
+
+pub fn void<T, I: Copy>(records: &mut impl AmendableStorage<T, I>, id: I, reason: &str, receptionist: &str) -> Result<(), ServiceError> {
+    let reason = reason.trim();
+
+    let mut checks = Checks::default();
+    checks.required_text(Field::Reason, reason, NOTES_MAX_LENGTH);
+    checks.finish()?;
+
+    records.void(id, reason, receptionist)?;
+
+    log::info!("voided record");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::{worker::{DbWorker, WorkerHandle}, records::{NewKeyTypeRecord, NewGameTypeRecord, NewItemTypeRecord, NewRestrictionRecord, NewCategory, NewReceptionistRecord}};
+
+    const STUDENT_NUMBER: &str = "ABCDEF001";
+    const RECEPTIONIST: &str = "Reception";
+
+    /// Storages of an in-memory database, migrated as any other is on opening.
+    struct Fixture {
+        worker: WorkerHandle,
+        key_types: KeyTypeStorage,
+        key_records: KeyStorage,
+        game_types: GameTypeStorage,
+        game_records: GameStorage,
+        item_types: ItemTypeStorage,
+        item_records: ItemStorage,
+        categories: CategoryStorage,
+        category_records: CategoryRecordStorage,
+        students: StudentStorage,
+        restrictions: RestrictionStorage,
+        limits: LoanLimitStorage,
+        receptionists: ReceptionistStorage,
+    }
+
+    impl Fixture {
+        /// Stocked with key K1 (due back in an hour), 1 Chess, 1 returnable Umbrella, Soap which is given away,
+        /// the categories Vacuums (returnable, 1 Upright in stock) and Flyers (not returnable, Events not counted),
+        /// and a receptionist who is not an admin.
+        fn new() -> Fixture {
+            let worker = DbWorker::spawn(Path::new(":memory:"), || {}).unwrap().handle();
+
+            let mut fixture = Fixture {
+                key_types: KeyTypeStorage::new(worker.clone()).unwrap(),
+                key_records: KeyStorage::new(worker.clone(), false).unwrap(),
+                game_types: GameTypeStorage::new(worker.clone()).unwrap(),
+                game_records: GameStorage::new(worker.clone(), false).unwrap(),
+                item_types: ItemTypeStorage::new(worker.clone()).unwrap(),
+                item_records: ItemStorage::new(worker.clone(), false).unwrap(),
+                categories: CategoryStorage::new(worker.clone()).unwrap(),
+                category_records: CategoryRecordStorage::new(worker.clone(), false).unwrap(),
+                students: StudentStorage::new(worker.clone()).unwrap(),
+                restrictions: RestrictionStorage::new(worker.clone()).unwrap(),
+                limits: LoanLimitStorage::new(worker.clone()).unwrap(),
+                receptionists: ReceptionistStorage::new(worker.clone()).unwrap(),
+                worker,
+            };
+
+            fixture.key_types.insert(NewKeyTypeRecord { key: "K1", loan_minutes: Some(60) }).unwrap();
+            fixture.game_types.insert(NewGameTypeRecord { game: "Chess", quantity: 1, loan_minutes: None }).unwrap();
+            fixture.item_types.insert(NewItemTypeRecord { item: "Umbrella", returnable: true, quantity: Some(1), low_stock: 0 }).unwrap();
+            fixture.item_types.insert(NewItemTypeRecord { item: "Soap", returnable: false, quantity: None, low_stock: 0 }).unwrap();
+
+            for (name, returnable, entry) in [("Vacuums", true, "Upright"), ("Flyers", false, "Events")] {
+                fixture.categories.insert(NewCategory { name, returnable, stock_limited: returnable, student_number_required: false, fields: &[] }).unwrap();
+
+                let id = fixture.categories.get_by_name(name).unwrap().id;
+                fixture.categories.insert_entry(id, entry, returnable.then_some(1)).unwrap();
+            }
+
+            fixture.receptionists.insert(NewReceptionistRecord { name: RECEPTIONIST }).unwrap();
+
+            fixture
+        }
+
+        fn sign_out_key(&mut self, key: &str, justification: Option<&str>) -> Result<(), ServiceError> {
+            let mut sign_out = SignOut { students: &mut self.students, restrictions: &self.restrictions, limits: &self.limits, receptionists: &self.receptionists };
+            let record = NewKeyRecord { key, student_name: "Alex", student_number: STUDENT_NUMBER, receptionist: RECEPTIONIST, notes: "" };
+
+            sign_out_key(&self.key_types, &mut self.key_records, &mut sign_out, record, justification)
+        }
+
+        fn sign_out_game(&mut self, game: &str, quantity: i64) -> Result<(), ServiceError> {
+            let mut sign_out = SignOut { students: &mut self.students, restrictions: &self.restrictions, limits: &self.limits, receptionists: &self.receptionists };
+            let record = NewGameRecord { game, quantity, student_name: "Alex", student_number: STUDENT_NUMBER, receptionist: RECEPTIONIST, notes: "" };
+
+            sign_out_game(&self.game_types, &mut self.game_records, &mut sign_out, record, None)
+        }
+
+        fn sign_out_item(&mut self, item: &str, quantity: i64) -> Result<(), ServiceError> {
+            let mut sign_out = SignOut { students: &mut self.students, restrictions: &self.restrictions, limits: &self.limits, receptionists: &self.receptionists };
+            let record = NewItemRecord { item, quantity, student_name: "Alex", student_number: STUDENT_NUMBER, receptionist: RECEPTIONIST, notes: "" };
+
+            sign_out_item(&mut self.item_types, &mut self.item_records, &mut sign_out, record, None)
+        }
+
+        fn sign_out_category(&mut self, category: &str, entry: &str) -> Result<(), ServiceError> {
+            let category_id = self.categories.get_by_name(category).unwrap().id;
+            let mut sign_out = SignOut { students: &mut self.students, restrictions: &self.restrictions, limits: &self.limits, receptionists: &self.receptionists };
+            let record = NewCategoryRecord { category_id, entry, quantity: 1, student_name: "Alex", student_number: STUDENT_NUMBER, receptionist: RECEPTIONIST, fields: &[], notes: "" };
+
+            sign_out_category(&self.categories, &mut self.category_records, &mut sign_out, record, None)
+        }
+
+        fn restrict(&mut self, record_type: Option<RecordType>, name: Option<&str>, blocking: bool) {
+            self.restrictions.insert(NewRestrictionRecord {
+                student_number: STUDENT_NUMBER,
+                record_type,
+                name,
+                reason: "Damaged the last one",
+                blocking,
+                time_start: chrono::Utc::now() - chrono::Duration::days(1),
+                time_end: None,
+                imposed_by: RECEPTIONIST,
+            }).unwrap();
+        }
+
+        /// Id of the record most recently added to `table`.
+        fn last_id(&self, table: &str) -> i64 {
+            let sql = format!("SELECT MAX(id) FROM {table}");
+
+            self.worker.call(move |connection| Ok(connection.query_row(&sql, (), |row| row.get(0))?)).unwrap()
+        }
+
+        fn count_overdue(&self) -> i64 {
+            self.worker.call(|connection| KeyStorage::count_overdue(connection)).unwrap()
+        }
+    }
+
+    fn is_invalid(result: Result<(), ServiceError>, field: Field) -> bool {
+        matches!(result, Err(ServiceError::Invalid(errors)) if errors.iter().any(|(f, _)| *f == field))
+    }
+
+    #[test]
+    fn key_already_out() {
+        let mut fixture = Fixture::new();
+
+        fixture.sign_out_key("K1", None).unwrap();
+
+        let result = fixture.sign_out_key("K1", None);
+        assert!(matches!(&result, Err(ServiceError::Invalid(errors)) if matches!(errors[..], [(Field::Item, ValidationError::KeyAlreadyOut(_))])), "{result:?}");
+
+        // signed back in, it can go out again
+        let id = fixture.last_id("key_records");
+        sign_in_key(&mut fixture.key_records, id, RECEPTIONIST).unwrap();
+        fixture.sign_out_key("K1", None).unwrap();
+    }
+
+    #[test]
+    fn game_stock_exhausted() {
+        let mut fixture = Fixture::new();
+
+        assert!(is_invalid(fixture.sign_out_game("Chess", 2), Field::Quantity));
+
+        fixture.sign_out_game("Chess", 1).unwrap();
+        assert!(is_invalid(fixture.sign_out_game("Chess", 1), Field::Quantity));
+
+        let id = fixture.last_id("game_records");
+        sign_in_game(&mut fixture.game_records, id, RECEPTIONIST).unwrap();
+        fixture.sign_out_game("Chess", 1).unwrap();
+    }
+
+    #[test]
+    fn item_stock_exhausted() {
+        let mut fixture = Fixture::new();
+
+        fixture.sign_out_item("Umbrella", 1).unwrap();
+        assert_eq!(fixture.item_types.get("Umbrella").unwrap().quantity, Some(0));
+        assert!(is_invalid(fixture.sign_out_item("Umbrella", 1), Field::Quantity));
+
+        let id = fixture.last_id("item_records");
+        sign_in_item(&mut fixture.item_types, &mut fixture.item_records, id, RECEPTIONIST).unwrap();
+        assert_eq!(fixture.item_types.get("Umbrella").unwrap().quantity, Some(1));
+
+        // stock which is not counted never runs out
+        fixture.sign_out_item("Soap", MAX_QUANTITY).unwrap();
+    }
+
+    #[test]
+    fn category_stock_exhausted() {
+        let mut fixture = Fixture::new();
+
+        fixture.sign_out_category("Vacuums", "Upright").unwrap();
+        assert!(is_invalid(fixture.sign_out_category("Vacuums", "Upright"), Field::Quantity));
+
+        let id = fixture.last_id("category_records");
+        assert!(matches!(amend(&mut fixture.category_records, id, "quantity", "2", None, "Took two", RECEPTIONIST), Err(ServiceError::StorageError(_))));
+
+        // a voided record no longer holds stock, and cannot be signed in
+        void(&mut fixture.category_records, id, "Wrong student", RECEPTIONIST).unwrap();
+        assert!(matches!(sign_in_category(&fixture.categories, &mut fixture.category_records, id, RECEPTIONIST), Err(ServiceError::Voided)));
+
+        fixture.sign_out_category("Vacuums", "Upright").unwrap();
+    }
+
+    #[test]
+    fn overdue() {
+        let mut fixture = Fixture::new();
+
+        fixture.sign_out_key("K1", None).unwrap();
+        assert_eq!(fixture.count_overdue(), 0);
+
+        let id = fixture.last_id("key_records");
+        let due = (chrono::Utc::now() - chrono::Duration::minutes(5)).timestamp();
+        fixture.worker.call(move |connection| Ok(connection.execute("UPDATE key_records SET time_due = ? WHERE id = ?", (due, id))?)).unwrap();
+
+        assert_eq!(fixture.count_overdue(), 1);
+        assert_eq!(fixture.key_records.fetch_overdue().unwrap().len(), 1);
+
+        // overdue records are signed in as any other
+        sign_in_key(&mut fixture.key_records, id, RECEPTIONIST).unwrap();
+        assert_eq!(fixture.count_overdue(), 0);
+    }
+
+    #[test]
+    fn restriction_warns() {
+        let mut fixture = Fixture::new();
+
+        fixture.restrict(Some(RecordType::Key), None, false);
+
+        assert_eq!(fixture.restrictions.applicable(STUDENT_NUMBER, RecordType::Key, "K1").len(), 1);
+        fixture.sign_out_key("K1", None).unwrap();
+    }
+
+    #[test]
+    fn restriction_blocks() {
+        let mut fixture = Fixture::new();
+
+        fixture.restrict(Some(RecordType::Key), Some("K1"), true);
+
+        assert!(matches!(fixture.sign_out_key("K1", None), Err(ServiceError::Restricted(blocking)) if blocking.len() == 1));
+        assert!(matches!(fixture.sign_out_key("K1", Some("  ")), Err(ServiceError::Restricted(_))));
+
+        // other records are not restricted
+        fixture.sign_out_game("Chess", 1).unwrap();
+
+        // only admins may override
+        assert!(matches!(fixture.sign_out_key("K1", Some("Warden approved")), Err(ServiceError::OverrideNotAllowed)));
+
+        let id = fixture.receptionists.get_all()[0].id;
+        fixture.receptionists.set_admin(id, true).unwrap();
+
+        fixture.sign_out_key("K1", Some("Warden approved")).unwrap();
+
+        let overrides: i64 = fixture.worker.call(|connection| Ok(connection.query_row("SELECT COUNT(*) FROM restriction_overrides", (), |row| row.get(0))?)).unwrap();
+        assert_eq!(overrides, 1);
+    }
+
+    #[test]
+    fn restriction_blocks_category() {
+        let mut fixture = Fixture::new();
+
+        fixture.restrict(Some(RecordType::Category), Some("Vacuums"), true);
+
+        assert!(matches!(fixture.sign_out_category("Vacuums", "Upright"), Err(ServiceError::Restricted(_))));
+        fixture.sign_out_category("Flyers", "Events").unwrap();
+    }
+
+    #[test]
+    fn loan_limit() {
+        let mut fixture = Fixture::new();
+
+        fixture.limits.set(None, None, 2).unwrap();
+
+        fixture.sign_out_key("K1", None).unwrap();
+        fixture.sign_out_item("Umbrella", 1).unwrap();
+
+        // items which are given away and records of categories which are not returned are never out
+        fixture.sign_out_item("Soap", 1).unwrap();
+        fixture.sign_out_category("Flyers", "Events").unwrap();
+
+        assert!(matches!(fixture.sign_out_game("Chess", 1), Err(ServiceError::LimitReached { open, .. }) if open.len() == 2));
+        assert!(matches!(fixture.sign_out_category("Vacuums", "Upright"), Err(ServiceError::LimitReached { .. })));
+
+        // returning a record frees up the limit
+        let id = fixture.last_id("key_records");
+        sign_in_key(&mut fixture.key_records, id, RECEPTIONIST).unwrap();
+        fixture.sign_out_game("Chess", 1).unwrap();
+    }
+
+    #[test]
+    fn sign_in_closed() {
+        let mut fixture = Fixture::new();
+
+        fixture.sign_out_key("K1", None).unwrap();
+        let id = fixture.last_id("key_records");
+
+        sign_in_key(&mut fixture.key_records, id, RECEPTIONIST).unwrap();
+        assert!(matches!(sign_in_key(&mut fixture.key_records, id, RECEPTIONIST), Err(ServiceError::AlreadySignedIn)));
+        assert!(matches!(sign_in_key(&mut fixture.key_records, id + 1, RECEPTIONIST), Err(ServiceError::NotFound)));
+
+        fixture.sign_out_game("Chess", 1).unwrap();
+        let id = fixture.last_id("game_records");
+
+        void(&mut fixture.game_records, id, "Wrong student", RECEPTIONIST).unwrap();
+        assert!(matches!(sign_in_game(&mut fixture.game_records, id, RECEPTIONIST), Err(ServiceError::Voided)));
+
+        fixture.sign_out_category("Vacuums", "Upright").unwrap();
+        let id = fixture.last_id("category_records");
+
+        sign_in_category(&fixture.categories, &mut fixture.category_records, id, RECEPTIONIST).unwrap();
+        assert!(matches!(sign_in_category(&fixture.categories, &mut fixture.category_records, id, RECEPTIONIST), Err(ServiceError::AlreadySignedIn)));
+    }
+
+    #[test]
+    fn sign_in_not_returnable() {
+        let mut fixture = Fixture::new();
+
+        fixture.sign_out_item("Soap", 1).unwrap();
+        let id = fixture.last_id("item_records");
+        assert!(matches!(sign_in_item(&mut fixture.item_types, &mut fixture.item_records, id, RECEPTIONIST), Err(ServiceError::NotReturnable)));
+
+        fixture.sign_out_category("Flyers", "Events").unwrap();
+        let id = fixture.last_id("category_records");
+        assert!(matches!(sign_in_category(&fixture.categories, &mut fixture.category_records, id, RECEPTIONIST), Err(ServiceError::NotReturnable)));
+    }
+}
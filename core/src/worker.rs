@@ -0,0 +1,317 @@
+use std::{path::PathBuf, sync::mpsc::{self, Receiver, Sender, RecvTimeoutError}, time::Duration};
+
+use crate::{open_database, backup::{self, Retention}, records::{RecordType, StorageError, KeyStorage, ParcelStorage, GameStorage, ItemStorage, CategoryStorage, CategoryRecordStorage, ExportableStorage}};
+
+/// How often the worker looks for changes made by other connections, e.g. the CLI, or by its own writes.
+const CHANGE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Work sent to the database worker.
+pub enum Command {
+    /// Run a read or write and send its result back on the channel it captured, see `WorkerHandle::query`.
+    Query(Box<dyn FnOnce(&mut rusqlite::Connection) + Send>),
+    /// Save a copy of the database to a file.
+    Backup(PathBuf),
+    /// Save an automatic backup to the directory, then rotate old backups.
+    AutoBackup { directory: PathBuf, retention: Retention },
+    Export { source: ExportSource, path: PathBuf },
+}
+
+/// Records exported to CSV, of a built in type or a custom category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportSource {
+    Records(RecordType),
+    Category(i64),
+}
+
+impl Default for ExportSource {
+    fn default() -> Self {
+        ExportSource::Records(RecordType::default())
+    }
+}
+
+/// A long running command, reported with a progress bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Task {
+    Backup,
+    AutoBackup,
+    Export(ExportSource),
+}
+
+impl std::fmt::Display for Task {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Task::Backup => f.write_str("Saving backup"),
+            Task::AutoBackup => f.write_str("Saving automatic backup"),
+            Task::Export(ExportSource::Records(record_type)) => write!(f, "Exporting {} records", record_type.to_string().to_lowercase()),
+            Task::Export(ExportSource::Category(_)) => f.write_str("Exporting category records"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Event {
+    /// Fraction of the task done so far, from 0 to 1.
+    Progress { task: Task, done: f32 },
+    Finished { task: Task, result: Result<(), String> },
+    /// Tables changed since the last check, by any connection. Empty if the changes cannot be told
+    /// apart, e.g. after restoring a backup, in which case everything should be refreshed.
+    Changed(Vec<String>),
+}
+
+/// Sends reads and writes to the database worker, kept by every storage since the worker's connection is the only one.
+#[derive(Debug, Clone)]
+pub struct WorkerHandle {
+    commands: Sender<Command>,
+}
+
+impl WorkerHandle {
+    /// Run a read or write on the worker. The result is received once it is done, or the channel
+    /// disconnects without a result if the worker has stopped.
+    pub fn query<T: Send + 'static>(&self, query: impl FnOnce(&mut rusqlite::Connection) -> T + Send + 'static) -> Receiver<T> {
+        let (sender, receiver) = mpsc::channel();
+
+        let command = Command::Query(Box::new(move |connection| {
+            // the storage may have asked for another page since, in which case nobody is waiting
+            let _ = sender.send(query(connection));
+        }));
+
+        if self.commands.send(command).is_err() {
+            log::error!("database worker stopped, query dropped");
+        }
+
+        receiver
+    }
+
+    /// Run a read or write on the worker and wait for its result, for those the receptionist waits on anyway,
+    /// e.g. a sign out or a small catalogue table. Commands sent before it are done first.
+    pub fn call<T: Send + 'static>(&self, call: impl FnOnce(&mut rusqlite::Connection) -> Result<T, StorageError> + Send + 'static) -> Result<T, StorageError> {
+        self.query(call).recv().unwrap_or(Err(StorageError::WorkerStopped))
+    }
+}
+
+/// Owns the connection to the database, which every read and write is sent to, so that the window never holds it.
+/// Pages of records and overdue counts are read in the background, other reads and writes are waited for. Backups
+/// and exports are left to a thread of their own, with a second connection, so that sign outs never wait on them.
+/// The threads stop once the worker and its handles are dropped.
+pub struct DbWorker {
+    handle: WorkerHandle,
+    events: Receiver<Event>,
+}
+
+impl DbWorker {
+    /// Open the connections to the database file and start the worker. `wake` is called whenever there is
+    /// a result or event to collect, e.g. to repaint the window. An in-memory database is not shared with the
+    /// thread saving backups and exports unless it is named with a shared cache.
+    pub fn spawn(path: &std::path::Path, wake: impl Fn() + Clone + Send + 'static) -> Result<DbWorker, StorageError> {
+        let connection = open_database(path)?;
+        let task_connection = open_database(path)?;
+
+        let (commands, command_receiver) = mpsc::channel();
+        let (task_sender, task_receiver) = mpsc::channel();
+        let (event_sender, events) = mpsc::channel();
+
+        let task_wake = wake.clone();
+        let task_events = event_sender.clone();
+
+        std::thread::Builder::new()
+            .name("database tasks".into())
+            .spawn(move || run_tasks(task_connection, task_receiver, task_events, task_wake))
+            .map_err(StorageError::FileError)?;
+
+        std::thread::Builder::new()
+            .name("database worker".into())
+            .spawn(move || run(connection, command_receiver, task_sender, event_sender, wake))
+            .map_err(StorageError::FileError)?;
+
+        log::info!("started database worker for {path:?}");
+
+        Ok(DbWorker {
+            handle: WorkerHandle { commands },
+            events,
+        })
+    }
+
+    pub fn handle(&self) -> WorkerHandle {
+        self.handle.clone()
+    }
+
+    pub fn send(&self, command: Command) {
+        if self.handle.commands.send(command).is_err() {
+            log::error!("database worker stopped, command dropped");
+        }
+    }
+
+    /// Events since the last call, oldest first.
+    pub fn events(&self) -> Vec<Event> {
+        self.events.try_iter().collect()
+    }
+}
+
+fn run(mut connection: rusqlite::Connection, commands: Receiver<Command>, tasks: Sender<Command>, events: Sender<Event>, wake: impl Fn()) {
+    let mut watch = ChangeWatch::default();
+
+    // the first check only takes note of where the database is at
+    if let Err(err) = watch.check(&connection, true) {
+        log::error!("failed to check database for changes: {err}");
+    }
+
+    loop {
+        let command = commands.recv_timeout(CHANGE_CHECK_INTERVAL);
+
+        // only wake the window when there is something for it
+        let mut woken = command.is_ok();
+
+        // the worker's own writes leave the data version as it is, so the audit log is read after every query
+        let queried = matches!(command, Ok(Command::Query(_)));
+
+        match command {
+            Ok(Command::Query(query)) => query(&mut connection),
+            Ok(task) => {
+                if tasks.send(task).is_err() {
+                    log::error!("database task thread stopped, task dropped");
+                }
+            },
+            Err(RecvTimeoutError::Timeout) => {},
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        match watch.check(&connection, queried) {
+            Ok(Some(tables)) => {
+                let _ = events.send(Event::Changed(tables));
+                woken = true;
+            },
+            Ok(None) => {},
+            Err(err) => log::error!("failed to check database for changes: {err}"),
+        }
+
+        if woken {
+            wake();
+        }
+    }
+
+    log::info!("stopped database worker");
+}
+
+/// Save backups and exports, one at a time, reporting their progress.
+fn run_tasks(connection: rusqlite::Connection, tasks: Receiver<Command>, events: Sender<Event>, wake: impl Fn()) {
+    for command in tasks {
+        match command {
+            Command::Backup(path) => {
+                let task = Task::Backup;
+
+                let result = backup::copy(&connection, &path, |done| {
+                    let _ = events.send(Event::Progress { task, done });
+                    wake();
+                });
+
+                match &result {
+                    Ok(_) => log::info!("saved backup to {path:?}"),
+                    Err(err) => log::error!("failed to backup database: {err}"),
+                }
+
+                let _ = events.send(Event::Finished { task, result: result.map_err(|err| err.to_string()) });
+            },
+            Command::AutoBackup { directory, retention } => {
+                let task = Task::AutoBackup;
+
+                let result = backup::backup_with_progress(&connection, &directory, retention, |done| {
+                    let _ = events.send(Event::Progress { task, done });
+                    wake();
+                });
+
+                if let Err(err) = &result {
+                    log::error!("failed to save automatic backup: {err}");
+                }
+
+                let _ = events.send(Event::Finished { task, result: result.map(|_| ()).map_err(|err| err.to_string()) });
+            },
+            Command::Export { source, path } => {
+                let task = Task::Export(source);
+
+                let progress = |done| {
+                    let _ = events.send(Event::Progress { task, done });
+                    wake();
+                };
+
+                let result = match source {
+                    ExportSource::Records(RecordType::Key) => KeyStorage::export_csv(&connection, path, progress),
+                    ExportSource::Records(RecordType::Parcel) => ParcelStorage::export_csv(&connection, path, progress),
+                    ExportSource::Records(RecordType::Game) => GameStorage::export_csv(&connection, path, progress),
+                    ExportSource::Records(RecordType::Item) => ItemStorage::export_csv(&connection, path, progress),
+                    ExportSource::Category(id) => export_category(&connection, id, path, progress),
+                    // categories have their own columns, so are exported one at a time
+                    ExportSource::Records(RecordType::Category) => Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "choose a category to export").into()),
+                };
+
+                match &result {
+                    Ok(_) => log::info!("export successful"),
+                    Err(err) => log::error!("failed to export: {err}"),
+                }
+
+                let _ = events.send(Event::Finished { task, result: result.map_err(|err| err.to_string()) });
+            },
+            // reads are run by the worker itself
+            Command::Query(_) => {},
+        }
+
+        wake();
+    }
+
+    log::info!("stopped database task thread");
+}
+
+fn export_category(connection: &rusqlite::Connection, category_id: i64, path: PathBuf, progress: impl FnMut(f32)) -> Result<(), StorageError> {
+    // the category may have been deleted since the export was asked for, leaving nothing to export
+    let Some(category) = CategoryStorage::fetch(connection, category_id)? else {
+        return Ok(());
+    };
+
+    CategoryRecordStorage::export_csv(connection, &category, path, progress)
+}
+
+/// Finds which tables changed from the audit log, which every change is written to.
+#[derive(Debug, Default)]
+struct ChangeWatch {
+    data_version: i64,
+    last_audit_id: i64,
+}
+
+impl ChangeWatch {
+    /// `written` if the connection checked may have written since the last check.
+    fn check(&mut self, connection: &rusqlite::Connection, written: bool) -> Result<Option<Vec<String>>, rusqlite::Error> {
+        // changes whenever another connection commits, so otherwise the audit log is only read after a change
+        let data_version: i64 = connection.query_row("PRAGMA data_version", (), |row| row.get(0))?;
+
+        if data_version == self.data_version && !written {
+            return Ok(None);
+        }
+
+        self.data_version = data_version;
+
+        let last_audit_id: i64 = connection.query_row("SELECT IFNULL(MAX(id), 0) FROM audit_log", (), |row| row.get(0))?;
+
+        let changed = if last_audit_id < self.last_audit_id {
+            // the audit log was replaced by a restore
+            Some(vec![])
+        } else if last_audit_id > self.last_audit_id {
+            let mut stmt = connection.prepare("SELECT DISTINCT record_table FROM audit_log WHERE id > ?")?;
+
+            let tables = stmt.query_map((self.last_audit_id,), |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            // merges and restores are logged against the whole database
+            if tables.iter().any(|table| table == "database") {
+                Some(vec![])
+            } else {
+                Some(tables)
+            }
+        } else {
+            // e.g. a shift started, which nothing is cached for
+            None
+        };
+
+        self.last_audit_id = last_audit_id;
+
+        Ok(changed)
+    }
+}
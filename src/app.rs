@@ -1,66 +1,292 @@
-use std::{path::PathBuf, thread::JoinHandle, sync::{Arc, Mutex}};
+use std::{fmt::Display, path::{Path, PathBuf}, thread::JoinHandle, sync::mpsc::{Receiver, TryRecvError}, time::{Duration, Instant}};
 
-use crate::{records::{RecordType, KeyTypeStorage, KeyStorage, ParcelStorage, GameStorage, GameTypeStorage, ItemTypeStorage, ItemStorage, PaginatedStorage, StorageError, ExportableStorage, Storage, StudentInfo}, modal::{AlertModal, KeyEntryModal, ExitModal, GameEntryModal, ItemEntryModal, ExportModal, AboutModal, SettingsModal, ConfirmationModal, SummaryModal}, panel::{KeyPanel, ParcelPanel, GamePanel, ItemPanel}};
+use crate::{records::{RecordType, KeyTypeStorage, KeyStorage, ParcelStorage, GameStorage, GameTypeStorage, ItemTypeStorage, ItemStorage, CategoryStorage, CategoryRecordStorage, PaginatedStorage, StorageError, Storage, StudentStorage, ReceptionistStorage, RestrictionStorage, LoanLimitStorage, AuditStorage, RestorePreview, Shift}, modal::{Catalogue, AlertModal, KeyEntryModal, ExitModal, GameEntryModal, ItemEntryModal, CategoryEntryModal, StudentEntryModal, RestrictionEntryModal, LoanLimitEntryModal, RosterImportModal, ReceptionistEntryModal, ProfileEntryModal, SignOnModal, ExportModal, AboutModal, SettingsModal, ConfirmationModal, SummaryModal, SummaryRecords, OverdueModal, AuditLogModal, RestoreModal, RestoreAction, DatabaseCheckModal}, service::SignOut, panel::{KeyPanel, ParcelPanel, GamePanel, ItemPanel, CategoryPanel}, backup::{self, Retention}, worker::{DbWorker, Command, Event, Task, ExportSource}};
 
-pub const APP_NAME: &str = "Blackcurrant";
+pub use blackcurrant_core::{APP_NAME, NAME_MAX_LENGTH, NOTES_MAX_LENGTH, STUDENT_NUMBER_LENGTH, ROOM_MAX_LENGTH, STAFF_NUMBER_LENGTH, MAX_QUANTITY, DATE_TIME_FORMAT, PAGE_SIZE};
+
+pub const DEFAULT_PROFILE_NAME: &str = "Main";
 
-pub const NAME_MAX_LENGTH: usize = 512;
-pub const NOTES_MAX_LENGTH: usize = 512;
-pub const STUDENT_NUMBER_LENGTH: usize = 9;
-pub const STAFF_NUMBER_LENGTH: usize = 8;
-pub const MAX_QUANTITY: i64 = 99;
-pub const DATE_TIME_FORMAT: &str = "%d/%m/%Y %H:%M";
 pub const BACKUP_DATE_TIME_FORMAT: &str = "%Y-%m-%d_%H-%M-%S.%f";
-pub const PAGE_SIZE: i64 = 100;
 pub const ROW_HEIGHT: f32 = 20.0;
 pub const COL_MIN_WIDTH: f32 = 64.0;
 pub const COL_MAX_WIDTH: f32 = 128.0;
 pub const COL_SMALL_INITIAL_WIDTH: f32 = 92.0;
 pub const COL_LARGE_INITIAL_WIDTH: f32 = 160.0;
+pub const OVERDUE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+pub const BACKUP_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Opened in place of a profile's database which cannot be opened, so that the window still comes up. Shared
+/// between the worker's connections, which would otherwise each get an empty database of their own.
+const PLACEHOLDER_DATABASE: &str = "file:placeholder?mode=memory&cache=shared";
 
 pub const CONFIRMATION_TITLE: &str = "Are you sure?";
 pub const RESTORE_CONFIRM_TEXT: &str = "Restoring from a backup will delete all records which are not present in the backup.";
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct AppConfig {
     pub facility_name: String,
+    /// Automatic backups are disabled if not set.
+    pub backup_directory: Option<PathBuf>,
+    /// Minutes between automatic backups, 0 for none.
+    pub backup_interval_minutes: u64,
+    pub backup_on_exit: bool,
+    pub backup_keep_hourly: usize,
+    pub backup_keep_daily: usize,
+    pub backup_keep_monthly: usize,
+    /// Keep databases next to the executable, instead of in the application data directory.
+    pub portable: bool,
+    /// Name of the profile currently open.
+    pub profile: String,
+    // tables must come after plain values in TOML, so this must be the last field
+    pub profiles: Vec<Profile>,
+}
+
+/// A separately kept set of records, e.g. for each reception using the same computer.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Profile {
+    pub name: String,
+    /// If not set, the database is kept in the database directory.
+    pub database_path: Option<PathBuf>,
+    /// Name of the database file in the database directory, which also names the profile's backups. Chosen
+    /// when the profile is added, so that it is not shared with another profile. Profiles from before it was
+    /// kept use a name made from the profile name.
+    #[serde(default)]
+    pub file_name: Option<String>,
+}
+
+impl Profile {
+    fn file_name(&self) -> String {
+        if let Some(file_name) = &self.file_name {
+            return file_name.clone();
+        }
+
+        if self.name == DEFAULT_PROFILE_NAME {
+            return "db.sqlite".into();
+        }
+
+        format!("db_{}.sqlite", file_name_slug(&self.name))
+    }
+}
+
+/// Part of a file name made from a profile name, which may be the same for different names.
+fn file_name_slug(name: &str) -> String {
+    name.to_lowercase().chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+impl AppConfig {
+    /// The open profile, or the first profile if it no longer exists.
+    pub fn current_profile(&self) -> Profile {
+        self.profiles.iter()
+            .find(|p| p.name == self.profile)
+            .or(self.profiles.first())
+            .cloned()
+            .unwrap_or(Profile { name: DEFAULT_PROFILE_NAME.into(), database_path: None, file_name: None })
+    }
+
+    /// File name for a new profile, numbered if another profile already has the same file name.
+    pub fn new_file_name(&self, name: &str) -> String {
+        let slug = file_name_slug(name);
+        let taken = |file_name: &str| self.profiles.iter().any(|p| p.file_name() == file_name);
+
+        let mut file_name = format!("db_{slug}.sqlite");
+
+        for n in 2.. {
+            if !taken(&file_name) {
+                break;
+            }
+
+            file_name = format!("db_{slug}_{n}.sqlite");
+        }
+
+        file_name
+    }
+
+    /// Directory databases are kept in, unless their profile has its own path.
+    pub fn database_directory(&self) -> PathBuf {
+        let executable_directory = std::env::current_exe().ok()
+            .and_then(|path| path.parent().map(Path::to_path_buf));
+
+        match (self.portable, executable_directory) {
+            (true, Some(directory)) => directory,
+            _ => dirs::data_dir().expect("no application data directory").join(APP_NAME),
+        }
+    }
+
+    pub fn database_path(&self, profile: &Profile) -> PathBuf {
+        match &profile.database_path {
+            Some(path) => path.clone(),
+            None => self.database_directory().join(profile.file_name()),
+        }
+    }
+
+    /// Automatic backups of each profile are kept apart, so that they are rotated separately.
+    pub fn profile_backup_directory(&self) -> Option<PathBuf> {
+        let profile = self.current_profile();
+
+        self.backup_directory.as_ref().map(|directory| {
+            if profile.name == DEFAULT_PROFILE_NAME {
+                directory.clone()
+            } else {
+                directory.join(profile.file_name().trim_end_matches(".sqlite"))
+            }
+        })
+    }
+
+    pub fn backup_retention(&self) -> Retention {
+        Retention {
+            hourly: self.backup_keep_hourly,
+            daily: self.backup_keep_daily,
+            monthly: self.backup_keep_monthly,
+        }
+    }
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
             facility_name: "".into(),
+            backup_directory: None,
+            backup_interval_minutes: 60,
+            backup_on_exit: true,
+            backup_keep_hourly: 24,
+            backup_keep_daily: 7,
+            backup_keep_monthly: 12,
+            portable: false,
+            profile: DEFAULT_PROFILE_NAME.into(),
+            profiles: vec![Profile { name: DEFAULT_PROFILE_NAME.into(), database_path: None, file_name: None }],
+        }
+    }
+}
+
+/// Storage failures which have not been dismissed yet, shown in a banner above the records.
+#[derive(Debug, Default)]
+pub struct StorageErrors {
+    errors: Vec<String>,
+}
+
+impl StorageErrors {
+    /// Record a failure, logging it only the first time so that a failure repeated every frame is logged once.
+    pub fn report(&mut self, context: &str, err: impl Display) {
+        let message = format!("{context}: {err}");
+
+        if !self.errors.contains(&message) {
+            log::error!("{message}");
+            self.errors.push(message);
+        }
+    }
+
+    /// The value of a storage call, or `None` if it failed and was reported.
+    pub fn check<T, E: Display>(&mut self, context: &str, result: Result<T, E>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.report(context, err);
+                None
+            },
         }
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.errors.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &String> {
+        self.errors.iter()
+    }
+}
+
+/// Every storage of a database, opened together so that a failure leaves the open storages untouched.
+type Storages = (StudentStorage, ReceptionistStorage, KeyTypeStorage, GameTypeStorage, ItemTypeStorage, RestrictionStorage, LoanLimitStorage, CategoryStorage, KeyStorage, ParcelStorage, GameStorage, ItemStorage, CategoryRecordStorage, AuditStorage);
+
+/// Every storage reads and writes on the worker. Pages of records are fetched in the background, while the smaller
+/// tables are kept whole, and read again when the worker reports that they changed.
+fn open_storages(worker: &DbWorker) -> Result<Storages, StorageError> {
+    Ok((
+        StudentStorage::new(worker.handle())?,
+        ReceptionistStorage::new(worker.handle())?,
+        KeyTypeStorage::new(worker.handle())?,
+        GameTypeStorage::new(worker.handle())?,
+        ItemTypeStorage::new(worker.handle())?,
+        RestrictionStorage::new(worker.handle())?,
+        LoanLimitStorage::new(worker.handle())?,
+        CategoryStorage::new(worker.handle())?,
+        KeyStorage::new(worker.handle(), true)?,
+        ParcelStorage::new(worker.handle(), true)?,
+        GameStorage::new(worker.handle(), true)?,
+        ItemStorage::new(worker.handle(), true)?,
+        CategoryRecordStorage::new(worker.handle(), true)?,
+        AuditStorage::new(worker.handle(), true)?,
+    ))
+}
+
+/// Open a database file along with its worker and storages.
+fn open_profile_database(ctx: &egui::Context, path: &Path) -> Result<(DbWorker, Storages), StorageError> {
+    let worker = spawn_worker(ctx, path)?;
+    let storages = open_storages(&worker)?;
+
+    Ok((worker, storages))
+}
+
+/// Start a worker for the database file, which repaints the window whenever it has something to show.
+fn spawn_worker(ctx: &egui::Context, path: &Path) -> Result<DbWorker, StorageError> {
+    let ctx = ctx.clone();
+
+    DbWorker::spawn(path, move || ctx.request_repaint())
 }
 
 pub struct App {
     current_panel: RecordType,
+    /// Custom category shown instead of the current panel, if any.
+    current_category: Option<i64>,
 
-    connection: Arc<Mutex<rusqlite::Connection>>,
-    student_info: Arc<Mutex<StudentInfo>>,
+    /// Owns the connection to the database, which every storage reads and writes on, and saves backups and exports.
+    worker: DbWorker,
+    /// Backup or export running on the worker, and how far along it is.
+    task: Option<(Task, f32)>,
 
     backup_path_handle: Option<JoinHandle<Option<PathBuf>>>,
     restore_path_handle: Option<JoinHandle<Option<PathBuf>>>,
-    export_path_handle: Option<JoinHandle<(RecordType, Option<PathBuf>)>>,
+    export_path_handle: Option<JoinHandle<(ExportSource, Option<PathBuf>)>>,
     
+    students: StudentStorage,
+    receptionists: ReceptionistStorage,
     key_types: KeyTypeStorage,
     game_types: GameTypeStorage,
     item_types: ItemTypeStorage,
+    restrictions: RestrictionStorage,
+    loan_limits: LoanLimitStorage,
+    categories: CategoryStorage,
     
     key_records: KeyStorage,
     parcel_records: ParcelStorage,
     game_records: GameStorage,
     item_records: ItemStorage,
+    category_records: CategoryRecordStorage,
+    audit: AuditStorage,
     
     key_panel: KeyPanel,
     parcel_panel: ParcelPanel,
     game_panel: GamePanel,
     item_panel: ItemPanel,
+    category_panel: CategoryPanel,
     
     key_entry_modal: Option<KeyEntryModal>,
     game_entry_modal: Option<GameEntryModal>,
     item_entry_modal: Option<ItemEntryModal>,
+    category_entry_modal: Option<CategoryEntryModal>,
+    student_entry_modal: Option<StudentEntryModal>,
+    restriction_entry_modal: Option<RestrictionEntryModal>,
+    loan_limit_entry_modal: Option<LoanLimitEntryModal>,
+    roster_import_modal: Option<RosterImportModal>,
+    receptionist_entry_modal: Option<ReceptionistEntryModal>,
+    profile_entry_modal: Option<ProfileEntryModal>,
 
     alert_modal: Option<AlertModal>,
     exit_modal: Option<ExitModal>,
@@ -68,57 +294,98 @@ pub struct App {
     about_modal: Option<AboutModal>,
     settings_modal: Option<SettingsModal>,
     local_restore_confirm_modal: Option<ConfirmationModal>,
+    restore_modal: Option<RestoreModal>,
+    /// Backup to restore once the replacement is confirmed.
+    restore_replace: Option<RestorePreview>,
     summary_modal: Option<SummaryModal>,
+    overdue_modal: Option<OverdueModal>,
+    audit_log_modal: Option<AuditLogModal>,
+    database_check_modal: Option<DatabaseCheckModal>,
+    sign_on_modal: Option<SignOnModal>,
+
+    shift: Option<Shift>,
+
+    overdue_count: i64,
+    overdue_checked: Option<Instant>,
+    /// Overdue count being taken on the worker.
+    overdue_pending: Option<Receiver<Result<i64, StorageError>>>,
+
+    /// Local time of the last successful automatic backup.
+    last_backup: Option<chrono::NaiveDateTime>,
+    backup_error: Option<String>,
+    backup_checked: Option<Instant>,
+
+    errors: StorageErrors,
+    /// Why the profile's database could not be opened, in which case a placeholder is open instead.
+    database_error: Option<String>,
 
     config: AppConfig,
 }
 
 impl App {
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        let db_dir = dirs::data_dir().expect("no application data directory").join(APP_NAME);
-
-        std::fs::create_dir_all(&db_dir).expect("failed to create application data directory");
-
-        let db_dir = db_dir.join("db.sqlite");
+        let config: AppConfig = confy::load(APP_NAME, None).unwrap_or_default();
 
-        let mut connection = rusqlite::Connection::open(&db_dir).expect(&format!("failed to open database file: {db_dir:?}"));
+        let db_path = config.database_path(&config.current_profile());
 
-        log::info!("connected to sqlite database");
+        // a locked or damaged database must not stop the window from opening, since it offers the way out
+        let (opened, database_error) = match open_profile_database(&cc.egui_ctx, &db_path) {
+            Ok(opened) => (opened, None),
+            Err(err) => {
+                log::error!("failed to open database file {db_path:?}: {err}");
 
-        crate::embedded::migrations::runner().run(&mut connection).expect("failed to run migrations");
+                let placeholder = open_profile_database(&cc.egui_ctx, Path::new(PLACEHOLDER_DATABASE))
+                    .expect("failed to open in-memory database");
 
-        log::info!("migrations complete");
+                (placeholder, Some(format!("Failed to open {db_path:?}: {err}")))
+            },
+        };
 
-        let student_info = Arc::new(Mutex::new(StudentInfo::new(&mut connection).unwrap()));
-        let connection = Arc::new(Mutex::new(connection));
+        let (worker, (students, receptionists, key_types, game_types, item_types, restrictions, loan_limits, categories, key_records, parcel_records, game_records, item_records, category_records, audit)) = opened;
 
-        let app = App {
+        let mut app = App {
             current_panel: RecordType::Key,
+            current_category: None,
 
             backup_path_handle: None,
             restore_path_handle: None,
             export_path_handle: None,
 
-            connection: Arc::clone(&connection),
-            student_info: Arc::clone(&student_info),
+            worker,
+            task: None,
 
-            key_types: KeyTypeStorage::new(Arc::clone(&connection)).expect("failed to initialise key type storage"),
-            game_types: GameTypeStorage::new(Arc::clone(&connection)).expect("failed to initialise game type storage"),
-            item_types: ItemTypeStorage::new(Arc::clone(&connection)).expect("failed to initialise item type storage"),
+            students,
+            receptionists,
+            key_types,
+            game_types,
+            item_types,
+            restrictions,
+            loan_limits,
+            categories,
 
             key_panel: KeyPanel::default(),
             parcel_panel: ParcelPanel::default(),
             game_panel: GamePanel::default(),
             item_panel: ItemPanel::default(),
-
-            key_records: KeyStorage::new(Arc::clone(&connection), Arc::clone(&student_info)).expect("failed to initialise key record storage"),
-            parcel_records: ParcelStorage::new(Arc::clone(&connection)).expect("failed to initialise parcel record storage"),
-            game_records: GameStorage::new(Arc::clone(&connection), Arc::clone(&student_info)).expect("failed to initialise game record storage"),
-            item_records: ItemStorage::new(Arc::clone(&connection), Arc::clone(&student_info)).expect("failed to initialise item record storage"),
+            category_panel: CategoryPanel::default(),
+
+            key_records,
+            parcel_records,
+            game_records,
+            item_records,
+            category_records,
+            audit,
             
             key_entry_modal: None,
             game_entry_modal: None,
             item_entry_modal: None,
+            category_entry_modal: None,
+            student_entry_modal: None,
+            restriction_entry_modal: None,
+            loan_limit_entry_modal: None,
+            roster_import_modal: None,
+            receptionist_entry_modal: None,
+            profile_entry_modal: None,
 
             alert_modal: None,
             exit_modal: None,
@@ -126,11 +393,37 @@ impl App {
             about_modal: None,
             settings_modal: None,
             local_restore_confirm_modal: None,
+            restore_modal: None,
+            restore_replace: None,
             summary_modal: None,
+            overdue_modal: None,
+            audit_log_modal: None,
+            database_check_modal: None,
+            // ask who is on duty as soon as the app opens
+            sign_on_modal: database_error.is_none().then(SignOnModal::default),
+
+            shift: None,
+
+            overdue_count: 0,
+            overdue_checked: None,
+            overdue_pending: None,
+
+            last_backup: None,
+            backup_error: None,
+            backup_checked: None,
 
-            config: confy::load(APP_NAME, None).unwrap_or_default(),
+            errors: StorageErrors::default(),
+            database_error,
+
+            config,
         };
+
+        app.last_backup = app.config.profile_backup_directory().as_deref().and_then(backup::last_backup);
         
+        // shifts are ended on close, any still open were left by a crash
+        let result = app.receptionists.end_open_shifts();
+        app.errors.check("failed to end open shifts", result);
+
         App::setup_custom_fonts(&cc.egui_ctx);
         
         cc.egui_ctx.set_visuals(egui::Visuals {
@@ -162,13 +455,386 @@ impl App {
 
         log::info!("fonts loaded");
     }
+
+    /// Save an automatic backup and wait for it, if a backup directory is configured. Used on exit,
+    /// when the window cannot wait for a task to report back.
+    fn auto_backup(&mut self) {
+        let Some(directory) = self.config.profile_backup_directory() else {
+            return;
+        };
+
+        let retention = self.config.backup_retention();
+
+        let result = self.worker.handle().query(move |connection| backup::backup(connection, &directory, retention).map_err(|err| err.to_string())).recv()
+            .unwrap_or_else(|_| Err(StorageError::WorkerStopped.to_string()));
+
+        match result {
+            Ok(_) => {
+                self.last_backup = Some(chrono::Local::now().naive_local());
+                self.backup_error = None;
+            },
+            Err(err) => {
+                log::error!("failed to save automatic backup: {err}");
+                self.backup_error = Some(err);
+            },
+        }
+    }
+
+    /// Reopen the database of a profile, rebuilding all storages. Whoever is on duty is signed off.
+    fn switch_profile(&mut self, ctx: &egui::Context, profile: &Profile) {
+        let db_path = self.config.database_path(profile);
+
+        let (worker, storages) = match open_profile_database(ctx, &db_path) {
+            Ok(opened) => opened,
+            Err(err) => {
+                self.alert_modal = Some(AlertModal {
+                    title: "Failed to Open Profile".into(),
+                    description: Some(format!("Failed to open {db_path:?}: {err}")),
+                });
+                log::error!("failed to open database file {db_path:?}: {err}");
+                return;
+            },
+        };
+
+        self.sign_off();
+        self.database_error = None;
+
+        // the old worker stops once it and the old storages are dropped, after finishing whatever it is doing
+        self.worker = worker;
+        self.task = None;
+
+        (
+            self.students, self.receptionists, self.key_types, self.game_types, self.item_types, self.restrictions, self.loan_limits, self.categories,
+            self.key_records, self.parcel_records, self.game_records, self.item_records, self.category_records, self.audit,
+        ) = storages;
+
+        let result = self.receptionists.end_open_shifts();
+        self.errors.check("failed to end open shifts", result);
+
+        // panels and modals may refer to records of the old profile
+        self.key_panel = KeyPanel::default();
+        self.parcel_panel = ParcelPanel::default();
+        self.game_panel = GamePanel::default();
+        self.item_panel = ItemPanel::default();
+        self.category_panel = CategoryPanel::default();
+        self.current_category = None;
+        self.category_entry_modal = None;
+        self.export_modal = None;
+        self.summary_modal = None;
+        self.restriction_entry_modal = None;
+        self.loan_limit_entry_modal = None;
+        self.overdue_modal = None;
+        self.audit_log_modal = None;
+        self.database_check_modal = None;
+        self.restore_modal = None;
+        self.restore_replace = None;
+        self.local_restore_confirm_modal = None;
+
+        self.config.profile = profile.name.clone();
+        self.save_config();
+
+        self.last_backup = self.config.profile_backup_directory().as_deref().and_then(backup::last_backup);
+        self.backup_error = None;
+        self.backup_checked = None;
+        self.overdue_checked = None;
+        self.overdue_pending = None;
+
+        self.sign_on_modal = Some(SignOnModal::default());
+
+        log::info!("switched to profile {}", profile.name);
+    }
+
+    /// Move to or from keeping databases next to the executable. The open database is copied
+    /// to its new location if there is no database there yet.
+    fn set_portable(&mut self, ctx: &egui::Context, portable: bool) {
+        let profile = self.config.current_profile();
+        let old_path = self.config.database_path(&profile);
+
+        self.config.portable = portable;
+
+        let new_path = self.config.database_path(&profile);
+
+        if new_path == old_path {
+            return;
+        }
+
+        if !new_path.exists() {
+            log::info!("copying database to {new_path:?}");
+
+            let result = std::fs::create_dir_all(self.config.database_directory())
+                .map_err(StorageError::FileError)
+                .and_then(|_| {
+                    let new_path = new_path.clone();
+
+                    self.worker.handle().call(move |connection| Ok(connection.backup(rusqlite::DatabaseName::Main, &new_path, None)?))
+                });
+
+            if let Err(err) = result {
+                self.alert_modal = Some(AlertModal {
+                    title: "Failed to Move Database".into(),
+                    description: Some(format!("Failed to copy database to {new_path:?}: {err}")),
+                });
+                log::error!("failed to copy database to {new_path:?}: {err}");
+
+                self.config.portable = !portable;
+                return;
+            }
+        }
+
+        self.switch_profile(ctx, &profile);
+    }
+
+    fn save_config(&self) {
+        match confy::store(APP_NAME, None, &self.config) {
+            Ok(_) => log::info!("updated configuration file"),
+            Err(err) => log::error!("failed to write to configuration file: {err}"),
+        }
+    }
+
+    /// Overwrite the database with a backup.
+    fn replace_database(&mut self, path: &Path) {
+        log::info!("restoring from database backup");
+
+        let backup_path = path.to_owned();
+
+        // migrations are run after restoring, since the backup may be from an older version
+        let restored = self.worker.handle().call(move |connection| {
+            connection.restore(rusqlite::DatabaseName::Main, &backup_path, None::<Box<dyn Fn(rusqlite::backup::Progress)>>)?;
+
+            crate::embedded::migrations::runner().run(connection)?;
+
+            Ok(())
+        });
+
+        let restored = match restored {
+            Ok(_) => {
+                self.alert_modal = Some(AlertModal { title: "Restore Successful".into(), description: None });
+                log::info!("restore successful");
+                true
+            },
+            Err(err) => {
+                self.alert_modal = Some(AlertModal {
+                    title: "Restore Failed".into(),
+                    description: Some(format!("Failed to restore database: {err}")),
+                });
+                log::error!("failed to restore database: {err}");
+                false
+            },
+        };
+        self.refresh_all();
+
+        if restored {
+            let result = self.audit.log_restore(&path.display().to_string());
+            self.errors.check("failed to log restore", result);
+        }
+    }
+
+    /// Put a backup in place of a database file which could not be opened, then open it. The file
+    /// replaced, and its journal if any, are kept beside it.
+    fn restore_unopened_database(&mut self, ctx: &egui::Context, backup_path: &Path) {
+        let profile = self.config.current_profile();
+        let db_path = self.config.database_path(&profile);
+
+        let suffix = chrono::Local::now().format(BACKUP_DATE_TIME_FORMAT).to_string();
+
+        let result = (|| {
+            for extension in ["sqlite", "sqlite-journal", "sqlite-wal", "sqlite-shm"] {
+                let path = db_path.with_extension(extension);
+
+                if path.exists() {
+                    std::fs::rename(&path, db_path.with_extension(format!("{extension}.{suffix}")))?;
+                }
+            }
+
+            std::fs::copy(backup_path, &db_path)
+        })();
+
+        if let Err(err) = result {
+            self.alert_modal = Some(AlertModal {
+                title: "Restore Failed".into(),
+                description: Some(format!("Failed to replace {db_path:?}: {err}")),
+            });
+            log::error!("failed to replace database file {db_path:?} with backup: {err}");
+            return;
+        }
+
+        log::info!("replaced database file {db_path:?} with backup {backup_path:?}");
+
+        self.switch_profile(ctx, &profile);
+
+        if self.database_error.is_none() {
+            self.alert_modal = Some(AlertModal { title: "Restore Successful".into(), description: None });
+
+            let result = self.audit.log_restore(&backup_path.display().to_string());
+            self.errors.check("failed to log restore", result);
+        }
+    }
+
+    /// Refresh every storage, after the database was changed as a whole or to retry after a failure.
+    fn refresh_all(&mut self) {
+        self.errors.check("failed to refresh students", self.students.refresh());
+        self.errors.check("failed to refresh receptionists", self.receptionists.refresh());
+        self.errors.check("failed to refresh key types", self.key_types.refresh());
+        self.errors.check("failed to refresh game types", self.game_types.refresh());
+        self.errors.check("failed to refresh item types", self.item_types.refresh());
+        self.errors.check("failed to refresh restrictions", self.restrictions.refresh());
+        self.errors.check("failed to refresh loan limits", self.loan_limits.refresh());
+        self.errors.check("failed to refresh categories", self.categories.refresh());
+        self.errors.check("failed to refresh key records", self.key_records.refresh());
+        self.errors.check("failed to refresh parcel records", self.parcel_records.refresh());
+        self.errors.check("failed to refresh game records", self.game_records.refresh());
+        self.errors.check("failed to refresh item records", self.item_records.refresh());
+        self.errors.check("failed to refresh category records", self.category_records.refresh());
+        self.errors.check("failed to refresh audit log", self.audit.refresh());
+    }
+
+    /// Refresh the storages of tables changed by this or another program, or every storage if `tables` is empty.
+    fn refresh_changed(&mut self, tables: &[String]) {
+        if tables.is_empty() {
+            self.refresh_all();
+            self.overdue_checked = None;
+
+            if let Some(modal) = &mut self.summary_modal {
+                modal.records_changed();
+            }
+
+            return;
+        }
+
+        for table in tables {
+            match table.as_str() {
+                "students" => { self.errors.check("failed to refresh students", self.students.refresh()); },
+                "receptionists" => { self.errors.check("failed to refresh receptionists", self.receptionists.refresh()); },
+                "keys" => { self.errors.check("failed to refresh key types", self.key_types.refresh()); },
+                "games" => { self.errors.check("failed to refresh game types", self.game_types.refresh()); },
+                "items" | "item_restocks" => { self.errors.check("failed to refresh item types", self.item_types.refresh()); },
+                "restrictions" => { self.errors.check("failed to refresh restrictions", self.restrictions.refresh()); },
+                "loan_limits" => { self.errors.check("failed to refresh loan limits", self.loan_limits.refresh()); },
+                "categories" | "category_fields" | "category_entries" => { self.errors.check("failed to refresh categories", self.categories.refresh()); },
+                "key_records" => { self.errors.check("failed to refresh key records", self.key_records.refresh()); },
+                "parcel_records" => { self.errors.check("failed to refresh parcel records", self.parcel_records.refresh()); },
+                "game_records" => { self.errors.check("failed to refresh game records", self.game_records.refresh()); },
+                "item_records" => {
+                    self.errors.check("failed to refresh item records", self.item_records.refresh());
+                    self.errors.check("failed to refresh item types", self.item_types.refresh());
+                },
+                "category_records" => { self.errors.check("failed to refresh category records", self.category_records.refresh()); },
+                _ => {},
+            }
+        }
+
+
+        // signing out registers the student
+        if tables.iter().any(|table| table.ends_with("_records")) {
+            self.errors.check("failed to refresh students", self.students.refresh());
+
+            if let Some(modal) = &mut self.summary_modal {
+                modal.records_changed();
+            }
+        }
+
+        if tables.iter().any(|table| table == "key_records" || table == "game_records") {
+            self.overdue_checked = None;
+        }
+
+        // every change is logged
+        self.errors.check("failed to refresh audit log", self.audit.refresh());
+    }
+
+    /// Collect events and pages from the worker.
+    fn poll_worker(&mut self) {
+        for event in self.worker.events() {
+            match event {
+                Event::Progress { task, done } => self.task = Some((task, done)),
+                Event::Finished { task, result } => {
+                    self.task = None;
+                    self.finish_task(task, result);
+                },
+                Event::Changed(tables) => self.refresh_changed(&tables),
+            }
+        }
+
+        if let Some(receiver) = &self.overdue_pending {
+            match receiver.try_recv() {
+                Ok(count) => {
+                    self.overdue_pending = None;
+
+                    // keep the last count if counting failed
+                    if let Some(count) = self.errors.check("failed to count overdue records", count) {
+                        self.overdue_count = count;
+                    }
+                },
+                Err(TryRecvError::Empty) => {},
+                Err(TryRecvError::Disconnected) => self.overdue_pending = None,
+            }
+        }
+
+        self.errors.check("failed to refresh key records", self.key_records.poll());
+        self.errors.check("failed to refresh parcel records", self.parcel_records.poll());
+        self.errors.check("failed to refresh game records", self.game_records.poll());
+        self.errors.check("failed to refresh item records", self.item_records.poll());
+        self.errors.check("failed to refresh category records", self.category_records.poll());
+        self.errors.check("failed to refresh audit log", self.audit.poll());
+    }
+
+    fn finish_task(&mut self, task: Task, result: Result<(), String>) {
+        match (task, result) {
+            (Task::Backup, Ok(_)) => {
+                self.alert_modal = Some(AlertModal { title: "Backup Successful".into(), description: None });
+            },
+            (Task::Backup, Err(err)) => {
+                self.alert_modal = Some(AlertModal {
+                    title: "Backup Failed".into(),
+                    description: Some(format!("Failed to backup database: {err}")),
+                });
+            },
+            (Task::AutoBackup, Ok(_)) => {
+                self.last_backup = Some(chrono::Local::now().naive_local());
+                self.backup_error = None;
+            },
+            (Task::AutoBackup, Err(err)) => {
+                self.backup_error = Some(err);
+            },
+            (Task::Export(_), Ok(_)) => {
+                self.alert_modal = Some(AlertModal { title: "Export Successful".into(), description: None });
+            },
+            (Task::Export(_), Err(err)) => {
+                self.alert_modal = Some(AlertModal {
+                    title: "Export Failed".into(),
+                    description: Some(format!("Failed to export data: {err}")),
+                });
+            },
+        }
+    }
+
+    /// Start a backup or export on the worker.
+    fn start_task(&mut self, task: Task, command: Command) {
+        self.worker.send(command);
+        self.task = Some((task, 0.0));
+    }
+
+    /// End the current shift, if anyone is on duty.
+    fn sign_off(&mut self) {
+        if let Some(shift) = self.shift.take() {
+            let result = self.receptionists.end_shift(&shift);
+            self.errors.check("failed to end shift", result);
+        }
+    }
     
 }
 
 impl eframe::App for App {
     fn on_close_event(&mut self) -> bool {
-        if let Some(modal) = &mut self.exit_modal {
-            modal.close_process
+        if let Some(close_process) = self.exit_modal.as_ref().map(|modal| modal.close_process) {
+            if close_process {
+                self.sign_off();
+
+                // the placeholder must not be rotated in amongst the backups of the profile
+                if self.config.backup_on_exit && self.database_error.is_none() {
+                    self.auto_backup();
+                }
+            }
+            close_process
         } else {
             self.exit_modal = Some(ExitModal::default());
             false
@@ -176,6 +842,8 @@ impl eframe::App for App {
     }
 
     fn update(&mut self, ctx: &eframe::egui::Context, frame: &mut eframe::Frame) {
+        self.poll_worker();
+
         // Backup file location thread running separately.
         if let Some(handle) = &self.backup_path_handle {
             if handle.is_finished() {
@@ -183,20 +851,8 @@ impl eframe::App for App {
 
                 if let Some(backup_path) = handle.join().expect("backup path thread panicked") {
                     log::info!("saving database backup");
-                    
-                    match self.connection.lock().unwrap().backup(rusqlite::DatabaseName::Main, backup_path, None) {
-                        Ok(_) => {
-                            self.alert_modal = Some(AlertModal { title: "Backup Successful".into(), description: None });
-                            log::info!("backup successful");
-                        },
-                        Err(err) => {
-                            self.alert_modal = Some(AlertModal {
-                                title: "Backup Failed".into(),
-                                description: Some(format!("Failed to backup database: {err}")),
-                            });
-                            log::error!("failed to backup database: {err}");
-                        },
-                    }
+
+                    self.start_task(Task::Backup, Command::Backup(backup_path));
                 }
             }
         }
@@ -207,32 +863,24 @@ impl eframe::App for App {
                 let handle = self.restore_path_handle.take().unwrap();
 
                 if let Some(restore_path) = handle.join().expect("restore path thread panicked") {
-                    log::info!("restoring from database backup");
-                    
-                    match self.connection.lock().unwrap().restore(rusqlite::DatabaseName::Main, restore_path, None::<Box<dyn Fn(rusqlite::backup::Progress) -> ()>>) {
-                        Ok(_) => {
-
-                            self.alert_modal = Some(AlertModal { title: "Restore Successful".into(), description: None });
-                            log::info!("restore successful");
-                        },
-                        Err(err) => {
-                            self.alert_modal = Some(AlertModal {
-                                title: "Restore Failed".into(),
-                                description: Some(format!("Failed to restore database: {err}")),
-                            });
-                            log::error!("failed to restore database: {err}");
-                        },
+                    if self.database_error.is_some() {
+                        self.restore_unopened_database(ctx, &restore_path);
+                    } else {
+                        log::info!("comparing database backup");
+
+                        match RestorePreview::open(self.worker.handle(), restore_path) {
+                            Ok(preview) => {
+                                self.restore_modal = Some(RestoreModal::new(preview));
+                            },
+                            Err(err) => {
+                                self.alert_modal = Some(AlertModal {
+                                    title: "Restore Failed".into(),
+                                    description: Some(format!("Failed to open backup: {err}")),
+                                });
+                                log::error!("failed to open backup: {err}");
+                            },
+                        }
                     }
-                    // after restore, run migrations
-                    crate::embedded::migrations::runner().run(&mut *self.connection.lock().unwrap()).expect("failed to run migrations after restore");
-                    // refresh everything after restore
-                    self.key_types.refresh().expect("failed to refresh key types");
-                    self.game_types.refresh().expect("failed to refresh game types");
-                    self.item_types.refresh().expect("failed to refresh item types");
-                    self.key_records.refresh().expect("failed to refresh key records");
-                    self.parcel_records.refresh().expect("failed to refresh parcel records");
-                    self.game_records.refresh().expect("failed to refresh game records");
-                    self.item_records.refresh().expect("failed to refresh item records");
                 }
             }
         }
@@ -242,37 +890,40 @@ impl eframe::App for App {
             if handle.is_finished() {
                 let handle = self.export_path_handle.take().unwrap();
 
-                if let (record_type, Some(export_path)) = handle.join().expect("file export thread panicked") {
-                    let result = match record_type {
-                        RecordType::Key => self.key_records.export_csv(export_path),
-                        RecordType::Parcel => self.parcel_records.export_csv(export_path),
-                        RecordType::Game => self.game_records.export_csv(export_path),
-                        RecordType::Item => self.item_records.export_csv(export_path),
-                    };
-
+                if let (source, Some(export_path)) = handle.join().expect("file export thread panicked") {
                     log::info!("exporting records");
 
-                    match result {
-                        Ok(_) => {
-                            self.alert_modal = Some(AlertModal { title: "Export Successful".into(), description: None });
-                            log::info!("export successful");
-                        },
-                        Err(err) => {
-                            self.alert_modal = Some(AlertModal {
-                                title: "Export Failed".into(),
-                                description: match &err {
-                                    StorageError::DbError(_) => Some("An expected error occurred while accessing the database.".into()),
-                                    StorageError::ExportCsvError(err) => Some(format!("Failed to export data: {err}")),
-                                    StorageError::ExportIoError(err) => Some(format!("Failed to export data: {err}")),
-                                }
-                            });
-                            log::error!("failed to export: {err}");
-                        },
-                    }
+                    self.start_task(Task::Export(source), Command::Export { source, path: export_path });
                 }
             }
         }
 
+        // Periodically count overdue keys and games, since they become overdue without any changes to the db.
+        if self.overdue_checked.map(|checked| checked.elapsed() >= OVERDUE_CHECK_INTERVAL).unwrap_or(true) {
+            // replacing an older count drops its result when it arrives
+            self.overdue_pending = Some(self.worker.handle().query(|connection| {
+                Ok(KeyStorage::count_overdue(connection)? + GameStorage::count_overdue(connection)?)
+            }));
+            self.overdue_checked = Some(Instant::now());
+
+            ctx.request_repaint_after(OVERDUE_CHECK_INTERVAL);
+        }
+
+        // Periodically check if an automatic backup is due.
+        if self.backup_checked.map(|checked| checked.elapsed() >= BACKUP_CHECK_INTERVAL).unwrap_or(true) {
+            let interval = chrono::Duration::minutes(self.config.backup_interval_minutes as i64);
+            let due = self.last_backup.map(|last| chrono::Local::now().naive_local() - last >= interval).unwrap_or(true);
+
+            // wait for a backup or export which is still running, and never back up the placeholder
+            if let (true, None, Some(directory)) = (self.config.backup_interval_minutes > 0 && due && self.database_error.is_none(), self.task, self.config.profile_backup_directory()) {
+                self.start_task(Task::AutoBackup, Command::AutoBackup { directory, retention: self.config.backup_retention() });
+            }
+
+            self.backup_checked = Some(Instant::now());
+
+            ctx.request_repaint_after(BACKUP_CHECK_INTERVAL);
+        }
+
         // Exit Modal
         if let Some(modal) = &mut self.exit_modal {
             let close_modal = modal.render(ctx, frame);
@@ -293,7 +944,7 @@ impl eframe::App for App {
 
         // Export Modal
         if let Some(modal) = &mut self.export_modal {
-            let close_modal = modal.render(ctx);
+            let close_modal = modal.render(ctx, &self.categories);
 
             if close_modal {
                 self.export_path_handle = modal.path_handle.take();
@@ -315,15 +966,69 @@ impl eframe::App for App {
             let close_modal = modal.render(ctx);
 
             if close_modal {
+                let modal = self.settings_modal.take().unwrap();
+
                 if !modal.cancelled {
                     self.config.facility_name = modal.facility_name.trim().into();
-                    
-                    match confy::store(APP_NAME, None, &self.config) {
-                        Ok(_) => log::info!("updated configuration file"),
-                        Err(err) => log::error!("failed to write to configuration file: {err}"),
+                    self.config.backup_directory = modal.backup_directory.clone();
+                    self.config.backup_interval_minutes = modal.backup_interval_minutes;
+                    self.config.backup_on_exit = modal.backup_on_exit;
+                    self.config.backup_keep_hourly = modal.backup_keep_hourly;
+                    self.config.backup_keep_daily = modal.backup_keep_daily;
+                    self.config.backup_keep_monthly = modal.backup_keep_monthly;
+
+                    self.last_backup = self.config.profile_backup_directory().as_deref().and_then(backup::last_backup);
+                    self.backup_error = None;
+                    // check straight away, in case a backup is now due
+                    self.backup_checked = None;
+
+                    if modal.portable != self.config.portable {
+                        self.set_portable(ctx, modal.portable);
                     }
+                    
+                    self.save_config();
+                }
+            }
+        }
+
+        // Restore Modal
+        if let Some(modal) = &mut self.restore_modal {
+            let close_modal = modal.render(ctx);
+
+            if close_modal {
+                let modal = self.restore_modal.take().unwrap();
+
+                match modal.action {
+                    Some(RestoreAction::Merge) => {
+                        match modal.preview.merge() {
+                            Ok(merged) => {
+                                self.alert_modal = Some(AlertModal {
+                                    title: "Merge Successful".into(),
+                                    description: Some(format!("Merged {merged} entries from the backup.")),
+                                });
+                            },
+                            Err(err) => {
+                                self.alert_modal = Some(AlertModal {
+                                    title: "Merge Failed".into(),
+                                    description: Some(format!("Failed to merge backup: {err}")),
+                                });
+                                log::error!("failed to merge backup: {err}");
+                            },
+                        }
+
+                        self.refresh_all();
+                    },
+                    Some(RestoreAction::Replace) => {
+                        self.local_restore_confirm_modal = Some(
+                            ConfirmationModal::new(
+                                CONFIRMATION_TITLE,
+                                Some(RESTORE_CONFIRM_TEXT)
+                            )
+                        );
+                        self.restore_replace = Some(modal.preview);
+                    },
+                    None => {},
                 }
-                self.settings_modal = None;
             }
         }
 
@@ -332,30 +1037,70 @@ impl eframe::App for App {
             let close_modal = modal.render(ctx);
 
             if modal.confirmed {
-                self.restore_path_handle = Some(std::thread::spawn(|| {
-                    rfd::FileDialog::new()
-                        .add_filter("Sqlite DB Backup", &["sqlite"])
-                        .pick_file()
-                }));
+                if let Some(preview) = self.restore_replace.take() {
+                    self.replace_database(&preview.path);
+                }
             }
 
             if close_modal {
                 self.local_restore_confirm_modal = None;
+                self.restore_replace = None;
             }
         }
 
         // Summary Modal
         if let Some(modal) = &mut self.summary_modal {
-            let close_modal = modal.render(ctx, &self.key_records, &self.parcel_records, &self.game_records, &self.item_records);
+            let records = SummaryRecords {
+                keys: &self.key_records,
+                parcels: &self.parcel_records,
+                games: &self.game_records,
+                items: &self.item_records,
+                categories: &self.categories,
+                category_records: &self.category_records,
+            };
+            let close_modal = modal.render(ctx, &self.config.facility_name, &records, &mut self.errors);
 
             if close_modal {
                 self.summary_modal = None;
             }
         }
 
+        // Overdue Modal
+        if let Some(modal) = &mut self.overdue_modal {
+            let close_modal = modal.render(ctx, &self.key_records, &self.game_records, &mut self.errors);
+
+            if close_modal {
+                self.overdue_modal = None;
+            }
+        }
+
+        // Audit Log Modal
+        if let Some(modal) = &mut self.audit_log_modal {
+            let close_modal = modal.render(ctx, &mut self.audit, &mut self.errors);
+
+            if close_modal {
+                self.audit_log_modal = None;
+            }
+        }
+
+        // Database Check Modal
+        if let Some(modal) = &mut self.database_check_modal {
+            let close_modal = modal.render(ctx, &self.worker.handle(), self.shift.as_ref(), &mut self.errors);
+
+            if modal.repaired {
+                modal.repaired = false;
+                self.refresh_all();
+                self.overdue_checked = None;
+            }
+
+            if close_modal {
+                self.database_check_modal = None;
+            }
+        }
+
         // Key Type Entry Modal
         if let Some(modal) = &mut self.key_entry_modal {
-            let close_modal = modal.render(ctx, &mut self.key_types);
+            let close_modal = modal.render(ctx, &mut self.key_types, &mut self.errors);
 
             if close_modal {
                 self.key_entry_modal = None;
@@ -364,7 +1109,7 @@ impl eframe::App for App {
 
         // Game Type Entry Modal
         if let Some(modal) = &mut self.game_entry_modal {
-            let close_modal = modal.render(ctx, &mut self.game_types);
+            let close_modal = modal.render(ctx, &mut self.game_types, &mut self.errors);
 
             if close_modal {
                 self.game_entry_modal = None;
@@ -373,13 +1118,135 @@ impl eframe::App for App {
 
         // Item Type Entry Modal
         if let Some(modal) = &mut self.item_entry_modal {
-            let close_modal = modal.render(ctx, &mut self.item_types);
+            let close_modal = modal.render(ctx, &mut self.item_types, self.shift.as_ref(), &mut self.errors);
 
             if close_modal {
                 self.item_entry_modal = None;
             }
         }
 
+        // Category Entry Modal
+        if let Some(modal) = &mut self.category_entry_modal {
+            let close_modal = modal.render(ctx, &mut self.categories, &mut self.errors);
+
+            if close_modal {
+                self.category_entry_modal = None;
+            }
+        }
+
+        // Student Entry Modal
+        if let Some(modal) = &mut self.student_entry_modal {
+            let close_modal = modal.render(ctx, &mut self.students, &mut self.errors);
+
+            if close_modal {
+                self.student_entry_modal = None;
+            }
+        }
+
+        let catalogue = Catalogue { key_types: &self.key_types, game_types: &self.game_types, item_types: &self.item_types, categories: &self.categories };
+
+        // Restriction Entry Modal
+        if let Some(modal) = &mut self.restriction_entry_modal {
+            let close_modal = modal.render(ctx, &mut self.restrictions, &catalogue, self.shift.as_ref(), &mut self.errors);
+
+            if close_modal {
+                self.restriction_entry_modal = None;
+            }
+        }
+
+        // Loan Limit Entry Modal
+        if let Some(modal) = &mut self.loan_limit_entry_modal {
+            let close_modal = modal.render(ctx, &mut self.loan_limits, &catalogue, &mut self.errors);
+
+            if close_modal {
+                self.loan_limit_entry_modal = None;
+            }
+        }
+
+        // Receptionist Entry Modal
+        if let Some(modal) = &mut self.receptionist_entry_modal {
+            let close_modal = modal.render(ctx, &mut self.receptionists, self.shift.as_ref(), &mut self.errors);
+
+            if close_modal {
+                self.receptionist_entry_modal = None;
+            }
+        }
+
+        // Sign On Modal
+        if let Some(modal) = &mut self.sign_on_modal {
+            let close_modal = modal.render(ctx, &self.receptionists);
+
+            if close_modal {
+                if let (true, Some(receptionist)) = (modal.confirmed, &modal.receptionist) {
+                    let result = self.receptionists.start_shift(receptionist);
+                    self.shift = self.errors.check("failed to start shift", result);
+                }
+                self.sign_on_modal = None;
+            }
+        }
+
+        // Profile Entry Modal
+        if let Some(modal) = &mut self.profile_entry_modal {
+            let close_modal = modal.render(ctx, &mut self.config);
+
+            if close_modal {
+                let open = modal.open.take();
+
+                self.profile_entry_modal = None;
+                self.save_config();
+
+                if let Some(profile) = open {
+                    self.switch_profile(ctx, &profile);
+                }
+            }
+        }
+
+        // Roster Import Modal
+        if let Some(modal) = &mut self.roster_import_modal {
+            let close_modal = modal.render(ctx, &mut self.students);
+
+            if close_modal {
+                if let Some(imported) = modal.imported {
+                    self.alert_modal = Some(AlertModal {
+                        title: "Import Successful".into(),
+                        description: Some(format!("Added or updated {imported} students.")),
+                    });
+                }
+                self.roster_import_modal = None;
+            }
+        }
+
+        // Storage Errors
+        if !self.errors.is_empty() {
+            let mut retry = false;
+
+            egui::TopBottomPanel::top("error_banner")
+                .frame(egui::Frame::side_top_panel(&ctx.style()).fill(egui::Rgba::from_rgb(0.25, 0.0, 0.0).into()))
+                .show(ctx, |ui| {
+                    ui.add_space(4.0);
+
+                    for error in self.errors.iter() {
+                        ui.label(egui::RichText::new(error).color(egui::Color32::WHITE));
+                    }
+
+                    ui.horizontal(|ui| {
+                        retry = ui.button("Retry").clicked();
+
+                        if ui.button("Dismiss").clicked() {
+                            self.errors.clear();
+                        }
+                    });
+
+                    ui.add_space(4.0);
+                });
+
+            if retry {
+                self.errors.clear();
+                self.refresh_all();
+                self.overdue_checked = None;
+            }
+        }
+
         egui::SidePanel::new(egui::panel::Side::Left, egui::Id::new("left_panel"))
             .show(ctx, |ui| {
                 egui::menu::bar(ui, |ui| {
@@ -392,12 +1259,20 @@ impl eframe::App for App {
                             self.summary_modal = Some(SummaryModal::default());
                             ui.close_menu();
                         }
+                        if ui.button("Audit Log").clicked() {
+                            self.audit_log_modal = Some(AuditLogModal::default());
+                            ui.close_menu();
+                        }
+                        if ui.button("Profiles").clicked() {
+                            self.profile_entry_modal = Some(ProfileEntryModal::default());
+                            ui.close_menu();
+                        }
                         if ui.button("Settings").clicked() {
-                            self.settings_modal = Some(SettingsModal::new(&mut self.config));
+                            self.settings_modal = Some(SettingsModal::new(&self.config));
                             ui.close_menu();
                         }
                         if ui.button("About").clicked() {
-                            self.about_modal = Some(AboutModal::default());
+                            self.about_modal = Some(AboutModal);
                             ui.close_menu();
                         }
                         if ui.button("Quit").clicked() {
@@ -418,24 +1293,51 @@ impl eframe::App for App {
                             self.item_entry_modal = Some(ItemEntryModal::default());
                             ui.close_menu();
                         }
+                        if ui.button("Edit Categories").clicked() {
+                            self.category_entry_modal = Some(CategoryEntryModal::default());
+                            ui.close_menu();
+                        }
+                        if ui.button("Edit Students").clicked() {
+                            self.student_entry_modal = Some(StudentEntryModal::default());
+                            ui.close_menu();
+                        }
+                        if ui.button("Edit Restrictions").clicked() {
+                            self.restriction_entry_modal = Some(RestrictionEntryModal::default());
+                            ui.close_menu();
+                        }
+                        if ui.button("Edit Borrowing Limits").clicked() {
+                            self.loan_limit_entry_modal = Some(LoanLimitEntryModal::default());
+                            ui.close_menu();
+                        }
+                        if ui.button("Edit Receptionists").clicked() {
+                            self.receptionist_entry_modal = Some(ReceptionistEntryModal::default());
+                            ui.close_menu();
+                        }
+                        if ui.button("Import Student Roster").clicked() {
+                            self.roster_import_modal = Some(RosterImportModal::default());
+                            ui.close_menu();
+                        }
                     });
                     ui.menu_button("Backup", |ui| {
                         if ui.button("Save Local Backup").clicked() {
                             self.backup_path_handle = Some(std::thread::spawn(|| {
                                 rfd::FileDialog::new()
                                     .add_filter("Sqlite DB Backup", &["sqlite"])
-                                    .set_file_name(&format!("backup_{}.sqlite", chrono::Local::now().format(BACKUP_DATE_TIME_FORMAT).to_string()))
+                                    .set_file_name(format!("backup_{}.sqlite", chrono::Local::now().format(BACKUP_DATE_TIME_FORMAT)))
                                     .save_file()
                             }));
                             ui.close_menu();
                         }
                         if ui.button("Restore Local Backup").clicked() {
-                            self.local_restore_confirm_modal = Some(
-                                ConfirmationModal::new(
-                                    CONFIRMATION_TITLE,
-                                    Some(RESTORE_CONFIRM_TEXT)
-                                )
-                            );
+                            self.restore_path_handle = Some(std::thread::spawn(|| {
+                                rfd::FileDialog::new()
+                                    .add_filter("Sqlite DB Backup", &["sqlite"])
+                                    .pick_file()
+                            }));
+                            ui.close_menu();
+                        }
+                        if ui.button("Check Database").clicked() {
+                            self.database_check_modal = Some(DatabaseCheckModal::default());
                             ui.close_menu();
                         }
                     });
@@ -448,57 +1350,217 @@ impl eframe::App for App {
                     ui.heading(egui::RichText::new(APP_NAME).color(egui::Color32::WHITE));
                 });
 
-                if self.config.facility_name.len() > 0 {
+                if !self.config.facility_name.is_empty() {
                     ui.label(&self.config.facility_name);
                 }
 
+                // Profile
+                if self.config.profiles.len() > 1 {
+                    let current = self.config.current_profile();
+                    let mut selected = current.clone();
+
+                    egui::ComboBox::from_id_source("profile")
+                        .selected_text(&current.name)
+                        .show_ui(ui, |ui| {
+                            for profile in &self.config.profiles {
+                                ui.selectable_value(&mut selected, profile.clone(), &profile.name);
+                            }
+                        });
+
+                    if selected != current {
+                        self.switch_profile(ctx, &selected);
+                    }
+                }
+
+                ui.add_space(4.0);
+
+                // On Duty
+                let mut sign_off = false;
+
+                match &self.shift {
+                    Some(shift) => {
+                        ui.label(format!("On duty: {}", shift.receptionist.name))
+                            .on_hover_text(format!("Since {}", chrono::DateTime::<chrono::Local>::from(shift.time_start).format(DATE_TIME_FORMAT)));
+                        ui.vertical_centered_justified(|ui| {
+                            sign_off = ui.button("Sign Off").clicked();
+                        });
+                    },
+                    None => {
+                        ui.colored_label(egui::Rgba::from_rgb(0.25, 0.0, 0.0), "Nobody on duty");
+                        ui.vertical_centered_justified(|ui| {
+                            if ui.add_enabled(self.database_error.is_none(), egui::Button::new("Sign On")).clicked() {
+                                self.sign_on_modal = Some(SignOnModal::default());
+                            }
+                        });
+                    },
+                }
+
+                if sign_off {
+                    self.sign_off();
+                    self.sign_on_modal = Some(SignOnModal::default());
+                }
+
                 ui.add_space(4.0);
 
+                // Automatic Backups
+                if let Some(error) = &self.backup_error {
+                    ui.colored_label(egui::Rgba::from_rgb(0.25, 0.0, 0.0), "Backup failed")
+                        .on_hover_text(error);
+                } else if self.config.backup_directory.is_none() {
+                    ui.colored_label(egui::Rgba::from_rgb(0.25, 0.0, 0.0), "Automatic backups off")
+                        .on_hover_text("Choose a backup folder in File → Settings.");
+                } else {
+                    match self.last_backup {
+                        Some(last_backup) => ui.weak(format!("Last backup: {}", last_backup.format(DATE_TIME_FORMAT))),
+                        None => ui.weak("No backups yet"),
+                    };
+                }
+
+                // Backup or Export
+                if let Some((task, done)) = self.task {
+                    ui.add(egui::ProgressBar::new(done).text(task.to_string()));
+                }
+
+                // Unreadable Rows
+                let corrupt = self.key_records.corrupt().iter()
+                    .chain(self.parcel_records.corrupt())
+                    .chain(self.game_records.corrupt())
+                    .chain(self.item_records.corrupt())
+                    .chain(self.category_records.corrupt())
+                    .chain(self.audit.corrupt())
+                    .map(|row| row.to_string())
+                    .collect::<Vec<_>>();
+
+                if !corrupt.is_empty() {
+                    ui.colored_label(egui::Rgba::from_rgb(0.25, 0.0, 0.0), format!("{} unreadable records skipped", corrupt.len()))
+                        .on_hover_text(corrupt.join("\n"));
+                }
+
+                ui.separator();
+
                 ui.vertical_centered_justified(|ui| {
                     if ui.button("Keys").clicked() {
                         self.current_panel = RecordType::Key;
-                        self.key_records.refresh().expect("failed to refresh key records");
+                        self.current_category = None;
+                        self.errors.check("failed to refresh key records", self.key_records.refresh());
                     }
                     if ui.button("Parcels").clicked() {
                         self.current_panel = RecordType::Parcel;
-                        self.parcel_records.refresh().expect("failed to refresh key records");
+                        self.current_category = None;
+                        self.errors.check("failed to refresh parcel records", self.parcel_records.refresh());
                     }
                     if ui.button("Games").clicked() {
                         self.current_panel = RecordType::Game;
-                        self.game_records.refresh().expect("failed to refresh key records");
+                        self.current_category = None;
+                        self.errors.check("failed to refresh game records", self.game_records.refresh());
                     }
                     if ui.button("Items").clicked() {
                         self.current_panel = RecordType::Item;
-                        self.item_records.refresh().expect("failed to refresh key records");
+                        self.current_category = None;
+                        self.errors.check("failed to refresh item records", self.item_records.refresh());
+                    }
+
+                    for category in self.categories.get_all() {
+                        if ui.button(&category.name).clicked() {
+                            self.current_category = Some(category.id);
+                            // filters and pages of the last category shown do not apply
+                            if self.category_records.category_id() != Some(category.id) {
+                                self.category_panel = CategoryPanel::default();
+                            }
+                            self.errors.check(&format!("failed to refresh {} records", category.name), self.category_records.set_category(category.id));
+                        }
+                    }
+
+                    if self.overdue_count > 0 {
+                        ui.add_space(4.0);
+
+                        let text = egui::RichText::new(format!("Overdue ({})", self.overdue_count)).color(egui::Color32::WHITE);
+                        
+                        if ui.add(egui::Button::new(text).fill(egui::Rgba::from_rgb(0.25, 0.0, 0.0))).clicked() {
+                            self.overdue_modal = Some(OverdueModal::default());
+                            // recount, in case something was signed in
+                            self.overdue_checked = None;
+                        }
                     }
                 });
             });
 
+        // Database Unavailable
+        if let Some(error) = &self.database_error {
+            let mut retry = false;
+
+            egui::CentralPanel::default()
+                .show(ctx, |ui| {
+                    ui.heading("Database Unavailable");
+                    ui.add_space(4.0);
+                    ui.colored_label(egui::Rgba::from_rgb(0.25, 0.0, 0.0), error);
+                    ui.label("Nothing can be signed in or out until it is opened. Retry once no other program is using it, open another profile, or restore a backup in its place.");
+                    ui.add_space(4.0);
+
+                    ui.horizontal(|ui| {
+                        retry = ui.button("Retry").clicked();
+
+                        if ui.button("Profiles").clicked() {
+                            self.profile_entry_modal = Some(ProfileEntryModal::default());
+                        }
+                        if ui.add_enabled(self.restore_path_handle.is_none(), egui::Button::new("Restore Local Backup")).clicked() {
+                            self.restore_path_handle = Some(std::thread::spawn(|| {
+                                rfd::FileDialog::new()
+                                    .add_filter("Sqlite DB Backup", &["sqlite"])
+                                    .pick_file()
+                            }));
+                        }
+                    });
+                });
+
+            if retry {
+                let profile = self.config.current_profile();
+                self.switch_profile(ctx, &profile);
+            }
+
+            return;
+        }
+
+        if self.current_category.and_then(|id| self.categories.get(id)).is_some() {
+            let mut sign_out = SignOut { students: &mut self.students, restrictions: &self.restrictions, limits: &self.loan_limits, receptionists: &self.receptionists };
+
+            egui::CentralPanel::default()
+                .show(ctx, |ui| {
+                    self.category_panel.render(ui, &self.categories, &mut self.category_records, &mut sign_out, self.shift.as_ref(), &mut self.errors);
+                });
+
+            return;
+        }
+
+        let mut sign_out = SignOut { students: &mut self.students, restrictions: &self.restrictions, limits: &self.loan_limits, receptionists: &self.receptionists };
+
         match self.current_panel {
             RecordType::Key => {
                 egui::CentralPanel::default()
                     .show(ctx, |ui| {
-                        self.key_panel.render(ctx, ui, &self.key_types, &mut self.key_records, Arc::clone(&self.student_info));
+                        self.key_panel.render(ui, &self.key_types, &mut self.key_records, &mut sign_out, self.shift.as_ref(), &mut self.errors);
                     });
             },
             RecordType::Parcel => {
                 egui::CentralPanel::default()
                     .show(ctx, |ui| {
-                        self.parcel_panel.render(ctx, ui, &mut self.parcel_records);
+                        self.parcel_panel.render(ui, &mut self.parcel_records, self.shift.as_ref(), &mut self.errors);
                     });
             },
             RecordType::Game => {
                 egui::CentralPanel::default()
                     .show(ctx, |ui| {
-                        self.game_panel.render(ctx, ui, &self.game_types, &mut self.game_records, Arc::clone(&self.student_info));
+                        self.game_panel.render(ui, &self.game_types, &mut self.game_records, &mut sign_out, self.shift.as_ref(), &mut self.errors);
                     });
             },
             RecordType::Item => {
                 egui::CentralPanel::default()
                     .show(ctx, |ui| {
-                        self.item_panel.render(ctx, ui, &self.item_types, &mut self.item_records, Arc::clone(&self.student_info));
+                        self.item_panel.render(ui, &mut self.item_types, &mut self.item_records, &mut sign_out, self.shift.as_ref(), &mut self.errors);
                     });
             },
+            // categories are shown by their own panel, above
+            RecordType::Category => {},
         };
     }
 }
@@ -0,0 +1,216 @@
+use std::{path::PathBuf, process::ExitCode};
+
+use chrono::Datelike;
+use clap::{Parser, Subcommand, ValueEnum};
+
+use blackcurrant::app::AppConfig;
+use blackcurrant_core::{APP_NAME, backup, records::{self, KeyStorage, ParcelStorage, GameStorage, ItemStorage, ExportableStorage, StorageError, Summary, CountWithin}, worker::DbWorker};
+
+/// Command-line companion to Blackcurrant, using the same database and settings.
+#[derive(Debug, Parser)]
+#[command(name = "blackcurrant-cli", version)]
+struct Cli {
+    /// Profile to use, instead of the one last opened.
+    #[arg(long, global = true)]
+    profile: Option<String>,
+    /// Database file to use, instead of the profile's database.
+    #[arg(long, global = true)]
+    database: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// List keys and games which are still signed out.
+    Outstanding,
+    /// Export all records of a type to a CSV file.
+    Export {
+        #[arg(value_enum)]
+        record_type: ExportType,
+        path: PathBuf,
+    },
+    /// Save a backup of the database. Defaults to the automatic backup folder.
+    Backup {
+        directory: Option<PathBuf>,
+    },
+    /// Check that a backup can be opened and is not corrupt.
+    Verify {
+        path: PathBuf,
+    },
+    /// Print the summary of a month, last month by default.
+    Summary {
+        /// Month in the format YYYY-MM.
+        #[arg(long)]
+        month: Option<String>,
+    },
+    /// Bring the database up to date, printing any migrations applied.
+    Migrate,
+    /// Check the database for corruption and invalid records. Problems are fixed from the window.
+    Check,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ExportType {
+    Keys,
+    Parcels,
+    Games,
+    Items,
+}
+
+fn main() -> ExitCode {
+    env_logger::init();
+
+    let cli = Cli::parse();
+
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        },
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let config: AppConfig = confy::load(APP_NAME, None)?;
+
+    let profile = match &cli.profile {
+        Some(name) => config.profiles.iter()
+            .find(|p| p.name.to_lowercase() == name.to_lowercase())
+            .cloned()
+            .ok_or(format!("no profile named {name:?}"))?,
+        None => config.current_profile(),
+    };
+
+    let database_path = cli.database.clone().unwrap_or(config.database_path(&profile));
+
+    if let Command::Migrate = cli.command {
+        let mut connection = rusqlite::Connection::open(&database_path)?;
+
+        let report = blackcurrant_core::embedded::migrations::runner().run(&mut connection)?;
+
+        if report.applied_migrations().is_empty() {
+            println!("{} is up to date", database_path.display());
+        }
+
+        for migration in report.applied_migrations() {
+            println!("applied {migration}");
+        }
+
+        return Ok(());
+    }
+
+    let worker = DbWorker::spawn(&database_path, || {})?;
+
+    match cli.command {
+        Command::Outstanding => {
+            let keys = KeyStorage::new(worker.handle(), false)?;
+            let games = GameStorage::new(worker.handle(), false)?;
+
+            for record in keys.fetch_outstanding()? {
+                println!("key\t{}\t{}\t{}\t{}\t{}",
+                    record.key,
+                    record.student_name,
+                    record.student_number,
+                    format_local(record.time_out),
+                    record.time_due.map(format_local).unwrap_or_default(),
+                );
+            }
+
+            for record in games.fetch_outstanding()? {
+                println!("game\t{} (x{})\t{}\t{}\t{}\t{}",
+                    record.game,
+                    record.quantity,
+                    record.student_name,
+                    record.student_number,
+                    format_local(record.time_out),
+                    record.time_due.map(format_local).unwrap_or_default(),
+                );
+            }
+        },
+        Command::Export { record_type, path } => {
+            match record_type {
+                ExportType::Keys => worker.handle().call(move |connection| KeyStorage::export_csv(connection, path, |_| {}))?,
+                ExportType::Parcels => worker.handle().call(move |connection| ParcelStorage::export_csv(connection, path, |_| {}))?,
+                ExportType::Games => worker.handle().call(move |connection| GameStorage::export_csv(connection, path, |_| {}))?,
+                ExportType::Items => worker.handle().call(move |connection| ItemStorage::export_csv(connection, path, |_| {}))?,
+            }
+        },
+        Command::Backup { directory } => {
+            let directory = directory
+                .or(config.profile_backup_directory())
+                .ok_or("no backup folder given, and automatic backups are off")?;
+
+            let retention = config.backup_retention();
+            let path = worker.handle()
+                .query(move |connection| backup::backup(connection, &directory, retention))
+                .recv()
+                .map_err(|_| StorageError::WorkerStopped)??;
+
+            println!("{}", path.display());
+        },
+        Command::Verify { path } => {
+            backup::verify(&path)?;
+
+            println!("{} is ok", path.display());
+        },
+        Command::Summary { month } => {
+            let month_start = match month {
+                Some(month) => chrono::NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d")
+                    .map_err(|_| format!("invalid month {month:?}, expected YYYY-MM"))?,
+                None => {
+                    let now = chrono::Local::now().date_naive();
+                    now - chrono::Days::new(now.day0() as u64) - chrono::Months::new(1)
+                },
+            };
+
+            let start = records::local_midnight(month_start);
+            let end = records::local_midnight(month_start + chrono::Months::new(1));
+
+            let keys = KeyStorage::new(worker.handle(), false)?;
+            let parcels = ParcelStorage::new(worker.handle(), false)?;
+            let games = GameStorage::new(worker.handle(), false)?;
+            let items = ItemStorage::new(worker.handle(), false)?;
+
+            println!("{}", month_start.format("%B %Y"));
+
+            println!("\nKeys");
+            print_summary(keys.summary(start, end)?);
+
+            println!("\nParcels\n{}", parcels.count_within(start, end)?);
+
+            println!("\nGames");
+            print_summary(games.summary(start, end)?);
+
+            println!("\nItems");
+            print_summary(items.summary(start, end)?);
+        },
+        Command::Check => {
+            let findings = worker.handle().call(|connection| records::check_database(connection))?;
+
+            for finding in &findings {
+                println!("{finding}");
+            }
+
+            if !findings.is_empty() {
+                return Err(format!("{} problems found", findings.len()).into());
+            }
+
+            println!("{} is ok", database_path.display());
+        },
+        Command::Migrate => unreachable!("migrations are run above"),
+    }
+
+    Ok(())
+}
+
+fn print_summary(summary: Vec<(String, i64)>) {
+    for (name, count) in summary {
+        println!("{name}\t{count}");
+    }
+}
+
+fn format_local(time: chrono::DateTime<chrono::Utc>) -> String {
+    time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string()
+}
@@ -0,0 +1,158 @@
+//! Charts drawn with the painter, for the summary.
+
+use chrono::Datelike;
+
+pub const CHART_WIDTH: f32 = 480.0;
+pub const CHART_HEIGHT: f32 = 96.0;
+const HEATMAP_CELL_SIZE: f32 = 18.0;
+/// Room left of the plot for the value labels.
+const AXIS_MARGIN: f32 = 32.0;
+/// Room under the plot for the date labels.
+const LABEL_HEIGHT: f32 = 14.0;
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChartStyle {
+    #[default]
+    Bar,
+    Line,
+}
+
+/// Chart of a count for each day, with the previous period's counts drawn faintly behind, day for day.
+pub fn daily_chart(ui: &mut egui::Ui, daily: &[(chrono::NaiveDate, i64)], previous: Option<&[(chrono::NaiveDate, i64)]>, style: ChartStyle) {
+    let (response, painter) = ui.allocate_painter(egui::vec2(CHART_WIDTH, CHART_HEIGHT + LABEL_HEIGHT), egui::Sense::hover());
+
+    let plot = egui::Rect::from_min_max(
+        response.rect.min + egui::vec2(AXIS_MARGIN, 0.0),
+        response.rect.max - egui::vec2(0.0, LABEL_HEIGHT),
+    );
+
+    let visuals = ui.visuals();
+    let colour = visuals.selection.bg_fill;
+    let previous_colour = visuals.weak_text_color();
+    let text_colour = visuals.text_color();
+    let font = egui::FontId::proportional(11.0);
+
+    let max = daily.iter()
+        .chain(previous.unwrap_or_default())
+        .map(|(_, count)| *count)
+        .max()
+        .unwrap_or_default()
+        .max(1);
+
+    painter.rect_filled(plot, 0.0, visuals.extreme_bg_color);
+    painter.text(plot.left_top() - egui::vec2(4.0, 0.0), egui::Align2::RIGHT_TOP, max.to_string(), font.clone(), text_colour);
+    painter.text(plot.left_bottom() - egui::vec2(4.0, 0.0), egui::Align2::RIGHT_BOTTOM, "0", font.clone(), text_colour);
+
+    if daily.is_empty() {
+        return;
+    }
+
+    let slot = plot.width() / daily.len() as f32;
+    let x = |i: usize| plot.left() + slot * (i as f32 + 0.5);
+    let y = |count: i64| plot.bottom() - plot.height() * count as f32 / max as f32;
+
+    let points = |counts: &[(chrono::NaiveDate, i64)]| counts.iter()
+        .take(daily.len())
+        .enumerate()
+        .map(|(i, (_, count))| egui::pos2(x(i), y(*count)))
+        .collect::<Vec<_>>();
+
+    match style {
+        ChartStyle::Bar => {
+            let bar = (slot * 0.8).max(1.0);
+
+            if let Some(previous) = previous {
+                for point in points(previous) {
+                    let rect = egui::Rect::from_min_max(egui::pos2(point.x - bar / 2.0, point.y), egui::pos2(point.x + bar / 2.0, plot.bottom()));
+                    painter.rect_stroke(rect, 0.0, egui::Stroke::new(1.0, previous_colour));
+                }
+            }
+
+            for point in points(daily) {
+                let rect = egui::Rect::from_min_max(egui::pos2(point.x - bar / 4.0, point.y), egui::pos2(point.x + bar / 4.0, plot.bottom()));
+                painter.rect_filled(rect, 0.0, colour);
+            }
+        },
+        ChartStyle::Line => {
+            if let Some(previous) = previous {
+                painter.extend(egui::Shape::dashed_line(&points(previous), egui::Stroke::new(1.0, previous_colour), 4.0, 4.0));
+            }
+
+            painter.add(egui::Shape::line(points(daily), egui::Stroke::new(2.0, colour)));
+        },
+    }
+
+    // first and last day, and the start of each month in between
+    for (i, (date, _)) in daily.iter().enumerate() {
+        if i == 0 || i == daily.len() - 1 || (date.day() == 1 && daily.len() > 31) {
+            let align = if i == 0 { egui::Align2::LEFT_TOP } else if i == daily.len() - 1 { egui::Align2::RIGHT_TOP } else { egui::Align2::CENTER_TOP };
+            let left = if i == 0 { plot.left() } else if i == daily.len() - 1 { plot.right() } else { x(i) };
+
+            painter.text(egui::pos2(left, plot.bottom() + 1.0), align, date.format("%d %b").to_string(), font.clone(), text_colour);
+        }
+    }
+
+    if let Some(pointer) = response.hover_pos().filter(|pointer| plot.contains(*pointer)) {
+        let i = (((pointer.x - plot.left()) / slot) as usize).min(daily.len() - 1);
+        let (date, count) = daily[i];
+
+        painter.vline(x(i), plot.y_range(), egui::Stroke::new(1.0, previous_colour));
+
+        let text = match previous.and_then(|previous| previous.get(i)) {
+            Some((previous_date, previous_count)) => format!("{}: {count}\n{}: {previous_count}", date.format("%a %d %b %Y"), previous_date.format("%a %d %b %Y")),
+            None => format!("{}: {count}", date.format("%a %d %b %Y")),
+        };
+
+        response.on_hover_text_at_pointer(text);
+    }
+}
+
+/// Grid of counts for each hour of each weekday, darker the busier.
+pub fn heatmap(ui: &mut egui::Ui, hourly: &[[i64; 24]; 7]) {
+    let size = egui::vec2(AXIS_MARGIN + HEATMAP_CELL_SIZE * 24.0, LABEL_HEIGHT + HEATMAP_CELL_SIZE * 7.0);
+    let (response, painter) = ui.allocate_painter(size, egui::Sense::hover());
+
+    let origin = response.rect.min + egui::vec2(AXIS_MARGIN, LABEL_HEIGHT);
+
+    let visuals = ui.visuals();
+    let colour = visuals.selection.bg_fill;
+    let text_colour = visuals.text_color();
+    let font = egui::FontId::proportional(11.0);
+
+    let max = hourly.iter().flatten().copied().max().unwrap_or_default().max(1);
+
+    let cell = |weekday: usize, hour: usize| egui::Rect::from_min_size(
+        origin + egui::vec2(HEATMAP_CELL_SIZE * hour as f32, HEATMAP_CELL_SIZE * weekday as f32),
+        egui::Vec2::splat(HEATMAP_CELL_SIZE),
+    );
+
+    for hour in (0..24).step_by(3) {
+        painter.text(cell(0, hour).left_top() - egui::vec2(0.0, 1.0), egui::Align2::LEFT_BOTTOM, format!("{hour:02}"), font.clone(), text_colour);
+    }
+
+    for (weekday, hours) in hourly.iter().enumerate() {
+        painter.text(cell(weekday, 0).left_center() - egui::vec2(4.0, 0.0), egui::Align2::RIGHT_CENTER, WEEKDAYS[weekday], font.clone(), text_colour);
+
+        for (hour, count) in hours.iter().enumerate() {
+            let rect = cell(weekday, hour).shrink(1.0);
+
+            painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+            painter.rect_filled(rect, 2.0, colour.gamma_multiply(*count as f32 / max as f32));
+        }
+    }
+
+    if let Some(pointer) = response.hover_pos() {
+        let offset = pointer - origin;
+
+        if offset.x >= 0.0 && offset.y >= 0.0 {
+            let hour = (offset.x / HEATMAP_CELL_SIZE) as usize;
+            let weekday = (offset.y / HEATMAP_CELL_SIZE) as usize;
+
+            if hour < 24 && weekday < 7 {
+                let count = hourly[weekday][hour];
+                response.on_hover_text_at_pointer(format!("{} {hour:02}:00–{:02}:00: {count}", WEEKDAYS[weekday], hour + 1));
+            }
+        }
+    }
+}
@@ -0,0 +1,6 @@
+pub use blackcurrant_core::{embedded, records, backup, service, worker, report};
+
+pub mod app;
+pub mod chart;
+pub mod modal;
+pub mod panel;
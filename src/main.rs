@@ -7,15 +7,7 @@
     windows_subsystem = "windows"
 )]
 
-mod embedded {
-    use refinery::embed_migrations;
-    embed_migrations!("migrations");
-}
-
-mod app;
-mod records;
-mod modal;
-mod panel;
+use blackcurrant::app;
 
 fn main() -> eframe::Result<()> {
     env_logger::init();
@@ -25,6 +25,6 @@ impl AboutModal {
                 });
             });
         
-        return close_modal;
+        close_modal
     }
 }
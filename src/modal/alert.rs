@@ -23,6 +23,6 @@ impl AlertModal {
                 });
             });
         
-        return close_modal;
+        close_modal
     }
 }
@@ -0,0 +1,248 @@
+use crate::{records::{AmendableStorage, AmendmentRecord, Shift}, service::{self, Field, ServiceError}, app::{StorageErrors, NAME_MAX_LENGTH, NOTES_MAX_LENGTH, DATE_TIME_FORMAT}};
+
+use super::render_modal_text_entry;
+
+/// A field of a record which can be amended, along with its current value.
+#[derive(Debug, Clone)]
+pub struct AmendField {
+    pub column: &'static str,
+    pub label: &'static str,
+    pub value: String,
+    /// If set, the new value must be chosen from these.
+    pub options: Option<Vec<String>>,
+}
+
+impl AmendField {
+    pub fn new(column: &'static str, label: &'static str, value: impl ToString) -> AmendField {
+        AmendField {
+            column,
+            label,
+            value: value.to_string(),
+            options: None,
+        }
+    }
+
+    pub fn with_options(mut self, options: Vec<String>) -> AmendField {
+        self.options = Some(options);
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AmendModal<I: Copy> {
+    pub record_id: I,
+    pub fields: Vec<AmendField>,
+    pub field: usize,
+    pub new_value: String,
+    pub new_value_error: Option<String>,
+    pub reason: String,
+    pub reason_error: Option<String>,
+    pub void: bool,
+    pub amendments: Option<Vec<AmendmentRecord>>,
+}
+
+impl<I: Copy> AmendModal<I> {
+    pub fn new(id: I, fields: Vec<AmendField>) -> AmendModal<I> {
+        AmendModal {
+            record_id: id,
+            fields,
+            field: 0,
+            new_value: Default::default(),
+            new_value_error: Default::default(),
+            reason: Default::default(),
+            reason_error: Default::default(),
+            void: false,
+            amendments: None,
+        }
+    }
+
+    pub fn render<T>(&mut self, ctx: &eframe::egui::Context, records: &mut impl AmendableStorage<T, I>, shift: &Shift, errors: &mut StorageErrors) -> bool {
+        let mut update_record = false;
+        let mut close_modal = false;
+
+        // taken for the duration of the frame, so the rest of the modal can still be borrowed mutably
+        let amendments = match self.amendments.take() {
+            Some(amendments) => amendments,
+            None => match errors.check("failed to fetch amendments from database", records.amendments(self.record_id)) {
+                Some(amendments) => amendments,
+                // without the history, it is not known whether the record was voided
+                None => return true,
+            },
+        };
+        let voided = amendments.iter().any(|a| a.field.is_none());
+
+        egui::Window::new("Amend Record")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                // History
+
+                if !amendments.is_empty() {
+                    egui::ScrollArea::vertical().max_height(128.0).show(ui, |ui| {
+                        for amendment in &amendments {
+                            ui.label(describe_amendment(&self.fields, amendment));
+                        }
+                    });
+
+                    ui.separator();
+                }
+
+                if voided {
+                    ui.label("This record has been voided.");
+
+                    ui.add_space(4.0);
+
+                    if ui.button("Close").clicked() {
+                        close_modal = true;
+                    }
+
+                    return;
+                }
+
+                ui.checkbox(&mut self.void, "Void this record");
+
+                ui.add_space(4.0);
+
+                if !self.void {
+                    // Field
+
+                    ui.label("Field");
+
+                    let old_field = self.field;
+
+                    egui::ComboBox::from_id_source("amend_field")
+                        .width(192.0)
+                        .selected_text(self.fields[self.field].label)
+                        .show_ui(ui, |ui| {
+                            for (i, field) in self.fields.iter().enumerate() {
+                                ui.selectable_value(&mut self.field, i, field.label);
+                            }
+                        });
+
+                    if self.field != old_field {
+                        self.new_value.clear();
+                        self.new_value_error = None;
+                    }
+
+                    let field = &self.fields[self.field];
+
+                    ui.label(format!("Currently: {}", field.value));
+
+                    ui.add_space(4.0);
+
+                    // New Value
+
+                    match &field.options {
+                        Some(options) => {
+                            ui.label("New Value");
+
+                            egui::ComboBox::from_id_source("amend_new_value")
+                                .width(192.0)
+                                .selected_text(&self.new_value)
+                                .show_ui(ui, |ui| {
+                                    for option in options {
+                                        ui.selectable_value(&mut self.new_value, option.clone(), option);
+                                    }
+                                });
+
+                            if let Some(error) = &self.new_value_error {
+                                ui.colored_label(egui::Rgba::from_rgb(0.25, 0.0, 0.0), error);
+                            }
+
+                            ui.add_space(4.0);
+                        },
+                        None => {
+                            render_modal_text_entry(ui, "New Value", &self.new_value_error, &mut self.new_value, NAME_MAX_LENGTH);
+                        },
+                    }
+                }
+
+                // Reason
+                render_modal_text_entry(ui, "Reason", &self.reason_error, &mut self.reason, NOTES_MAX_LENGTH);
+
+                ui.label(format!("Recorded as {}.", shift.receptionist.name));
+
+                ui.add_space(4.0);
+
+                // Buttons
+
+                ui.horizontal(|ui| {
+                    if ui.button(if self.void { "Void" } else { "Amend" }).clicked() {
+                        self.new_value_error = None;
+                        self.reason_error = None;
+
+                        if !self.void && self.is_unchanged() {
+                            self.new_value_error = Some("Same as the current value.".into());
+                        } else {
+                            update_record = true;
+                            close_modal = true;
+                        }
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close_modal = true;
+                    }
+                });
+            });
+
+        self.amendments = Some(amendments);
+
+        if update_record {
+            let field = &self.fields[self.field];
+
+            let result = if self.void {
+                service::void(records, self.record_id, &self.reason, &shift.receptionist.name)
+            } else {
+                service::amend(records, self.record_id, field.column, &self.new_value, field.options.as_deref(), &self.reason, &shift.receptionist.name)
+            };
+
+            match result {
+                Ok(()) => {},
+                Err(err) if err.is_failure() => {
+                    errors.report(if self.void { "failed to void record" } else { "failed to amend record" }, err);
+                    close_modal = false;
+                },
+                // e.g. changing the key to one which is already out, keep the modal open to show why
+                Err(err) => {
+                    self.new_value_error = err.field_error(service::amended_field(field.column))
+                        .or_else(|| matches!(err, ServiceError::StorageError(_)).then(|| err.to_string()));
+                    self.reason_error = err.field_error(Field::Reason);
+                    close_modal = false;
+                },
+            }
+        }
+
+        close_modal
+    }
+
+    /// Whether the new value is the same as the current value of the selected field.
+    fn is_unchanged(&self) -> bool {
+        let field = &self.fields[self.field];
+
+        match field.column {
+            "student_number" => self.new_value.trim().eq_ignore_ascii_case(&field.value),
+            _ => self.new_value.trim() == field.value,
+        }
+    }
+}
+
+fn describe_amendment(fields: &[AmendField], amendment: &AmendmentRecord) -> String {
+    let time = chrono::DateTime::<chrono::Local>::from(amendment.time).format(DATE_TIME_FORMAT);
+
+    match &amendment.field {
+        Some(column) => {
+            let label = fields.iter()
+                .find(|f| f.column == column)
+                .map(|f| f.label)
+                .unwrap_or(column.as_str());
+
+            format!(
+                "{time} {}: {label} changed from \"{}\" to \"{}\". {}",
+                amendment.receptionist,
+                amendment.old_value.as_deref().unwrap_or_default(),
+                amendment.new_value.as_deref().unwrap_or_default(),
+                amendment.reason,
+            )
+        },
+        None => format!("{time} {}: Voided. {}", amendment.receptionist, amendment.reason),
+    }
+}
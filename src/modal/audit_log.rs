@@ -0,0 +1,166 @@
+use egui_extras::{TableBuilder, Column};
+
+use crate::{records::{AuditStorage, AuditFilter, AuditRecord, Page}, app::{StorageErrors, DATE_TIME_FORMAT, ROW_HEIGHT, COL_LARGE_INITIAL_WIDTH, COL_SMALL_INITIAL_WIDTH, COL_MIN_WIDTH, COL_MAX_WIDTH}, panel::{pagination, render_filter_date}};
+
+const FILTER_ENTRY_WIDTH: f32 = 96.0;
+
+#[derive(Debug, Clone)]
+pub struct AuditLogModal {
+    page: Page,
+    filter: AuditFilter,
+    refresh: bool,
+}
+
+impl Default for AuditLogModal {
+    fn default() -> Self {
+        Self {
+            page: Page::LastPage,
+            filter: AuditFilter::default(),
+            refresh: true,
+        }
+    }
+}
+
+impl AuditLogModal {
+    pub fn render(&mut self, ctx: &eframe::egui::Context, audit: &mut AuditStorage, errors: &mut StorageErrors) -> bool {
+        let mut close_modal = false;
+
+        if self.refresh {
+            self.refresh = false;
+            errors.check("failed to refresh audit log", audit.refresh());
+        }
+
+        egui::Window::new("Audit Log")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                // Filter
+                let old_filter = self.filter.clone();
+
+                ui.horizontal_wrapped(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.filter.action).hint_text("Action").desired_width(FILTER_ENTRY_WIDTH));
+                    ui.add(egui::TextEdit::singleline(&mut self.filter.table).hint_text("Table").desired_width(FILTER_ENTRY_WIDTH));
+                    ui.add(egui::TextEdit::singleline(&mut self.filter.record_id).hint_text("Record").desired_width(FILTER_ENTRY_WIDTH));
+                    ui.add(egui::TextEdit::singleline(&mut self.filter.receptionist).hint_text("Receptionist").desired_width(FILTER_ENTRY_WIDTH));
+
+                    render_filter_date(ui, "audit_filter_from", "From", &mut self.filter.from);
+                    render_filter_date(ui, "audit_filter_to", "To", &mut self.filter.to);
+
+                    if ui.add_enabled(!self.filter.is_empty(), egui::Button::new("Clear")).clicked() {
+                        self.filter = AuditFilter::default();
+                    }
+                });
+
+                if self.filter != old_filter {
+                    self.page = Page::LastPage;
+                }
+
+                errors.check("failed to refresh audit log for filter", audit.set_filter(&self.filter));
+
+                ui.add_space(4.0);
+
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} entries", audit.count()));
+
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
+                        pagination(ui, &mut self.page, audit.position());
+                        errors.check(&format!("failed to refresh audit log for page {:?}", self.page), audit.set_page(self.page));
+                    });
+                });
+
+                ui.add_space(4.0);
+
+                TableBuilder::new(ui)
+                    .striped(true)
+                    .stick_to_bottom(true)
+                    .max_scroll_height(384.0)
+                    .column(Column::auto().at_most(COL_MAX_WIDTH))
+                    .column(Column::initial(COL_SMALL_INITIAL_WIDTH).at_least(COL_MIN_WIDTH).clip(true).resizable(true))
+                    .column(Column::auto().at_most(COL_MAX_WIDTH))
+                    .column(Column::auto().at_most(COL_MAX_WIDTH))
+                    .column(Column::auto().at_most(COL_MAX_WIDTH))
+                    .column(Column::initial(COL_LARGE_INITIAL_WIDTH * 2.0).at_least(COL_MIN_WIDTH).clip(true).resizable(true))
+                    .header(ROW_HEIGHT, |mut header| {
+                        for title in ["Time", "Receptionist", "Action", "Table", "Record", "Changes"] {
+                            header.col(|ui| {
+                                ui.label(egui::RichText::new(title).strong());
+                            });
+                        }
+                    })
+                    .body(|mut body| {
+                        for record in audit.get_all() {
+                            body.row(ROW_HEIGHT, |mut row| {
+                                row.col(|ui| {
+                                    ui.label(chrono::DateTime::<chrono::Local>::from(record.time).format(DATE_TIME_FORMAT).to_string());
+                                });
+                                row.col(|ui| {
+                                    ui.label(record.receptionist.as_deref().unwrap_or_default());
+                                });
+                                row.col(|ui| {
+                                    ui.label(&record.action);
+                                });
+                                row.col(|ui| {
+                                    ui.label(&record.record_table);
+                                });
+                                row.col(|ui| {
+                                    ui.label(&record.record_id);
+                                });
+                                row.col(|ui| {
+                                    let response = ui.label(describe_changes(record));
+
+                                    if record.before.is_some() || record.after.is_some() {
+                                        response.on_hover_ui(|ui| {
+                                            ui.label(format!("Before: {}", record.before.as_deref().unwrap_or("None")));
+                                            ui.label(format!("After: {}", record.after.as_deref().unwrap_or("None")));
+                                        });
+                                    }
+                                });
+                            });
+                        }
+                    });
+
+                ui.add_space(4.0);
+
+                if ui.button("Close").clicked() {
+                    close_modal = true;
+                }
+            });
+
+        close_modal
+    }
+}
+
+/// Summarise the fields changed by an audit log entry.
+fn describe_changes(record: &AuditRecord) -> String {
+    if record.before.is_none() && record.after.is_none() {
+        return String::new();
+    }
+
+    let parse = |json: &Option<String>| json.as_deref()
+        .and_then(|json| serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(json).ok())
+        .unwrap_or_default();
+
+    let before = parse(&record.before);
+    let after = parse(&record.after);
+
+    let format_value = |value: Option<&serde_json::Value>| match value {
+        Some(serde_json::Value::String(value)) => format!("\"{value}\""),
+        Some(value) => value.to_string(),
+        None => "null".into(),
+    };
+
+    let changes = after.keys().chain(before.keys().filter(|k| !after.contains_key(*k)))
+        .filter(|k| before.get(*k) != after.get(*k))
+        .map(|k| match (record.before.is_some(), record.after.is_some()) {
+            (true, true) => format!("{k}: {} → {}", format_value(before.get(k)), format_value(after.get(k))),
+            (false, _) => format!("{k}: {}", format_value(after.get(k))),
+            (true, false) => format!("{k}: {}", format_value(before.get(k))),
+        })
+        .collect::<Vec<_>>();
+
+    if changes.is_empty() {
+        "No changes".into()
+    } else {
+        changes.join(", ")
+    }
+}
@@ -0,0 +1,317 @@
+use egui_extras::{TableBuilder, Column};
+use strum::IntoEnumIterator;
+
+use crate::{records::{CategoryStorage, CategoryField, NewCategory, FieldKind, Storage, InsertableStorage, DeletableStorage}, app::{StorageErrors, NAME_MAX_LENGTH, MAX_QUANTITY}};
+
+use super::{render_modal_text_entry, filter_required, filter_length};
+
+#[derive(Debug, Clone)]
+pub struct CategoryEntryModal {
+    /// Category whose entries are being edited.
+    pub selected: Option<i64>,
+    pub entry: String,
+    pub entry_error: Option<String>,
+    pub quantity: i64,
+    pub name: String,
+    pub name_error: Option<String>,
+    pub returnable: bool,
+    pub stock_limited: bool,
+    pub student_number_required: bool,
+    pub fields: Vec<CategoryField>,
+    pub fields_error: Option<String>,
+}
+
+impl Default for CategoryEntryModal {
+    fn default() -> Self {
+        Self {
+            selected: None,
+            entry: Default::default(),
+            entry_error: Default::default(),
+            quantity: 1,
+            name: Default::default(),
+            name_error: Default::default(),
+            returnable: true,
+            stock_limited: false,
+            student_number_required: true,
+            fields: vec![],
+            fields_error: Default::default(),
+        }
+    }
+}
+
+impl CategoryEntryModal {
+    pub fn render(&mut self, ctx: &eframe::egui::Context, categories: &mut CategoryStorage, errors: &mut StorageErrors) -> bool {
+        let mut close_modal = false;
+        let mut delete_category = None;
+        let mut delete_entry = None;
+
+        if self.selected.is_some_and(|id| categories.get(id).is_none()) {
+            self.selected = None;
+        }
+
+        egui::Window::new("Categories")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let mut save_entry = None;
+
+                TableBuilder::new(ui)
+                    .max_scroll_height(192.0)
+                    .column(Column::remainder())
+                    .body(|mut body| {
+                        for category in categories.get_all() {
+                            body.row(24.0, |mut row| {
+                                row.col(|ui| {
+                                    ui.horizontal(|ui| {
+                                        if ui.add(egui::Button::new("−").small().fill(egui::Rgba::from_rgb(0.25, 0.0, 0.0))).clicked() {
+                                            delete_category = Some(category.id);
+                                        }
+
+                                        ui.add_space(8.0);
+
+                                        if ui.selectable_label(self.selected == Some(category.id), &category.name).clicked() {
+                                            self.selected = Some(category.id);
+                                            self.entry.clear();
+                                            self.entry_error = None;
+                                        }
+                                    });
+                                });
+                            })
+                        }
+                    });
+
+                if !categories.get_all().is_empty() {
+                    ui.separator();
+                }
+
+                if let Some(category) = self.selected.and_then(|id| categories.get(id)) {
+                    // Entries
+
+                    let mut description = vec![if category.returnable { "Returnable" } else { "Not returnable" }];
+
+                    if category.stock_limited {
+                        description.push("stock limited");
+                    }
+
+                    if category.student_number_required {
+                        description.push("student number required");
+                    }
+
+                    ui.label(egui::RichText::new(&category.name).strong());
+                    ui.weak(description.join(", "));
+
+                    if !category.fields.is_empty() {
+                        let fields = category.fields.iter()
+                            .map(|field| format!("{} ({}{})", field.name, field.kind, if field.required { ", required" } else { "" }))
+                            .collect::<Vec<_>>();
+
+                        ui.weak(format!("Fields: {}", fields.join(", ")));
+                    }
+
+                    ui.add_space(4.0);
+
+                    ui.push_id("category_entries", |ui| {
+                        TableBuilder::new(ui)
+                            .max_scroll_height(192.0)
+                            .column(Column::remainder())
+                            .body(|mut body| {
+                                for entry in &category.entries {
+                                    body.row(24.0, |mut row| {
+                                        row.col(|ui| {
+                                            ui.horizontal(|ui| {
+                                                if ui.add(egui::Button::new("−").small().fill(egui::Rgba::from_rgb(0.25, 0.0, 0.0))).clicked() {
+                                                    delete_entry = Some((category.id, entry.name.clone()));
+                                                }
+
+                                                ui.add_space(8.0);
+
+                                                match entry.quantity {
+                                                    Some(quantity) => ui.label(format!("{}: {quantity} in stock", entry.name)),
+                                                    None => ui.label(&entry.name),
+                                                };
+                                            });
+                                        });
+                                    })
+                                }
+                            });
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(&mut self.entry).hint_text("Name").char_limit(NAME_MAX_LENGTH));
+
+                        if category.stock_limited {
+                            ui.add(egui::DragValue::new(&mut self.quantity).clamp_range(0..=MAX_QUANTITY));
+                        }
+
+                        let existing = category.entry(&self.entry).map(|entry| entry.name.clone());
+
+                        let text = if existing.is_some() && category.stock_limited { "Set Stock" } else { "Add" };
+
+                        if ui.button(text).clicked() {
+                            self.entry_error = None;
+
+                            let entry = self.entry.trim();
+                            let quantity = category.stock_limited.then_some(self.quantity);
+
+                            let mut error = false;
+
+                            error |= filter_required(entry, &mut self.entry_error);
+                            error |= filter_length(entry, NAME_MAX_LENGTH, &mut self.entry_error);
+
+                            if !error {
+                                match (existing, quantity) {
+                                    (Some(_), None) => self.entry_error = Some(format!("{} already has this entry.", category.name)),
+                                    (existing, quantity) => save_entry = Some((category.id, existing.is_some(), existing.unwrap_or(entry.into()), quantity)),
+                                }
+                            }
+                        }
+                    });
+
+                    if let Some(error) = &self.entry_error {
+                        ui.colored_label(egui::Rgba::from_rgb(0.25, 0.0, 0.0), error);
+                    }
+
+                    ui.separator();
+                }
+
+                if let Some((id, existing, name, quantity)) = save_entry.take() {
+                    let result = match quantity {
+                        Some(quantity) if existing => categories.update_entry_quantity(id, &name, quantity),
+                        _ => categories.insert_entry(id, &name, quantity),
+                    };
+
+                    if errors.check("failed to save category entry to database", result).is_some() {
+                        self.entry.clear();
+                    }
+                }
+
+                // New Category
+                render_modal_text_entry(ui, "Category Name", &self.name_error, &mut self.name, NAME_MAX_LENGTH);
+
+                ui.checkbox(&mut self.returnable, "Returnable")
+                    .on_hover_text("Records are signed back in.");
+                ui.checkbox(&mut self.stock_limited, "Stock limited")
+                    .on_hover_text("Each entry has a number in stock, which sign outs may not exceed.");
+                ui.checkbox(&mut self.student_number_required, "Student number required");
+
+                ui.add_space(4.0);
+
+                // Fields
+                ui.label("Fields");
+
+                let mut remove_field = None;
+
+                for (i, field) in self.fields.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui.add(egui::Button::new("−").small().fill(egui::Rgba::from_rgb(0.25, 0.0, 0.0))).clicked() {
+                            remove_field = Some(i);
+                        }
+
+                        ui.add(egui::TextEdit::singleline(&mut field.name).hint_text("Name").desired_width(128.0).char_limit(NAME_MAX_LENGTH));
+
+                        egui::ComboBox::from_id_source(("category_field_kind", i))
+                            .width(64.0)
+                            .selected_text(field.kind.to_string())
+                            .show_ui(ui, |ui| {
+                                for kind in FieldKind::iter() {
+                                    ui.selectable_value(&mut field.kind, kind, kind.to_string());
+                                }
+                            });
+
+                        ui.checkbox(&mut field.required, "Required");
+                    });
+                }
+
+                if let Some(i) = remove_field {
+                    self.fields.remove(i);
+                }
+
+                if ui.small_button("+ Add Field").clicked() {
+                    self.fields.push(CategoryField {
+                        name: String::new(),
+                        kind: FieldKind::default(),
+                        required: false,
+                    });
+                }
+
+                if let Some(error) = &self.fields_error {
+                    ui.colored_label(egui::Rgba::from_rgb(0.25, 0.0, 0.0), error);
+                }
+
+                ui.add_space(4.0);
+
+                // Buttons
+
+                ui.horizontal(|ui| {
+                    if ui.button("Add Category").clicked() {
+                        let mut error = false;
+
+                        // Name
+                        self.name_error = None;
+
+                        let name = self.name.trim();
+
+                        error |= filter_required(name, &mut self.name_error);
+                        error |= filter_length(name, NAME_MAX_LENGTH, &mut self.name_error);
+
+                        if categories.get_by_name(name).is_some() {
+                            self.name_error = Some("A category with this name already exists.".into());
+                            error = true;
+                        }
+
+                        // Fields
+                        self.fields_error = None;
+
+                        let fields = self.fields.iter()
+                            .map(|field| CategoryField { name: field.name.trim().into(), ..field.clone() })
+                            .collect::<Vec<_>>();
+
+                        for (i, field) in fields.iter().enumerate() {
+                            if field.name.is_empty() {
+                                self.fields_error = Some("Every field needs a name.".into());
+                                error = true;
+                            } else if fields[..i].iter().any(|other| other.name.eq_ignore_ascii_case(&field.name)) {
+                                self.fields_error = Some(format!("There is more than one field named {}.", field.name));
+                                error = true;
+                            }
+                        }
+
+                        if !error {
+                            let category = NewCategory {
+                                name,
+                                returnable: self.returnable,
+                                stock_limited: self.stock_limited,
+                                student_number_required: self.student_number_required,
+                                fields: &fields,
+                            };
+
+                            // keep the entry on failure, so it can be added again
+                            if errors.check("failed to add category to database", categories.insert(category)).is_some() {
+                                log::info!("added category");
+
+                                self.selected = categories.get_by_name(&self.name).map(|category| category.id);
+                                *self = CategoryEntryModal { selected: self.selected, ..Default::default() };
+                            }
+                        }
+                    }
+                    if ui.button("Close").clicked() {
+                        close_modal = true;
+                    }
+                });
+            });
+
+        if let Some(id) = delete_category {
+            if errors.check("failed to delete category from database, it may still have records", categories.delete(id)).is_some() {
+                log::debug!("deleted category");
+            }
+        }
+
+        if let Some((id, name)) = delete_entry {
+            if errors.check("failed to delete category entry from database", categories.delete_entry(id, &name)).is_some() {
+                log::debug!("deleted category entry");
+            }
+        }
+
+        close_modal
+    }
+}
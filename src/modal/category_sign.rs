@@ -0,0 +1,198 @@
+use crate::{records::{CategoryStorage, CategoryRecordStorage, Storage, NewCategoryRecord, RecordType, FieldKind, Shift}, service::{self, SignOut, Field}, app::{StorageErrors, MAX_QUANTITY, NAME_MAX_LENGTH, STUDENT_NUMBER_LENGTH, NOTES_MAX_LENGTH}};
+
+use super::{render_modal_text_entry, render_student_number_popup, RestrictionOverride};
+
+#[derive(Debug, Clone)]
+pub struct CategorySignModal {
+    pub category_id: i64,
+    pub entry: String,
+    pub entry_error: Option<String>,
+    pub quantity: i64,
+    pub quantity_str: String,
+    pub quantity_error: Option<String>,
+    pub student_name: String,
+    pub student_name_error: Option<String>,
+    pub student_number: String,
+    pub student_number_error: Option<String>,
+    /// Value of each of the category's fields, in order.
+    pub fields: Vec<String>,
+    pub field_errors: Vec<Option<String>>,
+    pub notes: String,
+    pub notes_error: Option<String>,
+    pub restriction: RestrictionOverride,
+}
+
+impl CategorySignModal {
+    pub fn new(category_id: i64) -> CategorySignModal {
+        CategorySignModal {
+            category_id,
+            entry: Default::default(),
+            entry_error: Default::default(),
+            quantity: 1,
+            quantity_str: "1".into(),
+            quantity_error: Default::default(),
+            student_name: Default::default(),
+            student_name_error: Default::default(),
+            student_number: Default::default(),
+            student_number_error: Default::default(),
+            fields: vec![],
+            field_errors: vec![],
+            notes: Default::default(),
+            notes_error: Default::default(),
+            restriction: Default::default(),
+        }
+    }
+
+    pub fn render(&mut self, ctx: &eframe::egui::Context, categories: &CategoryStorage, category_records: &mut CategoryRecordStorage, sign_out: &mut SignOut, shift: &Shift, errors: &mut StorageErrors) -> bool {
+        let mut close_modal = false;
+
+        let Some(category) = categories.get(self.category_id) else {
+            // deleted while signing out
+            return true;
+        };
+
+        if self.entry.is_empty() && category.entries.len() == 1 {
+            self.entry = category.entries[0].name.clone();
+        }
+
+        // fields may have been added since the modal was opened
+        self.fields.resize(category.fields.len(), String::new());
+        self.field_errors.resize(category.fields.len(), None);
+
+        egui::Window::new(format!("Sign Out {}", category.name))
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                // Entry
+
+                ui.label(&category.name);
+
+                egui::ComboBox::from_label("")
+                    .width(192.0)
+                    .selected_text(&self.entry)
+                    .show_ui(ui, |ui| {
+                        for entry in &category.entries {
+                            ui.selectable_value(&mut self.entry, entry.name.clone(), &entry.name);
+                        }
+                    });
+
+                if let Some(error) = &self.entry_error {
+                    ui.colored_label(egui::Rgba::from_rgb(0.25, 0.0, 0.0), error);
+                }
+
+                ui.add_space(4.0);
+
+                // Quantity
+                ui.label("Quantity");
+
+                ui.horizontal(|ui| {
+                    let mut updated = false;
+
+                    if ui.button("+").clicked() {
+                        self.quantity += 1;
+                        updated = true;
+                    }
+
+                    let response = ui.add(egui::TextEdit::singleline(&mut self.quantity_str).desired_width(64.0));
+
+                    if !updated && (response.lost_focus() || response.clicked_elsewhere()) {
+                        self.quantity = self.quantity_str.chars().filter(|c| '0' <= *c && *c <= '9').collect::<String>().parse().unwrap_or(1);
+                        updated = true;
+                    }
+
+                    if ui.button("−").clicked() {
+                        self.quantity -= 1;
+                        updated = true;
+                    }
+
+                    if updated {
+                        self.quantity = self.quantity.clamp(1, MAX_QUANTITY);
+                        self.quantity_str = self.quantity.to_string();
+                    }
+                });
+
+                if let Some(error) = &self.quantity_error {
+                    ui.colored_label(egui::Rgba::from_rgb(0.25, 0.0, 0.0), error);
+                }
+
+                ui.add_space(4.0);
+
+                // Student Number
+                let label = if category.student_number_required { "Student Number" } else { "Student Number (optional)" };
+                let resp = render_modal_text_entry(ui, label, &self.student_number_error, &mut self.student_number, STUDENT_NUMBER_LENGTH);
+                render_student_number_popup(ui, sign_out.students, "category_sign_student_number_popup".into(), &resp, &mut self.student_number, &mut self.student_name);
+
+                // Student Name
+                render_modal_text_entry(ui, "Student Name", &self.student_name_error, &mut self.student_name, NAME_MAX_LENGTH);
+
+                // Custom Fields
+                for ((field, value), error) in category.fields.iter().zip(&mut self.fields).zip(&self.field_errors) {
+                    match field.kind {
+                        FieldKind::YesNo => {
+                            let mut checked = value == "Yes";
+
+                            if ui.checkbox(&mut checked, &field.name).changed() || value.is_empty() {
+                                *value = if checked { "Yes" } else { "No" }.into();
+                            }
+
+                            if let Some(error) = error {
+                                ui.colored_label(egui::Rgba::from_rgb(0.25, 0.0, 0.0), error);
+                            }
+
+                            ui.add_space(4.0);
+                        },
+                        FieldKind::Text | FieldKind::Number => {
+                            render_modal_text_entry(ui, &field.name, error, value, NOTES_MAX_LENGTH);
+                        },
+                    }
+                }
+
+                // Notes
+                render_modal_text_entry(ui, "Notes", &self.notes_error, &mut self.notes, NOTES_MAX_LENGTH);
+
+                // Restrictions
+                self.restriction.render(ui, sign_out.restrictions, sign_out.receptionists.is_admin(&shift.receptionist.name), &self.student_number, RecordType::Category, &category.name);
+
+                ui.add_space(4.0);
+
+                // Buttons
+
+                ui.horizontal(|ui| {
+                    if ui.button(self.restriction.sign_out_text()).clicked() {
+                        let result = service::sign_out_category(categories, category_records, sign_out, NewCategoryRecord {
+                            category_id: self.category_id,
+                            entry: &self.entry,
+                            quantity: self.quantity,
+                            student_name: &self.student_name,
+                            student_number: &self.student_number,
+                            receptionist: &shift.receptionist.name,
+                            fields: &self.fields,
+                            notes: &self.notes,
+                        }, self.restriction.justification());
+
+                        match result {
+                            Ok(()) => close_modal = true,
+                            Err(err) if err.is_failure() => errors.report("failed to add category record to database", err),
+                            Err(err) => {
+                                self.entry_error = err.field_error(Field::Item);
+                                self.quantity_error = err.field_error(Field::Quantity);
+                                self.student_name_error = err.field_error(Field::StudentName);
+                                self.student_number_error = err.field_error(Field::StudentNumber);
+                                self.notes_error = err.field_error(Field::Notes);
+                                self.restriction.handle_error(&err);
+
+                                for (i, error) in self.field_errors.iter_mut().enumerate() {
+                                    *error = err.field_error(Field::Custom(i));
+                                }
+                            },
+                        }
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close_modal = true;
+                    }
+                });
+            });
+
+        close_modal
+    }
+}
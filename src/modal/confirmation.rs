@@ -39,6 +39,6 @@ impl ConfirmationModal {
                 });
             });
         
-        return close_modal;
+        close_modal
     }
 }
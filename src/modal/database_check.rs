@@ -0,0 +1,97 @@
+use crate::{records::{self, Finding, Shift}, worker::WorkerHandle, app::StorageErrors};
+
+#[derive(Debug, Clone)]
+pub struct DatabaseCheckModal {
+    findings: Vec<Finding>,
+    refresh: bool,
+    /// Set when a fix was applied, so that the storages can be refreshed.
+    pub repaired: bool,
+}
+
+impl Default for DatabaseCheckModal {
+    fn default() -> Self {
+        Self {
+            findings: vec![],
+            refresh: true,
+            repaired: false,
+        }
+    }
+}
+
+impl DatabaseCheckModal {
+    pub fn render(&mut self, ctx: &eframe::egui::Context, worker: &WorkerHandle, shift: Option<&Shift>, errors: &mut StorageErrors) -> bool {
+        let mut close_modal = false;
+
+        if self.refresh {
+            self.refresh = false;
+            self.findings = errors.check("failed to check database", worker.call(|connection| records::check_database(connection))).unwrap_or_default();
+        }
+
+        let mut fix = vec![];
+
+        egui::Window::new("Check Database")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if self.findings.is_empty() {
+                    ui.label("No problems found.");
+                } else {
+                    ui.label(format!("{} problems found.", self.findings.len()));
+
+                    ui.add_space(4.0);
+
+                    egui::ScrollArea::vertical().max_height(384.0).show(ui, |ui| {
+                        for (i, finding) in self.findings.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                if let Some(finding_fix) = &finding.fix {
+                                    if ui.add_enabled(shift.is_some(), egui::Button::new(finding_fix.to_string())).clicked() {
+                                        fix.push(i);
+                                    }
+                                }
+
+                                ui.label(finding.to_string());
+                            });
+                        }
+                    });
+                }
+
+                ui.add_space(4.0);
+
+                match shift {
+                    Some(shift) => ui.label(format!("Fixes are recorded as {}.", shift.receptionist.name)),
+                    None => ui.label("Sign on to fix problems."),
+                };
+
+                ui.add_space(4.0);
+
+                ui.horizontal(|ui| {
+                    let fixable = self.findings.iter().any(|f| f.fix.is_some());
+
+                    if ui.add_enabled(shift.is_some() && fixable, egui::Button::new("Fix All")).clicked() {
+                        fix = (0..self.findings.len()).collect();
+                    }
+                    if ui.button("Check Again").clicked() {
+                        self.refresh = true;
+                    }
+                    if ui.button("Close").clicked() {
+                        close_modal = true;
+                    }
+                });
+            });
+
+        if let (false, Some(shift)) = (fix.is_empty(), shift) {
+            for i in fix {
+                let (finding, receptionist) = (self.findings[i].clone(), shift.receptionist.name.clone());
+
+                let result = worker.call(move |connection| records::repair(connection, &finding, &receptionist));
+                errors.check(&format!("failed to repair {}", self.findings[i]), result);
+            }
+
+            // fixes may resolve or reveal other problems
+            self.refresh = true;
+            self.repaired = true;
+        }
+
+        close_modal
+    }
+}
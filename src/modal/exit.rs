@@ -24,6 +24,6 @@ impl ExitModal {
                     });
                 });
         
-        return close_modal;
+        close_modal
     }
 }
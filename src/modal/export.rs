@@ -2,16 +2,16 @@ use std::{thread::JoinHandle, path::PathBuf};
 
 use strum::IntoEnumIterator;
 
-use crate::{records::RecordType, app::BACKUP_DATE_TIME_FORMAT};
+use crate::{records::{RecordType, CategoryStorage, Storage}, worker::ExportSource, app::BACKUP_DATE_TIME_FORMAT};
 
 #[derive(Debug, Default)]
 pub struct ExportModal {
-    pub record_type: RecordType,
-    pub path_handle: Option<JoinHandle<(RecordType, Option<PathBuf>)>>,
+    pub source: ExportSource,
+    pub path_handle: Option<JoinHandle<(ExportSource, Option<PathBuf>)>>,
 }
 
 impl ExportModal {
-    pub fn render(&mut self, ctx: &eframe::egui::Context) -> bool {
+    pub fn render(&mut self, ctx: &eframe::egui::Context, categories: &CategoryStorage) -> bool {
         let mut close_modal = false;
         
         egui::Window::new("Export Records")
@@ -19,28 +19,35 @@ impl ExportModal {
             .resizable(false)
             .show(ctx, |ui| {
                 for record_type in RecordType::iter() {
-                    ui.radio_value(&mut self.record_type, record_type, format!("{} Records", record_type));
+                    ui.radio_value(&mut self.source, ExportSource::Records(record_type), format!("{} Records", record_type));
+                }
+
+                for category in categories.get_all() {
+                    ui.radio_value(&mut self.source, ExportSource::Category(category.id), format!("{} Records", category.name));
                 }
 
                 // Buttons
                 
                 ui.horizontal(|ui| {
                     if ui.button("Export").clicked() {
-                        let record_type = self.record_type;
+                        let source = self.source;
+
+                        let source_str = match source {
+                            ExportSource::Records(record_type) => record_type.to_string().to_lowercase(),
+                            ExportSource::Category(id) => categories.get(id).map_or("category".into(), |category| category.name.to_lowercase().replace(' ', "_")),
+                        };
                         
                         self.path_handle = Some(std::thread::spawn(move || {
-                            let record_type_str = record_type.to_string().to_lowercase();
-
                             log::info!("opening export save dialogue");
                             
                             let path = rfd::FileDialog::new()
                                 .add_filter("CSV File", &["csv"])
-                                .set_file_name(&format!("{record_type_str}_records_{}.csv", chrono::Local::now().format(BACKUP_DATE_TIME_FORMAT).to_string()))
+                                .set_file_name(format!("{source_str}_records_{}.csv", chrono::Local::now().format(BACKUP_DATE_TIME_FORMAT)))
                                 .save_file();
 
                                 log::info!("finished export save dialogue");
                             
-                            (record_type, path)
+                            (source, path)
                         }));
                         
                         close_modal = true;
@@ -51,6 +58,6 @@ impl ExportModal {
                 });
             });
         
-        return close_modal;
+        close_modal
     }
 }
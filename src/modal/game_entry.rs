@@ -1,8 +1,8 @@
 use egui_extras::{TableBuilder, Column};
 
-use crate::{records::{GameTypeStorage, Storage, InsertableStorage, DeletableStorage, NewGameTypeRecord}, app::{MAX_QUANTITY, NAME_MAX_LENGTH}};
+use crate::{records::{GameTypeStorage, Storage, InsertableStorage, DeletableStorage, NewGameTypeRecord}, app::{StorageErrors, MAX_QUANTITY, NAME_MAX_LENGTH}};
 
-use super::{render_modal_text_entry, filter_required, filter_length};
+use super::{render_modal_text_entry, filter_required, filter_length, render_loan_period_combo};
 
 #[derive(Debug, Clone)]
 pub struct GameEntryModal {
@@ -11,6 +11,7 @@ pub struct GameEntryModal {
     pub quantity: i64,
     pub quantity_str: String,
     pub quantity_error: Option<String>,
+    pub loan_minutes: Option<i64>,
 }
 
 impl Default for GameEntryModal {
@@ -20,13 +21,14 @@ impl Default for GameEntryModal {
             game_error: Default::default(),
             quantity: 1,
             quantity_str: "1".into(),
-            quantity_error: Default::default()
+            quantity_error: Default::default(),
+            loan_minutes: Default::default(),
         }
     }
 }
 
 impl GameEntryModal {
-    pub fn render(&mut self, ctx: &eframe::egui::Context, game_types: &mut GameTypeStorage) -> bool {
+    pub fn render(&mut self, ctx: &eframe::egui::Context, game_types: &mut GameTypeStorage, errors: &mut StorageErrors) -> bool {
         let mut close_modal = false;
         let mut delete_game = None;
 
@@ -41,6 +43,7 @@ impl GameEntryModal {
                     .column(Column::remainder())
                     .body(|mut body| {
                         let mut update_quantity = None;
+                        let mut update_loan_period = None;
 
                         for game in game_types.get_all() {
                             body.row(24.0, |mut row| {
@@ -52,7 +55,7 @@ impl GameEntryModal {
                                         }
                                         
                                         ui.add_space(8.0);
-                                        ui.label(&format!("{} × {}", game.quantity, game.game));
+                                        ui.label(format!("{} × {}", game.quantity, game.game));
 
                                         ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
                                             if ui.button("+").clicked() {
@@ -62,6 +65,13 @@ impl GameEntryModal {
                                                 update_quantity = Some((game.game.clone(), game.quantity - 1));
                                             }
                                             ui.add_space(8.0);
+
+                                            let mut loan_minutes = game.loan_minutes;
+
+                                            if render_loan_period_combo(ui, ("game_loan_period", &game.game), &mut loan_minutes) {
+                                                update_loan_period = Some((game.game.clone(), loan_minutes));
+                                            }
+                                            ui.add_space(8.0);
                                         });
                                     });
                                 });
@@ -70,11 +80,15 @@ impl GameEntryModal {
 
                         if let Some((game, quantity)) = update_quantity {
                             let quantity = quantity.clamp(1, MAX_QUANTITY);
-                            game_types.update_quantity(&game, quantity).expect("failed to update game type quantity");
+                            errors.check("failed to update game type quantity", game_types.update_quantity(&game, quantity));
+                        }
+
+                        if let Some((game, loan_minutes)) = update_loan_period {
+                            errors.check("failed to update game type loan period", game_types.update_loan_period(&game, loan_minutes));
                         }
                     });
 
-                if game_types.get_all().len() > 0 {
+                if !game_types.get_all().is_empty() {
                     ui.separator();
                 }
 
@@ -114,6 +128,12 @@ impl GameEntryModal {
                     ui.colored_label(egui::Rgba::from_rgb(0.25, 0.0, 0.0), error);
                 }
 
+                ui.add_space(4.0);
+
+                // Loan Period
+                ui.label("Loan Period");
+                render_loan_period_combo(ui, "game_loan_period", &mut self.loan_minutes);
+
                 ui.add_space(8.0);
 
                 // Buttons
@@ -131,21 +151,26 @@ impl GameEntryModal {
                         error |= filter_length(game, NAME_MAX_LENGTH, &mut self.game_error);
 
                         // not particularly efficient, but unlikely to be an issue
-                        if let Some(_) = game_types.get_all().iter().find(|g| g.game == game) {
+                        if game_types.get_all().iter().find(|g| g.game == game).is_some() {
                             self.game_error = Some("A game with this name already exists.".into());
                             error = true;
                         }
 
                         // Entry valid, add record
                         if !error {
-                            game_types.insert(NewGameTypeRecord {
+                            let result = game_types.insert(NewGameTypeRecord {
                                 game: &self.game,
                                 quantity: self.quantity,
-                            }).expect("failed to add game type to database");
-                            
-                            self.game.clear();
-                            self.quantity = 1;
-                            self.quantity_str = self.quantity.to_string();
+                                loan_minutes: self.loan_minutes,
+                            });
+
+                            // keep the entry on failure, so it can be added again
+                            if errors.check("failed to add game type to database", result).is_some() {
+                                self.game.clear();
+                                self.loan_minutes = None;
+                                self.quantity = 1;
+                                self.quantity_str = self.quantity.to_string();
+                            }
                         }
                     }
                     if ui.button("Close").clicked() {
@@ -155,10 +180,11 @@ impl GameEntryModal {
             });
 
         if let Some(game) = delete_game {
-            game_types.delete(&game).expect("failed to delete game type from database");
-            log::debug!("deleted game type");
+            if errors.check("failed to delete game type from database", game_types.delete(&game)).is_some() {
+                log::debug!("deleted game type");
+            }
         }
 
-        return close_modal;
+        close_modal
     }
 }
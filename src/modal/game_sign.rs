@@ -1,8 +1,6 @@
-use std::sync::{Arc, Mutex};
+use crate::{records::{GameTypeStorage, GameStorage, Storage, NewGameRecord, RecordType, Shift}, service::{self, SignOut, Field}, app::{StorageErrors, NAME_MAX_LENGTH, STUDENT_NUMBER_LENGTH, NOTES_MAX_LENGTH}};
 
-use crate::{records::{GameTypeStorage, GameStorage, Storage, InsertableStorage, NewGameRecord, StudentInfo}, app::{NAME_MAX_LENGTH, STUDENT_NUMBER_LENGTH, NOTES_MAX_LENGTH}};
-
-use super::{render_modal_text_entry, filter_student_number, filter_required, filter_length, render_student_number_popup};
+use super::{render_modal_text_entry, render_student_number_popup, RestrictionOverride};
 
 #[derive(Debug, Clone)]
 pub struct GameSignModal {
@@ -15,10 +13,9 @@ pub struct GameSignModal {
     pub student_name_error: Option<String>,
     pub student_number: String,
     pub student_number_error: Option<String>,
-    // pub receptionist: String,
-    // pub receptionist_error: Option<String>,
     pub notes: String,
     pub notes_error: Option<String>,
+    pub restriction: RestrictionOverride,
 }
 
 impl Default for GameSignModal {
@@ -33,16 +30,15 @@ impl Default for GameSignModal {
             student_name_error: Default::default(),
             student_number: Default::default(),
             student_number_error: Default::default(),
-            // receptionist: Default::default(),
-            // receptionist_error: Default::default(),
             notes: Default::default(),
             notes_error: Default::default(),
+            restriction: Default::default(),
         }
     }
 }
 
 impl GameSignModal {
-    pub fn render(&mut self, ctx: &eframe::egui::Context, game_types: &GameTypeStorage, game_records: &mut GameStorage, student_info: Arc<Mutex<StudentInfo>>) -> bool {
+    pub fn render(&mut self, ctx: &eframe::egui::Context, game_types: &GameTypeStorage, game_records: &mut GameStorage, sign_out: &mut SignOut, shift: &Shift, errors: &mut StorageErrors) -> bool {
         let mut close_modal = false;
 
         egui::Window::new("Sign Out Game")
@@ -94,7 +90,7 @@ impl GameSignModal {
                     if updated {
                         let max_quantity = if let Some(game) = game_types.get(&self.game) {
                             // Count quantity of all games already out
-                            let already_out = game_records.get_signed_out(&self.game).expect("failed to get game signed status from database");
+                            let already_out = errors.check("failed to get game signed status from database", game_records.get_signed_out(&self.game)).unwrap_or_default();
 
                             (game.quantity - already_out).max(1)
                         } else {
@@ -113,7 +109,7 @@ impl GameSignModal {
 
                 // Student Number
                 let resp = render_modal_text_entry(ui, "Student Number", &self.student_number_error, &mut self.student_number, STUDENT_NUMBER_LENGTH);
-                render_student_number_popup(ui, student_info, "game_sign_student_number_popup".into(), &resp, &mut self.student_number, &mut self.student_name);
+                render_student_number_popup(ui, sign_out.students, "game_sign_student_number_popup".into(), &resp, &mut self.student_number, &mut self.student_name);
 
                 // Student Name
                 render_modal_text_entry(ui, "Student Name", &self.student_name_error, &mut self.student_name, NAME_MAX_LENGTH);
@@ -121,72 +117,35 @@ impl GameSignModal {
                 // Notes
                 render_modal_text_entry(ui, "Notes", &self.notes_error, &mut self.notes, NOTES_MAX_LENGTH);
 
+                // Restrictions
+                self.restriction.render(ui, sign_out.restrictions, sign_out.receptionists.is_admin(&shift.receptionist.name), &self.student_number, RecordType::Game, &self.game);
+
                 ui.add_space(4.0);
 
                 // Buttons
                 
                 ui.horizontal(|ui| {
-                    if ui.button("Sign Out").clicked() {
-                        let mut error = false;
-                        
-                        // Game
-                        self.game_error = None;
-
-                        let game = self.game.as_str();
-
-                        error |= filter_required(game, &mut self.game_error);
-                        error |= filter_length(game, NAME_MAX_LENGTH, &mut self.game_error);
-
-                        // Quantity
-                        self.quantity_error = None;
-                        
-                        if let Some(game) = game_types.get(game) {
-                            // Count quantity of all games already out
-                            let already_out = game_records.get_signed_out(&self.game).expect("failed to get game signed status from database");
-
-                            if game.quantity - already_out <= 0{
-                                self.quantity_error = Some(format!("Not enough of this game in stock."));
-                                error = true;
-                            }
-                        }
-
-                        // Student Name
-                        self.student_name_error = None;
-
-                        let student_name = self.student_name.trim();
-
-                        error |= filter_required(student_name, &mut self.student_name_error);
-                        error |= filter_length(student_name, NAME_MAX_LENGTH, &mut self.student_name_error);
-
-                        // Student Number
-                        self.student_number_error = None;
-
-                        let student_number = self.student_number.trim();
-
-                        // filter student number first so it overwrites with "required" text if blank
-                        error |= filter_student_number(student_number, &mut self.student_number_error);
-                        error |= filter_required(student_number, &mut self.student_number_error);
-
-                        // Notes
-                        self.notes_error = None;
-
-                        let notes = self.notes.trim();
-
-                        error |= filter_length(notes, NAME_MAX_LENGTH, &mut self.notes_error);
-
-                        // Entry valid, add record
-                        if !error {
-                            game_records.insert(NewGameRecord {
-                                game: &self.game,
-                                quantity: self.quantity,
-                                student_name: &self.student_name,
-                                student_number: &self.student_number,
-                                notes: &self.notes,
-                            }).expect("failed to add game record to database");
-
-                            log::info!("added game record");
-                            
-                            close_modal = true;
+                    if ui.button(self.restriction.sign_out_text()).clicked() {
+                        let result = service::sign_out_game(game_types, game_records, sign_out, NewGameRecord {
+                            game: &self.game,
+                            quantity: self.quantity,
+                            student_name: &self.student_name,
+                            student_number: &self.student_number,
+                            receptionist: &shift.receptionist.name,
+                            notes: &self.notes,
+                        }, self.restriction.justification());
+
+                        match result {
+                            Ok(()) => close_modal = true,
+                            Err(err) if err.is_failure() => errors.report("failed to add game record to database", err),
+                            Err(err) => {
+                                self.game_error = err.field_error(Field::Item);
+                                self.quantity_error = err.field_error(Field::Quantity);
+                                self.student_name_error = err.field_error(Field::StudentName);
+                                self.student_number_error = err.field_error(Field::StudentNumber);
+                                self.notes_error = err.field_error(Field::Notes);
+                                self.restriction.handle_error(&err);
+                            },
                         }
                     }
                     if ui.button("Cancel").clicked() {
@@ -195,6 +154,6 @@ impl GameSignModal {
                 });
             });
         
-        return close_modal;
+        close_modal
     }
 }
@@ -1,6 +1,6 @@
 use egui_extras::{TableBuilder, Column};
 
-use crate::{records::{ItemTypeStorage, Storage, InsertableStorage, DeletableStorage}, app::NAME_MAX_LENGTH};
+use crate::{records::{ItemTypeStorage, ItemRestockRecord, Storage, InsertableStorage, DeletableStorage, NewItemTypeRecord, Shift}, app::{StorageErrors, NAME_MAX_LENGTH, NOTES_MAX_LENGTH, MAX_QUANTITY, DATE_TIME_FORMAT}, panel::OVERDUE_COLOUR};
 
 use super::{render_modal_text_entry, filter_required, filter_length};
 
@@ -8,6 +8,14 @@ use super::{render_modal_text_entry, filter_required, filter_length};
 pub struct ItemEntryModal {
     pub item: String,
     pub item_error: Option<String>,
+    pub returnable: bool,
+    pub count_stock: bool,
+    pub quantity: i64,
+    pub low_stock: i64,
+    /// Item being restocked, along with its past restocks.
+    pub selected: Option<(String, Vec<ItemRestockRecord>)>,
+    pub restock_quantity: i64,
+    pub restock_notes: String,
 }
 
 impl Default for ItemEntryModal {
@@ -15,19 +23,45 @@ impl Default for ItemEntryModal {
         Self {
             item: Default::default(),
             item_error: Default::default(),
+            returnable: false,
+            count_stock: false,
+            quantity: 0,
+            low_stock: 0,
+            selected: None,
+            restock_quantity: 1,
+            restock_notes: Default::default(),
         }
     }
 }
 
 impl ItemEntryModal {
-    pub fn render(&mut self, ctx: &eframe::egui::Context, item_types: &mut ItemTypeStorage) -> bool {
+    pub fn render(&mut self, ctx: &eframe::egui::Context, item_types: &mut ItemTypeStorage, shift: Option<&Shift>, errors: &mut StorageErrors) -> bool {
         let mut close_modal = false;
         let mut delete_item = None;
+        let mut select_item = None;
+        let mut update_returnable = None;
+        let mut update_low_stock = None;
+        let mut restock = None;
+
+        if self.selected.as_ref().is_some_and(|(item, _)| item_types.get(item).is_none()) {
+            self.selected = None;
+        }
 
         egui::Window::new("Items")
             .collapsible(false)
             .resizable(false)
             .show(ctx, |ui| {
+                // Low Stock
+
+                let low_stock = item_types.low_stock()
+                    .map(|item| format!("{} ({})", item.item, item.quantity.unwrap_or_default()))
+                    .collect::<Vec<_>>();
+
+                if !low_stock.is_empty() {
+                    ui.colored_label(OVERDUE_COLOUR, format!("Low stock: {}", low_stock.join(", ")));
+                    ui.separator();
+                }
+
                 // Item Name
 
                 TableBuilder::new(ui)
@@ -40,32 +74,109 @@ impl ItemEntryModal {
                                     ui.horizontal(|ui| {
 
                                         if ui.add(egui::Button::new("−").small().fill(egui::Rgba::from_rgb(0.25, 0.0, 0.0))).clicked() {
-                                            delete_item = Some(item.clone());
+                                            delete_item = Some(item.item.clone());
                                         }
-                                        
+
                                         ui.add_space(8.0);
-                                        ui.label(item);
+
+                                        let selected = self.selected.as_ref().is_some_and(|(selected, _)| *selected == item.item);
+
+                                        if ui.selectable_label(selected, &item.item).clicked() {
+                                            select_item = Some(item.item.clone());
+                                        }
+
+                                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
+                                            let mut returnable = item.returnable;
+
+                                            if ui.checkbox(&mut returnable, "Returnable").changed() {
+                                                update_returnable = Some((item.item.clone(), returnable));
+                                            }
+                                            ui.add_space(8.0);
+
+                                            match item.quantity {
+                                                Some(quantity) if item.is_low_stock() => ui.colored_label(OVERDUE_COLOUR, format!("{quantity} in stock")),
+                                                Some(quantity) => ui.label(format!("{quantity} in stock")),
+                                                None => ui.weak("Not counted"),
+                                            };
+                                        });
                                     });
                                 });
                             })
                         }
                     });
 
-                if item_types.get_all().len() > 0 {
+                if !item_types.get_all().is_empty() {
+                    ui.separator();
+                }
+
+                // Restock
+
+                if let Some(item) = self.selected.as_ref().and_then(|(item, _)| item_types.get(item)) {
+                    ui.label(egui::RichText::new(&item.item).strong());
+
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Low stock at {}", item.low_stock));
+
+                        if ui.button("+").clicked() {
+                            update_low_stock = Some((item.item.clone(), item.low_stock + 1));
+                        }
+                        if ui.button("−").clicked() {
+                            update_low_stock = Some((item.item.clone(), item.low_stock - 1));
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut self.restock_quantity).clamp_range(1..=MAX_QUANTITY));
+                        ui.add(egui::TextEdit::singleline(&mut self.restock_notes).hint_text("Notes").char_limit(NOTES_MAX_LENGTH));
+
+                        if ui.add_enabled(shift.is_some(), egui::Button::new("Restock")).on_disabled_hover_text("Sign on to restock.").clicked() {
+                            restock = Some(item.item.clone());
+                        }
+                    });
+
+                    if let Some((_, restocks)) = &self.selected {
+                        for record in restocks.iter().take(5) {
+                            let time = chrono::DateTime::<chrono::Local>::from(record.time).format(DATE_TIME_FORMAT);
+                            let receptionist = record.receptionist.as_deref().unwrap_or("unknown");
+
+                            let mut text = format!("{time}: {} restocked by {receptionist}", record.quantity);
+
+                            if !record.notes.is_empty() {
+                                text += &format!(" ({})", record.notes);
+                            }
+
+                            ui.weak(text);
+                        }
+                    }
+
                     ui.separator();
                 }
 
                 // Item Name
                 render_modal_text_entry(ui, "Item Name", &self.item_error, &mut self.item, NAME_MAX_LENGTH);
 
+                ui.checkbox(&mut self.returnable, "Returnable")
+                    .on_hover_text("Records are signed back in, which puts the item back in stock.");
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.count_stock, "Count stock")
+                        .on_hover_text("Sign outs may not exceed the stock, which is added to by restocking.");
+
+                    if self.count_stock {
+                        ui.add(egui::DragValue::new(&mut self.quantity).clamp_range(0..=MAX_QUANTITY));
+                        ui.label("Low stock at");
+                        ui.add(egui::DragValue::new(&mut self.low_stock).clamp_range(0..=MAX_QUANTITY));
+                    }
+                });
+
                 ui.add_space(4.0);
 
                 // Buttons
-                
+
                 ui.horizontal(|ui| {
                     if ui.button("Add Item").clicked() {
                         let mut error = false;
-                        
+
                         // Item
                         self.item_error = None;
 
@@ -74,17 +185,24 @@ impl ItemEntryModal {
                         error |= filter_required(item, &mut self.item_error);
                         error |= filter_length(item, NAME_MAX_LENGTH, &mut self.item_error);
 
-                        // not particularly efficient, but unlikely to be an issue
-                        if let Some(_) = item_types.get_all().iter().find(|k| k.as_str() == item) {
+                        if item_types.get(item).is_some() {
                             self.item_error = Some("An item with this name already exists.".into());
                             error = true;
                         }
 
                         // Entry valid, add record
                         if !error {
-                            item_types.insert(&self.item).expect("failed to add item type to database");
-                            
-                            self.item.clear();
+                            let record = NewItemTypeRecord {
+                                item,
+                                returnable: self.returnable,
+                                quantity: self.count_stock.then_some(self.quantity),
+                                low_stock: if self.count_stock { self.low_stock } else { 0 },
+                            };
+
+                            // keep the entry on failure, so it can be added again
+                            if errors.check("failed to add item type to database", item_types.insert(record)).is_some() {
+                                *self = ItemEntryModal { selected: self.selected.take(), ..Default::default() };
+                            }
                         }
                     }
                     if ui.button("Close").clicked() {
@@ -93,11 +211,39 @@ impl ItemEntryModal {
                 });
             });
 
+        if let Some((item, returnable)) = update_returnable {
+            errors.check("failed to update item type returnable", item_types.update_returnable(&item, returnable));
+        }
+
+        if let Some((item, low_stock)) = update_low_stock {
+            let low_stock = low_stock.clamp(0, MAX_QUANTITY);
+            errors.check("failed to update item type low stock level", item_types.update_low_stock(&item, low_stock));
+        }
+
+        if let (Some(item), Some(shift)) = (restock, shift) {
+            let result = item_types.restock(&item, self.restock_quantity, &shift.receptionist.name, self.restock_notes.trim());
+
+            if errors.check("failed to restock item", result).is_some() {
+                log::info!("restocked {item} with {}", self.restock_quantity);
+
+                self.restock_quantity = 1;
+                self.restock_notes.clear();
+                select_item = Some(item);
+            }
+        }
+
+        if let Some(item) = select_item {
+            if let Some(restocks) = errors.check("failed to fetch item restocks", item_types.restocks(&item)) {
+                self.selected = Some((item, restocks));
+            }
+        }
+
         if let Some(item) = delete_item {
-            item_types.delete(&item).expect("failed to delete item type from database");
-            log::debug!("deleted item type");
+            if errors.check("failed to delete item type from database", item_types.delete(&item)).is_some() {
+                log::debug!("deleted item type");
+            }
         }
 
-        return close_modal;
+        close_modal
     }
 }
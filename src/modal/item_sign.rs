@@ -1,8 +1,6 @@
-use std::sync::{Mutex, Arc};
+use crate::{records::{ItemTypeStorage, ItemStorage, Storage, NewItemRecord, RecordType, Shift}, service::{self, SignOut, Field}, app::{StorageErrors, MAX_QUANTITY, NAME_MAX_LENGTH, STUDENT_NUMBER_LENGTH, NOTES_MAX_LENGTH}};
 
-use crate::{records::{ItemTypeStorage, ItemStorage, Storage, InsertableStorage, NewItemRecord, StudentInfo}, app::{MAX_QUANTITY, NAME_MAX_LENGTH, STUDENT_NUMBER_LENGTH, NOTES_MAX_LENGTH}};
-
-use super::{render_modal_text_entry, filter_student_number, filter_required, filter_length, render_student_number_popup};
+use super::{render_modal_text_entry, render_student_number_popup, RestrictionOverride};
 
 #[derive(Debug, Clone)]
 pub struct ItemSignModal {
@@ -15,10 +13,9 @@ pub struct ItemSignModal {
     pub student_name_error: Option<String>,
     pub student_number: String,
     pub student_number_error: Option<String>,
-    pub receptionist: String,
-    pub receptionist_error: Option<String>,
     pub notes: String,
     pub notes_error: Option<String>,
+    pub restriction: RestrictionOverride,
 }
 
 impl Default for ItemSignModal {
@@ -33,21 +30,19 @@ impl Default for ItemSignModal {
             student_name_error: Default::default(),
             student_number: Default::default(),
             student_number_error: Default::default(),
-            receptionist: Default::default(),
-            receptionist_error: Default::default(),
             notes: Default::default(),
             notes_error: Default::default(),
+            restriction: Default::default(),
         }
     }
 }
 
 impl ItemSignModal {
-    pub fn render(&mut self, ctx: &eframe::egui::Context, item_types: &ItemTypeStorage, item_records: &mut ItemStorage, student_info: Arc<Mutex<StudentInfo>>) -> bool {
-        let mut add_record = None;
+    pub fn render(&mut self, ctx: &eframe::egui::Context, item_types: &mut ItemTypeStorage, item_records: &mut ItemStorage, sign_out: &mut SignOut, shift: &Shift, errors: &mut StorageErrors) -> bool {
         let mut close_modal = false;
 
-        if self.item.len() == 0 && item_types.get_all().len() == 1 {
-            self.item = item_types.get_all()[0].clone();
+        if self.item.is_empty() && item_types.get_all().len() == 1 {
+            self.item = item_types.get_all()[0].item.clone();
         }
 
         egui::Window::new("Sign Out Item")
@@ -63,9 +58,20 @@ impl ItemSignModal {
                     .selected_text(&self.item)
                     .show_ui(ui, |ui| {
                         for item in item_types.get_all() {
-                            ui.selectable_value(&mut self.item, item.clone(), item);
+                            let text = match item.quantity {
+                                Some(quantity) => format!("{} ({quantity} in stock)", item.item),
+                                None => item.item.clone(),
+                            };
+
+                            ui.add_enabled_ui(item.quantity != Some(0), |ui| {
+                                ui.selectable_value(&mut self.item, item.item.clone(), text);
+                            });
                         }
                     });
+
+                if item_types.get(&self.item).is_some_and(|item| item.returnable) {
+                    ui.weak("To be signed back in.");
+                }
                 
                 if let Some(error) = &self.item_error {
                     ui.colored_label(egui::Rgba::from_rgb(0.25, 0.0, 0.0), error);
@@ -101,93 +107,52 @@ impl ItemSignModal {
                         self.quantity_str = self.quantity.to_string();
                     }
                 });
+                
+                if let Some(error) = &self.quantity_error {
+                    ui.colored_label(egui::Rgba::from_rgb(0.25, 0.0, 0.0), error);
+                }
 
                 ui.add_space(4.0);
 
                 // Student Number
                 let resp = render_modal_text_entry(ui, "Student Number", &self.student_number_error, &mut self.student_number, STUDENT_NUMBER_LENGTH);
-                render_student_number_popup(ui, student_info, "item_sign_student_number_popup".into(), &resp, &mut self.student_number, &mut self.student_name);
+                render_student_number_popup(ui, sign_out.students, "item_sign_student_number_popup".into(), &resp, &mut self.student_number, &mut self.student_name);
 
                 // Student Name
                 render_modal_text_entry(ui, "Student Name", &self.student_name_error, &mut self.student_name, NAME_MAX_LENGTH);
                 
-                // Student Receptionist
-                render_modal_text_entry(ui, "Receptionist", &self.receptionist_error, &mut self.receptionist, NAME_MAX_LENGTH);
-                
                 // Notes
                 render_modal_text_entry(ui, "Notes", &self.notes_error, &mut self.notes, NOTES_MAX_LENGTH);
+
+                // Restrictions
+                self.restriction.render(ui, sign_out.restrictions, sign_out.receptionists.is_admin(&shift.receptionist.name), &self.student_number, RecordType::Item, &self.item);
                 
                 ui.add_space(4.0);
 
                 // Buttons
                 
                 ui.horizontal(|ui| {
-                    if ui.button("Sign Out").clicked() {
-                        let mut error = false;
-                        
-                        // Item
-                        self.item_error = None;
-
-                        let item = self.item.as_str();
-
-                        error |= filter_required(item, &mut self.item_error);
-
-                        // Student Name
-                        self.student_name_error = None;
-
-                        let student_name = self.student_name.trim();
-
-                        error |= filter_required(student_name, &mut self.student_name_error);
-                        error |= filter_length(student_name, NAME_MAX_LENGTH, &mut self.student_name_error);
-
-                        // Student Number
-                        self.student_number_error = None;
-
-                        let student_number = self.student_number.trim();
-
-                        // filter student number first so it overwrites with "required" text if blank
-                        error |= filter_student_number(student_number, &mut self.student_number_error);
-                        error |= filter_required(student_number, &mut self.student_number_error);
-
-                        // Receptionist
-                        self.receptionist_error = None;
-
-                        let receptionist = self.receptionist.trim();
-
-                        if receptionist.len() == 0 {
-                            self.receptionist_error = Some("Required".into());
-                            error = true;
-                        }
-
-                        if receptionist.len() > NAME_MAX_LENGTH {
-                            self.receptionist_error = Some(format!("Name too long. (> {NAME_MAX_LENGTH} characters)"));
-                            error = true;
-                        }
-
-                        // Notes
-                        
-                        self.notes_error = None;
-
-                        let notes = self.notes.trim();
-
-                        if notes.len() > NAME_MAX_LENGTH {
-                            self.notes_error = Some(format!("Notes too long. (> {NAME_MAX_LENGTH} characters)"));
-                            error = true;
-                        }
-
-                        // Entry valid, add record
-                        if !error {
-                            add_record = Some(NewItemRecord {
-                                item: &self.item,
-                                quantity: self.quantity,
-                                student_name: &self.student_name,
-                                student_number: &self.student_number,
-                                receptionist: &self.receptionist,
-                                notes: &self.notes,
-                            });
-                            close_modal = true;
-
-                            log::info!("added item record");
+                    if ui.button(self.restriction.sign_out_text()).clicked() {
+                        let result = service::sign_out_item(item_types, item_records, sign_out, NewItemRecord {
+                            item: &self.item,
+                            quantity: self.quantity,
+                            student_name: &self.student_name,
+                            student_number: &self.student_number,
+                            receptionist: &shift.receptionist.name,
+                            notes: &self.notes,
+                        }, self.restriction.justification());
+
+                        match result {
+                            Ok(()) => close_modal = true,
+                            Err(err) if err.is_failure() => errors.report("failed to add item record to database", err),
+                            Err(err) => {
+                                self.item_error = err.field_error(Field::Item);
+                                self.quantity_error = err.field_error(Field::Quantity);
+                                self.student_name_error = err.field_error(Field::StudentName);
+                                self.student_number_error = err.field_error(Field::StudentNumber);
+                                self.notes_error = err.field_error(Field::Notes);
+                                self.restriction.handle_error(&err);
+                            },
                         }
                     }
                     if ui.button("Cancel").clicked() {
@@ -196,10 +161,6 @@ impl ItemSignModal {
                 });
             });
         
-        if let Some(record) = add_record {
-            item_records.insert(record).expect("failed to add item record to database");
-        }
-        
-        return close_modal;
+        close_modal
     }
 }
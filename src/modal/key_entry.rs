@@ -1,17 +1,18 @@
 use egui_extras::{TableBuilder, Column};
 
-use crate::{records::{KeyTypeStorage, Storage, InsertableStorage, DeletableStorage}, app::NAME_MAX_LENGTH};
+use crate::{records::{KeyTypeStorage, Storage, InsertableStorage, DeletableStorage, NewKeyTypeRecord}, app::{StorageErrors, NAME_MAX_LENGTH}};
 
-use super::render_modal_text_entry;
+use super::{render_modal_text_entry, render_loan_period_combo};
 
 #[derive(Debug, Clone, Default)]
 pub struct KeyEntryModal {
     pub key: String,
     pub key_error: Option<String>,
+    pub loan_minutes: Option<i64>,
 }
 
 impl KeyEntryModal {
-    pub fn render(&mut self, ctx: &eframe::egui::Context, key_types: &mut KeyTypeStorage) -> bool {
+    pub fn render(&mut self, ctx: &eframe::egui::Context, key_types: &mut KeyTypeStorage, errors: &mut StorageErrors) -> bool {
         let mut close_modal = false;
         let mut delete_key = None;
 
@@ -25,30 +26,49 @@ impl KeyEntryModal {
                     .max_scroll_height(f32::INFINITY)
                     .column(Column::remainder())
                     .body(|mut body| {
+                        let mut update_loan_period = None;
+
                         for key in key_types.get_all() {
                             body.row(24.0, |mut row| {
                                 row.col(|ui| {
                                     ui.horizontal(|ui| {
 
                                         if ui.add(egui::Button::new("−").small().fill(egui::Rgba::from_rgb(0.25, 0.0, 0.0))).clicked() {
-                                            delete_key = Some(key.clone());
+                                            delete_key = Some(key.key.clone());
                                         }
                                         
                                         ui.add_space(8.0);
-                                        ui.label(key);
+                                        ui.label(&key.key);
+
+                                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
+                                            let mut loan_minutes = key.loan_minutes;
+
+                                            if render_loan_period_combo(ui, ("key_loan_period", &key.key), &mut loan_minutes) {
+                                                update_loan_period = Some((key.key.clone(), loan_minutes));
+                                            }
+                                            ui.add_space(8.0);
+                                        });
                                     });
                                 });
                             })
                         }
+
+                        if let Some((key, loan_minutes)) = update_loan_period {
+                            errors.check("failed to update key type loan period", key_types.update_loan_period(&key, loan_minutes));
+                        }
                     });
 
-                if key_types.get_all().len() > 0 {
+                if !key_types.get_all().is_empty() {
                     ui.separator();
                 }
 
                 // Key Name
                 render_modal_text_entry(ui, "Key Name", &self.key_error, &mut self.key, NAME_MAX_LENGTH);
 
+                // Loan Period
+                ui.label("Loan Period");
+                render_loan_period_combo(ui, "key_loan_period", &mut self.loan_minutes);
+
                 ui.add_space(4.0);
 
                 // Buttons
@@ -62,7 +82,7 @@ impl KeyEntryModal {
 
                         let key = self.key.trim();
 
-                        if key.len() == 0 {
+                        if key.is_empty() {
                             self.key_error = Some("Required".into());
                             error = true;
                         }
@@ -73,16 +93,23 @@ impl KeyEntryModal {
                         }
 
                         // not particularly efficient, but unlikely to be an issue
-                        if let Some(_) = key_types.get_all().iter().find(|k| k.as_str() == key) {
+                        if key_types.get_all().iter().find(|k| k.key == key).is_some() {
                             self.key_error = Some("A key with this name already exists.".into());
                             error = true;
                         }
 
                         // Entry valid, add record
                         if !error {
-                            key_types.insert(&self.key).expect("failed to add key type to database");
-                            
-                            self.key.clear();
+                            let result = key_types.insert(NewKeyTypeRecord {
+                                key: &self.key,
+                                loan_minutes: self.loan_minutes,
+                            });
+
+                            // keep the entry on failure, so it can be added again
+                            if errors.check("failed to add key type to database", result).is_some() {
+                                self.key.clear();
+                                self.loan_minutes = None;
+                            }
                         }
                     }
                     if ui.button("Close").clicked() {
@@ -92,10 +119,11 @@ impl KeyEntryModal {
             });
 
         if let Some(key) = delete_key {
-            key_types.delete(&key).expect("failed to delete key type from database");
-            log::debug!("deleted key type");
+            if errors.check("failed to delete key type from database", key_types.delete(&key)).is_some() {
+                log::debug!("deleted key type");
+            }
         }
 
-        return close_modal;
+        close_modal
     }
 }
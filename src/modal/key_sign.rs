@@ -1,8 +1,6 @@
-use std::sync::{Arc, Mutex};
+use crate::{records::{KeyTypeStorage, KeyStorage, Storage, NewKeyRecord, RecordType, Shift}, service::{self, SignOut, Field}, app::{StorageErrors, NAME_MAX_LENGTH, STUDENT_NUMBER_LENGTH, NOTES_MAX_LENGTH}};
 
-use crate::{records::{KeyTypeStorage, KeyStorage, Storage, InsertableStorage, NewKeyRecord, StudentInfo}, app::{DATE_TIME_FORMAT, NAME_MAX_LENGTH, STUDENT_NUMBER_LENGTH, NOTES_MAX_LENGTH}};
-
-use super::{render_modal_text_entry, filter_student_number, filter_required, filter_length, render_student_number_popup};
+use super::{render_modal_text_entry, render_student_number_popup, RestrictionOverride};
 
 #[derive(Debug, Clone, Default)]
 pub struct KeySignModal {
@@ -12,15 +10,13 @@ pub struct KeySignModal {
     pub student_name_error: Option<String>,
     pub student_number: String,
     pub student_number_error: Option<String>,
-    // pub receptionist: String,
-    // pub receptionist_error: Option<String>,
     pub notes: String,
     pub notes_error: Option<String>,
+    pub restriction: RestrictionOverride,
 }
 
 impl KeySignModal {
-    pub fn render(&mut self, ctx: &eframe::egui::Context, key_types: &KeyTypeStorage, key_records: &mut KeyStorage, student_info: Arc<Mutex<StudentInfo>>) -> bool {
-        let mut add_record = None;
+    pub fn render(&mut self, ctx: &eframe::egui::Context, key_types: &KeyTypeStorage, key_records: &mut KeyStorage, sign_out: &mut SignOut, shift: &Shift, errors: &mut StorageErrors) -> bool {
         let mut close_modal = false;
 
         egui::Window::new("Sign Out Key")
@@ -36,7 +32,7 @@ impl KeySignModal {
                     .selected_text(&self.key)
                     .show_ui(ui, |ui| {
                         for key in key_types.get_all() {
-                            ui.selectable_value(&mut self.key, key.clone(), key);
+                            ui.selectable_value(&mut self.key, key.key.clone(), &key.key);
                         }
                     });
                 
@@ -48,70 +44,41 @@ impl KeySignModal {
 
                 // Student Number                
                 let resp = render_modal_text_entry(ui, "Student Number", &self.student_number_error, &mut self.student_number, STUDENT_NUMBER_LENGTH);
-                render_student_number_popup(ui, student_info, "key_sign_student_number_popup".into(), &resp, &mut self.student_number, &mut self.student_name);
+                render_student_number_popup(ui, sign_out.students, "key_sign_student_number_popup".into(), &resp, &mut self.student_number, &mut self.student_name);
 
                 // Student Name
                 render_modal_text_entry(ui, "Student Name", &self.student_name_error, &mut self.student_name, NAME_MAX_LENGTH);
                 
                 // Notes
                 render_modal_text_entry(ui, "Notes", &self.notes_error, &mut self.notes, NOTES_MAX_LENGTH);
+
+                // Restrictions
+                self.restriction.render(ui, sign_out.restrictions, sign_out.receptionists.is_admin(&shift.receptionist.name), &self.student_number, RecordType::Key, &self.key);
                 
                 ui.add_space(4.0);
 
                 // Buttons
                 
                 ui.horizontal(|ui| {
-                    if ui.button("Sign Out").clicked() {
-                        let mut error = false;
-                        
-                        // Key
-                        self.key_error = None;
-
-                        let key = self.key.as_str();
-
-                        error |= filter_required(key, &mut self.key_error);
-
-                        if let Some(record) = key_records.get_signed_out(key).expect("failed to get key signed status from database") {
-                            self.key_error = Some(format!("Key already signed out on {}.", chrono::DateTime::<chrono::Local>::from(record.time_out).format(DATE_TIME_FORMAT)));
-                            error = true;
-                        }
-
-                        // Student Name
-                        self.student_name_error = None;
-
-                        let student_name = self.student_name.trim();
-
-                        error |= filter_required(student_name, &mut self.student_name_error);
-                        error |= filter_length(student_name, NAME_MAX_LENGTH, &mut self.student_name_error);
-
-                        // Student Number
-                        self.student_number_error = None;
-
-                        let student_number = self.student_number.trim();
-
-                        // filter student number first so it overwrites with "required" text if blank
-                        error |= filter_student_number(student_number, &mut self.student_number_error);
-                        error |= filter_required(student_number, &mut self.student_number_error);
-
-                        // Notes
-                        
-                        self.notes_error = None;
-
-                        let notes = self.notes.trim();
-
-                        error |= filter_length(notes, NAME_MAX_LENGTH, &mut self.notes_error);
-
-                        // Entry valid, add record
-                        if !error {
-                            add_record = Some(NewKeyRecord {
-                                key: &self.key,
-                                student_name: &self.student_name,
-                                student_number: &self.student_number,
-                                notes: &self.notes,
-                            });
-                            close_modal = true;
-
-                            log::info!("added key record");
+                    if ui.button(self.restriction.sign_out_text()).clicked() {
+                        let result = service::sign_out_key(key_types, key_records, sign_out, NewKeyRecord {
+                            key: &self.key,
+                            student_name: &self.student_name,
+                            student_number: &self.student_number,
+                            receptionist: &shift.receptionist.name,
+                            notes: &self.notes,
+                        }, self.restriction.justification());
+
+                        match result {
+                            Ok(()) => close_modal = true,
+                            Err(err) if err.is_failure() => errors.report("failed to add key record to database", err),
+                            Err(err) => {
+                                self.key_error = err.field_error(Field::Item);
+                                self.student_name_error = err.field_error(Field::StudentName);
+                                self.student_number_error = err.field_error(Field::StudentNumber);
+                                self.notes_error = err.field_error(Field::Notes);
+                                self.restriction.handle_error(&err);
+                            },
                         }
                     }
                     if ui.button("Cancel").clicked() {
@@ -120,10 +87,6 @@ impl KeySignModal {
                 });
             });
         
-        if let Some(record) = add_record {
-            key_records.insert(record).expect("failed to add key record to database");
-        }
-        
-        return close_modal;
+        close_modal
     }
 }
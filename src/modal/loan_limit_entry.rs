@@ -0,0 +1,129 @@
+use egui_extras::{TableBuilder, Column};
+
+use crate::{records::{LoanLimitStorage, RecordType, Storage, DeletableStorage}, app::{StorageErrors, MAX_QUANTITY}};
+
+use super::Catalogue;
+
+/// Types of record which are lent out, and so can be limited. Only returnable items and categories are offered.
+const LIMITED_TYPES: [RecordType; 4] = [RecordType::Key, RecordType::Game, RecordType::Item, RecordType::Category];
+
+#[derive(Debug, Clone)]
+pub struct LoanLimitEntryModal {
+    pub record_type: Option<RecordType>,
+    /// Empty to limit the whole type.
+    pub name: String,
+    pub max_open: i64,
+}
+
+impl Default for LoanLimitEntryModal {
+    fn default() -> Self {
+        Self {
+            record_type: None,
+            name: Default::default(),
+            max_open: 1,
+        }
+    }
+}
+
+impl LoanLimitEntryModal {
+    pub fn render(&mut self, ctx: &eframe::egui::Context, limits: &mut LoanLimitStorage, catalogue: &Catalogue, errors: &mut StorageErrors) -> bool {
+        let mut close_modal = false;
+        let mut delete_limit = None;
+
+        egui::Window::new("Borrowing Limits")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("Most records a student may have out at once.");
+
+                ui.add_space(4.0);
+
+                TableBuilder::new(ui)
+                    .max_scroll_height(256.0)
+                    .column(Column::remainder())
+                    .body(|mut body| {
+                        for limit in limits.get_all() {
+                            body.row(24.0, |mut row| {
+                                row.col(|ui| {
+                                    ui.horizontal(|ui| {
+                                        if ui.add(egui::Button::new("−").small().fill(egui::Rgba::from_rgb(0.25, 0.0, 0.0))).clicked() {
+                                            delete_limit = Some(limit.id);
+                                        }
+
+                                        ui.add_space(8.0);
+                                        ui.label(format!("{}: {}", limit.scope(), limit.max_open));
+                                    });
+                                });
+                            })
+                        }
+                    });
+
+                if !limits.get_all().is_empty() {
+                    ui.separator();
+                }
+
+                // Scope
+                ui.label("Limit");
+
+                ui.horizontal(|ui| {
+                    let type_text = |record_type: Option<RecordType>| match record_type {
+                        Some(record_type) => record_type.plural(),
+                        None => "Everything",
+                    };
+
+                    egui::ComboBox::from_id_source("loan_limit_record_type")
+                        .width(96.0)
+                        .selected_text(type_text(self.record_type))
+                        .show_ui(ui, |ui| {
+                            for record_type in [None].into_iter().chain(LIMITED_TYPES.map(Some)) {
+                                if ui.selectable_value(&mut self.record_type, record_type, type_text(record_type)).changed() {
+                                    self.name.clear();
+                                }
+                            }
+                        });
+
+                    let names = catalogue.names(self.record_type, true);
+
+                    if self.record_type.is_some() {
+                        egui::ComboBox::from_id_source("loan_limit_name")
+                            .width(128.0)
+                            .selected_text(if !self.name.is_empty() { self.name.as_str() } else { "All" })
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut self.name, String::new(), "All");
+
+                                for name in names {
+                                    ui.selectable_value(&mut self.name, name.clone(), name);
+                                }
+                            });
+                    }
+
+                    ui.add(egui::DragValue::new(&mut self.max_open).clamp_range(0..=MAX_QUANTITY));
+                });
+
+                ui.add_space(4.0);
+
+                // Buttons
+
+                ui.horizontal(|ui| {
+                    if ui.button("Set Limit").clicked() {
+                        let name = if !self.name.is_empty() { Some(self.name.as_str()) } else { None };
+
+                        if errors.check("failed to set loan limit", limits.set(self.record_type, name, self.max_open)).is_some() {
+                            log::info!("set loan limit");
+                        }
+                    }
+                    if ui.button("Close").clicked() {
+                        close_modal = true;
+                    }
+                });
+            });
+
+        if let Some(id) = delete_limit {
+            if errors.check("failed to delete loan limit from database", limits.delete(id)).is_some() {
+                log::debug!("deleted loan limit");
+            }
+        }
+
+        close_modal
+    }
+}
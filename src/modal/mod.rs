@@ -1,17 +1,26 @@
-use std::sync::{Arc, Mutex};
-
-use crate::{app::{STUDENT_NUMBER_LENGTH, STAFF_NUMBER_LENGTH}, records::StudentInfo};
+use crate::{records::{StudentStorage, Storage, RestrictionStorage, KeyTypeStorage, GameTypeStorage, ItemTypeStorage, CategoryStorage, RecordType}, service::{self, ValidationError, ServiceError, Field}, app::NOTES_MAX_LENGTH, panel::OVERDUE_COLOUR};
 
 pub mod key_sign;
 pub mod parcel_sign;
 pub mod game_sign;
 pub mod item_sign;
+pub mod category_sign;
 
 pub mod key_entry;
 pub mod game_entry;
 pub mod item_entry;
+pub mod category_entry;
+pub mod student_entry;
+pub mod student_history;
+pub mod restriction_entry;
+pub mod loan_limit_entry;
+pub mod roster_import;
+pub mod receptionist_entry;
+pub mod profile_entry;
 
 pub mod sign_in;
+pub mod sign_on;
+pub mod amend;
 pub mod exit;
 pub mod alert;
 pub mod export;
@@ -19,17 +28,32 @@ pub mod about;
 pub mod settings;
 pub mod confirmation;
 pub mod summary;
+pub mod overdue;
+pub mod audit_log;
+pub mod restore;
+pub mod database_check;
 
 pub use key_sign::*;
 pub use parcel_sign::*;
 pub use game_sign::*;
 pub use item_sign::*;
+pub use category_sign::*;
 
 pub use key_entry::*;
 pub use game_entry::*;
 pub use item_entry::*;
+pub use category_entry::*;
+pub use student_entry::*;
+pub use student_history::*;
+pub use restriction_entry::*;
+pub use loan_limit_entry::*;
+pub use roster_import::*;
+pub use receptionist_entry::*;
+pub use profile_entry::*;
 
 pub use sign_in::*;
+pub use sign_on::*;
+pub use amend::*;
 pub use exit::*;
 pub use alert::*;
 pub use export::*;
@@ -37,6 +61,10 @@ pub use about::*;
 pub use settings::*;
 pub use confirmation::*;
 pub use summary::*;
+pub use overdue::*;
+pub use audit_log::*;
+pub use restore::*;
+pub use database_check::*;
 
 fn render_modal_text_entry(ui: &mut egui::Ui, label: &str, error: &Option<String>, input: &mut String, max_length: usize) -> egui::Response {
     ui.label(label);
@@ -52,20 +80,27 @@ fn render_modal_text_entry(ui: &mut egui::Ui, label: &str, error: &Option<String
     response
 }
 
-fn render_student_number_popup(ui: &mut egui::Ui, student_info: Arc<Mutex<StudentInfo>>, popup_id: egui::Id, resp: &egui::Response, student_number: &mut String, student_name: &mut String) {
-    let mut info = student_info.lock().unwrap();
-    let students = info.get().unwrap().into_iter()
-            .filter(|s| s.number.contains(&student_number.to_uppercase())).collect::<Vec<_>>();
+fn render_student_number_popup(ui: &mut egui::Ui, students: &StudentStorage, popup_id: egui::Id, resp: &egui::Response, student_number: &mut String, student_name: &mut String) {
+    let student_number_upper = student_number.to_uppercase();
+
+    // fill in the name of a registered student once their full number is entered
+    if student_name.is_empty() {
+        if let Some(student) = students.get(&student_number_upper) {
+            *student_name = student.student_name.clone();
+        }
+    }
 
-    let show_popup = student_number.len() > 0 && resp.has_focus() && students.len() > 0;
+    let students = students.get_all().iter()
+            .filter(|s| s.student_number.contains(&student_number_upper)).collect::<Vec<_>>();
+
+    let show_popup = !student_number.is_empty() && resp.has_focus() && !students.is_empty();
 
     let mut set_student = None;
 
-    egui::popup_below_widget(ui, popup_id, &resp, |ui| {
-        for i in 0..(students.len().min(10)) {
-            let student = students[i];
-            if ui.selectable_label(false, format!("{} – {}", student.number, student.name)).clicked() {
-                set_student = Some((student.number.clone(), student.name.clone()));
+    egui::popup_below_widget(ui, popup_id, resp, |ui| {
+        for student in students.iter().take(10) {
+            if ui.selectable_label(false, format!("{} – {}", student.student_number, student.student_name)).clicked() {
+                set_student = Some((student.student_number.clone(), student.student_name.clone()));
             }
         }
     });
@@ -86,40 +121,145 @@ fn render_student_number_popup(ui: &mut egui::Ui, student_info: Arc<Mutex<Studen
     }
 }
 
-fn filter_student_number(student_number: &str, error_text: &mut Option<String>) -> bool {
-    match student_number.parse::<i64>() {
-        // Maybe Staff Number
-        Ok(_) => {
-            if student_number.len() != STAFF_NUMBER_LENGTH {
-                *error_text = Some("Invalid staff number.".into());
+/// Restrictions on the student signing out a record, shown while the sign out is filled in,
+/// and the justification asked for once blocking restrictions refuse it, if the receptionist is an admin.
+#[derive(Debug, Clone, Default)]
+pub struct RestrictionOverride {
+    pub overriding: bool,
+    pub can_override: bool,
+    pub justification: String,
+    pub justification_error: Option<String>,
+}
+
+impl RestrictionOverride {
+    pub fn render(&mut self, ui: &mut egui::Ui, restrictions: &RestrictionStorage, can_override: bool, student_number: &str, record_type: RecordType, name: &str) {
+        self.can_override = can_override;
+
+        let applicable = restrictions.applicable(student_number, record_type, name);
+
+        if applicable.is_empty() {
+            self.overriding = false;
+            return;
+        }
+
+        for restriction in applicable {
+            if restriction.blocking {
+                ui.colored_label(OVERDUE_COLOUR, format!("Restricted. {restriction}"));
+            } else {
+                ui.colored_label(ui.visuals().warn_fg_color, format!("Warning. {restriction}"));
             }
-        },
-        // Maybe Student Number
-        Err(_) => {
-            if student_number.len() != STUDENT_NUMBER_LENGTH ||
-                    !student_number.chars().enumerate().all(|(i, c)| i < 6 && c.is_ascii_alphabetic() || i >= 6 && c.is_ascii_digit()) {
-                *error_text = Some("Invalid student number.".into());
+        }
+
+        if self.overriding && self.can_override {
+            render_modal_text_entry(ui, "Override Justification", &self.justification_error, &mut self.justification, NOTES_MAX_LENGTH);
+        } else if self.overriding {
+            ui.colored_label(OVERDUE_COLOUR, ServiceError::OverrideNotAllowed.to_string());
+        }
+
+        ui.add_space(4.0);
+    }
+
+    /// Justification to pass to the sign out, once the receptionist has been asked for one.
+    pub fn justification(&self) -> Option<&str> {
+        (self.overriding && self.can_override).then_some(self.justification.as_str())
+    }
+
+    pub fn sign_out_text(&self) -> &'static str {
+        if self.overriding && self.can_override { "Sign Out Anyway" } else { "Sign Out" }
+    }
+
+    pub fn handle_error(&mut self, err: &ServiceError) {
+        self.justification_error = err.field_error(Field::Justification);
+
+        if let ServiceError::Restricted(_) = err {
+            if self.overriding {
+                self.justification_error = Some("A justification is required to override.".into());
             }
-        },
+
+            self.overriding = true;
+        }
     }
+}
 
-    error_text.is_some()
+/// The keys, games, items, and categories which restrictions and loan limits may be scoped to.
+pub struct Catalogue<'a> {
+    pub key_types: &'a KeyTypeStorage,
+    pub game_types: &'a GameTypeStorage,
+    pub item_types: &'a ItemTypeStorage,
+    pub categories: &'a CategoryStorage,
 }
 
-fn filter_required(value: &str, error_text: &mut Option<String>) -> bool {
-    if value.len() == 0 {
-        *error_text = Some("Required".into());
-        true
-    } else {
-        false
+impl Catalogue<'_> {
+    /// Names of the given type of record, optionally only those which are returned.
+    fn names(&self, record_type: Option<RecordType>, returnable_only: bool) -> Vec<String> {
+        match record_type {
+            Some(RecordType::Key) => self.key_types.get_all().iter().map(|key| key.key.clone()).collect(),
+            Some(RecordType::Game) => self.game_types.get_all().iter().map(|game| game.game.clone()).collect(),
+            Some(RecordType::Item) => self.item_types.get_all().iter().filter(|item| item.returnable || !returnable_only).map(|item| item.item.clone()).collect(),
+            Some(RecordType::Category) => self.categories.get_all().iter().filter(|category| category.returnable || !returnable_only).map(|category| category.name.clone()).collect(),
+            _ => vec![],
+        }
     }
 }
 
+fn filter_student_number(student_number: &str, error_text: &mut Option<String>) -> bool {
+    filter(service::check_student_number(student_number), error_text)
+}
+
+fn filter_required(value: &str, error_text: &mut Option<String>) -> bool {
+    filter(service::check_required(value), error_text)
+}
+
 fn filter_length(value: &str, max: usize, error_text: &mut Option<String>) -> bool {
-    if value.len() > max {
-        *error_text = Some(format!("Too long. (> {max} characters)"));
-        true
-    } else {
-        false
+    filter(service::check_length(value, max), error_text)
+}
+
+fn filter(result: Result<(), ValidationError>, error_text: &mut Option<String>) -> bool {
+    match result {
+        Ok(()) => false,
+        Err(error) => {
+            *error_text = Some(error.to_string());
+            true
+        },
+    }
+}
+
+/// Loan periods (in minutes) which can be chosen for keys and games.
+const LOAN_PERIODS: &[Option<i64>] = &[
+    None,
+    Some(30),
+    Some(60),
+    Some(2 * 60),
+    Some(3 * 60),
+    Some(4 * 60),
+    Some(6 * 60),
+    Some(12 * 60),
+    Some(24 * 60),
+    Some(2 * 24 * 60),
+    Some(7 * 24 * 60),
+];
+
+fn format_loan_period(loan_minutes: Option<i64>) -> String {
+    match loan_minutes {
+        None => "No Limit".into(),
+        Some(minutes) if minutes % (24 * 60) == 0 => format!("{} day(s)", minutes / (24 * 60)),
+        Some(minutes) if minutes % 60 == 0 => format!("{} hour(s)", minutes / 60),
+        Some(minutes) => format!("{minutes} minutes"),
     }
 }
+
+/// Renders a combo box for selecting a loan period, returns true if changed.
+fn render_loan_period_combo(ui: &mut egui::Ui, id_source: impl std::hash::Hash, loan_minutes: &mut Option<i64>) -> bool {
+    let mut changed = false;
+
+    egui::ComboBox::from_id_source(id_source)
+        .width(96.0)
+        .selected_text(format_loan_period(*loan_minutes))
+        .show_ui(ui, |ui| {
+            for period in LOAN_PERIODS {
+                changed |= ui.selectable_value(loan_minutes, *period, format_loan_period(*period)).changed();
+            }
+        });
+
+    changed
+}
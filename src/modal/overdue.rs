@@ -0,0 +1,99 @@
+use egui_extras::{TableBuilder, Column};
+
+use crate::{records::{KeyStorage, GameStorage, KeyRecord, GameRecord}, app::{StorageErrors, DATE_TIME_FORMAT, ROW_HEIGHT, COL_LARGE_INITIAL_WIDTH, COL_SMALL_INITIAL_WIDTH, COL_MIN_WIDTH, COL_MAX_WIDTH}};
+
+#[derive(Debug, Clone)]
+pub struct OverdueModal {
+    keys: Vec<KeyRecord>,
+    games: Vec<GameRecord>,
+    refresh: bool,
+}
+
+impl Default for OverdueModal {
+    fn default() -> Self {
+        Self {
+            keys: vec![],
+            games: vec![],
+            refresh: true,
+        }
+    }
+}
+
+impl OverdueModal {
+    pub fn render(&mut self, ctx: &eframe::egui::Context, keys: &KeyStorage, games: &GameStorage, errors: &mut StorageErrors) -> bool {
+        let mut close_modal = false;
+
+        if self.refresh {
+            self.refresh = false;
+            self.keys = errors.check("failed to fetch overdue key records from database", keys.fetch_overdue()).unwrap_or_default();
+            self.games = errors.check("failed to fetch overdue game records from database", games.fetch_overdue()).unwrap_or_default();
+        }
+
+        egui::Window::new("Overdue")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if self.keys.is_empty() && self.games.is_empty() {
+                    ui.label("Nothing is overdue.");
+                } else {
+                    TableBuilder::new(ui)
+                        .striped(true)
+                        .max_scroll_height(384.0)
+                        .column(Column::auto().at_most(COL_MAX_WIDTH))
+                        .column(Column::auto().at_most(COL_MAX_WIDTH))
+                        .column(Column::initial(COL_LARGE_INITIAL_WIDTH).at_least(COL_MIN_WIDTH).clip(true).resizable(true))
+                        .column(Column::initial(COL_SMALL_INITIAL_WIDTH).at_least(COL_MIN_WIDTH).clip(true).resizable(true))
+                        .column(Column::auto().at_least(COL_MIN_WIDTH).at_most(COL_MAX_WIDTH))
+                        .header(ROW_HEIGHT, |mut header| {
+                            for title in ["Due", "Time Out", "Key / Game", "Student Name", "Student Number"] {
+                                header.col(|ui| {
+                                    ui.label(egui::RichText::new(title).strong());
+                                });
+                            }
+                        })
+                        .body(|mut body| {
+                            let keys = self.keys.iter()
+                                .map(|r| (r.time_due, r.time_out, r.key.clone(), &r.student_name, &r.student_number));
+                            let games = self.games.iter()
+                                .map(|r| (r.time_due, r.time_out, format!("{} × {}", r.quantity, r.game), &r.student_name, &r.student_number));
+
+                            for (time_due, time_out, item, student_name, student_number) in keys.chain(games) {
+                                body.row(ROW_HEIGHT, |mut row| {
+                                    row.col(|ui| {
+                                        if let Some(time_due) = time_due {
+                                            ui.label(chrono::DateTime::<chrono::Local>::from(time_due).format(DATE_TIME_FORMAT).to_string());
+                                        }
+                                    });
+                                    row.col(|ui| {
+                                        ui.label(chrono::DateTime::<chrono::Local>::from(time_out).format(DATE_TIME_FORMAT).to_string());
+                                    });
+                                    row.col(|ui| {
+                                        ui.label(item);
+                                    });
+                                    row.col(|ui| {
+                                        ui.label(student_name);
+                                    });
+                                    row.col(|ui| {
+                                        ui.label(student_number);
+                                    });
+                                });
+                            }
+                        });
+                }
+
+                ui.add_space(4.0);
+
+                // Buttons
+                ui.horizontal(|ui| {
+                    if ui.button("Refresh").clicked() {
+                        self.refresh = true;
+                    }
+                    if ui.button("Close").clicked() {
+                        close_modal = true;
+                    }
+                });
+            });
+
+        close_modal
+    }
+}
@@ -0,0 +1,156 @@
+use std::{path::PathBuf, thread::JoinHandle};
+
+use egui_extras::{TableBuilder, Column};
+
+use crate::app::{AppConfig, Profile, NAME_MAX_LENGTH};
+
+use super::{render_modal_text_entry, filter_required, filter_length};
+
+#[derive(Debug, Default)]
+pub struct ProfileEntryModal {
+    pub name: String,
+    pub name_error: Option<String>,
+    pub database_path: Option<PathBuf>,
+    pub database_path_error: Option<String>,
+    pub database_path_handle: Option<JoinHandle<Option<PathBuf>>>,
+    /// Set when a profile is chosen to be opened.
+    pub open: Option<Profile>,
+}
+
+impl ProfileEntryModal {
+    /// Changes the profiles in the config, which should be saved once the modal is closed.
+    pub fn render(&mut self, ctx: &eframe::egui::Context, config: &mut AppConfig) -> bool {
+        let mut close_modal = false;
+        let mut remove = None;
+
+        if let Some(handle) = &self.database_path_handle {
+            if handle.is_finished() {
+                let handle = self.database_path_handle.take().unwrap();
+
+                if let Some(path) = handle.join().expect("database path thread panicked") {
+                    self.database_path = Some(path);
+                }
+            }
+        }
+
+        let current = config.current_profile();
+
+        egui::Window::new("Profiles")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                TableBuilder::new(ui)
+                    .max_scroll_height(256.0)
+                    .column(Column::remainder())
+                    .body(|mut body| {
+                        for (i, profile) in config.profiles.iter().enumerate() {
+                            body.row(24.0, |mut row| {
+                                row.col(|ui| {
+                                    ui.horizontal(|ui| {
+                                        if profile.name == current.name {
+                                            ui.label(egui::RichText::new(&profile.name).strong())
+                                                .on_hover_text(config.database_path(profile).display().to_string());
+                                            ui.weak("(open)");
+                                        } else {
+                                            // the database file is kept, so that a removed profile can be added back
+                                            if ui.button("Remove").clicked() {
+                                                remove = Some(i);
+                                            }
+                                            if ui.button("Open").clicked() {
+                                                self.open = Some(profile.clone());
+                                                close_modal = true;
+                                            }
+                                            ui.label(&profile.name)
+                                                .on_hover_text(config.database_path(profile).display().to_string());
+                                        }
+                                    });
+                                });
+                            })
+                        }
+                    });
+
+                ui.separator();
+
+                // Name
+                render_modal_text_entry(ui, "Profile Name", &self.name_error, &mut self.name, NAME_MAX_LENGTH);
+
+                // Database File
+                ui.label("Database File");
+
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(self.database_path_handle.is_none(), egui::Button::new("Choose File")).clicked() {
+                        self.database_path_handle = Some(std::thread::spawn(|| {
+                            rfd::FileDialog::new()
+                                .add_filter("Sqlite DB", &["sqlite"])
+                                .save_file()
+                        }));
+                    }
+
+                    match &self.database_path {
+                        Some(path) => ui.label(path.display().to_string()),
+                        None => ui.weak("Default location"),
+                    };
+                });
+
+                if let Some(error) = &self.database_path_error {
+                    ui.colored_label(egui::Rgba::from_rgb(0.25, 0.0, 0.0), error);
+                }
+
+                ui.add_space(4.0);
+
+                // Buttons
+
+                ui.horizontal(|ui| {
+                    if ui.button("Add Profile").clicked() {
+                        let mut error = false;
+
+                        // Name
+                        self.name_error = None;
+
+                        let name = self.name.trim();
+
+                        error |= filter_required(name, &mut self.name_error);
+                        error |= filter_length(name, NAME_MAX_LENGTH, &mut self.name_error);
+
+                        if config.profiles.iter().any(|p| p.name.to_lowercase() == name.to_lowercase()) {
+                            self.name_error = Some("A profile with this name already exists.".into());
+                            error = true;
+                        }
+
+                        // Database File
+                        self.database_path_error = None;
+
+                        if let Some(path) = &self.database_path {
+                            if config.profiles.iter().any(|p| config.database_path(p) == *path) {
+                                self.database_path_error = Some("Another profile already uses this database file.".into());
+                                error = true;
+                            }
+                        }
+
+                        // Entry valid, add profile
+                        if !error {
+                            config.profiles.push(Profile {
+                                name: name.into(),
+                                database_path: self.database_path.take(),
+                                file_name: Some(config.new_file_name(name)),
+                            });
+
+                            log::info!("added profile");
+
+                            self.name.clear();
+                        }
+                    }
+                    if ui.button("Close").clicked() {
+                        close_modal = true;
+                    }
+                });
+            });
+
+        if let Some(i) = remove {
+            config.profiles.remove(i);
+            log::info!("removed profile");
+        }
+
+        close_modal
+    }
+}
@@ -0,0 +1,123 @@
+use egui_extras::{TableBuilder, Column};
+
+use crate::{records::{ReceptionistStorage, Storage, InsertableStorage, NewReceptionistRecord, Shift}, app::{StorageErrors, NAME_MAX_LENGTH}};
+
+use super::{render_modal_text_entry, filter_required, filter_length};
+
+#[derive(Debug, Clone, Default)]
+pub struct ReceptionistEntryModal {
+    pub name: String,
+    pub name_error: Option<String>,
+}
+
+impl ReceptionistEntryModal {
+    pub fn render(&mut self, ctx: &eframe::egui::Context, receptionists: &mut ReceptionistStorage, shift: Option<&Shift>, errors: &mut StorageErrors) -> bool {
+        let mut close_modal = false;
+        let mut set_active = None;
+        let mut set_admin = None;
+
+        // admins are made by other admins, apart from the first
+        let can_set_admin = match shift {
+            Some(shift) => receptionists.is_admin(&shift.receptionist.name) || !receptionists.get_all().iter().any(|r| r.admin),
+            None => false,
+        };
+
+        egui::Window::new("Receptionists")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                TableBuilder::new(ui)
+                    .max_scroll_height(256.0)
+                    .column(Column::remainder())
+                    .body(|mut body| {
+                        for receptionist in receptionists.get_all() {
+                            body.row(24.0, |mut row| {
+                                row.col(|ui| {
+                                    ui.horizontal(|ui| {
+                                        // receptionists are never deleted, so that their records keep a valid name
+                                        let mut active = receptionist.active;
+
+                                        if ui.checkbox(&mut active, "").on_hover_text("Active").changed() {
+                                            set_active = Some((receptionist.id, active));
+                                        }
+
+                                        if receptionist.active {
+                                            ui.label(&receptionist.name);
+                                        } else {
+                                            ui.weak(&receptionist.name);
+                                        }
+
+                                        let mut admin = receptionist.admin;
+
+                                        if ui.add_enabled(can_set_admin, egui::Checkbox::new(&mut admin, "Admin")).on_hover_text("May override restrictions").changed() {
+                                            set_admin = Some((receptionist.id, admin));
+                                        }
+                                    });
+                                });
+                            })
+                        }
+                    });
+
+                if !receptionists.get_all().is_empty() {
+                    ui.separator();
+                }
+
+                // Name
+                render_modal_text_entry(ui, "Receptionist Name", &self.name_error, &mut self.name, NAME_MAX_LENGTH);
+
+                ui.add_space(4.0);
+
+                // Buttons
+                
+                ui.horizontal(|ui| {
+                    if ui.button("Add Receptionist").clicked() {
+                        let mut error = false;
+                        
+                        // Name
+                        self.name_error = None;
+
+                        let name = self.name.trim();
+
+                        error |= filter_required(name, &mut self.name_error);
+                        error |= filter_length(name, NAME_MAX_LENGTH, &mut self.name_error);
+
+                        if receptionists.get_all().iter().any(|r| r.name.to_lowercase() == name.to_lowercase()) {
+                            self.name_error = Some("A receptionist with this name already exists.".into());
+                            error = true;
+                        }
+
+                        // Entry valid, add record
+                        if !error {
+                            let result = receptionists.insert(NewReceptionistRecord {
+                                name,
+                            });
+
+                            // keep the entry on failure, so it can be added again
+                            if errors.check("failed to add receptionist to database", result).is_some() {
+                                log::info!("added receptionist");
+
+                                self.name.clear();
+                            }
+                        }
+                    }
+                    if ui.button("Close").clicked() {
+                        close_modal = true;
+                    }
+                });
+            });
+
+        if let Some((id, active)) = set_active {
+            if errors.check("failed to update receptionist in database", receptionists.set_active(id, active)).is_some() {
+                log::debug!("set receptionist {id} active to {active}");
+            }
+        }
+
+        if let Some((id, admin)) = set_admin {
+            if errors.check("failed to update receptionist in database", receptionists.set_admin(id, admin)).is_some() {
+                log::info!("set receptionist {id} admin to {admin}");
+            }
+        }
+
+        close_modal
+    }
+}
@@ -0,0 +1,120 @@
+use egui_extras::{TableBuilder, Column};
+
+use crate::{records::RestorePreview, app::{ROW_HEIGHT, COL_LARGE_INITIAL_WIDTH, COL_SMALL_INITIAL_WIDTH, COL_MIN_WIDTH}, panel::OVERDUE_COLOUR};
+
+/// What to do with a previewed backup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestoreAction {
+    /// Copy records missing from the current database out of the backup.
+    Merge,
+    /// Overwrite the current database with the backup.
+    Replace,
+}
+
+#[derive(Debug)]
+pub struct RestoreModal {
+    pub preview: RestorePreview,
+    pub action: Option<RestoreAction>,
+}
+
+impl RestoreModal {
+    pub fn new(preview: RestorePreview) -> RestoreModal {
+        RestoreModal {
+            preview,
+            action: None,
+        }
+    }
+
+    pub fn render(&mut self, ctx: &eframe::egui::Context) -> bool {
+        let mut close_modal = false;
+
+        egui::Window::new("Restore Backup")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(self.preview.path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default());
+
+                ui.add_space(4.0);
+
+                // Differences
+                TableBuilder::new(ui)
+                    .striped(true)
+                    .column(Column::initial(COL_LARGE_INITIAL_WIDTH).at_least(COL_MIN_WIDTH))
+                    .columns(Column::initial(COL_SMALL_INITIAL_WIDTH).at_least(COL_MIN_WIDTH), 3)
+                    .header(ROW_HEIGHT, |mut header| {
+                        for title in ["", "Only in Backup", "Only Current", "Conflicting"] {
+                            header.col(|ui| {
+                                ui.label(egui::RichText::new(title).strong());
+                            });
+                        }
+                    })
+                    .body(|mut body| {
+                        for table in &self.preview.tables {
+                            body.row(ROW_HEIGHT, |mut row| {
+                                row.col(|ui| {
+                                    ui.label(table.label);
+                                });
+                                row.col(|ui| {
+                                    ui.label(table.only_backup.to_string());
+                                });
+                                row.col(|ui| {
+                                    ui.label(table.only_current.to_string());
+                                });
+                                row.col(|ui| {
+                                    ui.label(table.conflicting.to_string());
+                                });
+                            });
+                        }
+                    });
+
+                // Catalogue Conflicts
+                if !self.preview.conflicts.is_empty() {
+                    ui.separator();
+
+                    ui.label("These differ between the backup and the current records. Choose which to keep when merging.");
+
+                    ui.add_space(4.0);
+
+                    egui::ScrollArea::vertical().max_height(192.0).show(ui, |ui| {
+                        egui::Grid::new("restore_conflicts").num_columns(3).striped(true).show(ui, |ui| {
+                            for conflict in &mut self.preview.conflicts {
+                                ui.label(format!("{} {}", conflict.label, conflict.id));
+                                ui.radio_value(&mut conflict.use_backup, false, format!("Current ({})", conflict.current));
+                                ui.radio_value(&mut conflict.use_backup, true, format!("Backup ({})", conflict.backup));
+                                ui.end_row();
+                            }
+                        });
+                    });
+                }
+
+                if !self.preview.open_keys.is_empty() {
+                    ui.separator();
+
+                    ui.colored_label(OVERDUE_COLOUR, format!("Signed out in the backup, and signed out again since, so not merged: {}", self.preview.open_keys.join(", ")));
+                }
+
+                ui.separator();
+
+                ui.label("Merging adds what is only in the backup and keeps everything current. Conflicting records keep their current values, and shifts left open in the backup are not merged.");
+
+                ui.add_space(4.0);
+
+                // Buttons
+                ui.horizontal(|ui| {
+                    if ui.button("Merge").clicked() {
+                        self.action = Some(RestoreAction::Merge);
+                        close_modal = true;
+                    }
+                    if ui.button("Replace Everything").clicked() {
+                        self.action = Some(RestoreAction::Replace);
+                        close_modal = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close_modal = true;
+                    }
+                });
+            });
+
+        close_modal
+    }
+}
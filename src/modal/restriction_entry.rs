@@ -0,0 +1,271 @@
+use egui_extras::{TableBuilder, Column};
+
+use crate::{records::{self, RestrictionStorage, RestrictionRecord, NewRestrictionRecord, RecordType, Storage, InsertableStorage, Shift}, app::{StorageErrors, DATE_TIME_FORMAT, STUDENT_NUMBER_LENGTH, NOTES_MAX_LENGTH, ROW_HEIGHT, COL_MIN_WIDTH, COL_MAX_WIDTH, COL_LARGE_INITIAL_WIDTH}, panel::OVERDUE_COLOUR};
+
+use super::{Catalogue, render_modal_text_entry, filter_student_number, filter_required, filter_length};
+
+/// Types of record a restriction can be limited to, parcels are never signed out.
+const RESTRICTABLE_TYPES: [RecordType; 4] = [RecordType::Key, RecordType::Game, RecordType::Item, RecordType::Category];
+
+#[derive(Debug, Clone)]
+pub struct RestrictionEntryModal {
+    pub search: String,
+    pub show_ended: bool,
+    pub student_number: String,
+    pub student_number_error: Option<String>,
+    pub record_type: Option<RecordType>,
+    /// Empty to restrict the whole type.
+    pub name: String,
+    pub reason: String,
+    pub reason_error: Option<String>,
+    pub blocking: bool,
+    pub start: chrono::NaiveDate,
+    pub end: Option<chrono::NaiveDate>,
+    pub end_error: Option<String>,
+}
+
+impl Default for RestrictionEntryModal {
+    fn default() -> Self {
+        let today = chrono::Local::now().date_naive();
+
+        Self {
+            search: Default::default(),
+            show_ended: false,
+            student_number: Default::default(),
+            student_number_error: Default::default(),
+            record_type: None,
+            name: Default::default(),
+            reason: Default::default(),
+            reason_error: Default::default(),
+            blocking: true,
+            start: today,
+            end: None,
+            end_error: Default::default(),
+        }
+    }
+}
+
+impl RestrictionEntryModal {
+    pub fn render(&mut self, ctx: &eframe::egui::Context, restrictions: &mut RestrictionStorage, catalogue: &Catalogue, shift: Option<&Shift>, errors: &mut StorageErrors) -> bool {
+        let mut close_modal = false;
+        let mut lift_restriction = None;
+
+        egui::Window::new("Restrictions")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.search).hint_text("Search"));
+                    ui.checkbox(&mut self.show_ended, "Show ended");
+                });
+
+                ui.add_space(4.0);
+
+                let now = chrono::Utc::now();
+                let search = self.search.trim().to_uppercase();
+
+                let listed = restrictions.get_all().iter()
+                    .filter(|r| self.show_ended || r.time_end.is_none_or(|time_end| now < time_end))
+                    .filter(|r| r.student_number.contains(&search))
+                    .collect::<Vec<_>>();
+
+                TableBuilder::new(ui)
+                    .striped(true)
+                    .max_scroll_height(192.0)
+                    .column(Column::auto().at_least(COL_MIN_WIDTH))
+                    .column(Column::auto().at_most(COL_MAX_WIDTH))
+                    .column(Column::initial(COL_LARGE_INITIAL_WIDTH).at_least(COL_MIN_WIDTH).clip(true).resizable(true))
+                    .column(Column::auto().at_most(COL_MAX_WIDTH))
+                    .column(Column::auto().at_most(COL_MAX_WIDTH))
+                    .column(Column::auto().at_least(COL_MIN_WIDTH))
+                    .header(ROW_HEIGHT, |mut header| {
+                        for title in ["Student Number", "Scope", "Reason", "Period", "Imposed By", ""] {
+                            header.col(|ui| {
+                                ui.label(egui::RichText::new(title).strong());
+                            });
+                        }
+                    })
+                    .body(|mut body| {
+                        for restriction in listed {
+                            body.row(ROW_HEIGHT, |mut row| {
+                                let active = restriction.is_active(now);
+
+                                let cells = [
+                                    restriction.student_number.clone(),
+                                    restriction.scope(),
+                                    restriction.reason.clone(),
+                                    format_period(restriction),
+                                    restriction.imposed_by.clone(),
+                                ];
+
+                                for cell in cells {
+                                    row.col(|ui| {
+                                        if active && restriction.blocking {
+                                            ui.visuals_mut().override_text_color = Some(OVERDUE_COLOUR);
+                                        } else if !active {
+                                            ui.visuals_mut().override_text_color = Some(ui.visuals().weak_text_color());
+                                        }
+
+                                        ui.label(cell);
+                                    });
+                                }
+
+                                row.col(|ui| {
+                                    if restriction.time_end.is_none_or(|time_end| now < time_end) && ui.small_button("Lift").clicked() {
+                                        lift_restriction = Some(restriction.id);
+                                    }
+                                });
+                            });
+                        }
+                    });
+
+                ui.separator();
+
+                // Student Number
+                render_modal_text_entry(ui, "Student Number", &self.student_number_error, &mut self.student_number, STUDENT_NUMBER_LENGTH);
+
+                // Scope
+                ui.label("Restricted From");
+
+                ui.horizontal(|ui| {
+                    let type_text = |record_type: Option<RecordType>| match record_type {
+                        Some(record_type) => record_type.plural(),
+                        None => "Everything",
+                    };
+
+                    egui::ComboBox::from_id_source("restriction_record_type")
+                        .width(96.0)
+                        .selected_text(type_text(self.record_type))
+                        .show_ui(ui, |ui| {
+                            for record_type in [None].into_iter().chain(RESTRICTABLE_TYPES.map(Some)) {
+                                if ui.selectable_value(&mut self.record_type, record_type, type_text(record_type)).changed() {
+                                    self.name.clear();
+                                }
+                            }
+                        });
+
+                    let names = catalogue.names(self.record_type, false);
+
+                    if self.record_type.is_some() {
+                        egui::ComboBox::from_id_source("restriction_name")
+                            .width(128.0)
+                            .selected_text(if !self.name.is_empty() { self.name.as_str() } else { "All" })
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut self.name, String::new(), "All");
+
+                                for name in names {
+                                    ui.selectable_value(&mut self.name, name.clone(), name);
+                                }
+                            });
+                    }
+                });
+
+                ui.add_space(4.0);
+
+                // Reason
+                render_modal_text_entry(ui, "Reason", &self.reason_error, &mut self.reason, NOTES_MAX_LENGTH);
+
+                ui.checkbox(&mut self.blocking, "Refuse sign outs")
+                    .on_hover_text("Otherwise the receptionist is only warned.");
+
+                ui.add_space(4.0);
+
+                // Period
+                ui.horizontal(|ui| {
+                    ui.label("From");
+                    ui.add(egui_extras::DatePickerButton::new(&mut self.start).id_source("restriction_start"));
+
+                    let mut until = self.end.is_some();
+
+                    if ui.checkbox(&mut until, "Until").changed() {
+                        self.end = until.then_some(self.start);
+                    }
+
+                    if let Some(end) = &mut self.end {
+                        ui.add(egui_extras::DatePickerButton::new(end).id_source("restriction_end"));
+                    }
+                });
+
+                if let Some(error) = &self.end_error {
+                    ui.colored_label(egui::Rgba::from_rgb(0.25, 0.0, 0.0), error);
+                }
+
+                ui.add_space(4.0);
+
+                // Buttons
+
+                ui.horizontal(|ui| {
+                    let add = ui.add_enabled(shift.is_some(), egui::Button::new("Add Restriction"))
+                        .on_disabled_hover_text("Sign on to add restrictions.");
+
+                    if let (true, Some(shift)) = (add.clicked(), shift) {
+                        let mut error = false;
+
+                        // Student Number
+                        self.student_number_error = None;
+
+                        let student_number = self.student_number.trim();
+
+                        error |= filter_student_number(student_number, &mut self.student_number_error);
+                        error |= filter_required(student_number, &mut self.student_number_error);
+
+                        // Reason
+                        self.reason_error = None;
+
+                        let reason = self.reason.trim();
+
+                        error |= filter_required(reason, &mut self.reason_error);
+                        error |= filter_length(reason, NOTES_MAX_LENGTH, &mut self.reason_error);
+
+                        // Period
+                        self.end_error = None;
+
+                        if self.end.is_some_and(|end| end < self.start) {
+                            self.end_error = Some("Cannot end before it starts.".into());
+                            error = true;
+                        }
+
+                        if !error {
+                            let record = NewRestrictionRecord {
+                                student_number,
+                                record_type: self.record_type,
+                                name: if !self.name.is_empty() { Some(self.name.as_str()) } else { None },
+                                reason,
+                                blocking: self.blocking,
+                                time_start: records::local_midnight(self.start),
+                                // the end date is included
+                                time_end: self.end.map(|end| records::local_midnight(end + chrono::Days::new(1))),
+                                imposed_by: &shift.receptionist.name,
+                            };
+
+                            if errors.check("failed to add restriction to database", restrictions.insert(record)).is_some() {
+                                log::info!("added restriction");
+
+                                self.student_number.clear();
+                                self.reason.clear();
+                                self.name.clear();
+                            }
+                        }
+                    }
+                    if ui.button("Close").clicked() {
+                        close_modal = true;
+                    }
+                });
+            });
+
+        if let Some(id) = lift_restriction {
+            errors.check("failed to lift restriction", restrictions.lift(id));
+        }
+
+        close_modal
+    }
+}
+
+fn format_period(restriction: &RestrictionRecord) -> String {
+    let format = |time| chrono::DateTime::<chrono::Local>::from(time).format(DATE_TIME_FORMAT).to_string();
+
+    match restriction.time_end {
+        Some(time_end) => format!("{} – {}", format(restriction.time_start), format(time_end)),
+        None => format!("From {}", format(restriction.time_start)),
+    }
+}
@@ -0,0 +1,352 @@
+use std::{thread::JoinHandle, path::PathBuf, collections::HashSet};
+
+use crate::{records::{StudentStorage, Storage, Roster, RosterEntry}, app::{NAME_MAX_LENGTH, ROOM_MAX_LENGTH}};
+
+use super::{filter_student_number, filter_required, filter_length};
+
+/// Maximum number of conflicting rows listed in the preview.
+const MAX_LISTED_CONFLICTS: usize = 100;
+
+/// How the rows of a roster would be applied to the student registry.
+#[derive(Debug, Clone, Default)]
+struct RosterPreview {
+    inserts: Vec<RosterEntry>,
+    updates: Vec<RosterEntry>,
+    unchanged: usize,
+    /// Row number (1-based, as shown in a spreadsheet) and the reason it is skipped.
+    conflicts: Vec<(usize, String)>,
+}
+
+#[derive(Debug)]
+pub struct RosterImportModal {
+    pub path_handle: Option<JoinHandle<Option<PathBuf>>>,
+    pub path: Option<PathBuf>,
+    pub roster: Roster,
+    pub read_error: Option<String>,
+    pub has_header: bool,
+    pub student_number_column: Option<usize>,
+    pub student_name_column: Option<usize>,
+    pub room_column: Option<usize>,
+    pub block_column: Option<usize>,
+    pub import_error: Option<String>,
+    /// Number of students added or updated, set once the import is complete.
+    pub imported: Option<usize>,
+}
+
+impl Default for RosterImportModal {
+    fn default() -> Self {
+        Self {
+            path_handle: None,
+            path: None,
+            roster: Roster::default(),
+            read_error: None,
+            has_header: true,
+            student_number_column: None,
+            student_name_column: None,
+            room_column: None,
+            block_column: None,
+            import_error: None,
+            imported: None,
+        }
+    }
+}
+
+impl RosterImportModal {
+    pub fn render(&mut self, ctx: &eframe::egui::Context, students: &mut StudentStorage) -> bool {
+        let mut close_modal = false;
+
+        if let Some(handle) = &self.path_handle {
+            if handle.is_finished() {
+                let handle = self.path_handle.take().unwrap();
+
+                if let Some(path) = handle.join().expect("roster open thread panicked") {
+                    self.load(path);
+                }
+            }
+        }
+
+        let preview = self.preview(students);
+
+        egui::Window::new("Import Student Roster")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                // File
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(self.path_handle.is_none(), egui::Button::new("Choose File")).clicked() {
+                        self.path_handle = Some(std::thread::spawn(|| {
+                            log::info!("opening roster open dialogue");
+
+                            let path = rfd::FileDialog::new()
+                                .add_filter("Roster", &["csv", "xlsx", "xls", "ods"])
+                                .pick_file();
+
+                            log::info!("finished roster open dialogue");
+
+                            path
+                        }));
+                    }
+
+                    match &self.path {
+                        Some(path) => ui.label(path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default()),
+                        None => ui.weak("No file chosen"),
+                    };
+                });
+
+                if let Some(error) = &self.read_error {
+                    ui.colored_label(egui::Rgba::from_rgb(0.25, 0.0, 0.0), error);
+                }
+
+                if self.roster.rows.is_empty() {
+                    ui.add_space(4.0);
+
+                    if ui.button("Close").clicked() {
+                        close_modal = true;
+                    }
+
+                    return;
+                }
+
+                ui.separator();
+
+                // Column Mapping
+                ui.checkbox(&mut self.has_header, "First row is a header");
+
+                ui.add_space(4.0);
+
+                let headers = self.column_names();
+
+                egui::Grid::new("roster_columns").num_columns(2).show(ui, |ui| {
+                    render_column_combo(ui, "Student Number*", &headers, &mut self.student_number_column);
+                    render_column_combo(ui, "Student Name*", &headers, &mut self.student_name_column);
+                    render_column_combo(ui, "Room", &headers, &mut self.room_column);
+                    render_column_combo(ui, "Block", &headers, &mut self.block_column);
+                });
+
+                ui.separator();
+
+                // Preview
+                match &preview {
+                    Some(preview) => {
+                        ui.label(format!("{} new students", preview.inserts.len()));
+                        ui.label(format!("{} updated students", preview.updates.len()));
+                        ui.label(format!("{} unchanged students", preview.unchanged));
+
+                        if !preview.conflicts.is_empty() {
+                            ui.colored_label(egui::Rgba::from_rgb(0.25, 0.0, 0.0), format!("{} rows will be skipped", preview.conflicts.len()));
+
+                            egui::ScrollArea::vertical().max_height(128.0).show(ui, |ui| {
+                                for (row, reason) in preview.conflicts.iter().take(MAX_LISTED_CONFLICTS) {
+                                    ui.label(format!("Row {row}: {reason}"));
+                                }
+                            });
+                        }
+                    },
+                    None => {
+                        ui.label("Choose the student number and name columns.");
+                    },
+                }
+
+                if let Some(error) = &self.import_error {
+                    ui.colored_label(egui::Rgba::from_rgb(0.25, 0.0, 0.0), error);
+                }
+
+                ui.add_space(4.0);
+
+                // Buttons
+
+                ui.horizontal(|ui| {
+                    let changes = preview.as_ref().map(|p| p.inserts.len() + p.updates.len()).unwrap_or(0);
+
+                    if ui.add_enabled(changes > 0, egui::Button::new("Import")).clicked() {
+                        let preview = preview.as_ref().unwrap();
+                        let entries = preview.inserts.iter().chain(&preview.updates).cloned().collect::<Vec<_>>();
+
+                        match students.import(&entries) {
+                            Ok(_) => {
+                                self.imported = Some(entries.len());
+                                close_modal = true;
+                            },
+                            Err(err) => {
+                                log::error!("failed to import roster: {err}");
+                                self.import_error = Some(err.to_string());
+                            },
+                        }
+                    }
+                    if ui.button("Close").clicked() {
+                        close_modal = true;
+                    }
+                });
+            });
+
+        close_modal
+    }
+
+    fn load(&mut self, path: PathBuf) {
+        self.read_error = None;
+        self.import_error = None;
+
+        match Roster::read(&path) {
+            Ok(roster) => {
+                self.roster = roster;
+                self.guess_columns();
+            },
+            Err(err) => {
+                log::error!("failed to read roster: {err}");
+                self.roster = Roster::default();
+                self.read_error = Some(err.to_string());
+            },
+        }
+
+        self.path = Some(path);
+    }
+
+    /// Labels for each column, using the header row if there is one.
+    fn column_names(&self) -> Vec<String> {
+        let header = self.roster.rows.first();
+
+        (0..self.roster.column_count()).map(|i| {
+            let letter = column_letter(i);
+
+            match header.and_then(|h| h.get(i)).filter(|_| self.has_header) {
+                Some(name) if !name.is_empty() => format!("{letter} – {name}"),
+                _ => format!("Column {letter}"),
+            }
+        }).collect()
+    }
+
+    /// Preselect columns whose header looks like one of the fields.
+    fn guess_columns(&mut self) {
+        self.student_number_column = None;
+        self.student_name_column = None;
+        self.room_column = None;
+        self.block_column = None;
+
+        let Some(header) = self.roster.rows.first() else {
+            return;
+        };
+
+        for (i, name) in header.iter().enumerate() {
+            let name = name.to_lowercase();
+
+            if name.contains("number") || name.contains("no.") || name == "id" {
+                self.student_number_column.get_or_insert(i);
+            } else if name.contains("name") {
+                self.student_name_column.get_or_insert(i);
+            } else if name.contains("room") {
+                self.room_column.get_or_insert(i);
+            } else if name.contains("block") || name.contains("residence") {
+                self.block_column.get_or_insert(i);
+            }
+        }
+    }
+
+    fn preview(&self, students: &StudentStorage) -> Option<RosterPreview> {
+        let (Some(number_column), Some(name_column)) = (self.student_number_column, self.student_name_column) else {
+            return None;
+        };
+
+        let mut preview = RosterPreview::default();
+        let mut seen = HashSet::new();
+
+        let skip = if self.has_header { 1 } else { 0 };
+
+        for (i, row) in self.roster.rows.iter().enumerate().skip(skip) {
+            let cell = |column: Option<usize>| column
+                .and_then(|c| row.get(c))
+                .map(|c| c.trim())
+                .unwrap_or("");
+
+            let optional_cell = |column: Option<usize>| Some(cell(column))
+                .filter(|c| !c.is_empty())
+                .map(|c| c.to_string());
+
+            // ignore blank lines
+            if row.iter().all(|c| c.trim().is_empty()) {
+                continue;
+            }
+
+            let student_number = cell(Some(number_column)).to_uppercase();
+            let student_name = cell(Some(name_column));
+
+            let mut error = None;
+
+            filter_student_number(&student_number, &mut error);
+            filter_required(&student_number, &mut error);
+
+            if let Some(error) = error {
+                preview.conflicts.push((i + 1, format!("Student number: {error}")));
+                continue;
+            }
+
+            if filter_required(student_name, &mut error) || filter_length(student_name, NAME_MAX_LENGTH, &mut error) {
+                preview.conflicts.push((i + 1, format!("Student name: {}", error.unwrap_or_default())));
+                continue;
+            }
+
+            let entry = RosterEntry {
+                student_number,
+                student_name: student_name.to_string(),
+                room: optional_cell(self.room_column),
+                block: optional_cell(self.block_column),
+            };
+
+            if [&entry.room, &entry.block].into_iter().flatten().any(|c| filter_length(c, ROOM_MAX_LENGTH, &mut error)) {
+                preview.conflicts.push((i + 1, format!("Room/Block: {}", error.unwrap_or_default())));
+                continue;
+            }
+
+            if !seen.insert(entry.student_number.clone()) {
+                preview.conflicts.push((i + 1, format!("{} appears more than once.", entry.student_number)));
+                continue;
+            }
+
+            match students.get(&entry.student_number) {
+                None => preview.inserts.push(entry),
+                Some(existing) => {
+                    if existing.student_name == entry.student_name && existing.room == entry.room && existing.block == entry.block {
+                        preview.unchanged += 1;
+                    } else {
+                        preview.updates.push(entry);
+                    }
+                },
+            }
+        }
+
+        Some(preview)
+    }
+}
+
+fn render_column_combo(ui: &mut egui::Ui, label: &str, headers: &[String], column: &mut Option<usize>) {
+    ui.label(label);
+
+    egui::ComboBox::from_id_source(label)
+        .width(192.0)
+        .selected_text(column.and_then(|c| headers.get(c)).map(|h| h.as_str()).unwrap_or("None"))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(column, None, "None");
+
+            for (i, header) in headers.iter().enumerate() {
+                ui.selectable_value(column, Some(i), header);
+            }
+        });
+
+    ui.end_row();
+}
+
+/// Spreadsheet style column name, e.g. A, B, ..., Z, AA.
+fn column_letter(mut index: usize) -> String {
+    let mut letters = vec![];
+
+    loop {
+        letters.push((b'A' + (index % 26) as u8) as char);
+
+        if index < 26 {
+            break;
+        }
+
+        index = index / 26 - 1;
+    }
+
+    letters.iter().rev().collect()
+}
@@ -1,11 +1,35 @@
+use std::{path::PathBuf, thread::JoinHandle};
+
 use crate::app::{AppConfig, NAME_MAX_LENGTH};
 
 use super::{render_modal_text_entry, filter_length};
 
+/// Choices of time between automatic backups, in minutes.
+const BACKUP_INTERVALS: &[(u64, &str)] = &[
+    (0, "Never"),
+    (15, "Every 15 minutes"),
+    (30, "Every 30 minutes"),
+    (60, "Every hour"),
+    (120, "Every 2 hours"),
+    (240, "Every 4 hours"),
+    (720, "Every 12 hours"),
+    (1440, "Every day"),
+];
+
 #[derive(Debug)]
 pub struct SettingsModal {
     pub facility_name: String,
     facility_name_error: Option<String>,
+    pub backup_directory: Option<PathBuf>,
+    backup_directory_handle: Option<JoinHandle<Option<PathBuf>>>,
+    pub backup_interval_minutes: u64,
+    pub backup_on_exit: bool,
+    pub backup_keep_hourly: usize,
+    pub backup_keep_daily: usize,
+    pub backup_keep_monthly: usize,
+    pub portable: bool,
+    was_portable: bool,
+    database_directory: PathBuf,
     pub cancelled: bool,
 }
 
@@ -14,34 +38,128 @@ impl SettingsModal {
         SettingsModal {
             facility_name: config.facility_name.clone(),
             facility_name_error: None,
+            backup_directory: config.backup_directory.clone(),
+            backup_directory_handle: None,
+            backup_interval_minutes: config.backup_interval_minutes,
+            backup_on_exit: config.backup_on_exit,
+            backup_keep_hourly: config.backup_keep_hourly,
+            backup_keep_daily: config.backup_keep_daily,
+            backup_keep_monthly: config.backup_keep_monthly,
+            portable: config.portable,
+            was_portable: config.portable,
+            database_directory: config.database_directory(),
             cancelled: false,
         }
     }
 
     pub fn render(&mut self, ctx: &eframe::egui::Context) -> bool {
         let mut close_modal = false;
-        
+
+        if let Some(handle) = &self.backup_directory_handle {
+            if handle.is_finished() {
+                let handle = self.backup_directory_handle.take().unwrap();
+
+                if let Some(directory) = handle.join().expect("backup directory thread panicked") {
+                    self.backup_directory = Some(directory);
+                }
+            }
+        }
+
         egui::Window::new("Settings")
             .collapsible(false)
             .resizable(false)
             .show(ctx, |ui| {
                 render_modal_text_entry(ui, "Facility Name", &self.facility_name_error, &mut self.facility_name, NAME_MAX_LENGTH);
-                
+
+                ui.checkbox(&mut self.portable, "Portable mode")
+                    .on_hover_text("Keep databases next to the program, e.g. to run it from a USB drive.");
+
+                if self.portable == self.was_portable {
+                    ui.weak(format!("Databases are kept in {}", self.database_directory.display()));
+                } else {
+                    ui.weak("The open database will be copied to its new location.");
+                }
+
+                ui.separator();
+
+                // Automatic Backups
+                ui.label(egui::RichText::new("Automatic Backups").strong());
+
+                ui.add_space(4.0);
+
+                ui.label("Backup Folder");
+
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(self.backup_directory_handle.is_none(), egui::Button::new("Choose Folder")).clicked() {
+                        self.backup_directory_handle = Some(std::thread::spawn(|| {
+                            rfd::FileDialog::new().pick_folder()
+                        }));
+                    }
+
+                    if self.backup_directory.is_some() && ui.button("Clear").clicked() {
+                        self.backup_directory = None;
+                    }
+                });
+
+                match &self.backup_directory {
+                    Some(directory) => ui.label(directory.display().to_string()),
+                    None => ui.weak("Automatic backups are off"),
+                };
+
+                ui.add_space(4.0);
+
+                ui.add_enabled_ui(self.backup_directory.is_some(), |ui| {
+                    egui::Grid::new("backup_settings").num_columns(2).show(ui, |ui| {
+                        ui.label("Back Up");
+
+                        let selected = BACKUP_INTERVALS.iter()
+                            .find(|(minutes, _)| *minutes == self.backup_interval_minutes)
+                            .map(|(_, label)| label.to_string())
+                            .unwrap_or(format!("Every {} minutes", self.backup_interval_minutes));
+
+                        egui::ComboBox::from_id_source("backup_interval")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                for (minutes, label) in BACKUP_INTERVALS {
+                                    ui.selectable_value(&mut self.backup_interval_minutes, *minutes, *label);
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label("");
+                        ui.checkbox(&mut self.backup_on_exit, "Back up on exit");
+                        ui.end_row();
+
+                        ui.label("Keep Hourly");
+                        ui.add(egui::DragValue::new(&mut self.backup_keep_hourly).clamp_range(0..=999));
+                        ui.end_row();
+
+                        ui.label("Keep Daily");
+                        ui.add(egui::DragValue::new(&mut self.backup_keep_daily).clamp_range(0..=999));
+                        ui.end_row();
+
+                        ui.label("Keep Monthly");
+                        ui.add(egui::DragValue::new(&mut self.backup_keep_monthly).clamp_range(0..=999));
+                        ui.end_row();
+                    });
+                });
+
+                ui.add_space(4.0);
+
                 // Buttons
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
                     if ui.button("Cancel").clicked() {
                         close_modal = true;
                         self.cancelled = true;
                     }
-                    if ui.button("OK").clicked() {
-                        if !filter_length(&self.facility_name, NAME_MAX_LENGTH, &mut self.facility_name_error) {
+                    if ui.button("OK").clicked()
+                        && !filter_length(&self.facility_name, NAME_MAX_LENGTH, &mut self.facility_name_error) {
                             // only close if no error
                             close_modal = true;
                         }
-                    }
                 });
             });
-        
-        return close_modal;
+
+        close_modal
     }
 }
@@ -1,24 +1,21 @@
-use crate::{records::ReceptionistSignableStorage, app::NAME_MAX_LENGTH};
-
-use super::{render_modal_text_entry, filter_required, filter_length};
+use crate::{records::Shift, service::ServiceError, app::StorageErrors};
 
 #[derive(Debug, Clone)]
 pub struct SignInModal<I: Copy> {
-    pub receptionist: String,
-    pub receptionist_error: Option<String>,
     pub record_id: I,
+    pub error: Option<String>,
 }
 
 impl<I: Copy> SignInModal<I> {
     pub fn new(id: I) -> SignInModal<I> {
         SignInModal {
-            receptionist: Default::default(),
-            receptionist_error: Default::default(),
             record_id: id,
+            error: None,
         }
     }
 
-    pub fn render<T>(&mut self, ctx: &eframe::egui::Context, records: &mut impl ReceptionistSignableStorage<T, I>) -> bool {
+    /// Signs in the record with the given service function, e.g. `service::sign_in_key`.
+    pub fn render(&mut self, ctx: &eframe::egui::Context, shift: &Shift, errors: &mut StorageErrors, sign_in: impl FnOnce(I, &str) -> Result<(), ServiceError>) -> bool {
         let mut update_record = false;
         let mut close_modal = false;
 
@@ -28,30 +25,19 @@ impl<I: Copy> SignInModal<I> {
             .show(ctx, |ui| {
                 
                 // Receptionist
-                render_modal_text_entry(ui, "Receptionist", &self.receptionist_error, &mut self.receptionist, NAME_MAX_LENGTH);
+                ui.label(format!("Signing in as {}.", shift.receptionist.name));
                 
+                if let Some(error) = &self.error {
+                    ui.colored_label(egui::Rgba::from_rgb(0.25, 0.0, 0.0), error);
+                }
+
                 ui.add_space(4.0);
 
                 // Buttons
                 
                 ui.horizontal(|ui| {
                     if ui.button("Sign In").clicked() {
-                        let mut error = false;
-                        
-                        // Receptionist
-                        
-                        self.receptionist_error = None;
-
-                        let receptionist = self.receptionist.trim();
-
-                        error |= filter_required(receptionist, &mut self.receptionist_error);
-                        error |= filter_length(receptionist, NAME_MAX_LENGTH, &mut self.receptionist_error);
-
-                        // Entry valid, add record
-                        if !error {
-                            update_record = true;
-                            close_modal = true;
-                        }
+                        update_record = true;
                     }
                     if ui.button("Cancel").clicked() {
                         close_modal = true;
@@ -60,10 +46,13 @@ impl<I: Copy> SignInModal<I> {
             });
         
         if update_record {
-            records.signin(self.record_id, &self.receptionist).expect("failed to update receptionist on record");
-            log::info!("signed in record");
+            match sign_in(self.record_id, &shift.receptionist.name) {
+                Ok(()) => close_modal = true,
+                Err(err) if err.is_failure() => errors.report("failed to update receptionist on record", err),
+                Err(err) => self.error = Some(err.to_string()),
+            }
         }
         
-        return close_modal;
+        close_modal
     }
 }
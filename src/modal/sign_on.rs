@@ -0,0 +1,68 @@
+use crate::records::{ReceptionistStorage, ReceptionistRecord};
+
+/// Choose which receptionist is on duty.
+#[derive(Debug, Clone, Default)]
+pub struct SignOnModal {
+    pub receptionist: Option<ReceptionistRecord>,
+    pub receptionist_error: Option<String>,
+    pub confirmed: bool,
+}
+
+impl SignOnModal {
+    pub fn render(&mut self, ctx: &eframe::egui::Context, receptionists: &ReceptionistStorage) -> bool {
+        let mut close_modal = false;
+
+        egui::Window::new("Sign On")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, (0.0, 0.0))
+            .show(ctx, |ui| {
+                // Receptionist
+
+                ui.label("Receptionist");
+                
+                egui::ComboBox::from_id_source("sign_on_receptionist")
+                    .width(192.0)
+                    .selected_text(self.receptionist.as_ref().map(|r| r.name.as_str()).unwrap_or_default())
+                    .show_ui(ui, |ui| {
+                        for receptionist in receptionists.get_active() {
+                            let selected = self.receptionist.as_ref().is_some_and(|r| r.id == receptionist.id);
+                            
+                            if ui.selectable_label(selected, &receptionist.name).clicked() {
+                                self.receptionist = Some(receptionist.clone());
+                            }
+                        }
+                    });
+
+                if receptionists.get_active().next().is_none() {
+                    ui.weak("Add receptionists under Edit > Edit Receptionists.");
+                }
+
+                if let Some(error) = &self.receptionist_error {
+                    ui.colored_label(egui::Rgba::from_rgb(0.25, 0.0, 0.0), error);
+                }
+
+                ui.add_space(4.0);
+
+                // Buttons
+                
+                ui.horizontal(|ui| {
+                    if ui.button("Sign On").clicked() {
+                        self.receptionist_error = None;
+
+                        if self.receptionist.is_some() {
+                            self.confirmed = true;
+                            close_modal = true;
+                        } else {
+                            self.receptionist_error = Some("Required".into());
+                        }
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close_modal = true;
+                    }
+                });
+            });
+        
+        close_modal
+    }
+}
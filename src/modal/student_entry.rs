@@ -0,0 +1,194 @@
+use egui_extras::{TableBuilder, Column};
+
+use crate::{records::{StudentStorage, Storage, InsertableStorage, DeletableStorage, NewStudentRecord}, app::{StorageErrors, NAME_MAX_LENGTH, STUDENT_NUMBER_LENGTH, ROOM_MAX_LENGTH}};
+
+use super::{StudentHistoryModal, render_modal_text_entry, filter_student_number, filter_required, filter_length};
+
+/// Maximum number of students listed at once, narrow down with the search.
+const MAX_LISTED_STUDENTS: usize = 100;
+
+#[derive(Debug, Clone, Default)]
+pub struct StudentEntryModal {
+    pub search: String,
+    pub student_number: String,
+    pub student_number_error: Option<String>,
+    pub student_name: String,
+    pub student_name_error: Option<String>,
+    pub room: String,
+    pub room_error: Option<String>,
+    pub block: String,
+    pub block_error: Option<String>,
+    pub delete_error: Option<String>,
+    pub history_modal: Option<StudentHistoryModal>,
+}
+
+impl StudentEntryModal {
+    pub fn render(&mut self, ctx: &eframe::egui::Context, students: &mut StudentStorage, errors: &mut StorageErrors) -> bool {
+        let mut close_modal = false;
+        let mut delete_student = None;
+        let mut select_student = None;
+
+        egui::Window::new("Students")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                // Search
+                ui.add(egui::TextEdit::singleline(&mut self.search).hint_text("Search"));
+
+                ui.add_space(4.0);
+
+                TableBuilder::new(ui)
+                    .max_scroll_height(256.0)
+                    .column(Column::remainder())
+                    .body(|mut body| {
+                        for student in students.search(&self.search).take(MAX_LISTED_STUDENTS) {
+                            body.row(24.0, |mut row| {
+                                row.col(|ui| {
+                                    ui.horizontal(|ui| {
+                                        if ui.add(egui::Button::new("−").small().fill(egui::Rgba::from_rgb(0.25, 0.0, 0.0))).clicked() {
+                                            delete_student = Some(student.student_number.clone());
+                                        }
+                                        
+                                        ui.add_space(8.0);
+                                        
+                                        if ui.selectable_label(false, format!("{} – {}", student.student_number, student.student_name)).clicked() {
+                                            select_student = Some(student.clone());
+                                        }
+
+                                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                            if ui.small_button("History").clicked() {
+                                                self.history_modal = Some(StudentHistoryModal::new(&student.student_number));
+                                            }
+                                        });
+                                    });
+                                });
+                            })
+                        }
+                    });
+
+                if let Some(error) = &self.delete_error {
+                    ui.colored_label(egui::Rgba::from_rgb(0.25, 0.0, 0.0), error);
+                }
+
+                ui.separator();
+
+                // Student Number
+                render_modal_text_entry(ui, "Student Number", &self.student_number_error, &mut self.student_number, STUDENT_NUMBER_LENGTH);
+
+                // Student Name
+                render_modal_text_entry(ui, "Student Name", &self.student_name_error, &mut self.student_name, NAME_MAX_LENGTH);
+
+                // Room
+                render_modal_text_entry(ui, "Room", &self.room_error, &mut self.room, ROOM_MAX_LENGTH);
+
+                // Block
+                render_modal_text_entry(ui, "Block", &self.block_error, &mut self.block, ROOM_MAX_LENGTH);
+
+                ui.add_space(4.0);
+
+                let existing = students.get(self.student_number.trim()).is_some();
+
+                // Buttons
+                
+                ui.horizontal(|ui| {
+                    if ui.button(if existing { "Update Student" } else { "Add Student" }).clicked() {
+                        let mut error = false;
+                        
+                        // Student Number
+                        self.student_number_error = None;
+
+                        let student_number = self.student_number.trim();
+
+                        // filter student number first so it overwrites with "required" text if blank
+                        error |= filter_student_number(student_number, &mut self.student_number_error);
+                        error |= filter_required(student_number, &mut self.student_number_error);
+
+                        // Student Name
+                        self.student_name_error = None;
+
+                        let student_name = self.student_name.trim();
+
+                        error |= filter_required(student_name, &mut self.student_name_error);
+                        error |= filter_length(student_name, NAME_MAX_LENGTH, &mut self.student_name_error);
+
+                        // Room
+                        self.room_error = None;
+
+                        let room = self.room.trim();
+
+                        error |= filter_length(room, ROOM_MAX_LENGTH, &mut self.room_error);
+
+                        // Block
+                        self.block_error = None;
+
+                        let block = self.block.trim();
+
+                        error |= filter_length(block, ROOM_MAX_LENGTH, &mut self.block_error);
+
+                        // Entry valid, add or update student
+                        if !error {
+                            let record = NewStudentRecord {
+                                student_number,
+                                student_name,
+                                room: if !room.is_empty() { Some(room) } else { None },
+                                block: if !block.is_empty() { Some(block) } else { None },
+                            };
+
+                            let saved = if existing {
+                                errors.check("failed to update student in database", students.update(record)).is_some()
+                            } else {
+                                errors.check("failed to add student to database", students.insert(record)).is_some()
+                            };
+
+                            // keep the entry on failure, so it can be saved again
+                            if saved {
+                                log::info!("{} student", if existing { "updated" } else { "added" });
+
+                                self.student_number.clear();
+                                self.student_name.clear();
+                                self.room.clear();
+                                self.block.clear();
+                            }
+                        }
+                    }
+                    if ui.button("Close").clicked() {
+                        close_modal = true;
+                    }
+                });
+            });
+
+        if let Some(modal) = &mut self.history_modal {
+            let close_modal = modal.render(ctx, students, errors);
+
+            if close_modal {
+                self.history_modal = None;
+            }
+        }
+
+        if let Some(student) = select_student {
+            self.student_number = student.student_number;
+            self.student_name = student.student_name;
+            self.room = student.room.unwrap_or_default();
+            self.block = student.block.unwrap_or_default();
+            self.student_number_error = None;
+            self.student_name_error = None;
+            self.room_error = None;
+            self.block_error = None;
+        }
+
+        if let Some(student_number) = delete_student {
+            self.delete_error = None;
+
+            match students.delete(&student_number) {
+                Ok(_) => log::debug!("deleted student"),
+                Err(err) => {
+                    // students referenced by records cannot be deleted
+                    log::warn!("failed to delete student: {err}");
+                    self.delete_error = Some(format!("Cannot remove {student_number}, they have existing records."));
+                },
+            }
+        }
+
+        close_modal
+    }
+}
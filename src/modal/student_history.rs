@@ -0,0 +1,161 @@
+use egui_extras::{TableBuilder, Column};
+use strum::IntoEnumIterator;
+
+use crate::{records::{StudentStorage, StudentHistory, HistoryRecord, RecordType}, app::{StorageErrors, DATE_TIME_FORMAT, ROW_HEIGHT, COL_LARGE_INITIAL_WIDTH, COL_MIN_WIDTH, COL_MAX_WIDTH}, panel::OVERDUE_COLOUR};
+
+#[derive(Debug, Clone)]
+pub struct StudentHistoryModal {
+    student_number: String,
+    history: Option<StudentHistory>,
+    refresh: bool,
+}
+
+impl StudentHistoryModal {
+    pub fn new(student_number: &str) -> StudentHistoryModal {
+        StudentHistoryModal {
+            student_number: student_number.to_string(),
+            history: None,
+            refresh: true,
+        }
+    }
+
+    pub fn render(&mut self, ctx: &eframe::egui::Context, students: &StudentStorage, errors: &mut StorageErrors) -> bool {
+        let mut close_modal = false;
+
+        if self.refresh {
+            self.refresh = false;
+            self.history = errors.check(&format!("failed to fetch history of student {}", self.student_number), students.history(&self.student_number));
+        }
+
+        egui::Window::new("Student History")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if let Some(history) = &self.history {
+                    let title = match history.student_name.len() {
+                        0 => history.student_number.clone(),
+                        _ => format!("{} – {}", history.student_number, history.student_name),
+                    };
+
+                    ui.label(egui::RichText::new(title).strong());
+
+                    ui.horizontal(|ui| {
+                        for record_type in RecordType::iter() {
+                            ui.label(format!("{record_type}s: {}", history.count(record_type)));
+                        }
+                    });
+
+                    if !history.corrupt.is_empty() {
+                        ui.colored_label(OVERDUE_COLOUR, format!("{} records could not be read.", history.corrupt.len()));
+                    }
+
+                    ui.separator();
+
+                    let outstanding = history.outstanding().collect::<Vec<_>>();
+
+                    ui.label(egui::RichText::new("Outstanding").strong());
+
+                    if outstanding.is_empty() {
+                        ui.weak("Nothing is out.");
+                    } else {
+                        ui.push_id("student_outstanding", |ui| {
+                            render_history_table(ui, outstanding.into_iter(), 128.0);
+                        });
+                    }
+
+                    ui.separator();
+
+                    ui.label(egui::RichText::new("Timeline").strong());
+
+                    if history.records.is_empty() {
+                        ui.weak("No records.");
+                    } else {
+                        ui.push_id("student_timeline", |ui| {
+                            render_history_table(ui, history.records.iter(), 256.0);
+                        });
+                    }
+                }
+
+                ui.add_space(4.0);
+
+                // Buttons
+                ui.horizontal(|ui| {
+                    if ui.button("Refresh").clicked() {
+                        self.refresh = true;
+                    }
+
+                    if ui.button("Close").clicked() {
+                        close_modal = true;
+                    }
+                });
+            });
+
+        close_modal
+    }
+}
+
+/// What became of a record, e.g. whether it was returned.
+fn status(record: &HistoryRecord) -> &'static str {
+    match (record.record_type, record.voided, record.time_in) {
+        (_, true, _) => "Void",
+        (RecordType::Item, _, _) if !record.returnable => "Taken",
+        (RecordType::Parcel, _, Some(_)) => "Collected",
+        (RecordType::Parcel, _, None) => "Waiting",
+        (_, _, Some(_)) => "Returned",
+        (_, _, None) if record.is_overdue() => "Overdue",
+        (_, _, None) => "Out",
+    }
+}
+
+fn format_time(time: Option<chrono::DateTime<chrono::Utc>>) -> String {
+    time.map(|time| chrono::DateTime::<chrono::Local>::from(time).format(DATE_TIME_FORMAT).to_string())
+        .unwrap_or_default()
+}
+
+fn render_history_table<'a>(ui: &mut egui::Ui, records: impl Iterator<Item = &'a HistoryRecord>, max_height: f32) {
+    TableBuilder::new(ui)
+        .striped(true)
+        .max_scroll_height(max_height)
+        .column(Column::auto().at_least(COL_MIN_WIDTH))
+        .column(Column::initial(COL_LARGE_INITIAL_WIDTH).at_least(COL_MIN_WIDTH).clip(true).resizable(true))
+        .column(Column::auto().at_most(COL_MAX_WIDTH))
+        .column(Column::auto().at_most(COL_MAX_WIDTH))
+        .column(Column::auto().at_most(COL_MAX_WIDTH))
+        .column(Column::auto().at_least(COL_MIN_WIDTH))
+        .header(ROW_HEIGHT, |mut header| {
+            for title in ["Type", "Record", "Out / Arrived", "Due", "In / Collected", "Status"] {
+                header.col(|ui| {
+                    ui.label(egui::RichText::new(title).strong());
+                });
+            }
+        })
+        .body(|mut body| {
+            for record in records {
+                let name = match record.quantity {
+                    1 => record.name.clone(),
+                    quantity => format!("{quantity} × {}", record.name),
+                };
+
+                body.row(ROW_HEIGHT, |mut row| {
+                    let cells = [
+                        record.record_type.to_string(),
+                        name,
+                        format_time(Some(record.time_out)),
+                        format_time(record.time_due),
+                        format_time(record.time_in),
+                        status(record).to_string(),
+                    ];
+
+                    for cell in cells {
+                        row.col(|ui| {
+                            if record.is_overdue() {
+                                ui.visuals_mut().override_text_color = Some(OVERDUE_COLOUR);
+                            }
+
+                            ui.label(cell);
+                        });
+                    }
+                });
+            }
+        });
+}
@@ -1,229 +1,456 @@
-use chrono::Datelike;
+use std::{collections::BTreeMap, path::PathBuf, thread::JoinHandle};
+
 use egui_extras::{TableBuilder, Column};
+use strum::IntoEnumIterator;
+
+use crate::{records::{KeyStorage, Summary, ParcelStorage, GameStorage, ItemStorage, CategoryStorage, CategoryRecordStorage, Storage, CountWithin, ReceptionistSummary, SignOutTimes, Period, PeriodKind, Activity, RecordType}, app::{StorageErrors, ROW_HEIGHT, COL_LARGE_INITIAL_WIDTH, COL_MIN_WIDTH, COL_MAX_WIDTH}, chart::{self, ChartStyle}, report::{Totals, Report, compare}};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum SummaryTab {
+    #[default]
+    Totals,
+    Charts,
+    Heatmap,
+}
+
+/// The records which are summarised.
+#[derive(Clone, Copy)]
+pub struct SummaryRecords<'a> {
+    pub keys: &'a KeyStorage,
+    pub parcels: &'a ParcelStorage,
+    pub games: &'a GameStorage,
+    pub items: &'a ItemStorage,
+    pub categories: &'a CategoryStorage,
+    pub category_records: &'a CategoryRecordStorage,
+}
 
-use crate::{records::{KeyStorage, Summary, ParcelStorage, GameStorage, ItemStorage, CountWithin}, app::{ROW_HEIGHT, COL_LARGE_INITIAL_WIDTH, COL_MIN_WIDTH, COL_MAX_WIDTH}};
+/// Count records signed out over a period, reporting any failures.
+fn fetch_totals(period: &Period, records: &SummaryRecords,  errors: &mut StorageErrors) -> Totals {
+    let SummaryRecords { keys, parcels, games, items, categories, category_records } = *records;
+    let (start, end) = (period.start(), period.end());
+
+    let mut activity = vec![];
+
+    for record_type in RecordType::iter() {
+        let times = match record_type {
+            RecordType::Key => keys.sign_out_times(start, end),
+            RecordType::Parcel => parcels.sign_out_times(start, end),
+            RecordType::Game => games.sign_out_times(start, end),
+            RecordType::Item => items.sign_out_times(start, end),
+            // summarised per category, below
+            RecordType::Category => continue,
+        };
+
+        let times = errors.check(&format!("failed to fetch {} activity from database", record_type.to_string().to_lowercase()), times).unwrap_or_default();
+
+        activity.push((record_type, Activity::new(period, &times)));
+    }
 
-#[derive(Debug, Clone)]
+    let mut category_summaries = vec![];
+    let mut category_activity = vec![];
+
+    for category in categories.get_all() {
+        let summary = errors.check(&format!("failed to fetch {} summary info from database", category.name), category_records.summary(category.id, start, end)).unwrap_or_default();
+        let times = errors.check(&format!("failed to fetch {} activity from database", category.name), category_records.sign_out_times(category.id, start, end)).unwrap_or_default();
+
+        category_summaries.push((category.name.clone(), summary));
+        category_activity.push((category.name.clone(), Activity::new(period, &times)));
+    }
+
+    Totals {
+        key_summary: errors.check("failed to fetch key summary info from database", keys.summary(start, end)).unwrap_or_default(),
+        parcel_count: errors.check("failed to fetch parcel summary info from database", parcels.count_within(start, end)).unwrap_or_default(),
+        game_summary: errors.check("failed to fetch game summary info from database", games.summary(start, end)).unwrap_or_default(),
+        item_summary: errors.check("failed to fetch item summary info from database", items.summary(start, end)).unwrap_or_default(),
+        category_summaries,
+        activity,
+        category_activity,
+    }
+}
+
+#[derive(Debug)]
 pub struct SummaryModal {
-    month_start: chrono::NaiveDate,
-    key_summary: Vec<(String, i64)>,
-    parcel_count: i64,
-    game_summary: Vec<(String, i64)>,
-    item_summary: Vec<(String, i64)>,
+    period: Period,
+    tab: SummaryTab,
+    chart_style: ChartStyle,
+    /// Show the previous period alongside.
+    compare: bool,
+    /// Type of record shown on the heatmap, or every type if `None`.
+    heatmap_type: Option<RecordType>,
+    totals: Totals,
+    previous: Totals,
+    /// Keys and games signed out and signed in by each receptionist.
+    receptionist_summary: Vec<(String, i64, i64)>,
     refresh: bool,
+    report_path_handle: Option<JoinHandle<Option<PathBuf>>>,
+    /// Where the last report was saved.
+    report_path: Option<PathBuf>,
 }
 
 impl Default for SummaryModal {
     fn default() -> Self {
-        let now = chrono::Local::now().date_naive();
-        let month_start = now - chrono::Days::new(now.day0() as u64) - chrono::Months::new(1);
-
         Self {
-            month_start,
-            key_summary: vec![],
-            parcel_count: 0,
-            game_summary: vec![],
-            item_summary: vec![],
+            period: Period::default(),
+            tab: SummaryTab::default(),
+            chart_style: ChartStyle::default(),
+            compare: false,
+            heatmap_type: None,
+            totals: Totals::default(),
+            previous: Totals::default(),
+            receptionist_summary: vec![],
             refresh: true,
+            report_path_handle: None,
+            report_path: None,
         }
     }
 }
 
 impl SummaryModal {
-    fn get_summaries(&mut self, keys: &KeyStorage, parcels: &ParcelStorage, games: &GameStorage, items: &ItemStorage) {
+    fn get_summaries(&mut self, records: &SummaryRecords, errors: &mut StorageErrors) {
         if !self.refresh {
             return;
         }
 
         self.refresh = false;
 
-        let start = self.month_start.and_time(chrono::NaiveTime::MIN)
-            // convert to 00:00 in local time zone
-            .and_local_timezone(chrono::Local).single()
-            // convert to UTC, since db in UTC
-            .map(|date| date.with_timezone(&chrono::Utc))
-            // if datetime does not exist (due to when countries change their timezone), default to UTC
-            .unwrap_or(
-                self.month_start.and_time(chrono::NaiveTime::MIN).and_local_timezone(chrono::Utc).unwrap()
-            );
-        let end = self.month_start + chrono::Months::new(1);
-        let end = end.and_time(chrono::NaiveTime::MIN)
-            // convert to 23:59:99.99 in local time zone
-            .and_local_timezone(chrono::Local).single()
-            // convert to UTC, since db in UTC
-            .map(|date| date.with_timezone(&chrono::Utc))
-            // if datetime does not exist (due to when countries change their timezone), default to UTC
-            .unwrap_or(
-                end.and_time(chrono::NaiveTime::MIN).and_local_timezone(chrono::Utc).unwrap()
-            );
-                
-        self.key_summary = keys.summary(start, end).expect("failed to fetch key summary info from database");
-        self.parcel_count = parcels.count_within(start, end).expect("failed to fetch parcel summary info from database");
-        self.game_summary = games.summary(start, end).expect("failed to fetch game summary info from database");
-        self.item_summary = items.summary(start, end).expect("failed to fetch item summary info from database");
+        self.totals = fetch_totals(&self.period, records, errors);
+        self.previous = fetch_totals(&self.period.previous(), records, errors);
+
+        let SummaryRecords { keys, games, .. } = *records;
+        let (start, end) = (self.period.start(), self.period.end());
+
+        let mut receptionist_summary = BTreeMap::new();
+
+        let key_receptionists = errors.check("failed to fetch key receptionist summary info from database", keys.receptionist_summary(start, end)).unwrap_or_default();
+        let game_receptionists = errors.check("failed to fetch game receptionist summary info from database", games.receptionist_summary(start, end)).unwrap_or_default();
+
+        for (receptionist, signed_out, signed_in) in key_receptionists.into_iter().chain(game_receptionists) {
+            let counts = receptionist_summary.entry(receptionist).or_insert((0, 0));
+            counts.0 += signed_out;
+            counts.1 += signed_in;
+        }
+
+        self.receptionist_summary = receptionist_summary.into_iter()
+            .map(|(receptionist, (signed_out, signed_in))| (receptionist, signed_out, signed_in))
+            .collect();
     }
-}
 
-impl SummaryModal {
-    pub fn render(&mut self, ctx: &eframe::egui::Context, keys: &KeyStorage, parcels: &ParcelStorage, games: &GameStorage, items: &ItemStorage) -> bool {
-        let mut close_modal = false;
+    /// Fetch the totals again, since the records they count have changed.
+    pub fn records_changed(&mut self) {
+        self.refresh = true;
+    }
 
-        self.get_summaries(keys, parcels, games, items);
+    fn set_period(&mut self, period: Period) {
+        if period != self.period {
+            self.period = period;
+            self.refresh = true;
+        }
+    }
 
-        egui::Window::new("Summary")
-            .collapsible(false)
-            .resizable(false)
-            .show(ctx, |ui| {
-                ui.horizontal(|ui| {
-                    egui_extras::StripBuilder::new(ui)
-                        .size(egui_extras::Size::exact(14.0))
-                        .size(egui_extras::Size::initial(96.0))
-                        .size(egui_extras::Size::exact(14.0))
-                        .horizontal(|mut s| {
-                            s.cell(|ui| {
-                                if ui.button("<").clicked() {
-                                    self.month_start = self.month_start - chrono::Months::new(1);
-                                    self.refresh = true;
-                                }
-                            });
-                            s.cell(|ui| {
-                                ui.vertical_centered(|ui| {
-                                    ui.add_space(2.0);
-                                    ui.label(self.month_start.format("%B %Y").to_string());
-                                });
-                            });
-                            s.cell(|ui| {
-                                let current_month = chrono::Utc::now().date_naive();
-                                let current_month = current_month - chrono::Days::new(current_month.day0() as u64);
-                                if ui.add_enabled(self.month_start < current_month, egui::Button::new(">")).clicked() {
-                                    self.month_start = self.month_start + chrono::Months::new(1);
-                                    self.refresh = true;
-                                }
-                            });
-                        });
+    fn render_period(&mut self, ui: &mut egui::Ui) {
+        let today = chrono::Local::now().date_naive();
+
+        ui.horizontal(|ui| {
+            let mut kind = self.period.kind;
+
+            egui::ComboBox::from_id_source("summary_period")
+                .selected_text(kind.to_string())
+                .show_ui(ui, |ui| {
+                    for option in PeriodKind::iter() {
+                        ui.selectable_value(&mut kind, option, option.to_string());
+                    }
                 });
 
-                TableBuilder::new(ui)
-                    .striped(true)
-                    .max_scroll_height(384.0)
-                    .column(Column::initial(COL_LARGE_INITIAL_WIDTH).at_least(COL_MIN_WIDTH).clip(true).resizable(true))
-                    .column(Column::auto().at_least(COL_MIN_WIDTH).at_most(COL_MAX_WIDTH).resizable(false))
-                    .body(|mut body| {
-                        let mut total = 0;
-                        self.key_summary.iter().for_each(|s| total += s.1);
-                        
-                        body.row(ROW_HEIGHT, |mut row| {
-                            row.col(|ui| {
-                                ui.horizontal(|ui| {
-                                    ui.label(egui::RichText::new("Keys").strong());
-                                });
-                            });
-                            row.col(|ui| {
-                                ui.horizontal(|ui| {
-                                    ui.label(egui::RichText::new(total.to_string()).strong());
-                                });
-                            });
+            if kind != self.period.kind {
+                self.set_period(self.period.with_kind(kind));
+            }
+
+            if ui.button("<").clicked() {
+                self.set_period(self.period.previous());
+            }
+
+            if self.period.kind == PeriodKind::Custom {
+                let (mut from, mut to) = (self.period.from, self.period.to);
+
+                ui.add(egui_extras::DatePickerButton::new(&mut from).id_source("summary_from"));
+                ui.label("to");
+                ui.add(egui_extras::DatePickerButton::new(&mut to).id_source("summary_to"));
+
+                self.set_period(Period::custom(from, to));
+            } else {
+                ui.label(self.period.to_string());
+            }
+
+            if ui.add_enabled(self.period.next().from <= today, egui::Button::new(">")).clicked() {
+                self.set_period(self.period.next());
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.compare, "Compare with previous period");
+
+            if self.compare {
+                ui.weak(self.period.previous().to_string());
+            }
+        });
+    }
+
+    fn render_totals(&self, ui: &mut egui::Ui) {
+        let sections = self.totals.sections().into_iter()
+            .map(|(title, current)| (title, compare(current, self.previous.section(title))))
+            .collect::<Vec<_>>();
+
+        let mut table = TableBuilder::new(ui)
+            .striped(true)
+            .max_scroll_height(384.0)
+            .column(Column::initial(COL_LARGE_INITIAL_WIDTH).at_least(COL_MIN_WIDTH).clip(true).resizable(true))
+            .column(Column::auto().at_least(COL_MIN_WIDTH).at_most(COL_MAX_WIDTH).resizable(false));
+
+        if self.compare {
+            table = table
+                .column(Column::auto().at_least(COL_MIN_WIDTH).at_most(COL_MAX_WIDTH).resizable(false))
+                .column(Column::auto().at_least(COL_MIN_WIDTH).at_most(COL_MAX_WIDTH).resizable(false));
+        }
+
+        let row = |body: &mut egui_extras::TableBody, name: &str, count: i64, previous: i64, strong: bool| {
+            body.row(ROW_HEIGHT, |mut row| {
+                let mut cells = vec![name.to_string(), count.to_string()];
+
+                if self.compare {
+                    cells.push(previous.to_string());
+                    cells.push(format!("{:+}", count - previous));
+                }
+
+                for cell in cells {
+                    row.col(|ui| {
+                        ui.horizontal(|ui| {
+                            if strong {
+                                ui.label(egui::RichText::new(cell).strong());
+                            } else {
+                                ui.label(cell);
+                            }
                         });
+                    });
+                }
+            });
+        };
 
-                        for (key, count) in &self.key_summary {
-                            body.row(ROW_HEIGHT, |mut row| {
-                                row.col(|ui| {
-                                    ui.horizontal(|ui| {
-                                        ui.label(key);
-                                    });
-                                });
-                                row.col(|ui| {
-                                    ui.horizontal(|ui| {
-                                        ui.label(&count.to_string());
-                                    });
-                                });
-                            });
-                        }
-                        
-                        let mut total = 0;
-                        self.game_summary.iter().for_each(|s| total += s.1);
+        let body = |mut body: egui_extras::TableBody| {
+            for (title, rows) in &sections {
+                let count = rows.iter().map(|(_, count, _)| count).sum();
+                let previous = rows.iter().map(|(_, _, previous)| previous).sum();
 
-                        body.row(ROW_HEIGHT, |mut row| {
-                            row.col(|ui| {
-                                ui.horizontal(|ui| {
-                                    ui.label(egui::RichText::new("Games").strong());
-                                });
-                            });
-                            row.col(|ui| {
-                                ui.horizontal(|ui| {
-                                    ui.label(egui::RichText::new(total.to_string()).strong());
-                                });
-                            });
+                row(&mut body, title, count, previous, true);
+
+                for (name, count, previous) in rows {
+                    row(&mut body, name, *count, *previous, false);
+                }
+            }
+
+            row(&mut body, "Parcels", self.totals.parcel_count, self.previous.parcel_count, true);
+        };
+
+        if self.compare {
+            table.header(ROW_HEIGHT, |mut header| {
+                for title in ["", "Count", "Previous", "Change"] {
+                    header.col(|ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new(title).strong());
                         });
-                        
-                        for (game, count) in &self.game_summary {
-                            body.row(ROW_HEIGHT, |mut row| {
-                                row.col(|ui| {
-                                    ui.horizontal(|ui| {
-                                        ui.label(game);
-                                    });
-                                });
-                                row.col(|ui| {
-                                    ui.horizontal(|ui| {
-                                        ui.label(&count.to_string());
-                                    });
-                                });
-                            });
-                        }
+                    });
+                }
+            }).body(body);
+        } else {
+            table.body(body);
+        }
 
-                        let mut total = 0;
-                        self.item_summary.iter().for_each(|s| total += s.1);
+        ui.separator();
 
+        ui.push_id("receptionist_summary", |ui| {
+            TableBuilder::new(ui)
+                .striped(true)
+                .max_scroll_height(192.0)
+                .column(Column::initial(COL_LARGE_INITIAL_WIDTH).at_least(COL_MIN_WIDTH).clip(true).resizable(true))
+                .column(Column::auto().at_least(COL_MIN_WIDTH).at_most(COL_MAX_WIDTH).resizable(false))
+                .column(Column::auto().at_least(COL_MIN_WIDTH).at_most(COL_MAX_WIDTH).resizable(false))
+                .header(ROW_HEIGHT, |mut header| {
+                    header.col(|ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new("Receptionist").strong());
+                        });
+                    });
+                    header.col(|ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new("Signed Out").strong());
+                        });
+                    });
+                    header.col(|ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new("Signed In").strong());
+                        });
+                    });
+                })
+                .body(|mut body| {
+                    for (receptionist, signed_out, signed_in) in &self.receptionist_summary {
                         body.row(ROW_HEIGHT, |mut row| {
                             row.col(|ui| {
                                 ui.horizontal(|ui| {
-                                    ui.label(egui::RichText::new("Items").strong());
+                                    ui.label(receptionist);
                                 });
                             });
                             row.col(|ui| {
                                 ui.horizontal(|ui| {
-                                    ui.label(egui::RichText::new(total.to_string()).strong());
-                                });
-                            });
-                        });
-                        
-                        for (item, count) in &self.item_summary {
-                            body.row(ROW_HEIGHT, |mut row| {
-                                row.col(|ui| {
-                                    ui.horizontal(|ui| {
-                                        ui.label(item);
-                                    });
-                                });
-                                row.col(|ui| {
-                                    ui.horizontal(|ui| {
-                                        ui.label(&count.to_string());
-                                    });
-                                });
-                            });
-                        }
-
-                        body.row(ROW_HEIGHT, |mut row| {
-                            row.col(|ui| {
-                                ui.horizontal(|ui| {
-                                    ui.label(egui::RichText::new("Parcels").strong());
+                                    ui.label(signed_out.to_string());
                                 });
                             });
                             row.col(|ui| {
                                 ui.horizontal(|ui| {
-                                    ui.label(egui::RichText::new(self.parcel_count.to_string()).strong());
+                                    ui.label(signed_in.to_string());
                                 });
                             });
                         });
-                    });
+                    }
+                });
+        });
+    }
+
+    fn render_charts(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.chart_style, ChartStyle::Bar, "Bar");
+            ui.selectable_value(&mut self.chart_style, ChartStyle::Line, "Line");
+        });
+
+        egui::ScrollArea::vertical().max_height(480.0).show(ui, |ui| {
+            for ((name, activity), (_, previous)) in self.totals.activities().into_iter().zip(self.previous.activities()) {
+                let title = if self.compare {
+                    format!("{name}: {} (previous {})", activity.total(), previous.total())
+                } else {
+                    format!("{name}: {}", activity.total())
+                };
+
+                ui.label(egui::RichText::new(title).strong());
+
+                let previous = self.compare.then_some(previous.daily.as_slice());
+                chart::daily_chart(ui, &activity.daily, previous, self.chart_style);
 
                 ui.add_space(4.0);
-                
-                // Buttons
-                if ui.button("Close").clicked() {
-                    close_modal = true;
+            }
+        });
+    }
+
+    fn render_heatmap(&mut self, ui: &mut egui::Ui) {
+        egui::ComboBox::from_id_source("summary_heatmap_type")
+            .selected_text(self.heatmap_type.map_or("All Records".into(), |record_type| format!("{record_type}s")))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.heatmap_type, None, "All Records");
+
+                for record_type in RecordType::iter() {
+                    ui.selectable_value(&mut self.heatmap_type, Some(record_type), format!("{record_type}s"));
                 }
             });
-        
-        return close_modal;
+
+        ui.weak("Records signed out in each hour of the week, in local time.");
+
+        ui.add_space(4.0);
+
+        if let Some(activity) = self.totals.activity(self.heatmap_type) {
+            chart::heatmap(ui, &activity.hourly);
+        }
+    }
+
+    /// Write the report to the file chosen, as CSV if it ends in `.csv`, otherwise as PDF.
+    fn export_report(&mut self, path: PathBuf, facility_name: &str, errors: &mut StorageErrors) {
+        let report = Report {
+            facility_name,
+            period: self.period,
+            totals: &self.totals,
+            previous: self.compare.then_some(&self.previous),
+            receptionists: &self.receptionist_summary,
+            generated: chrono::Local::now(),
+        };
+
+        let is_csv = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+
+        let (path, result) = if is_csv {
+            let result = report.write_csv(&path);
+            (path, result)
+        } else {
+            let path = path.with_extension("pdf");
+            let result = report.write_pdf(&path);
+            (path, result)
+        };
+
+        if errors.check("failed to export summary report", result).is_some() {
+            log::info!("exported summary report to {path:?}");
+            self.report_path = Some(path);
+        }
+    }
+
+    pub fn render(&mut self, ctx: &eframe::egui::Context, facility_name: &str, records: &SummaryRecords, errors: &mut StorageErrors) -> bool {
+        let mut close_modal = false;
+
+        self.get_summaries(records, errors);
+
+        if self.report_path_handle.as_ref().is_some_and(|handle| handle.is_finished()) {
+            let handle = self.report_path_handle.take().unwrap();
+
+            if let Some(path) = handle.join().expect("report save dialogue thread panicked") {
+                self.export_report(path, facility_name, errors);
+            }
+        }
+
+        egui::Window::new("Summary")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                self.render_period(ui);
+
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.tab, SummaryTab::Totals, "Totals");
+                    ui.selectable_value(&mut self.tab, SummaryTab::Charts, "Charts");
+                    ui.selectable_value(&mut self.tab, SummaryTab::Heatmap, "Heatmap");
+                });
+
+                ui.separator();
+
+                match self.tab {
+                    SummaryTab::Totals => self.render_totals(ui),
+                    SummaryTab::Charts => self.render_charts(ui),
+                    SummaryTab::Heatmap => self.render_heatmap(ui),
+                }
+
+                ui.add_space(4.0);
+
+                if let Some(path) = &self.report_path {
+                    ui.weak(format!("Saved report to {}", path.display()));
+                }
+
+                // Buttons
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(self.report_path_handle.is_none(), egui::Button::new("Export Report")).clicked() {
+                        let file_name = format!("summary_{}", self.period.from.format("%Y-%m-%d"));
+
+                        self.report_path_handle = Some(std::thread::spawn(move || {
+                            log::info!("opening report save dialogue");
+
+                            let path = rfd::FileDialog::new()
+                                .add_filter("PDF Document", &["pdf"])
+                                .add_filter("CSV File", &["csv"])
+                                .set_file_name(format!("{file_name}.pdf"))
+                                .save_file();
+
+                            log::info!("finished report save dialogue");
+
+                            path
+                        }));
+                    }
+
+                    if ui.button("Close").clicked() {
+                        close_modal = true;
+                    }
+                });
+            });
+
+        close_modal
     }
 }
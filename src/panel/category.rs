@@ -0,0 +1,257 @@
+use egui_extras::{TableBuilder, Column};
+
+use crate::{service::{self, SignOut}, records::{Page, RecordFilter, Storage, CategoryStorage, CategoryRecordStorage, PaginatedStorage, NotedStorage, Shift}, modal::{StudentHistoryModal, CategorySignModal, SignInModal, AmendModal, AmendField}, app::{StorageErrors, DATE_TIME_FORMAT, ROW_HEIGHT, COL_MAX_WIDTH, COL_LARGE_INITIAL_WIDTH, COL_SMALL_INITIAL_WIDTH, COL_MIN_WIDTH}};
+
+use super::{pagination, render_notes_entry, render_filter_toggle, render_filter_bar, highlight_voided, render_amend_button, render_student_number};
+
+/// Records of whichever custom category is selected, which `category_records` is already showing.
+#[derive(Debug, Default)]
+pub struct CategoryPanel {
+    page: Page,
+    show_filter: bool,
+    filter: RecordFilter,
+
+    sign_modal: Option<CategorySignModal>,
+    sign_in_modal: Option<SignInModal<i64>>,
+    amend_modal: Option<AmendModal<i64>>,
+    history_modal: Option<StudentHistoryModal>,
+
+    current_notes: Option<(i64, String)>,
+}
+
+impl CategoryPanel {
+    pub fn render(&mut self, ui: &mut egui::Ui, categories: &CategoryStorage, category_records: &mut CategoryRecordStorage, sign_out: &mut SignOut, shift: Option<&Shift>, errors: &mut StorageErrors) {
+        let Some(category) = category_records.category_id().and_then(|id| categories.get(id)) else {
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            // Sign Out Modal Button
+            if ui.add_enabled(shift.is_some(), egui::Button::new(format!("Sign Out {}", category.name))).clicked() {
+                self.sign_modal = Some(CategorySignModal::new(category.id));
+            }
+
+            render_filter_toggle(ui, &mut self.show_filter);
+
+            // Pagination
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
+                pagination(ui, &mut self.page, category_records.position());
+                errors.check(&format!("failed to refresh {} records for page {:?}", category.name, self.page), category_records.set_page(self.page));
+            });
+        });
+
+        if self.show_filter {
+            ui.add_space(4.0);
+
+            if render_filter_bar(ui, "category_filter", &mut self.filter, &category.name, category.returnable.then_some("Still Out"), false) {
+                self.page = Page::LastPage;
+            }
+        } else if !self.filter.is_empty() {
+            self.filter = RecordFilter::default();
+            self.page = Page::LastPage;
+        }
+
+        errors.check(&format!("failed to refresh {} records for filter", category.name), category_records.set_filter(&self.filter));
+
+        ui.add_space(8.0);
+
+        // Sign Out Modal
+        if let (Some(modal), Some(shift)) = (&mut self.sign_modal, shift) {
+            let close_modal = modal.render(ui.ctx(), categories, category_records, sign_out, shift, errors);
+
+            if close_modal {
+                self.sign_modal = None;
+            }
+        }
+
+        // Sign In Modal
+        if let (Some(modal), Some(shift)) = (&mut self.sign_in_modal, shift) {
+            let close_modal = modal.render(ui.ctx(), shift, errors, |id, receptionist| service::sign_in_category(categories, category_records, id, receptionist));
+
+            if close_modal {
+                self.sign_in_modal = None;
+            }
+        }
+
+        if let (Some(modal), Some(shift)) = (&mut self.amend_modal, shift) {
+            let close_modal = modal.render(ui.ctx(), category_records, shift, errors);
+
+            if close_modal {
+                self.amend_modal = None;
+            }
+        }
+
+        if let Some(modal) = &mut self.history_modal {
+            let close_modal = modal.render(ui.ctx(), sign_out.students, errors);
+
+            if close_modal {
+                self.history_modal = None;
+            }
+        }
+
+        let mut update_notes = None;
+
+        let mut titles = vec!["", "Time Out"];
+
+        if category.returnable {
+            titles.push("Time In");
+        }
+
+        titles.extend([category.name.as_str(), "Student Name", "Student Number", "Receptionist Out"]);
+
+        if category.returnable {
+            titles.push("Receptionist In");
+        }
+
+        titles.extend(category.fields.iter().map(|field| field.name.as_str()));
+        titles.push("Notes");
+
+        egui::ScrollArea::horizontal().show(ui, |ui| {
+            let mut table = TableBuilder::new(ui)
+                .striped(true)
+                .stick_to_bottom(true)
+                .max_scroll_height(f32::INFINITY)
+                .column(Column::auto().resizable(false))
+                .column(Column::auto().at_most(COL_MAX_WIDTH).resizable(true));
+
+            if category.returnable {
+                table = table.column(Column::auto().at_most(COL_MAX_WIDTH).resizable(true));
+            }
+
+            table = table
+                .column(Column::initial(COL_LARGE_INITIAL_WIDTH).at_least(COL_MAX_WIDTH).clip(true).resizable(true))
+                .column(Column::initial(COL_SMALL_INITIAL_WIDTH).at_least(COL_MAX_WIDTH).clip(true).resizable(true))
+                .column(Column::auto().at_least(COL_MIN_WIDTH).at_most(COL_MAX_WIDTH).resizable(true))
+                .column(Column::initial(COL_SMALL_INITIAL_WIDTH).at_least(COL_MIN_WIDTH).clip(true).resizable(true));
+
+            if category.returnable {
+                table = table.column(Column::initial(COL_SMALL_INITIAL_WIDTH).at_least(COL_MIN_WIDTH).clip(true).resizable(true));
+            }
+
+            for _ in &category.fields {
+                table = table.column(Column::initial(COL_SMALL_INITIAL_WIDTH).at_least(COL_MIN_WIDTH).clip(true).resizable(true));
+            }
+
+            table
+                .column(Column::remainder().at_least(COL_MIN_WIDTH).clip(true).resizable(true))
+                .header(ROW_HEIGHT, |mut header| {
+                    for title in titles {
+                        header.col(|ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(egui::RichText::new(title).strong());
+                            });
+                        });
+                    }
+                })
+                .body(|mut body| {
+                    for record in category_records.get_all() {
+                        body.row(ROW_HEIGHT, |mut row| {
+                            // Amend
+                            row.col(|ui| {
+                                highlight_voided(ui, record.voided);
+                                ui.horizontal(|ui| {
+                                    ui.set_enabled(shift.is_some());
+
+                                    if render_amend_button(ui, record.amended, record.voided) {
+                                        self.amend_modal = Some(AmendModal::new(record.id, vec![
+                                            AmendField::new("entry", "Entry", &record.entry).with_options(category.entries.iter().map(|entry| entry.name.clone()).collect()),
+                                            AmendField::new("quantity", "Quantity", record.quantity),
+                                            AmendField::new("student_name", "Student Name", &record.student_name),
+                                            AmendField::new("student_number", "Student Number", record.student_number.as_deref().unwrap_or_default()),
+                                        ]));
+                                    }
+                                });
+                            });
+                            // Time Out
+                            row.col(|ui| {
+                                highlight_voided(ui, record.voided);
+                                ui.horizontal(|ui| {
+                                    ui.label(chrono::DateTime::<chrono::Local>::from(record.time_out).format(DATE_TIME_FORMAT).to_string());
+                                });
+                            });
+                            // Time In
+                            if category.returnable {
+                                row.col(|ui| {
+                                    highlight_voided(ui, record.voided);
+                                    ui.horizontal(|ui| {
+                                        if let Some(time_in) = record.time_in {
+                                            ui.label(chrono::DateTime::<chrono::Local>::from(time_in).format(DATE_TIME_FORMAT).to_string());
+                                        } else if ui.add_enabled(shift.is_some() && !record.voided, egui::Button::new("Sign In")).clicked() {
+                                            self.sign_in_modal = Some(SignInModal::new(record.id));
+                                        }
+                                    });
+                                });
+                            }
+                            // Entry & Quantity
+                            row.col(|ui| {
+                                highlight_voided(ui, record.voided);
+                                ui.horizontal(|ui| {
+                                    ui.label(format!("{} × {}", record.quantity, record.entry));
+                                });
+                            });
+                            // Student Name
+                            row.col(|ui| {
+                                highlight_voided(ui, record.voided);
+                                ui.horizontal(|ui| {
+                                    ui.label(&record.student_name);
+                                });
+                            });
+                            // Student Number
+                            row.col(|ui| {
+                                highlight_voided(ui, record.voided);
+                                ui.horizontal(|ui| {
+                                    if let Some(student_number) = &record.student_number {
+                                        if render_student_number(ui, student_number) {
+                                            self.history_modal = Some(StudentHistoryModal::new(student_number));
+                                        }
+                                    }
+                                });
+                            });
+                            // Receptionist Out
+                            row.col(|ui| {
+                                highlight_voided(ui, record.voided);
+                                ui.horizontal(|ui| {
+                                    ui.label(record.receptionist_out.as_ref().unwrap_or(&String::new()));
+                                });
+                            });
+                            // Receptionist In
+                            if category.returnable {
+                                row.col(|ui| {
+                                    highlight_voided(ui, record.voided);
+                                    ui.horizontal(|ui| {
+                                        ui.label(record.receptionist_in.as_ref().unwrap_or(&String::new()));
+                                    });
+                                });
+                            }
+                            // Custom Fields
+                            for field in &category.fields {
+                                row.col(|ui| {
+                                    highlight_voided(ui, record.voided);
+                                    ui.horizontal(|ui| {
+                                        ui.label(record.field(&field.name));
+                                    });
+                                });
+                            }
+                            // Notes
+                            row.col(|ui| {
+                                highlight_voided(ui, record.voided);
+                                let update = render_notes_entry(ui, record.id, &record.notes, &mut self.current_notes);
+                                if update_notes.is_none() {
+                                    update_notes = update;
+                                }
+                            });
+                        })
+                    }
+                });
+        });
+
+        // Update notes down here to avoid mutating while immutably borrowed
+        if let Some((id, notes)) = update_notes {
+            let result = category_records.update_notes(id, &notes);
+
+            if errors.check(&format!("failed to update notes for {} record {id}", category.name), result).is_some() {
+                log::info!("updated notes for {id} to {notes:?}");
+            }
+        }
+    }
+}
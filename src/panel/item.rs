@@ -1,43 +1,86 @@
-use std::sync::{Arc, Mutex};
-
 use egui_extras::{TableBuilder, Column};
 
-use crate::{records::{Page, ItemTypeStorage, ItemStorage, NotedStorage, PaginatedStorage, StudentInfo}, modal::ItemSignModal, app::{DATE_TIME_FORMAT, ROW_HEIGHT, COL_MAX_WIDTH, COL_LARGE_INITIAL_WIDTH, COL_SMALL_INITIAL_WIDTH, COL_MIN_WIDTH}};
+use crate::{service::{self, SignOut}, records::{Page, Storage, RecordFilter, ItemTypeStorage, ItemStorage, NotedStorage, PaginatedStorage, Shift}, modal::{StudentHistoryModal, ItemSignModal, SignInModal, AmendModal, AmendField}, app::{StorageErrors, DATE_TIME_FORMAT, ROW_HEIGHT, COL_MAX_WIDTH, COL_LARGE_INITIAL_WIDTH, COL_SMALL_INITIAL_WIDTH, COL_MIN_WIDTH}};
 
-use super::{pagination, render_notes_entry};
+use super::{pagination, render_notes_entry, render_filter_toggle, render_filter_bar, highlight_voided, render_amend_button, render_student_number};
 
 #[derive(Debug, Default)]
 pub struct ItemPanel {
     page: Page,
+    show_filter: bool,
+    filter: RecordFilter,
 
     item_sign_modal: Option<ItemSignModal>,
+    item_sign_in_modal: Option<SignInModal<i64>>,
+    amend_modal: Option<AmendModal<i64>>,
+    history_modal: Option<StudentHistoryModal>,
     
     current_notes: Option<(i64, String)>,
 }
 
 impl ItemPanel {
-    pub fn render(&mut self, ctx: &eframe::egui::Context, ui: &mut egui::Ui, item_types: &ItemTypeStorage, item_records: &mut ItemStorage, student_info: Arc<Mutex<StudentInfo>>) {
+    pub fn render(&mut self, ui: &mut egui::Ui, item_types: &mut ItemTypeStorage, item_records: &mut ItemStorage, sign_out: &mut SignOut, shift: Option<&Shift>, errors: &mut StorageErrors) {
         ui.horizontal(|ui| {
-            if ui.button("Sign Out Item").clicked() {
+            if ui.add_enabled(shift.is_some(), egui::Button::new("Sign Out Item")).clicked() {
                 self.item_sign_modal = Some(ItemSignModal::default());
             }
+
+            render_filter_toggle(ui, &mut self.show_filter);
     
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
-                pagination(ui, &mut self.page, item_records.count());
-                item_records.set_page(self.page).expect(&format!("failed to refresh parcel records for page: {:?}", self.page));
+                pagination(ui, &mut self.page, item_records.position());
+                errors.check(&format!("failed to refresh item records for page {:?}", self.page), item_records.set_page(self.page));
             });
         });
 
+        if self.show_filter {
+            ui.add_space(4.0);
+
+            if render_filter_bar(ui, "item_filter", &mut self.filter, "Item", Some("Still Out"), false) {
+                self.page = Page::LastPage;
+            }
+        } else if !self.filter.is_empty() {
+            self.filter = RecordFilter::default();
+            self.page = Page::LastPage;
+        }
+
+        errors.check("failed to refresh item records for filter", item_records.set_filter(&self.filter));
+
         ui.add_space(8.0);
 
-        if let Some(modal) = &mut self.item_sign_modal {
-            let close_modal = modal.render(ctx, item_types, item_records, student_info);
+        if let (Some(modal), Some(shift)) = (&mut self.item_sign_modal, shift) {
+            let close_modal = modal.render(ui.ctx(), item_types, item_records, sign_out, shift, errors);
 
             if close_modal {
                 self.item_sign_modal = None;
             }
         }
+
+        // Sign In Modal
+        if let (Some(modal), Some(shift)) = (&mut self.item_sign_in_modal, shift) {
+            let close_modal = modal.render(ui.ctx(), shift, errors, |id, receptionist| service::sign_in_item(item_types, item_records, id, receptionist));
+
+            if close_modal {
+                self.item_sign_in_modal = None;
+            }
+        }
         
+        if let (Some(modal), Some(shift)) = (&mut self.amend_modal, shift) {
+            let close_modal = modal.render(ui.ctx(), item_records, shift, errors);
+
+            if close_modal {
+                self.amend_modal = None;
+            }
+        }
+
+        if let Some(modal) = &mut self.history_modal {
+            let close_modal = modal.render(ui.ctx(), sign_out.students, errors);
+
+            if close_modal {
+                self.history_modal = None;
+            }
+        }
+
         let mut update_notes = None;
         
         egui::ScrollArea::horizontal().show(ui, |ui| {
@@ -45,18 +88,27 @@ impl ItemPanel {
                 .striped(true)
                 .stick_to_bottom(true)
                 .max_scroll_height(f32::INFINITY)
+                .column(Column::auto().resizable(false))
+                .column(Column::auto().at_most(COL_MAX_WIDTH).resizable(true))
                 .column(Column::auto().at_most(COL_MAX_WIDTH).resizable(true))
                 .column(Column::initial(COL_LARGE_INITIAL_WIDTH).at_least(COL_MAX_WIDTH).clip(true).resizable(true))
                 .column(Column::initial(COL_SMALL_INITIAL_WIDTH).at_least(COL_MAX_WIDTH).clip(true).resizable(true))
                 .column(Column::auto().at_least(COL_MIN_WIDTH).at_most(COL_MAX_WIDTH).resizable(true))
                 .column(Column::initial(COL_SMALL_INITIAL_WIDTH).at_least(COL_MIN_WIDTH).clip(true).resizable(true))
+                .column(Column::initial(COL_SMALL_INITIAL_WIDTH).at_least(COL_MIN_WIDTH).clip(true).resizable(true))
                 .column(Column::remainder().at_least(COL_MIN_WIDTH).clip(true).resizable(true))
                 .header(ROW_HEIGHT, |mut header| {
+                    header.col(|_| {});
                     header.col(|ui| {
                         ui.horizontal(|ui| {
                             ui.label(egui::RichText::new("Time Out").strong());
                         });
                     });
+                    header.col(|ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new("Time In").strong());
+                        });
+                    });
                     header.col(|ui| {
                         ui.horizontal(|ui| {
                             ui.label(egui::RichText::new("Item").strong());
@@ -74,7 +126,12 @@ impl ItemPanel {
                     });
                     header.col(|ui| {
                         ui.horizontal(|ui| {
-                            ui.label(egui::RichText::new("Receptionist").strong());
+                            ui.label(egui::RichText::new("Signed Out By").strong());
+                        });
+                    });
+                    header.col(|ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new("Signed In By").strong());
                         });
                     });
                     header.col(|ui| {
@@ -86,38 +143,80 @@ impl ItemPanel {
                 .body(|mut body| {
                     for record in item_records.get_all() {
                         body.row(ROW_HEIGHT, |mut row| {
+                            // Amend
+                            row.col(|ui| {
+                                highlight_voided(ui, record.voided);
+                                ui.horizontal(|ui| {
+                                    ui.set_enabled(shift.is_some());
+
+                                    if render_amend_button(ui, record.amended, record.voided) {
+                                        self.amend_modal = Some(AmendModal::new(record.id, vec![
+                                            AmendField::new("item", "Item", &record.item).with_options(item_types.get_all().iter().map(|item| item.item.clone()).collect()),
+                                            AmendField::new("quantity", "Quantity", record.quantity),
+                                            AmendField::new("student_name", "Student Name", &record.student_name),
+                                            AmendField::new("student_number", "Student Number", &record.student_number),
+                                        ]));
+                                    }
+                                });
+                            });
                             // Time Out
                             row.col(|ui| {
+                                highlight_voided(ui, record.voided);
+                                ui.horizontal(|ui| {
+                                    ui.label(chrono::DateTime::<chrono::Local>::from(record.time_out).format(DATE_TIME_FORMAT).to_string());
+                                });
+                            });
+                            // Time In, items which are not returnable are never signed in
+                            row.col(|ui| {
+                                highlight_voided(ui, record.voided);
                                 ui.horizontal(|ui| {
-                                    ui.label(&chrono::DateTime::<chrono::Local>::from(record.time_out).format(DATE_TIME_FORMAT).to_string());
+                                    if let Some(time_in) = record.time_in {
+                                        ui.label(chrono::DateTime::<chrono::Local>::from(time_in).format(DATE_TIME_FORMAT).to_string());
+                                    } else if record.returnable && ui.add_enabled(shift.is_some() && !record.voided, egui::Button::new("Sign In")).clicked() {
+                                        self.item_sign_in_modal = Some(SignInModal::new(record.id));
+                                    }
                                 });
                             });
                             // Item & Quantity
                             row.col(|ui| {
+                                highlight_voided(ui, record.voided);
                                 ui.horizontal(|ui| {
-                                    ui.label(&format!("{} × {}", record.quantity, record.item));
+                                    ui.label(format!("{} × {}", record.quantity, record.item));
                                 });
                             });
                             // Student Name
                             row.col(|ui| {
+                                highlight_voided(ui, record.voided);
                                 ui.horizontal(|ui| {
                                     ui.label(&record.student_name);
                                 });
                             });
                             // Student Number
                             row.col(|ui| {
+                                highlight_voided(ui, record.voided);
                                 ui.horizontal(|ui| {
-                                    ui.label(&record.student_number);
+                                    if render_student_number(ui, &record.student_number) {
+                                        self.history_modal = Some(StudentHistoryModal::new(&record.student_number));
+                                    }
                                 });
                             });
-                            // Receptionist
+                            // Signed Out By
                             row.col(|ui| {
+                                highlight_voided(ui, record.voided);
                                 ui.horizontal(|ui| {
                                     ui.label(&record.receptionist);
                                 });
                             });
+                            // Signed In By
+                            row.col(|ui| {
+                                highlight_voided(ui, record.voided);
+                                ui.horizontal(|ui| {
+                                    ui.label(record.receptionist_in.as_ref().unwrap_or(&String::new()));
+                                });
+                            });
                             // Notes
                             row.col(|ui| {
+                                highlight_voided(ui, record.voided);
                                 let update = render_notes_entry(ui, record.id, &record.notes, &mut self.current_notes);
                                 if update_notes.is_none() {
                                     update_notes = update;
@@ -130,8 +229,11 @@ impl ItemPanel {
 
         // Update notes down here to avoid mutating while immutably borrowed
         if let Some((id, notes)) = update_notes {
-            item_records.update_notes(id, &notes).expect(&format!("failed to update notes for item record: {id}"));
-            log::info!("updated notes for {id} to {notes:?}");
+            let result = item_records.update_notes(id, &notes);
+
+            if errors.check(&format!("failed to update notes for item record {id}"), result).is_some() {
+                log::info!("updated notes for {id} to {notes:?}");
+            }
         }
     }
 }
@@ -1,52 +1,85 @@
-use std::sync::{Arc, Mutex};
-
 use egui_extras::{TableBuilder, Column};
 
-use crate::{records::{Page, KeyStorage, KeyTypeStorage, PaginatedStorage, NotedStorage, StudentInfo}, modal::{KeySignModal, SignInModal}, app::{DATE_TIME_FORMAT, ROW_HEIGHT, COL_MIN_WIDTH, COL_MAX_WIDTH, COL_LARGE_INITIAL_WIDTH, COL_SMALL_INITIAL_WIDTH}};
+use crate::{service::{self, SignOut}, records::{Page, Storage, RecordFilter, KeyStorage, KeyTypeStorage, PaginatedStorage, NotedStorage, Shift}, modal::{StudentHistoryModal, KeySignModal, SignInModal, AmendModal, AmendField}, app::{StorageErrors, DATE_TIME_FORMAT, ROW_HEIGHT, COL_MIN_WIDTH, COL_MAX_WIDTH, COL_LARGE_INITIAL_WIDTH, COL_SMALL_INITIAL_WIDTH}};
 
-use super::{pagination, render_notes_entry};
+use super::{pagination, render_notes_entry, render_filter_toggle, render_filter_bar, highlight_overdue, format_optional_time, highlight_voided, render_amend_button, render_student_number};
 
 #[derive(Debug, Default)]
 pub struct KeyPanel {
     page: Page,
+    show_filter: bool,
+    filter: RecordFilter,
 
     key_sign_modal: Option<KeySignModal>,
     key_sign_in_modal: Option<SignInModal<i64>>,
+    amend_modal: Option<AmendModal<i64>>,
+    history_modal: Option<StudentHistoryModal>,
     
     current_notes: Option<(i64, String)>,
 }
 
 impl KeyPanel {
-    pub fn render(&mut self, ctx: &eframe::egui::Context, ui: &mut egui::Ui, key_types: &KeyTypeStorage, key_records: &mut KeyStorage, student_info: Arc<Mutex<StudentInfo>>) {
+    pub fn render(&mut self, ui: &mut egui::Ui, key_types: &KeyTypeStorage, key_records: &mut KeyStorage, sign_out: &mut SignOut, shift: Option<&Shift>, errors: &mut StorageErrors) {
         ui.horizontal(|ui| {
-            if ui.button("Sign Out Key").clicked() {
+            if ui.add_enabled(shift.is_some(), egui::Button::new("Sign Out Key")).clicked() {
                 self.key_sign_modal = Some(KeySignModal::default());
             }
+
+            render_filter_toggle(ui, &mut self.show_filter);
     
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
-                pagination(ui, &mut self.page, key_records.count());
-                key_records.set_page(self.page).expect(&format!("failed to refresh key records for page: {:?}", self.page));
+                pagination(ui, &mut self.page, key_records.position());
+                errors.check(&format!("failed to refresh key records for page {:?}", self.page), key_records.set_page(self.page));
             });
         });
 
+        if self.show_filter {
+            ui.add_space(4.0);
+
+            if render_filter_bar(ui, "key_filter", &mut self.filter, "Key", Some("Still Out"), true) {
+                self.page = Page::LastPage;
+            }
+        } else if !self.filter.is_empty() {
+            self.filter = RecordFilter::default();
+            self.page = Page::LastPage;
+        }
+
+        errors.check("failed to refresh key records for filter", key_records.set_filter(&self.filter));
+
         ui.add_space(8.0);
 
-        if let Some(modal) = &mut self.key_sign_modal {
-            let close_modal = modal.render(ctx, key_types, key_records, student_info);
+        if let (Some(modal), Some(shift)) = (&mut self.key_sign_modal, shift) {
+            let close_modal = modal.render(ui.ctx(), key_types, key_records, sign_out, shift, errors);
 
             if close_modal {
                 self.key_sign_modal = None;
             }
         }
 
-        if let Some(modal) = &mut self.key_sign_in_modal {
-            let close_modal = modal.render(ctx, key_records);
+        if let (Some(modal), Some(shift)) = (&mut self.key_sign_in_modal, shift) {
+            let close_modal = modal.render(ui.ctx(), shift, errors, |id, receptionist| service::sign_in_key(key_records, id, receptionist));
 
             if close_modal {
                 self.key_sign_in_modal = None;
             }
         }
         
+        if let (Some(modal), Some(shift)) = (&mut self.amend_modal, shift) {
+            let close_modal = modal.render(ui.ctx(), key_records, shift, errors);
+
+            if close_modal {
+                self.amend_modal = None;
+            }
+        }
+
+        if let Some(modal) = &mut self.history_modal {
+            let close_modal = modal.render(ui.ctx(), sign_out.students, errors);
+
+            if close_modal {
+                self.history_modal = None;
+            }
+        }
+
         let mut update_notes = None;
         
         egui::ScrollArea::horizontal().show(ui, |ui| {
@@ -54,19 +87,28 @@ impl KeyPanel {
                 .striped(true)
                 .stick_to_bottom(true)
                 .max_scroll_height(f32::INFINITY)
+                .column(Column::auto().resizable(false))
                 .column(Column::auto().at_most(COL_MAX_WIDTH).resizable(true))
+                .column(Column::auto().at_least(COL_MIN_WIDTH).at_most(COL_MAX_WIDTH).resizable(true))
                 .column(Column::auto().at_most(COL_MAX_WIDTH).resizable(true))
                 .column(Column::initial(COL_LARGE_INITIAL_WIDTH).at_least(COL_MIN_WIDTH).clip(true).resizable(true))
                 .column(Column::initial(COL_SMALL_INITIAL_WIDTH).at_least(COL_MIN_WIDTH).clip(true).resizable(true))
                 .column(Column::auto().at_least(COL_MIN_WIDTH).at_most(COL_MAX_WIDTH).resizable(true))
                 .column(Column::initial(COL_SMALL_INITIAL_WIDTH).at_least(COL_MIN_WIDTH).clip(true).resizable(true))
+                .column(Column::initial(COL_SMALL_INITIAL_WIDTH).at_least(COL_MIN_WIDTH).clip(true).resizable(true))
                 .column(Column::remainder().at_least(COL_MIN_WIDTH).clip(true).resizable(true))
                 .header(ROW_HEIGHT, |mut header| {
+                    header.col(|_| {});
                     header.col(|ui| {
                         ui.horizontal(|ui| {
                             ui.label(egui::RichText::new("Time Out").strong());
                         });
                     });
+                    header.col(|ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new("Due").strong());
+                        });
+                    });
                     header.col(|ui| {
                         ui.horizontal(|ui| {
                             ui.label(egui::RichText::new("Time In").strong());
@@ -89,7 +131,12 @@ impl KeyPanel {
                     });
                     header.col(|ui| {
                         ui.horizontal(|ui| {
-                            ui.label(egui::RichText::new("Receptionist").strong());
+                            ui.label(egui::RichText::new("Signed Out By").strong());
+                        });
+                    });
+                    header.col(|ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new("Signed In By").strong());
                         });
                     });
                     header.col(|ui| {
@@ -100,49 +147,98 @@ impl KeyPanel {
                 })
                 .body(|mut body| {
                     for record in key_records.get_all() {
+                        let overdue = record.is_overdue();
+                        
                         body.row(ROW_HEIGHT, |mut row| {
+                            // Amend
+                            row.col(|ui| {
+                                highlight_voided(ui, record.voided);
+                                ui.horizontal(|ui| {
+                                    ui.set_enabled(shift.is_some());
+
+                                    if render_amend_button(ui, record.amended, record.voided) {
+                                        self.amend_modal = Some(AmendModal::new(record.id, vec![
+                                            AmendField::new("key", "Key", &record.key).with_options(key_types.get_all().iter().map(|k| k.key.clone()).collect()),
+                                            AmendField::new("student_name", "Student Name", &record.student_name),
+                                            AmendField::new("student_number", "Student Number", &record.student_number),
+                                        ]));
+                                    }
+                                });
+                            });
                             // Time Out
                             row.col(|ui| {
+                                highlight_overdue(ui, overdue);
+                                highlight_voided(ui, record.voided);
                                 ui.horizontal(|ui| {
-                                    ui.label(&chrono::DateTime::<chrono::Local>::from(record.time_out).format(DATE_TIME_FORMAT).to_string());
+                                    ui.label(chrono::DateTime::<chrono::Local>::from(record.time_out).format(DATE_TIME_FORMAT).to_string());
+                                });
+                            });
+                            // Due
+                            row.col(|ui| {
+                                highlight_overdue(ui, overdue);
+                                highlight_voided(ui, record.voided);
+                                ui.horizontal(|ui| {
+                                    ui.label(format_optional_time(record.time_due));
                                 });
                             });
                             // Time In
                             row.col(|ui| {
+                                highlight_overdue(ui, overdue);
+                                highlight_voided(ui, record.voided);
                                 ui.horizontal(|ui| {
                                     if let Some(time_in) = record.time_in {
-                                        ui.label(&chrono::DateTime::<chrono::Local>::from(time_in).format(DATE_TIME_FORMAT).to_string());
-                                    } else if ui.button("Sign In").clicked() {
+                                        ui.label(chrono::DateTime::<chrono::Local>::from(time_in).format(DATE_TIME_FORMAT).to_string());
+                                    } else if ui.add_enabled(shift.is_some() && !record.voided, egui::Button::new("Sign In")).clicked() {
                                         self.key_sign_in_modal = Some(SignInModal::new(record.id));
                                     }
                                 });
                             });
                             // Key
                             row.col(|ui| {
+                                highlight_overdue(ui, overdue);
+                                highlight_voided(ui, record.voided);
                                 ui.horizontal(|ui| {
                                     ui.label(&record.key);
                                 });
                             });
                             // Student Name
                             row.col(|ui| {
+                                highlight_overdue(ui, overdue);
+                                highlight_voided(ui, record.voided);
                                 ui.horizontal(|ui| {
                                     ui.label(&record.student_name);
                                 });
                             });
                             // Student Number
                             row.col(|ui| {
+                                highlight_overdue(ui, overdue);
+                                highlight_voided(ui, record.voided);
                                 ui.horizontal(|ui| {
-                                    ui.label(&record.student_number);
+                                    if render_student_number(ui, &record.student_number) {
+                                        self.history_modal = Some(StudentHistoryModal::new(&record.student_number));
+                                    }
                                 });
                             });
-                            // Receptionist
+                            // Signed Out By
                             row.col(|ui| {
+                                highlight_overdue(ui, overdue);
+                                highlight_voided(ui, record.voided);
                                 ui.horizontal(|ui| {
-                                    ui.label(record.receptionist.as_ref().unwrap_or(&String::new()));
+                                    ui.label(record.receptionist_out.as_ref().unwrap_or(&String::new()));
+                                });
+                            });
+                            // Signed In By
+                            row.col(|ui| {
+                                highlight_overdue(ui, overdue);
+                                highlight_voided(ui, record.voided);
+                                ui.horizontal(|ui| {
+                                    ui.label(record.receptionist_in.as_ref().unwrap_or(&String::new()));
                                 });
                             });
                             // Notes
                             row.col(|ui| {
+                                highlight_overdue(ui, overdue);
+                                highlight_voided(ui, record.voided);
                                 let update = render_notes_entry(ui, record.id, &record.notes, &mut self.current_notes);
                                 if update_notes.is_none() {
                                     update_notes = update;
@@ -155,8 +251,11 @@ impl KeyPanel {
 
         // Update notes down here to avoid mutating while immutably borrowed
         if let Some((id, notes)) = update_notes {
-            key_records.update_notes(id, &notes).expect(&format!("failed to update notes for key record: {id}"));
-            log::info!("updated notes for {id} to {notes:?}");
+            let result = key_records.update_notes(id, &notes);
+
+            if errors.check(&format!("failed to update notes for key record {id}"), result).is_some() {
+                log::info!("updated notes for {id} to {notes:?}");
+            }
         }
     }
 }
@@ -2,49 +2,46 @@ pub mod key;
 pub mod parcel;
 pub mod game;
 pub mod item;
+pub mod category;
 
 pub use key::*;
 pub use parcel::*;
 pub use game::*;
 pub use item::*;
+pub use category::*;
 
-use crate::{records::Page, app::{PAGE_SIZE, NOTES_MAX_LENGTH}};
+use crate::{records::{Page, PagePosition, RecordFilter}, app::{NOTES_MAX_LENGTH, DATE_TIME_FORMAT}};
 
 const PENCIL_ICON: &str = "\u{f303}";
+const SEARCH_ICON: &str = "\u{f002}";
+const AMEND_ICON: &str = "\u{f044}";
+const FILTER_ENTRY_WIDTH: f32 = 96.0;
+pub(crate) const OVERDUE_COLOUR: egui::Color32 = egui::Color32::from_rgb(255, 96, 96);
+const VOIDED_COLOUR: egui::Color32 = egui::Color32::from_gray(96);
 
-fn pagination(ui: &mut eframe::egui::Ui, page: &mut Page, count: i64) {
-    let max_page = (count - 1) / PAGE_SIZE;
-
-    let mut page_num = page.as_i64(count);
-    
+pub(crate) fn pagination(ui: &mut eframe::egui::Ui, page: &mut Page, position: PagePosition) {
     // skip to end
-    if ui.add_enabled(page_num < max_page, egui::Button::new(">>")).clicked() {
-        page_num = max_page;
+    if ui.add_enabled(position.after > 0, egui::Button::new(">>")).clicked() {
+        *page = Page::LastPage;
     }
     
     // next page
-    if ui.add_enabled(page_num < max_page, egui::Button::new(">")).clicked() {
-        page_num += 1;
+    if ui.add_enabled(position.after > 0, egui::Button::new(">")).clicked() {
+        *page = position.next();
     }
     
     // current page text
-    ui.label(format!("{}", page_num+1));
+    ui.label(format!("{}", position.number()+1));
     
     // previous page
-    if ui.add_enabled(page_num > 0, egui::Button::new("<")).clicked() {
-        page_num -= 1;
+    if ui.add_enabled(position.before > 0, egui::Button::new("<")).clicked() {
+        *page = position.previous();
     }
     
     // skip to start
-    if ui.add_enabled(page_num > 0, egui::Button::new("<<")).clicked() {
-        page_num = 0;
+    if ui.add_enabled(position.before > 0, egui::Button::new("<<")).clicked() {
+        *page = Page::FirstPage;
     }
-
-    *page = if page_num >= max_page {
-        Page::LastPage
-    } else {
-        Page::Page(page_num.clamp(0, max_page))
-    };
 }
 
 fn render_notes_entry(ui: &mut egui::Ui, record_id: i64, record_notes: &str, current_notes: &mut Option<(i64, String)>) -> Option<(i64, String)> {
@@ -83,3 +80,99 @@ fn render_notes_entry(ui: &mut egui::Ui, record_id: i64, record_notes: &str, cur
 
     update_notes
 }
+
+fn render_filter_toggle(ui: &mut egui::Ui, show_filter: &mut bool) {
+    let text = egui::RichText::new(SEARCH_ICON).family(egui::FontFamily::Name("icons".into()));
+
+    if ui.add(egui::SelectableLabel::new(*show_filter, text)).on_hover_text("Search & Filter").clicked() {
+        *show_filter = !*show_filter;
+    }
+}
+
+/// Renders the search and filter bar, returns true if the filter was changed.
+fn render_filter_bar(ui: &mut egui::Ui, id_source: &str, filter: &mut RecordFilter, item_label: &str, still_out_label: Option<&str>, show_overdue: bool) -> bool {
+    let old_filter = filter.clone();
+
+    ui.horizontal_wrapped(|ui| {
+        ui.add(egui::TextEdit::singleline(&mut filter.student).hint_text("Student").desired_width(FILTER_ENTRY_WIDTH));
+        ui.add(egui::TextEdit::singleline(&mut filter.item).hint_text(item_label).desired_width(FILTER_ENTRY_WIDTH));
+        ui.add(egui::TextEdit::singleline(&mut filter.receptionist).hint_text("Receptionist").desired_width(FILTER_ENTRY_WIDTH));
+        ui.add(egui::TextEdit::singleline(&mut filter.notes).hint_text("Notes").desired_width(FILTER_ENTRY_WIDTH));
+
+        render_filter_date(ui, &format!("{id_source}_from"), "From", &mut filter.from);
+        render_filter_date(ui, &format!("{id_source}_to"), "To", &mut filter.to);
+
+        if let Some(label) = still_out_label {
+            ui.checkbox(&mut filter.still_out, label);
+        }
+
+        if show_overdue {
+            ui.checkbox(&mut filter.overdue, "Overdue");
+        }
+
+        if ui.add_enabled(!filter.is_empty(), egui::Button::new("Clear")).clicked() {
+            *filter = RecordFilter::default();
+        }
+    });
+
+    *filter != old_filter
+}
+
+pub(crate) fn render_filter_date(ui: &mut egui::Ui, id_source: &str, label: &str, date: &mut Option<chrono::NaiveDate>) {
+    let mut enabled = date.is_some();
+
+    if ui.checkbox(&mut enabled, label).changed() {
+        *date = if enabled {
+            Some(chrono::Local::now().date_naive())
+        } else {
+            None
+        };
+    }
+
+    if let Some(date) = date {
+        ui.add(egui_extras::DatePickerButton::new(date).id_source(id_source));
+    }
+}
+
+/// Colour the text of the current cell if its record is overdue.
+fn highlight_overdue(ui: &mut egui::Ui, overdue: bool) {
+    if overdue {
+        ui.visuals_mut().override_text_color = Some(OVERDUE_COLOUR);
+    }
+}
+
+/// Renders a student number as a link, returns whether it was clicked to show the student's history.
+fn render_student_number(ui: &mut egui::Ui, student_number: &str) -> bool {
+    ui.link(student_number)
+        .on_hover_text("Show History")
+        .clicked()
+}
+
+/// Grey out the text of the current cell if its record has been voided.
+fn highlight_voided(ui: &mut egui::Ui, voided: bool) {
+    if voided {
+        ui.visuals_mut().override_text_color = Some(VOIDED_COLOUR);
+    }
+}
+
+/// Renders the button which opens the amendment modal, with an indicator if the record has been amended or voided.
+fn render_amend_button(ui: &mut egui::Ui, amended: bool, voided: bool) -> bool {
+    let clicked = ui.button(egui::RichText::new(AMEND_ICON).family(egui::FontFamily::Name("icons".into())))
+        .on_hover_text("Amend or Void")
+        .clicked();
+
+    if voided {
+        ui.label("Void");
+    } else if amended {
+        ui.label("Amended");
+    }
+
+    clicked
+}
+
+fn format_optional_time(time: Option<chrono::DateTime<chrono::Utc>>) -> String {
+    match time {
+        Some(time) => chrono::DateTime::<chrono::Local>::from(time).format(DATE_TIME_FORMAT).to_string(),
+        None => "".into(),
+    }
+}
@@ -1,42 +1,68 @@
 use egui_extras::{TableBuilder, Column};
 
-use crate::{records::{Page, ParcelStorage, PaginatedStorage, NotedStorage, SignableStorage}, modal::ParcelSignModal, app::{DATE_TIME_FORMAT, ROW_HEIGHT, COL_MAX_WIDTH, COL_MIN_WIDTH, COL_SMALL_INITIAL_WIDTH, COL_LARGE_INITIAL_WIDTH}};
+use crate::{records::{Page, RecordFilter, ParcelStorage, PaginatedStorage, NotedStorage, Shift}, service, modal::{ParcelSignModal, AmendModal, AmendField}, app::{StorageErrors, DATE_TIME_FORMAT, ROW_HEIGHT, COL_MAX_WIDTH, COL_MIN_WIDTH, COL_SMALL_INITIAL_WIDTH, COL_LARGE_INITIAL_WIDTH}};
 
-use super::{pagination, render_notes_entry};
+use super::{pagination, render_notes_entry, render_filter_toggle, render_filter_bar, highlight_voided, render_amend_button};
 
 #[derive(Debug, Default)]
 pub struct ParcelPanel {
     page: Page,
+    show_filter: bool,
+    filter: RecordFilter,
     record_confirm: Option<i64>,
     
     parcel_sign_modal: Option<ParcelSignModal>,
+    amend_modal: Option<AmendModal<i64>>,
     
     current_notes: Option<(i64, String)>,
 }
 
 impl ParcelPanel {
-    pub fn render(&mut self, ctx: &eframe::egui::Context, ui: &mut egui::Ui, parcel_records: &mut ParcelStorage) {
+    pub fn render(&mut self, ui: &mut egui::Ui, parcel_records: &mut ParcelStorage, shift: Option<&Shift>, errors: &mut StorageErrors) {
         ui.horizontal(|ui| {
-            if ui.button("Sign In Parcel").clicked() {
+            if ui.add_enabled(shift.is_some(), egui::Button::new("Sign In Parcel")).clicked() {
                 self.parcel_sign_modal = Some(ParcelSignModal::default());
             }
+
+            render_filter_toggle(ui, &mut self.show_filter);
     
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
-                pagination(ui, &mut self.page, parcel_records.count());
-                parcel_records.set_page(self.page).expect(&format!("failed to refresh parcel records for page: {:?}", self.page));
+                pagination(ui, &mut self.page, parcel_records.position());
+                errors.check(&format!("failed to refresh parcel records for page {:?}", self.page), parcel_records.set_page(self.page));
             });
         });
 
+        if self.show_filter {
+            ui.add_space(4.0);
+
+            if render_filter_bar(ui, "parcel_filter", &mut self.filter, "Parcel", Some("Not Collected"), false) {
+                self.page = Page::LastPage;
+            }
+        } else if !self.filter.is_empty() {
+            self.filter = RecordFilter::default();
+            self.page = Page::LastPage;
+        }
+
+        errors.check("failed to refresh parcel records for filter", parcel_records.set_filter(&self.filter));
+
         ui.add_space(8.0);
 
-        if let Some(modal) = &mut self.parcel_sign_modal {
-            let close_modal = modal.render(ctx, parcel_records);
+        if let (Some(modal), Some(shift)) = (&mut self.parcel_sign_modal, shift) {
+            let close_modal = modal.render(ui.ctx(), parcel_records, shift, errors);
 
             if close_modal {
                 self.parcel_sign_modal = None;
             }
         }
 
+        if let (Some(modal), Some(shift)) = (&mut self.amend_modal, shift) {
+            let close_modal = modal.render(ui.ctx(), parcel_records, shift, errors);
+
+            if close_modal {
+                self.amend_modal = None;
+            }
+        }
+
         let mut update_record = None;
         let mut update_notes = None;
         
@@ -45,6 +71,7 @@ impl ParcelPanel {
                 .striped(true)
                 .stick_to_bottom(true)
                 .max_scroll_height(f32::INFINITY)
+                .column(Column::auto().resizable(false))
                 .column(Column::auto().at_most(COL_MAX_WIDTH).resizable(true))
                 .column(Column::auto().at_most(COL_MAX_WIDTH).resizable(true))
                 .column(Column::initial(COL_LARGE_INITIAL_WIDTH).at_least(COL_MIN_WIDTH).clip(true).resizable(true))
@@ -52,6 +79,7 @@ impl ParcelPanel {
                 .column(Column::initial(COL_SMALL_INITIAL_WIDTH).at_least(COL_MIN_WIDTH).clip(true).resizable(true))
                 .column(Column::remainder().at_least(COL_MIN_WIDTH).clip(true).resizable(true))
                 .header(ROW_HEIGHT, |mut header| {
+                    header.col(|_| {});
                     header.col(|ui| {
                         ui.horizontal(|ui| {
                             ui.label(egui::RichText::new("Time In").strong());
@@ -86,17 +114,33 @@ impl ParcelPanel {
                 .body(|mut body| {
                     for record in parcel_records.get_all() {
                         body.row(ROW_HEIGHT, |mut row| {
+                            // Amend
+                            row.col(|ui| {
+                                highlight_voided(ui, record.voided);
+                                ui.horizontal(|ui| {
+                                    ui.set_enabled(shift.is_some());
+
+                                    if render_amend_button(ui, record.amended, record.voided) {
+                                        self.amend_modal = Some(AmendModal::new(record.id, vec![
+                                            AmendField::new("parcel_desc", "Parcel Description", &record.parcel_desc),
+                                            AmendField::new("student_name", "Recipient Name", &record.student_name),
+                                        ]));
+                                    }
+                                });
+                            });
                             // time_in
                             // time_out
                             // parcel_desc
                             // student_name
                             // receptionist
                             row.col(|ui| {
+                                highlight_voided(ui, record.voided);
                                 ui.horizontal(|ui| {
-                                    ui.label(&chrono::DateTime::<chrono::Local>::from(record.time_in).format(DATE_TIME_FORMAT).to_string());
+                                    ui.label(chrono::DateTime::<chrono::Local>::from(record.time_in).format(DATE_TIME_FORMAT).to_string());
                                 });
                             });
                             row.col(|ui| {
+                                highlight_voided(ui, record.voided);
                                 let require_confirmation = if let Some(record_id) = self.record_confirm {
                                     record_id == record.id
                                 } else {
@@ -105,7 +149,7 @@ impl ParcelPanel {
                                 
                                 let response = ui.horizontal(|ui| {
                                     if let Some(time_out) = record.time_out {
-                                        ui.label(&chrono::DateTime::<chrono::Local>::from(time_out).format(DATE_TIME_FORMAT).to_string());
+                                        ui.label(chrono::DateTime::<chrono::Local>::from(time_out).format(DATE_TIME_FORMAT).to_string());
                                     } else if require_confirmation {
                                         if ui.button("Confirm").clicked() {
                                             update_record = Some(record.id);
@@ -115,7 +159,7 @@ impl ParcelPanel {
                                             self.record_confirm = None;
                                         }
                                     } else {
-                                        if ui.button("Sign Out").clicked() {
+                                        if ui.add_enabled(!record.voided, egui::Button::new("Sign Out")).clicked() {
                                             self.record_confirm = Some(record.id);
                                         }
                                     }
@@ -126,21 +170,25 @@ impl ParcelPanel {
                                 }
                             });
                             row.col(|ui| {
+                                highlight_voided(ui, record.voided);
                                 ui.horizontal(|ui| {
                                     ui.label(&record.parcel_desc);
                                 });
                             });
                             row.col(|ui| {
+                                highlight_voided(ui, record.voided);
                                 ui.horizontal(|ui| {
                                     ui.label(&record.student_name);
                                 });
                             });
                             row.col(|ui| {
+                                highlight_voided(ui, record.voided);
                                 ui.horizontal(|ui| {
                                     ui.label(&record.receptionist);
                                 });
                             });
                             row.col(|ui| {
+                                highlight_voided(ui, record.voided);
                                 let update = render_notes_entry(ui, record.id, &record.notes, &mut self.current_notes);
                                 if update_notes.is_none() {
                                     update_notes = update;
@@ -153,13 +201,20 @@ impl ParcelPanel {
 
         // Update time down here to avoid mutating while immutably borrowed
         if let Some(record_id) = update_record {
-            parcel_records.signin(record_id).expect(&format!("failed to update time for parcel record: {record_id}"));
+            match service::collect_parcel(parcel_records, record_id) {
+                Ok(()) => {},
+                Err(err) if err.is_failure() => errors.report(&format!("failed to sign in parcel record {record_id}"), err),
+                Err(err) => log::warn!("could not sign in parcel record {record_id}: {err}"),
+            }
         }
 
         // Update notes down here to avoid mutating while immutably borrowed
         if let Some((id, notes)) = update_notes {
-            parcel_records.update_notes(id, &notes).expect(&format!("failed to update notes for parcel record: {id}"));
-            log::info!("updated notes for {id} to {notes:?}");
+            let result = parcel_records.update_notes(id, &notes);
+
+            if errors.check(&format!("failed to update notes for parcel record {id}"), result).is_some() {
+                log::info!("updated notes for {id} to {notes:?}");
+            }
         }
     }
 }